@@ -1,6 +1,9 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
 use rayon::prelude::*;
-use shimmytok::Tokenizer;
+use shimmytok::{Tokenizer, Vocabulary};
+use std::io::Cursor;
 use std::path::Path;
 use std::time::Duration;
 
@@ -66,6 +69,12 @@ fn bench_load(c: &mut Criterion) {
     c.bench_function("load_tokenizer", |b| {
         b.iter(|| Tokenizer::from_gguf_file(black_box(&model_path)));
     });
+
+    // Compare against the memory-mapped loader: `cargo bench --features mmap`.
+    #[cfg(feature = "mmap")]
+    c.bench_function("load_tokenizer_mmap", |b| {
+        b.iter(|| Tokenizer::from_gguf_file_mmap(black_box(&model_path)));
+    });
 }
 
 fn bench_encode_batch(c: &mut Criterion) {
@@ -162,6 +171,33 @@ fn bench_sentencepiece_models(c: &mut Criterion) {
     }
 }
 
+/// Model-free encode of a long (~100 KB) GPT-2 style document.
+///
+/// Every pre-tokenized fragment runs the BPE merge loop against the merge-rank
+/// map prepared once at load time; this tracks the per-fragment cost that used
+/// to be dominated by rebuilding that map.
+fn bench_long_document(c: &mut Criterion) {
+    let tok = Tokenizer::from_bytes(&common::bpe_gpt2_fixture()).expect("fixture tokenizer");
+    let unit = " abc acb ab 12";
+    let text = unit.repeat(100 * 1024 / unit.len());
+
+    let mut group = c.benchmark_group("long_document");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("gpt2_100kb", |b| {
+        b.iter(|| tok.encode(black_box(&text), false));
+    });
+
+    // One long word: every adjacent pair is a merge candidate, so this is
+    // dominated by bigram lookups, which are keyed by token IDs and never
+    // allocate.
+    let word = "abc".repeat(4 * 1024);
+    group.throughput(Throughput::Bytes(word.len() as u64));
+    group.bench_function("gpt2_merge_heavy_12kb", |b| {
+        b.iter(|| tok.encode(black_box(&word), false));
+    });
+    group.finish();
+}
+
 /// Portable, model-free comparison of the sequential vs parallel batch
 /// backends across batch sizes and payload sizes.
 ///
@@ -219,6 +255,149 @@ fn bench_batch_backends(c: &mut Criterion) {
     group.finish();
 }
 
+/// `decode_batch` scaling for beam-search-sized workloads: 1000 short
+/// sequences, decoded sequentially vs. through `decode_batch` (which runs
+/// across the Rayon pool when the `parallel` feature is enabled).
+fn bench_decode_batch(c: &mut Criterion) {
+    let tok = Tokenizer::from_bytes(&common::bpe_gpt2_fixture()).expect("fixture tokenizer");
+    let encoded: Vec<Vec<u32>> = (0..1000)
+        .map(|i| {
+            tok.encode(&" abc acb ab 12".repeat(1 + i % 4), false)
+                .expect("fixture encode")
+        })
+        .collect();
+    let sequences: Vec<&[u32]> = encoded.iter().map(Vec::as_slice).collect();
+
+    let mut group = c.benchmark_group("decode_batch");
+    group.throughput(Throughput::Elements(sequences.len() as u64));
+    group.bench_function("sequential/1000", |b| {
+        b.iter(|| {
+            let r: Result<Vec<_>, _> = sequences
+                .iter()
+                .map(|s| tok.decode(black_box(s), false))
+                .collect();
+            black_box(r)
+        });
+    });
+    group.bench_function("decode_batch/1000", |b| {
+        b.iter(|| black_box(tok.decode_batch(black_box(&sequences), false)));
+    });
+    group.finish();
+}
+
+/// `decode_cow` joining literal ASCII pieces against `decode`'s byte-level
+/// rebuild, for a single token (borrowed) and a 64 KiB run.
+fn bench_decode_cow(c: &mut Criterion) {
+    let tok = Tokenizer::from_bytes(&common::bpe_gpt2_fixture()).expect("fixture tokenizer");
+    let tokens = tok
+        .encode(&"abcab12".repeat(64 * 1024 / 7), false)
+        .expect("fixture encode");
+    assert_eq!(
+        tok.decode_cow(&tokens, false).expect("fixture decode"),
+        tok.decode(&tokens, false).expect("fixture decode")
+    );
+
+    let mut group = c.benchmark_group("decode_cow");
+    group.bench_function("decode/1", |b| {
+        b.iter(|| black_box(tok.decode(black_box(&tokens[..1]), false)));
+    });
+    group.bench_function("decode_cow/1", |b| {
+        b.iter(|| black_box(tok.decode_cow(black_box(&tokens[..1]), false)));
+    });
+    group.throughput(Throughput::Elements(tokens.len() as u64));
+    group.bench_function("decode/64k", |b| {
+        b.iter(|| black_box(tok.decode(black_box(&tokens), false)));
+    });
+    group.bench_function("decode_cow/64k", |b| {
+        b.iter(|| black_box(tok.decode_cow(black_box(&tokens), false)));
+    });
+    group.finish();
+}
+
+/// Tokenizer construction over a 150k-token RWKV vocabulary: a fresh
+/// vocabulary builds the trie, a clone of a loaded one reuses the cached
+/// backend. Vocabulary parsing and cloning are excluded from both.
+fn bench_backend_construction(c: &mut Criterion) {
+    let tokens: Vec<String> = (0..150_000).map(|i| format!("tok{i}")).collect();
+    let token_refs: Vec<&str> = tokens.iter().map(String::as_str).collect();
+    let bytes = common::GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "rwkv")
+        .with_string_array("tokenizer.ggml.tokens", &token_refs)
+        .build();
+    let load = || Vocabulary::from_gguf_reader(Cursor::new(&bytes)).expect("rwkv vocab");
+    let loaded = load();
+    Tokenizer::from_vocabulary(loaded.clone()).expect("rwkv tokenizer");
+
+    let mut group = c.benchmark_group("backend_construction_rwkv_150k");
+    group.sample_size(10);
+    group.bench_function("fresh_vocabulary", |b| {
+        b.iter_batched(
+            load,
+            |vocab| black_box(Tokenizer::from_vocabulary(vocab)),
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("cached_backend", |b| {
+        b.iter_batched(
+            || loaded.clone(),
+            |vocab| black_box(Tokenizer::from_vocabulary(vocab)),
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+/// Byte-level encoding of a mixed ASCII/UTF-8 payload through the array
+/// tables, against the equivalent per-byte `HashMap` lookup they replaced.
+fn bench_byte_encoder(c: &mut Criterion) {
+    use shimmytok::byte_encoder::{
+        bytes_to_unicode, decode_to_bytes, encode_bytes, unicode_to_bytes,
+    };
+
+    let unit = "The quick brown fox — naïve café, 日本語 🦀\n";
+    let text = unit.repeat(64 * 1024 / unit.len());
+    let encoded = encode_bytes(&text);
+    let map = bytes_to_unicode();
+
+    let mut group = c.benchmark_group("byte_encoder");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("encode/table", |b| {
+        b.iter(|| encode_bytes(black_box(&text)));
+    });
+    group.bench_function("encode/hashmap", |b| {
+        b.iter(|| {
+            black_box(&text)
+                .bytes()
+                .map(|byte| map[&byte])
+                .collect::<String>()
+        });
+    });
+    group.bench_function("decode/table", |b| {
+        b.iter(|| decode_to_bytes(black_box(&encoded)));
+    });
+    group.finish();
+
+    // Multi-megabyte decode through the reverse array table, against the
+    // per-char `HashMap` lookup it replaced.
+    let large = encode_bytes(&unit.repeat(4 * 1024 * 1024 / unit.len()));
+    let mut group = c.benchmark_group("byte_decoder_4mb");
+    group.throughput(Throughput::Bytes(large.len() as u64));
+    group.sample_size(20);
+    group.bench_function("decode_to_bytes", |b| {
+        b.iter(|| decode_to_bytes(black_box(&large)));
+    });
+    let reverse = unicode_to_bytes();
+    group.bench_function("hashmap", |b| {
+        b.iter(|| {
+            black_box(&large)
+                .chars()
+                .filter_map(|ch| reverse.get(&ch).copied())
+                .collect::<Vec<u8>>()
+        });
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_encode,
@@ -227,6 +406,11 @@ criterion_group!(
     bench_encode_batch,
     bench_multi_pattern_models,
     bench_sentencepiece_models,
-    bench_batch_backends
+    bench_long_document,
+    bench_batch_backends,
+    bench_decode_batch,
+    bench_decode_cow,
+    bench_backend_construction,
+    bench_byte_encoder
 );
 criterion_main!(benches);
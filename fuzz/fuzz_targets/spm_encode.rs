@@ -0,0 +1,78 @@
+//! SentencePiece encode/decode on arbitrary UTF-8.
+//!
+//! The vocabulary merges characters of every UTF-8 width, so a symbol range
+//! that drifts off a char boundary surfaces as a panic (the debug invariant
+//! in `sentencepiece::symbol_text`) or a `TokenizationFailed` error, which
+//! this target also treats as a crash.
+
+#![no_main]
+
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use common::GgufBuilder;
+use libfuzzer_sys::fuzz_target;
+use shimmytok::Tokenizer;
+use std::sync::OnceLock;
+
+fn tokenizer() -> &'static Tokenizer {
+    static TOKENIZER: OnceLock<Tokenizer> = OnceLock::new();
+    TOKENIZER.get_or_init(|| {
+        let mut tokens: Vec<String> = vec!["<unk>".into(), "<s>".into(), "</s>".into()];
+        let mut scores = vec![0.0f32; 3];
+        let mut types = vec![2i32, 3, 3];
+        tokens.extend((0..=255u8).map(|b| format!("<0x{b:02X}>")));
+        scores.extend([0.0f32; 256]);
+        types.extend([6i32; 256]);
+        for (piece, score) in [
+            ("▁", -5.0f32),
+            ("a", -5.0),
+            ("é", -5.0),
+            ("日", -5.0),
+            ("😀", -5.0),
+            ("\u{301}", -5.0),
+            ("▁a", -1.0),
+            ("a\u{301}", -2.0),
+            ("é日", -2.0),
+            ("日😀", -1.5),
+            ("😀😀", -1.0),
+            ("▁é日", -0.5),
+        ] {
+            tokens.push(piece.to_string());
+            scores.push(score);
+            types.push(1);
+        }
+        let token_refs: Vec<&str> = tokens.iter().map(String::as_str).collect();
+        let bytes = GgufBuilder::new()
+            .with_string("tokenizer.ggml.model", "llama")
+            .with_string_array("tokenizer.ggml.tokens", &token_refs)
+            .with_f32_array("tokenizer.ggml.scores", &scores)
+            .with_i32_array("tokenizer.ggml.token_type", &types)
+            .with_u32("tokenizer.ggml.unknown_token_id", 0)
+            .with_u32("tokenizer.ggml.bos_token_id", 1)
+            .with_u32("tokenizer.ggml.eos_token_id", 2)
+            .with_bool("tokenizer.ggml.add_space_prefix", true)
+            .build();
+        Tokenizer::from_bytes(&bytes).expect("fuzz vocabulary loads")
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let tokenizer = tokenizer();
+    let tokens = tokenizer.encode(text, false).expect("SPM encode failed");
+    let decoded = tokenizer.decode(&tokens, false).expect("SPM decode failed");
+    // SPM decode renders a literal `Ġ` as a space too, so such input cannot
+    // round-trip; it still has to encode and decode without panicking.
+    if text.contains('Ġ') {
+        return;
+    }
+    let prefix = if text.is_empty() || text.starts_with(' ') {
+        ""
+    } else {
+        " "
+    };
+    assert_eq!(decoded, format!("{prefix}{}", text.replace('▁', " ")));
+});
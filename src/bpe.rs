@@ -25,9 +25,22 @@
 
 use crate::vocab::Vocabulary;
 use crate::TokenId;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap};
 
+/// Maximum decoded output size (100 MB, Issue R3#8).
+const MAX_DECODED_SIZE: usize = 100 * 1024 * 1024;
+
+/// Approximate input bytes per work item in [`BPETokenizer::encode_parallel`].
+///
+/// Large enough that per-chunk overhead (a Rayon task and one output `Vec`)
+/// is negligible next to the merge work, small enough that a few dozen KiB
+/// of text already spreads across several threads.
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK_BYTES: usize = 16 * 1024;
+
 /// Symbol representing a text fragment during BPE merging
 #[derive(Debug, Clone)]
 struct Symbol {
@@ -84,8 +97,259 @@ struct BPEPreparedState {
     merge_ranks: HashMap<(TokenId, TokenId), usize>,
     /// llama3 `ignore_merges` optimization flag, derived from the pre-type.
     ignore_merges: bool,
+    /// [`Vocabulary::byte_fallback`], read once: whether an unmatched symbol
+    /// is split into single-byte pieces before falling back to UNK.
+    byte_fallback: bool,
+    /// See [`PreTokenizer::splits_before_space`].
+    splits_before_space: bool,
 }
 
+/// Pre-tokenization patterns shared by one or more `tokenizer.ggml.pre` names.
+///
+/// Patterns are applied **sequentially** (not as alternates in a single regex).
+/// This matches llama.cpp's implementation where each pattern refines the tokenization boundaries.
+///
+/// # Multi-Pattern Strategy
+/// For models with multiple patterns (e.g., DeepSeek-LLM with 6 patterns):
+/// 1. Start with full text as single fragment
+/// 2. Apply pattern 1: split matches vs non-matches (preserve both)
+/// 3. Apply pattern 2 to each fragment from step 2, further refining
+/// 4. Continue until all patterns applied
+///
+/// This differs from single-pattern models (GPT-2, Llama-3) that match directly.
+///
+/// # Reference
+/// Based on llama.cpp `llama-vocab.cpp` lines 300-450 (model-specific pattern definitions)
+/// and `unicode.cpp` `unicode_regex_split_stl()` for sequential application logic.
+struct PreTokenizer {
+    names: &'static [&'static str],
+    patterns: &'static [&'static str],
+    /// Whether the patterns always start a new fragment at a space that
+    /// follows a non-whitespace character.
+    ///
+    /// Merges never cross a fragment, so text before such a space encodes
+    /// the same whatever follows it; [`crate::Tokenizer::encode_incremental`]
+    /// relies on this. It holds when some alternative of the last pattern
+    /// matches starting at any space and none consumes a non-whitespace
+    /// character followed by a space. Checked against the patterns by
+    /// `pre_tokenizer_space_flags_match_patterns`.
+    splits_before_space: bool,
+}
+
+/// Known pre-tokenizers, looked up by name in [`BPETokenizer::pre_tokenizer`].
+const PRE_TOKENIZERS: &[PreTokenizer] = &[
+    // Llama-3 family
+    PreTokenizer {
+        names: &["llama3", "llama-v3", "llama-bpe"],
+        patterns: &[
+            r"(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
+        ],
+        splits_before_space: true,
+    },
+    // DeepSeek family (patterns from llama.cpp llama-vocab.cpp lines 323-332)
+    // Note: Original deepseek-llm pattern has explicit Unicode ranges that include astral plane
+    // characters (e.g., 𐐀-𐑏) which Rust's regex crate doesn't fully support.
+    // We use \p{L} as a practical approximation that covers most use cases.
+    // Neither deepseek-llm nor deepseek-coder has a catch-all pattern, so
+    // symbols outside their classes (e.g. emoji, or `€` for deepseek-coder)
+    // form gaps across spaces.
+    PreTokenizer {
+        names: &["deepseek-llm"],
+        patterns: &[
+            r"[\r\n]",
+            r"\s?\p{L}+", // Simplified from explicit Unicode ranges
+            r"\s?[!-/:-~！-／：-～'-‟　-。]+",
+            r"\s+$",
+            r"[一-龥ࠀ-一가-퟿]+",
+            r"\p{N}+",
+        ],
+        splits_before_space: false,
+    },
+    PreTokenizer {
+        names: &["deepseek-coder"],
+        patterns: &[
+            r"[\r\n]",
+            r"\s?\p{L}+",
+            r"\s?\p{P}+",
+            r"[一-龥ࠀ-一가-퟿]+",
+            r"\p{N}",
+        ],
+        splits_before_space: false,
+    },
+    PreTokenizer {
+        names: &["deepseek-v3"],
+        patterns: &[
+            r"\p{N}{1,3}",
+            r"[一-龥぀-ゟ゠-ヿ]+",
+            r"[!#$%&'()*+,\-./:;<=>?@\[\\\]^_`{|}~][A-Za-z]+|[^\r\n\p{L}\p{P}\p{S}]?[\p{L}\p{M}]+| ?[\p{P}\p{S}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
+        ],
+        splits_before_space: true,
+    },
+    PreTokenizer {
+        names: &["deepseek-r1-qwen"],
+        patterns: &[
+            r"(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
+        ],
+        splits_before_space: true,
+    },
+    // Falcon
+    PreTokenizer {
+        names: &["falcon"],
+        patterns: &[r"\n| ?[\p{L}\p{N}]+| ?[^\s\p{L}\p{N}]+|\s+"],
+        splits_before_space: true,
+    },
+    // StarCoder family (TWO patterns!)
+    PreTokenizer {
+        names: &[
+            "starcoder",
+            "refact",
+            "command-r",
+            "smollm",
+            "codeshell",
+            "exaone",
+            "minerva",
+        ],
+        patterns: &[
+            r"\p{N}", // First: split individual digits
+            r"'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)",
+        ],
+        splits_before_space: true,
+    },
+    // GPT-2 family
+    PreTokenizer {
+        names: &[
+            "gpt-2",
+            "phi-2",
+            "jina-es",
+            "jina-de",
+            "mpt",
+            "olmo",
+            "jais",
+            "trillion",
+            "granite-docling",
+            "exaone4",
+        ],
+        patterns: &[r"'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)"],
+        splits_before_space: true,
+    },
+    // Qwen2 family
+    PreTokenizer {
+        names: &["qwen2", "stablelm2", "hunyuan", "megrez"],
+        patterns: &[
+            r"(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
+        ],
+        splits_before_space: true,
+    },
+    // Bloom family
+    PreTokenizer {
+        names: &["bloom", "poro-chat", "gpt3-finnish"],
+        patterns: &[r"\s+|\S+"],
+        splits_before_space: true,
+    },
+    // ChatGLM — same pattern as Llama-3 but with 1–3 digit numeric chunks
+    PreTokenizer {
+        names: &["chatglm4", "glm4", "chatglm-bpe", "dbrx", "smaug-bpe"],
+        patterns: &[
+            r"(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
+        ],
+        splits_before_space: true,
+    },
+    // Norwegian. Runs of `.`, `,` and similar are left as gaps, so a gap
+    // such as ", ," spans a space.
+    PreTokenizer {
+        names: &["viking"],
+        patterns: &[r" ?[^(\s|.,!?…。，、।۔،)]+"],
+        splits_before_space: false,
+    },
+    // Advanced/Specialized
+    PreTokenizer {
+        names: &["tekken"],
+        patterns: &[
+            r"[^\r\n\p{L}\p{N}]?((?=[\p{L}])([^a-z]))*((?=[\p{L}])([^A-Z]))+|[^\r\n\p{L}\p{N}]?((?=[\p{L}])([^a-z]))+((?=[\p{L}])([^A-Z]))*|\p{N}| ?[^\s\p{L}\p{N}]+[\r\n/]*|\s*[\r\n]+|\s+(?!\S)|\s+",
+        ],
+        splits_before_space: true,
+    },
+    PreTokenizer {
+        names: &["chameleon"],
+        patterns: &[
+            r"<sentinel:[0-9]+>|(IMGIMG)((A|B|C|D|E|F|G|H|I){1,4})Z|([\t\n]|    |  )|\p{N}|[\p{P}!-/:-@\[-`{-~]|'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)",
+        ],
+        splits_before_space: true,
+    },
+    PreTokenizer {
+        names: &["gpt-4o", "llama4"],
+        patterns: &[
+            r"[^\r\n\p{L}\p{N}]?((?=[\p{L}])([^a-z]))*((?=[\p{L}])([^A-Z]))+(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])?|[^\r\n\p{L}\p{N}]?((?=[\p{L}])([^a-z]))+((?=[\p{L}])([^A-Z]))*(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])?|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n/]*|\s*[\r\n]+|\s+(?!\S)|\s+",
+        ],
+        splits_before_space: true,
+    },
+    // llama.cpp registers `\p{Han}+` only as a trigger for its custom
+    // K2 splitter, which implements this pattern from Kimi's
+    // `tokenization_kimi.py`: Han runs, then GPT-4o-style words over
+    // non-Han letters, 1–3 digit groups, punctuation and whitespace.
+    PreTokenizer {
+        names: &["kimi-k2"],
+        patterns: &[
+            r"[\p{Han}]+|[^\r\n\p{L}\p{N}]?[\p{Lu}\p{Lt}\p{Lm}\p{Lo}\p{M}&&[^\p{Han}]]*[\p{Ll}\p{Lm}\p{Lo}\p{M}&&[^\p{Han}]]+(?i:'s|'t|'re|'ve|'m|'ll|'d)?|[^\r\n\p{L}\p{N}]?[\p{Lu}\p{Lt}\p{Lm}\p{Lo}\p{M}&&[^\p{Han}]]+[\p{Ll}\p{Lm}\p{Lo}\p{M}&&[^\p{Han}]]*(?i:'s|'t|'re|'ve|'m|'ll|'d)?|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
+        ],
+        splits_before_space: true,
+    },
+    // Two passes, as in llama.cpp: a single alternation would let
+    // `\p{N}+` win at the start of every number. The zero-width
+    // lookahead then cuts each digit run into groups of three counted
+    // from the right ("1000000" → "1", "000", "000"). Only digits are
+    // split, so words keep their following spaces.
+    PreTokenizer {
+        names: &["superbpe"],
+        patterns: &[r"\p{N}+", r"(?=(\d{3})+(?!\d))"],
+        splits_before_space: false,
+    },
+    PreTokenizer {
+        names: &["bailingmoe", "bailingmoe2", "llada-moe"],
+        patterns: &[
+            r"'(?:[sSdDmMtT]|[lL][lL]|[vV][eE]|[rR][eE])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]|\s+(?!\S)|\s+",
+        ],
+        splits_before_space: true,
+    },
+    PreTokenizer {
+        names: &["seed-coder"],
+        patterns: &[
+            r"(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1}| ?[^\s\p{L}\p{N}\r\n]+|\s*[\r\n]+|\s+(?!\S)|\s+",
+        ],
+        splits_before_space: true,
+    },
+    PreTokenizer {
+        names: &["hunyuan-dense"],
+        patterns: &[
+            r"\p{N}{1,3}",
+            r"[一-龥぀-ゟ゠-ヿ]+",
+            r"[!#$%&'()*+,\-./:;<=>?@\[\\\]^_`{|}~][A-Za-z]+|[^\r\n\p{L}\p{P}\p{S}]?[\p{L}\p{M}]+| ?[\p{P}\p{S}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
+        ],
+        splits_before_space: true,
+    },
+    PreTokenizer {
+        names: &["grok-2"],
+        patterns: &[
+            r"(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
+        ],
+        splits_before_space: true,
+    },
+];
+
+/// Default pre-tokenizer (from llama.cpp lines 439-446).
+/// Used when the model file doesn't specify a pre-tokenizer type, or names
+/// one not in [`PRE_TOKENIZERS`]. Four patterns matching llama.cpp exactly.
+const DEFAULT_PRE_TOKENIZER: PreTokenizer = PreTokenizer {
+    names: &["default"],
+    patterns: &[
+        r"[\p{P}\$\+<=>^~\|]+",
+        r"'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)",
+        r"\p{N}+",
+        r"[0-9][0-9][0-9]",
+    ],
+    splits_before_space: true,
+};
+
 pub struct BPETokenizer {
     prepared: BPEPreparedState,
 }
@@ -101,9 +365,9 @@ impl BPETokenizer {
         let pre_type = vocab.pre_type().unwrap_or("default");
 
         // Compile the pre-tokenization regexes once.
-        let patterns = Self::get_patterns(pre_type);
-        let mut regexes = Vec::with_capacity(patterns.len());
-        for pattern in patterns {
+        let pre_tokenizer = Self::pre_tokenizer(pre_type);
+        let mut regexes = Vec::with_capacity(pre_tokenizer.patterns.len());
+        for pattern in pre_tokenizer.patterns {
             let regex = fancy_regex::Regex::new(pattern).map_err(|e| {
                 crate::Error::TokenizationFailed(format!(
                     "Failed to compile regex for '{pre_type}': {e}"
@@ -135,139 +399,28 @@ impl BPETokenizer {
                 regexes,
                 merge_ranks,
                 ignore_merges,
+                byte_fallback: vocab.byte_fallback(),
+                splits_before_space: pre_tokenizer.splits_before_space,
             },
         })
     }
 
-    /// Get pre-tokenization regex patterns for a given model type.
-    ///
-    /// Returns patterns that are applied **sequentially** (not as alternates in a single regex).
-    /// This matches llama.cpp's implementation where each pattern refines the tokenization boundaries.
-    ///
-    /// # Multi-Pattern Strategy
-    /// For models with multiple patterns (e.g., DeepSeek-LLM with 6 patterns):
-    /// 1. Start with full text as single fragment
-    /// 2. Apply pattern 1: split matches vs non-matches (preserve both)
-    /// 3. Apply pattern 2 to each fragment from step 2, further refining
-    /// 4. Continue until all patterns applied
-    ///
-    /// This differs from single-pattern models (GPT-2, Llama-3) that match directly.
-    ///
-    /// # Reference
-    /// Based on llama.cpp `llama-vocab.cpp` lines 300-450 (model-specific pattern definitions)
-    /// and `unicode.cpp` `unicode_regex_split_stl()` for sequential application logic.
-    fn get_patterns(pre_type: &str) -> Vec<&'static str> {
-        match pre_type {
-            // Llama-3 family
-            "llama3" | "llama-v3" | "llama-bpe" => vec![
-                r"(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
-            ],
-
-            // DeepSeek family (patterns from llama.cpp llama-vocab.cpp lines 323-332)
-            // Note: Original deepseek-llm pattern has explicit Unicode ranges that include astral plane
-            // characters (e.g., 𐐀-𐑏) which Rust's regex crate doesn't fully support.
-            // We use \p{L} as a practical approximation that covers most use cases.
-            "deepseek-llm" => vec![
-                r"[\r\n]",
-                r"\s?\p{L}+", // Simplified from explicit Unicode ranges
-                r"\s?[!-/:-~！-／：-～'-‟　-。]+",
-                r"\s+$",
-                r"[一-龥ࠀ-一가-퟿]+",
-                r"\p{N}+",
-            ],
-            "deepseek-coder" => vec![
-                r"[\r\n]",
-                r"\s?\p{L}+",
-                r"\s?\p{P}+",
-                r"[一-龥ࠀ-一가-퟿]+",
-                r"\p{N}",
-            ],
-            "deepseek-v3" => vec![
-                r"\p{N}{1,3}",
-                r"[一-龥぀-ゟ゠-ヿ]+",
-                r"[!#$%&'()*+,\-./:;<=>?@\[\\\]^_`{|}~][A-Za-z]+|[^\r\n\p{L}\p{P}\p{S}]?[\p{L}\p{M}]+| ?[\p{P}\p{S}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
-            ],
-            "deepseek-r1-qwen" => vec![
-                r"(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
-            ],
-
-            // Falcon
-            "falcon" => vec![r"\n| ?[\p{L}\p{N}]+| ?[^\s\p{L}\p{N}]+|\s+"],
-
-            // StarCoder family (TWO patterns!)
-            "starcoder" | "refact" | "command-r" | "smollm" | "codeshell" | "exaone"
-            | "minerva" => vec![
-                r"\p{N}", // First: split individual digits
-                r"'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)",
-            ],
-
-            // GPT-2 family
-            "gpt-2" | "phi-2" | "jina-es" | "jina-de" | "mpt" | "olmo" | "jais" | "trillion"
-            | "granite-docling" | "exaone4" => {
-                vec![r"'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)"]
-            }
-
-            // Qwen2 family
-            "qwen2" | "stablelm2" | "hunyuan" | "megrez" => vec![
-                r"(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
-            ],
-
-            // Bloom family
-            "bloom" | "poro-chat" | "gpt3-finnish" => vec![r"\s+|\S+"],
-
-            // ChatGLM — same pattern as Llama-3 but with 1–3 digit numeric chunks
-            "chatglm4" | "glm4" | "chatglm-bpe" | "dbrx" | "smaug-bpe" => vec![
-                r"(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
-            ],
-
-            // Norwegian
-            "viking" => vec![r" ?[^(\s|.,!?…。，、।۔،)]+"],
-
-            // Advanced/Specialized
-            "tekken" => vec![
-                r"[^\r\n\p{L}\p{N}]?((?=[\p{L}])([^a-z]))*((?=[\p{L}])([^A-Z]))+|[^\r\n\p{L}\p{N}]?((?=[\p{L}])([^a-z]))+((?=[\p{L}])([^A-Z]))*|\p{N}| ?[^\s\p{L}\p{N}]+[\r\n/]*|\s*[\r\n]+|\s+(?!\S)|\s+",
-            ],
-            "chameleon" => vec![
-                r"<sentinel:[0-9]+>|(IMGIMG)((A|B|C|D|E|F|G|H|I){1,4})Z|([\t\n]|    |  )|\p{N}|[\p{P}!-/:-@\[-`{-~]|'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)",
-            ],
-            "gpt-4o" | "llama4" => vec![
-                r"[^\r\n\p{L}\p{N}]?((?=[\p{L}])([^a-z]))*((?=[\p{L}])([^A-Z]))+(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])?|[^\r\n\p{L}\p{N}]?((?=[\p{L}])([^a-z]))+((?=[\p{L}])([^A-Z]))*(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])?|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n/]*|\s*[\r\n]+|\s+(?!\S)|\s+",
-            ],
-            "kimi-k2" => vec![r"\p{Han}+"],
-            "superbpe" => vec![r"\p{N}+|(?=(\d{3})+(?!\d))"],
-            "bailingmoe" | "bailingmoe2" | "llada-moe" => vec![
-                r"'(?:[sSdDmMtT]|[lL][lL]|[vV][eE]|[rR][eE])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]|\s+(?!\S)|\s+",
-            ],
-            "seed-coder" => vec![
-                r"(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1}| ?[^\s\p{L}\p{N}\r\n]+|\s*[\r\n]+|\s+(?!\S)|\s+",
-            ],
-            "hunyuan-dense" => vec![
-                r"\p{N}{1,3}",
-                r"[一-龥぀-ゟ゠-ヿ]+",
-                r"[!#$%&'()*+,\-./:;<=>?@\[\\\]^_`{|}~][A-Za-z]+|[^\r\n\p{L}\p{P}\p{S}]?[\p{L}\p{M}]+| ?[\p{P}\p{S}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
-            ],
-            "grok-2" => vec![
-                r"(?:'[sS]|'[tT]|'[rR][eE]|'[vV][eE]|'[mM]|'[lL][lL]|'[dD])|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+",
-            ],
-
-            // Default case (from llama.cpp lines 439-446)
-            // Used when model file doesn't specify pre-tokenizer type.
-            // Four patterns matching llama.cpp exactly.
-            _ => vec![
-                r"[\p{P}\$\+<=>^~\|]+",
-                r"'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)",
-                r"\p{N}+",
-                r"[0-9][0-9][0-9]",
-            ],
-        }
+    /// Look up the pre-tokenizer for `pre_type`, falling back to
+    /// [`DEFAULT_PRE_TOKENIZER`] for unrecognised names.
+    fn pre_tokenizer(pre_type: &str) -> &'static PreTokenizer {
+        PRE_TOKENIZERS
+            .iter()
+            .find(|p| p.names.contains(&pre_type))
+            .unwrap_or(&DEFAULT_PRE_TOKENIZER)
     }
 
     /// Pre-tokenize text into fragments using sequential regex pattern matching.
     ///
     /// # Algorithm
     /// Implements llama.cpp's offset-based approach (`unicode_regex_split_stl`):
-    /// - Single pattern: Direct regex matching (fast path)
-    /// - Multiple patterns: Sequential refinement preserving both matches AND gaps
+    /// each pattern in turn refines the current fragments, keeping both its
+    /// matches AND the gaps between them. A single-pattern model is simply
+    /// one refinement pass.
     ///
     /// ## Multi-Pattern Example
     /// Text: "Hello123World"
@@ -283,76 +436,32 @@ impl BPETokenizer {
     ///
     /// # Reference
     /// llama.cpp `unicode.cpp` lines 531-563 (`unicode_regex_split_stl`)
-    fn pre_tokenize(&self, text: &str) -> Vec<String> {
-        let regexes = &self.prepared.regexes;
-
-        if regexes.len() == 1 {
-            // Fast path for single-pattern models (most common)
-            return regexes[0]
-                .find_iter(text)
-                .filter_map(std::result::Result::ok)
-                .map(|m| m.as_str().to_string())
-                .collect();
-        }
-
-        // For multiple patterns, use offset-based approach like llama.cpp
-        // Each pattern refines the boundaries, preserving both matches and non-matches
+    fn pre_tokenize(&self, text: &str) -> Vec<(usize, usize)> {
         let mut offsets: Vec<(usize, usize)> = vec![(0, text.len())];
 
-        for regex in regexes {
-            let mut new_offsets = Vec::new();
-
+        for regex in &self.prepared.regexes {
+            let mut new_offsets = Vec::with_capacity(offsets.len());
             for (start, end) in offsets {
-                let fragment = &text[start..end];
-
-                let matches: Vec<_> = regex
-                    .find_iter(fragment)
-                    .filter_map(std::result::Result::ok)
-                    .collect();
-
-                if matches.is_empty() {
-                    // No matches - keep the original offset unchanged
-                    new_offsets.push((start, end));
-                } else {
-                    // Split into matched and unmatched regions
-                    let mut last_pos = 0;
-
-                    for m in matches {
-                        if m.start() > last_pos {
-                            new_offsets.push((start + last_pos, start + m.start()));
-                        }
-
-                        new_offsets.push((start + m.start(), start + m.end()));
-                        last_pos = m.end();
-                    }
-
-                    if last_pos < fragment.len() {
-                        new_offsets.push((start + last_pos, end));
-                    }
-                }
+                split_keeping_gaps(regex, text, start, end, &mut new_offsets);
             }
-
             offsets = new_offsets;
         }
 
         offsets
-            .iter()
-            .map(|(start, end)| text[*start..*end].to_string())
-            .collect()
     }
 
     /// Apply Byte Pair Encoding merge algorithm to a single text fragment.
     ///
     /// # Algorithm Overview (from llama.cpp)
     /// 1. **Initialize symbols**: Split text into UTF-8 characters as initial symbols
-    /// 2. **Build merge rank map**: Create (left, right) → priority mapping from vocab
+    /// 2. **Borrow merge rank map**: (left_id, right_id) → priority, prepared at load time
     /// 3. **Create work queue**: Priority queue of all adjacent bigram candidates
     /// 4. **Merge loop**: Pop highest-priority bigram, validate, merge, add new neighbors
     /// 5. **Convert to tokens**: Map final symbols to token IDs (with byte fallback)
     ///
     /// # Data Structures
     /// - `Symbol`: Text fragment with byte position + doubly-linked list pointers
-    /// - `Bigram`: Merge candidate with (left_idx, right_idx, rank, text)
+    /// - `Bigram`: Merge candidate with (left_idx, right_idx, rank); no text is copied
     /// - `BinaryHeap`: Priority queue ordered by merge rank (lower rank = higher priority)
     ///
     /// # Merge Validation
@@ -469,12 +578,15 @@ impl BPETokenizer {
                 let token_text = &text[sym.text_start..sym.text_start + sym.text_len];
                 if let Some(id) = vocab.get_token_id(token_text) {
                     result.push(id);
+                } else if !self.prepared.byte_fallback {
+                    result.push(vocab.unk_token_id());
                 } else {
                     // Byte fallback: look up each byte-encoded character individually.
                     // NOT using hex format <0xXX> — that's SentencePiece only.
+                    let mut buf = [0u8; 4];
                     for byte_char in token_text.chars() {
-                        let byte_str = byte_char.to_string();
-                        if let Some(id) = vocab.get_token_id(&byte_str) {
+                        let byte_str = byte_char.encode_utf8(&mut buf);
+                        if let Some(id) = vocab.get_token_id(byte_str) {
                             result.push(id);
                         } else {
                             result.push(vocab.unk_token_id());
@@ -508,45 +620,143 @@ impl BPETokenizer {
         // Regexes were compiled once at construction — no lock, no clone here.
         let fragments = self.pre_tokenize(text);
 
-        // Apply BPE to each fragment (after GPT-2 byte-encoding). Merge ranks
-        // and the ignore-merges flag were prepared once at construction.
         let mut result = Vec::new();
-        let ignore_merges = self.prepared.ignore_merges;
-        for fragment in fragments {
-            let fragment_encoded = crate::byte_encoder::encode_bytes(&fragment);
-            // llama.cpp `tokenizer_ignore_merges` optimization: if the whole
-            // byte-encoded fragment is already a single vocabulary token, emit
-            // it directly without running the merge algorithm. This is required
-            // for llama3/llama-bpe models where many multi-char tokens exist
-            // that have no explicit merge rules (e.g. "Ġ{" = token 314).
-            if ignore_merges {
-                if let Some(tok_id) = vocab.get_token_id(&fragment_encoded) {
-                    result.push(tok_id);
-                    continue;
-                }
-            }
-            let tokens = self.bpe_fragment(&fragment_encoded, vocab)?;
-            if result.len() + tokens.len() > crate::MAX_OUTPUT_TOKENS {
+        for (start, end) in fragments {
+            self.encode_fragment(&text[start..end], vocab, &mut result)?;
+            if result.len() > crate::MAX_OUTPUT_TOKENS {
                 return Err(crate::Error::TokenizationFailed(format!(
                     "Output would exceed max tokens: {} (max: {})",
-                    result.len() + tokens.len(),
+                    result.len(),
                     crate::MAX_OUTPUT_TOKENS
                 )));
             }
-            result.extend(tokens);
         }
 
         Ok(result)
     }
 
+    /// Encode text to token IDs using BPE, merging fragments across the Rayon
+    /// thread pool.
+    ///
+    /// The text is pre-tokenized serially, then consecutive fragments are
+    /// grouped into chunks of about `PARALLEL_CHUNK_BYTES` and each chunk is
+    /// encoded on its own. Chunks only ever end on fragment boundaries, and
+    /// BPE merges never cross a fragment boundary, so the concatenated output
+    /// is identical to [`BPETokenizer::encode`]. Any failure (including the
+    /// output limit) is reported by re-running the serial path, so errors are
+    /// identical too.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`BPETokenizer::encode`].
+    #[cfg(feature = "parallel")]
+    pub fn encode_parallel(
+        &self,
+        text: &str,
+        vocab: &Vocabulary,
+    ) -> Result<Vec<TokenId>, crate::Error> {
+        if text.len() > crate::MAX_INPUT_SIZE || text.len() < 2 * PARALLEL_CHUNK_BYTES {
+            return self.encode(text, vocab);
+        }
+
+        let fragments = self.pre_tokenize(text);
+        let mut chunks = Vec::with_capacity(text.len() / PARALLEL_CHUNK_BYTES + 1);
+        let mut chunk_start = 0;
+        for (i, &(_, end)) in fragments.iter().enumerate() {
+            if end - fragments[chunk_start].0 >= PARALLEL_CHUNK_BYTES {
+                chunks.push(&fragments[chunk_start..=i]);
+                chunk_start = i + 1;
+            }
+        }
+        if chunk_start < fragments.len() {
+            chunks.push(&fragments[chunk_start..]);
+        }
+
+        let results: Vec<Result<Vec<TokenId>, crate::Error>> = chunks
+            .par_iter()
+            .map(|chunk| {
+                let mut tokens = Vec::new();
+                for &(start, end) in *chunk {
+                    self.encode_fragment(&text[start..end], vocab, &mut tokens)?;
+                }
+                Ok(tokens)
+            })
+            .collect();
+
+        let mut result = Vec::new();
+        for tokens in results {
+            match tokens {
+                Ok(tokens) if result.len() + tokens.len() <= crate::MAX_OUTPUT_TOKENS => {
+                    result.extend(tokens);
+                }
+                _ => return self.encode(text, vocab),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Apply BPE to one pre-tokenized fragment (after GPT-2 byte-encoding),
+    /// appending its tokens to `result`. Merge ranks and the ignore-merges
+    /// flag were prepared once at construction.
+    fn encode_fragment(
+        &self,
+        fragment: &str,
+        vocab: &Vocabulary,
+        result: &mut Vec<TokenId>,
+    ) -> Result<(), crate::Error> {
+        let fragment_encoded = crate::byte_encoder::encode_bytes(fragment);
+        // llama.cpp `tokenizer_ignore_merges` optimization: if the whole
+        // byte-encoded fragment is already a single vocabulary token, emit
+        // it directly without running the merge algorithm. This is required
+        // for llama3/llama-bpe models where many multi-char tokens exist
+        // that have no explicit merge rules (e.g. "Ġ{" = token 314).
+        if self.prepared.ignore_merges {
+            if let Some(tok_id) = vocab.get_token_id(&fragment_encoded) {
+                result.push(tok_id);
+                return Ok(());
+            }
+        }
+        result.extend(self.bpe_fragment(&fragment_encoded, vocab)?);
+        Ok(())
+    }
+
     /// Decode token IDs back to text.
     ///
+    /// Byte sequences that are not valid UTF-8 are replaced with U+FFFD; use
+    /// [`BPETokenizer::decode_bytes`] to inspect the raw bytes instead.
+    ///
     /// # Errors
     ///
     /// Returns [`crate::Error::InvalidToken`] if any token ID is not present in the
     /// vocabulary, or [`crate::Error::TokenizationFailed`] if the decoded output
     /// would exceed 100 MB.
     pub fn decode(&self, tokens: &[TokenId], vocab: &Vocabulary) -> Result<String, crate::Error> {
+        let bytes = self.decode_bytes(tokens, vocab)?;
+        let decoded = String::from_utf8_lossy(&bytes).into_owned();
+
+        // Validate final decoded size (Issue R3#8) - lossy replacement can expand
+        if decoded.len() > MAX_DECODED_SIZE {
+            return Err(crate::Error::TokenizationFailed(format!(
+                "Final decoded text too large: {} bytes (max: {})",
+                decoded.len(),
+                MAX_DECODED_SIZE
+            )));
+        }
+
+        Ok(decoded)
+    }
+
+    /// Decode token IDs back to the raw bytes they represent, without UTF-8
+    /// validation.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`BPETokenizer::decode`].
+    pub fn decode_bytes(
+        &self,
+        tokens: &[TokenId],
+        vocab: &Vocabulary,
+    ) -> Result<Vec<u8>, crate::Error> {
         for &id in tokens {
             if vocab.get_token_text(id).is_none() {
                 return Err(crate::Error::InvalidToken(format!(
@@ -555,16 +765,12 @@ impl BPETokenizer {
             }
         }
 
-        let byte_encoded_text: String = tokens
-            .iter()
-            .filter_map(|&id| vocab.get_token_text(id))
-            .collect::<Vec<_>>()
-            .join("");
-
-        let decoded = crate::byte_encoder::decode_bytes(&byte_encoded_text);
+        let mut decoded = Vec::new();
+        for piece in tokens.iter().filter_map(|&id| vocab.get_token_text(id)) {
+            crate::byte_encoder::decode_into(piece, &mut decoded);
+        }
 
         // Validate final decoded size (Issue R3#8) - decoding can expand
-        const MAX_DECODED_SIZE: usize = 100 * 1024 * 1024; // 100MB
         if decoded.len() > MAX_DECODED_SIZE {
             return Err(crate::Error::TokenizationFailed(format!(
                 "Final decoded text too large: {} bytes (max: {})",
@@ -577,6 +783,48 @@ impl BPETokenizer {
     }
 }
 
+/// Split `text[start..end]` by `regex`, pushing each match and each
+/// non-empty gap between matches onto `out`, so the pushed spans always
+/// cover the whole range.
+///
+/// Zero-width matches (lookaheads such as SuperBPE's digit grouping, or an
+/// alternative that can match nothing) only mark a split point, like the
+/// zero-length offsets llama.cpp's `unicode_regex_split_stl` emits and then
+/// skips: no empty span is ever pushed. `find_iter` resumes one character
+/// past an empty match, so the scan always advances; a match that would
+/// start before the previous one ended is treated as a runtime error.
+///
+/// A regex runtime error (e.g. fancy-regex's backtrack limit) ends the scan:
+/// the rest of the range is kept as one gap rather than dropped. The error
+/// must not be skipped, since `find_iter` would report it again forever.
+fn split_keeping_gaps(
+    regex: &fancy_regex::Regex,
+    text: &str,
+    start: usize,
+    end: usize,
+    out: &mut Vec<(usize, usize)>,
+) {
+    let mut last = start;
+    for m in regex.find_iter(&text[start..end]) {
+        let Ok(m) = m else { break };
+        let (m_start, m_end) = (start + m.start(), start + m.end());
+        debug_assert!(m_start >= last, "match {m_start}..{m_end} before {last}");
+        if m_start < last {
+            break;
+        }
+        if m_start > last {
+            out.push((last, m_start));
+        }
+        if m_end > m_start {
+            out.push((m_start, m_end));
+        }
+        last = m_end;
+    }
+    if last < end {
+        out.push((last, end));
+    }
+}
+
 /// Try to add a bigram to the work queue if the two symbols form a valid merge pair.
 ///
 /// Extracts the text of both symbols from `text` and looks up the merge rank.
@@ -612,11 +860,161 @@ fn try_add_bigram(
 }
 
 impl crate::TokenizerImpl for BPETokenizer {
+    fn algorithm(&self) -> &'static str {
+        "bpe"
+    }
+
     fn encode(&self, text: &str, vocab: &Vocabulary) -> Result<Vec<TokenId>, crate::Error> {
         BPETokenizer::encode(self, text, vocab)
     }
 
+    /// The byte-encoded pre-tokenizer fragments, separated by plain spaces
+    /// (which byte encoding never produces, since a space becomes `Ġ`).
+    fn normalize(&self, text: &str, _vocab: &Vocabulary) -> String {
+        self.pre_tokenize(text)
+            .into_iter()
+            .map(|(start, end)| crate::byte_encoder::encode_bytes(&text[start..end]))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn is_byte_level(&self) -> bool {
+        true
+    }
+
+    fn splits_before_space(&self) -> bool {
+        self.prepared.splits_before_space
+    }
+
+    fn fragments(&self, text: &str) -> Option<Vec<(usize, usize)>> {
+        Some(self.pre_tokenize(text))
+    }
+
+    #[cfg(feature = "parallel")]
+    fn encode_large(&self, text: &str, vocab: &Vocabulary) -> Result<Vec<TokenId>, crate::Error> {
+        BPETokenizer::encode_parallel(self, text, vocab)
+    }
+
+    /// One run per pre-tokenizer fragment: merges never cross a fragment,
+    /// so each can be encoded on its own.
+    fn encode_chunks<'a>(&'a self, text: &'a str, vocab: &'a Vocabulary) -> crate::TokenChunks<'a> {
+        if text.len() > crate::MAX_INPUT_SIZE {
+            return Box::new(std::iter::once_with(move || self.encode(text, vocab)));
+        }
+        Box::new(
+            std::iter::once_with(move || self.pre_tokenize(text)).flat_map(move |fragments| {
+                fragments.into_iter().map(move |(start, end)| {
+                    let mut tokens = Vec::new();
+                    self.encode_fragment(&text[start..end], vocab, &mut tokens)?;
+                    Ok(tokens)
+                })
+            }),
+        )
+    }
+
     fn decode(&self, tokens: &[TokenId], vocab: &Vocabulary) -> Result<String, crate::Error> {
         BPETokenizer::decode(self, tokens, vocab)
     }
+
+    fn decode_bytes(
+        &self,
+        tokens: &[TokenId],
+        vocab: &Vocabulary,
+    ) -> Result<Vec<u8>, crate::Error> {
+        BPETokenizer::decode_bytes(self, tokens, vocab)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_keeping_gaps, DEFAULT_PRE_TOKENIZER, PRE_TOKENIZERS};
+
+    fn split(pattern: &str, text: &str, start: usize, end: usize) -> Vec<String> {
+        let regex = fancy_regex::Regex::new(pattern).unwrap();
+        let mut spans = Vec::new();
+        split_keeping_gaps(&regex, text, start, end, &mut spans);
+
+        // Spans are non-empty, contiguous and cover exactly start..end.
+        let mut pos = start;
+        for &(s, e) in &spans {
+            assert_eq!(s, pos, "{pattern:?} on {text:?}: {spans:?}");
+            assert!(e > s, "{pattern:?} on {text:?}: empty span in {spans:?}");
+            pos = e;
+        }
+        assert_eq!(pos, end, "{pattern:?} on {text:?}: {spans:?}");
+
+        spans.iter().map(|&(s, e)| text[s..e].to_string()).collect()
+    }
+
+    #[test]
+    fn zero_width_matches_split_without_empty_fragments() {
+        let digits = r"(?=(\d{3})+(?!\d))";
+        assert_eq!(split(digits, "1000000", 0, 7), ["1", "000", "000"]);
+        assert_eq!(split(digits, "123456", 0, 6), ["123", "456"]);
+        assert_eq!(split(digits, "12", 0, 2), ["12"]);
+        assert_eq!(split(r"\b", "ab cd", 0, 5), ["ab", " ", "cd"]);
+    }
+
+    #[test]
+    fn patterns_matching_empty_everywhere_terminate() {
+        assert_eq!(split("", "abc", 0, 3), ["a", "b", "c"]);
+        assert_eq!(split("x*", "axxb", 0, 4), ["a", "xx", "b"]);
+        assert_eq!(split(r"\p{N}*|(?=a)", "a1b", 0, 3), ["a", "1", "b"]);
+        // Empty matches advance by whole characters.
+        assert_eq!(split("", "你好", 0, 6), ["你", "好"]);
+        assert!(split("", "", 0, 0).is_empty());
+    }
+
+    #[test]
+    fn subrange_offsets_are_absolute() {
+        assert_eq!(split(r"(?=(\d{3})+(?!\d))", "ab1000cd", 2, 6), ["1", "000"]);
+        assert_eq!(split("", "abcd", 1, 3), ["b", "c"]);
+    }
+
+    /// Whether pre-tokenizing `text` with `regexes` starts a fragment at
+    /// every space that follows a non-whitespace character.
+    fn splits_every_space(regexes: &[fancy_regex::Regex], text: &str) -> bool {
+        let mut spans = vec![(0, text.len())];
+        for regex in regexes {
+            let mut refined = Vec::new();
+            for (start, end) in spans {
+                split_keeping_gaps(regex, text, start, end, &mut refined);
+            }
+            spans = refined;
+        }
+
+        text.char_indices()
+            .zip(text.chars().skip(1))
+            .filter(|&((_, prev), next)| !prev.is_whitespace() && next == ' ')
+            .all(|((i, prev), _)| spans.iter().any(|&(s, _)| s == i + prev.len_utf8()))
+    }
+
+    #[test]
+    fn pre_tokenizer_space_flags_match_patterns() {
+        let probes = [
+            "Hello world, this is a test.",
+            "a, , b. . c! d? e",
+            "price: 100 € € and < 5 > 3",
+            "emoji 😀 😀 end",
+            "ab 1234 5 x1 y",
+            "it's 'quoted' (text) [x] {y}",
+            "tab\tthen space \nnewline end",
+            "你好 世界 こんにちは 한국어 a",
+            "café naïve Ünïcödé",
+        ];
+
+        for pre_tokenizer in PRE_TOKENIZERS.iter().chain([&DEFAULT_PRE_TOKENIZER]) {
+            let regexes: Vec<_> = pre_tokenizer
+                .patterns
+                .iter()
+                .map(|p| fancy_regex::Regex::new(p).unwrap())
+                .collect();
+            let observed = probes.iter().all(|text| splits_every_space(&regexes, text));
+            assert_eq!(
+                observed, pre_tokenizer.splits_before_space,
+                "{:?}: splits_before_space flag disagrees with its patterns",
+                pre_tokenizer.names
+            );
+        }
+    }
 }
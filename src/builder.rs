@@ -0,0 +1,195 @@
+//! Loading tokenizers with caller-supplied metadata overrides.
+//!
+//! [`Tokenizer::from_gguf_file`] trusts the GGUF metadata completely, but
+//! mislabeled files exist in the wild (e.g. a GPT-2 BPE vocabulary tagged
+//! `tokenizer.ggml.model = "llama"`, or missing BOS/EOS keys).
+//! [`TokenizerBuilder`] parses the file, lets the caller correct those
+//! fields, and only then picks a backend — no GGUF editing required.
+
+use crate::gguf::{self, GGUFMetadata, SpecialTokenIds};
+use crate::{Error, TokenId, Tokenizer, Vocabulary};
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+/// Builder for a [`Tokenizer`] whose detected configuration can be overridden.
+///
+/// Overrides are applied on top of the parsed metadata before any validation
+/// or backend selection, so the result behaves exactly as if the GGUF file had
+/// contained the overridden values.
+///
+/// # Example
+///
+/// ```no_run
+/// use shimmytok::TokenizerBuilder;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // A BPE vocabulary that was mislabeled as "llama" by its converter.
+/// let tokenizer = TokenizerBuilder::from_gguf_file("model.gguf")?
+///     .model_type("gpt2")
+///     .pre_type("gpt-2")
+///     .add_bos_token(false)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "a TokenizerBuilder does nothing until .build() is called"]
+pub struct TokenizerBuilder {
+    metadata: GGUFMetadata,
+}
+
+impl TokenizerBuilder {
+    /// Parse a GGUF file and start a builder from its metadata.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Tokenizer::from_gguf_file`], except that an unsupported model
+    /// type is only reported by [`build`](Self::build), after overrides.
+    pub fn from_gguf_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(Self {
+            metadata: gguf::load_metadata(path)?,
+        })
+    }
+
+    /// Parse a HuggingFace `tokenizer.json` and start a builder from it.
+    ///
+    /// Requires the `hf` feature.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Tokenizer::from_hf_json`].
+    #[cfg(feature = "hf")]
+    pub fn from_hf_json<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(Self {
+            metadata: crate::hf::load_metadata(path)?,
+        })
+    }
+
+    /// Parse a `.tiktoken` vocabulary and start a builder from it.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Tokenizer::from_tiktoken`].
+    pub fn from_tiktoken<P: AsRef<Path>>(
+        path: P,
+        special_tokens: &[(String, TokenId)],
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            metadata: crate::tiktoken::load_metadata(path, special_tokens)?,
+        })
+    }
+
+    /// Parse GGUF data from any [`Read`] source and start a builder.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Tokenizer::from_reader`].
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        Ok(Self {
+            metadata: gguf::load_metadata_from_reader(reader)?,
+        })
+    }
+
+    /// Parse GGUF data from a byte slice and start a builder.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Tokenizer::from_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            metadata: gguf::load_metadata_from_seekable(Cursor::new(bytes))?,
+        })
+    }
+
+    /// Override `tokenizer.ggml.model`, which selects the backend
+    /// (e.g. `"gpt2"`, `"llama"`, `"bert"`).
+    pub fn model_type(mut self, model_type: impl Into<String>) -> Self {
+        self.metadata.model_type = model_type.into();
+        self
+    }
+
+    /// Override `tokenizer.ggml.pre`, the BPE pre-tokenizer type
+    /// (e.g. `"gpt-2"`, `"llama3"`).
+    pub fn pre_type(mut self, pre_type: impl Into<String>) -> Self {
+        self.metadata.pre_type = Some(pre_type.into());
+        self
+    }
+
+    /// Override the BOS token ID.
+    pub fn bos_token_id(mut self, id: TokenId) -> Self {
+        self.metadata.special.bos = Some(id);
+        self
+    }
+
+    /// Override the EOS token ID.
+    pub fn eos_token_id(mut self, id: TokenId) -> Self {
+        self.metadata.special.eos = Some(id);
+        self
+    }
+
+    /// Override whether `encode(.., true)` prepends BOS.
+    pub fn add_bos_token(mut self, add: bool) -> Self {
+        self.metadata.flags.add_bos_token = add;
+        self
+    }
+
+    /// Override whether `encode(.., true)` appends EOS.
+    pub fn add_eos_token(mut self, add: bool) -> Self {
+        self.metadata.flags.add_eos_token = add;
+        self
+    }
+
+    /// Build the tokenizer with the overrides applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::VocabularyError`] if any special token ID, overridden
+    /// or read from the metadata, is outside the vocabulary,
+    /// [`Error::UnsupportedModel`] if the (possibly overridden) model type has
+    /// no backend, and otherwise the same errors as
+    /// [`Tokenizer::from_gguf_file`].
+    pub fn build(self) -> Result<Tokenizer, Error> {
+        let n_tokens = self.metadata.tokens.len();
+        // Destructured so that a new special token cannot skip this check.
+        let SpecialTokenIds {
+            bos,
+            eos,
+            unk,
+            pad,
+            eot,
+            eom,
+            eog,
+            sep,
+            cls,
+            nl,
+            fim_pre,
+            fim_suf,
+            fim_mid,
+            mask,
+        } = self.metadata.special;
+        for (name, id) in [
+            ("BOS", bos),
+            ("EOS", eos),
+            ("UNK", unk),
+            ("PAD", pad),
+            ("EOT", eot),
+            ("EOM", eom),
+            ("EOG", eog),
+            ("SEP", sep),
+            ("CLS", cls),
+            ("newline", nl),
+            ("FIM prefix", fim_pre),
+            ("FIM suffix", fim_suf),
+            ("FIM middle", fim_mid),
+            ("MASK", mask),
+        ] {
+            if let Some(id) = id.filter(|&id| id as usize >= n_tokens) {
+                return Err(Error::VocabularyError(format!(
+                    "{name} token ID {id} out of range for vocabulary of {n_tokens} tokens"
+                )));
+            }
+        }
+
+        let vocab = Vocabulary::from_metadata(self.metadata)?;
+        Tokenizer::from_vocab(vocab)
+    }
+}
@@ -18,75 +18,153 @@
 use std::collections::HashMap;
 use std::sync::OnceLock;
 
+/// Every mapped character is below this code point (`U+0100 + 67`), so the
+/// reverse table can be indexed directly by `char as usize`.
+const CHAR_TABLE_LEN: usize = 0x144;
+
+/// Byte → character table, computed at compile time.
+const BYTE_TO_CHAR: [char; 256] = build_byte_to_char();
+
+/// Character (by code point) → byte table; `None` for unmapped characters.
+const CHAR_TO_BYTE: [Option<u8>; CHAR_TABLE_LEN] = build_char_to_byte();
+
+/// Bytes that GPT-2 maps to themselves: printable ASCII, `¡`–`¬` and `®`–`ÿ`.
+const fn maps_to_itself(b: u8) -> bool {
+    matches!(b, b'!'..=b'~' | 0xA1..=0xAC | 0xAE..=0xFF)
+}
+
+/// The remaining bytes take consecutive code points from U+0100, in byte order.
+const fn build_byte_to_char() -> [char; 256] {
+    let mut table = ['\0'; 256];
+    let mut next_high = 0x100u32;
+    let mut b = 0;
+    while b < 256 {
+        let code_point = if maps_to_itself(b as u8) {
+            b as u32
+        } else {
+            next_high += 1;
+            next_high - 1
+        };
+        table[b] = match char::from_u32(code_point) {
+            Some(c) => c,
+            None => panic!("byte-level code points are valid chars"),
+        };
+        b += 1;
+    }
+    table
+}
+
+const fn build_char_to_byte() -> [Option<u8>; CHAR_TABLE_LEN] {
+    let mut table = [None; CHAR_TABLE_LEN];
+    let mut b = 0;
+    while b < 256 {
+        table[BYTE_TO_CHAR[b] as usize] = Some(b as u8);
+        b += 1;
+    }
+    table
+}
+
+/// Map one byte to its GPT-2 byte-level character.
+#[inline]
+#[must_use]
+pub fn byte_to_char(byte: u8) -> char {
+    BYTE_TO_CHAR[byte as usize]
+}
+
+/// Map a GPT-2 byte-level character back to its byte, or `None` if `c` is
+/// not one of the 256 mapped characters.
+#[inline]
+#[must_use]
+pub fn char_to_byte(c: char) -> Option<u8> {
+    CHAR_TO_BYTE.get(c as usize).copied().flatten()
+}
+
 /// Returns the GPT-2 byte-to-unicode mapping.
 ///
 /// This maps each byte value (0-255) to a unique Unicode character for byte-level BPE.
-/// The mapping is computed once and cached for subsequent calls.
+/// The mapping is computed once and cached for subsequent calls. Encoding and
+/// decoding use [`byte_to_char`] and [`char_to_byte`], which are plain array
+/// lookups; this map is kept for callers that want the whole table.
 ///
 /// # Returns
 ///
 /// A static reference to the byte-to-character mapping.
 pub fn bytes_to_unicode() -> &'static HashMap<u8, char> {
     static BYTE_ENCODER: OnceLock<HashMap<u8, char>> = OnceLock::new();
-    BYTE_ENCODER.get_or_init(|| {
-        // Start with printable ASCII and some extended characters
-        let mut bs: Vec<u32> = Vec::new();
-
-        // !"#$%&'()*+,-./0123456789:;<=>?@ABC...XYZ[\]^_`abc...xyz{|}~
-        bs.extend(u32::from(b'!')..=u32::from(b'~'));
-
-        // ¡¢£¤¥¦§¨©ª«
-        bs.extend(0xA1..=0xAC);
-
-        // ®¯°±²³´µ¶·¸¹º»¼½¾¿À...ÿ
-        bs.extend(0xAE..=0xFF);
-
-        let mut cs = bs.clone();
-        let mut n = 0;
-
-        // For bytes not in the printable set, map to high unicode (256+)
-        for b in 0u32..256u32 {
-            if !bs.contains(&b) {
-                bs.push(b);
-                cs.push(256 + n);
-                n += 1;
-            }
-        }
-
-        let mut result = HashMap::new();
-        for (byte_val, &unicode_val) in bs.iter().zip(cs.iter()) {
-            result.insert(*byte_val as u8, char::from_u32(unicode_val).unwrap());
-        }
-
-        result
-    })
+    BYTE_ENCODER.get_or_init(|| (0..=255u8).map(|b| (b, byte_to_char(b))).collect())
 }
 
 /// Get the reverse mapping (unicode char -> byte)
 pub fn unicode_to_bytes() -> &'static HashMap<char, u8> {
     static BYTE_DECODER: OnceLock<HashMap<char, u8>> = OnceLock::new();
-    BYTE_DECODER.get_or_init(|| bytes_to_unicode().iter().map(|(&k, &v)| (v, k)).collect())
+    BYTE_DECODER.get_or_init(|| (0..=255u8).map(|b| (byte_to_char(b), b)).collect())
+}
+
+/// The byte a SentencePiece byte token such as `<0x0A>` stands for, or
+/// `None` if `piece` is not exactly `<0x` + two hex digits + `>`.
+///
+/// This is the single parser for `<0xNN>` pieces; every backend and the
+/// decoder go through it.
+///
+/// ```
+/// use shimmytok::byte_encoder::parse_hex_byte_token;
+///
+/// assert_eq!(parse_hex_byte_token("<0x0A>"), Some(b'\n'));
+/// assert_eq!(parse_hex_byte_token("<0x0>"), None);
+/// ```
+#[must_use]
+pub fn parse_hex_byte_token(piece: &str) -> Option<u8> {
+    let hex = piece.strip_prefix("<0x")?.strip_suffix('>')?;
+    if hex.len() == 2 && hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        u8::from_str_radix(hex, 16).ok()
+    } else {
+        None
+    }
+}
+
+/// The SentencePiece byte token for `byte`, e.g. `<0x0A>` for `\n`.
+///
+/// Hex digits are uppercase, matching llama.cpp vocabularies; the inverse
+/// of [`parse_hex_byte_token`].
+#[must_use]
+pub fn format_hex_byte_token(byte: u8) -> String {
+    format!("<0x{byte:02X}>")
 }
 
 /// Encode text bytes to GPT-2 unicode representation
 #[must_use]
 pub fn encode_bytes(text: &str) -> String {
-    let byte_encoder = bytes_to_unicode();
-    text.bytes()
-        .map(|b| byte_encoder.get(&b).copied().unwrap_or('�'))
-        .collect()
+    text.bytes().map(byte_to_char).collect()
 }
 
 /// Decode GPT-2 byte-encoded text back to a regular string.
-/// Note: For very large inputs (>100MB), consider streaming to avoid memory spikes.
+///
+/// Invalid UTF-8 in the decoded bytes is replaced with `�`; valid output is
+/// returned without an extra copy.
 #[must_use]
 pub fn decode_bytes(text: &str) -> String {
-    let byte_decoder = unicode_to_bytes();
-    let bytes: Vec<u8> = text
-        .chars()
-        .filter_map(|c| byte_decoder.get(&c).copied())
-        .collect();
-    String::from_utf8_lossy(&bytes).into_owned()
+    String::from_utf8(decode_to_bytes(text))
+        .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned())
+}
+
+/// Decode GPT-2 byte-encoded text back to the raw bytes it represents.
+///
+/// Unlike [`decode_bytes`], the result is not validated as UTF-8, so callers
+/// can detect (rather than silently replace) invalid sequences. Characters
+/// outside the byte-level alphabet are dropped.
+#[must_use]
+pub fn decode_to_bytes(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    decode_into(text, &mut out);
+    out
+}
+
+/// Append the bytes that byte-encoded `text` represents to `out`.
+///
+/// One pass over the chars, each resolved by indexing the reverse table with
+/// its code point; lets callers decode several pieces into one buffer.
+pub(crate) fn decode_into(text: &str, out: &mut Vec<u8>) {
+    out.extend(text.chars().filter_map(char_to_byte));
 }
 
 #[cfg(test)]
@@ -100,6 +178,80 @@ mod tests {
         assert_eq!(encoded.chars().next().unwrap() as u32, 0x0120);
     }
 
+    /// OpenAI's `bytes_to_unicode`, transcribed as-is.
+    fn reference_table() -> HashMap<u8, char> {
+        let mut bs: Vec<u32> = Vec::new();
+        bs.extend(u32::from(b'!')..=u32::from(b'~'));
+        bs.extend(0xA1..=0xAC);
+        bs.extend(0xAE..=0xFF);
+        let mut cs = bs.clone();
+        let mut n = 0;
+        for b in 0u32..256u32 {
+            if !bs.contains(&b) {
+                bs.push(b);
+                cs.push(256 + n);
+                n += 1;
+            }
+        }
+        bs.iter()
+            .zip(&cs)
+            .map(|(&b, &c)| (b as u8, char::from_u32(c).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_tables_match_reference() {
+        let reference = reference_table();
+        assert_eq!(bytes_to_unicode(), &reference);
+        for b in 0..=255u8 {
+            let c = reference[&b];
+            assert_eq!(byte_to_char(b), c);
+            assert_eq!(char_to_byte(c), Some(b));
+            assert_eq!(unicode_to_bytes()[&c], b);
+        }
+    }
+
+    #[test]
+    fn test_unmapped_chars() {
+        for c in ['\0', ' ', '\u{AD}', '\u{144}', '▁', '😀'] {
+            assert_eq!(char_to_byte(c), None, "{c:?}");
+        }
+        assert_eq!(decode_to_bytes("Ġa😀b"), b" ab");
+        assert_eq!(decode_to_bytes("日Ā本ġ"), [0x00, 0x7F]);
+    }
+
+    #[test]
+    fn test_decode_matches_char_lookup() {
+        // Every byte-level char, plus unmapped chars of each UTF-8 width.
+        let mut text: String = (0..=255u8).map(byte_to_char).collect();
+        text.push_str(" \u{AD}\u{144}\u{7FF}€日😀");
+        let expected: Vec<u8> = text.chars().filter_map(char_to_byte).collect();
+        assert_eq!(decode_to_bytes(&text), expected);
+        assert_eq!(expected, (0..=255u8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_hex_byte_token_roundtrip() {
+        for b in 0..=255u8 {
+            let piece = format_hex_byte_token(b);
+            assert_eq!(piece.len(), 6);
+            assert_eq!(parse_hex_byte_token(&piece), Some(b), "{piece}");
+            assert_eq!(parse_hex_byte_token(&piece.to_ascii_lowercase()), Some(b));
+        }
+        assert_eq!(format_hex_byte_token(0x0A), "<0x0A>");
+        assert_eq!(format_hex_byte_token(0xFF), "<0xFF>");
+    }
+
+    #[test]
+    fn test_hex_byte_token_rejects_malformed() {
+        for piece in [
+            "", "<0x>", "<0x0>", "<0x0A", "0x0A>", "<0x0AB>", "<0X0A>", "<0xG0>", "<0x+A>",
+            "<0x A>", "< 0x0A>", "<0x0A> ", "<0x０A>", "<0x0A>>", "<<0x0A>",
+        ] {
+            assert_eq!(parse_hex_byte_token(piece), None, "{piece:?}");
+        }
+    }
+
     #[test]
     fn test_hello() {
         let encoded = encode_bytes("Hello");
@@ -0,0 +1,85 @@
+//! Chat template rendering (`chat` feature).
+//!
+//! GGUF chat models ship a Jinja2 template in `tokenizer.chat_template` that
+//! turns a list of messages into the exact prompt layout the model was
+//! trained on. This module renders it with [`minijinja`], configured like
+//! HuggingFace's `apply_chat_template`:
+//!
+//! - `trim_blocks` and `lstrip_blocks` are enabled, loop controls
+//!   (`break`/`continue`) are available
+//! - Python string and dict methods (`.strip()`, `.startswith()`,
+//!   `.items()`, ...) work through `minijinja-contrib`'s pycompat layer
+//! - `raise_exception(msg)` aborts rendering with `msg`
+//! - The context holds `messages`, `add_generation_prompt`, `bos_token` and
+//!   `eos_token`
+//!
+//! Tool-calling variables (`tools`, `documents`) are not provided; templates
+//! that require them fail with [`Error::TokenizationFailed`].
+
+use crate::Error;
+use minijinja::{context, Environment, ErrorKind, Value};
+
+/// One message of a conversation passed to
+/// [`Tokenizer::encode_chat`](crate::Tokenizer::encode_chat).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    /// Speaker role, e.g. `"system"`, `"user"` or `"assistant"`
+    pub role: String,
+    /// Message text
+    pub content: String,
+}
+
+impl ChatMessage {
+    /// Create a message from a role and its content.
+    #[must_use]
+    pub fn new(role: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            role: role.into(),
+            content: content.into(),
+        }
+    }
+}
+
+/// Render a Jinja2 chat template over `messages`.
+///
+/// `bos_token` and `eos_token` are the piece texts the template may print
+/// (`{{ bos_token }}`). With `add_generation_prompt`, templates that support
+/// it end with the header of an assistant turn.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidMetadata`] if the template does not parse, and
+/// [`Error::TokenizationFailed`] if rendering fails, including
+/// `raise_exception` calls (e.g. roles that do not alternate).
+pub fn render_chat_template(
+    template: &str,
+    messages: &[ChatMessage],
+    add_generation_prompt: bool,
+    bos_token: &str,
+    eos_token: &str,
+) -> Result<String, Error> {
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.set_unknown_method_callback(minijinja_contrib::pycompat::unknown_method_callback);
+    env.add_function("raise_exception", |msg: String| -> Result<Value, _> {
+        Err(minijinja::Error::new(ErrorKind::InvalidOperation, msg))
+    });
+
+    let tmpl = env
+        .template_from_str(template)
+        .map_err(|e| Error::InvalidMetadata(format!("Invalid chat template: {e}")))?;
+
+    let messages: Vec<Value> = messages
+        .iter()
+        .map(|m| context! { role => m.role, content => m.content })
+        .collect();
+
+    tmpl.render(context! {
+        messages,
+        add_generation_prompt,
+        bos_token,
+        eos_token,
+    })
+    .map_err(|e| Error::TokenizationFailed(format!("Chat template rendering failed: {e}")))
+}
@@ -6,6 +6,7 @@
 //!
 //! # Supported Versions
 //!
+//! - GGUF v1: Legacy format with 32-bit counts and lengths
 //! - GGUF v2: Standard format
 //! - GGUF v3: Extended format (used by GPT-2 and newer models)
 //!
@@ -13,8 +14,13 @@
 //!
 //! This parser includes protections against malicious files:
 //! - String allocation limits to prevent OOM attacks
-//! - Bounds checking on array sizes
+//! - All limits are adjustable through [`LoaderOptions`]
+//! - Array lengths are capped at [`MAX_ARRAY_LEN`] and, when the source size
+//!   is known, checked against the bytes remaining before any element is
+//!   read, including for skipped keys
 //! - Validation of file structure
+//! - Keys outside `tokenizer.*`/`general.*` are skipped by their computed
+//!   length, never allocated
 //!
 //! # Reference
 //!
@@ -24,9 +30,70 @@
 use crate::{Error, TokenType};
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
 
+/// Maximum element count accepted for a GGUF metadata array.
+///
+/// Vocabularies, scores, token types and merges are the largest arrays a
+/// tokenizer needs, and all of them stay well below this. Longer arrays are
+/// rejected with [`Error::InvalidMetadata`] before anything is allocated.
+pub const MAX_ARRAY_LEN: usize = 16 * 1024 * 1024;
+
+/// Most elements reserved up front for a metadata array.
+///
+/// The declared length cannot always be checked against the bytes left (see
+/// [`load_metadata_from_reader`]), so arrays start no larger than this and
+/// grow as elements are actually read.
+const INITIAL_ARRAY_CAPACITY: usize = 4096;
+
+/// Size limits enforced while loading a GGUF tokenizer.
+///
+/// The defaults are safe for untrusted files and fit every tokenizer
+/// llama.cpp ships. Raise them only for sources you trust, e.g. a vocabulary
+/// with more than a million tokens or very long added-token strings:
+///
+/// ```no_run
+/// use shimmytok::{LoaderOptions, Tokenizer};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let options = LoaderOptions {
+///     max_vocab_size: 4_000_000,
+///     ..LoaderOptions::default()
+/// };
+/// let tokenizer = Tokenizer::from_gguf_file_with_options("model.gguf", &options)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoaderOptions {
+    /// Maximum number of tokens in the vocabulary (default 1,000,000)
+    pub max_vocab_size: usize,
+    /// Maximum length of a single token piece in bytes (default 1024)
+    pub max_token_length: usize,
+    /// Maximum number of BPE merge rules (default 1,000,000)
+    pub max_merge_count: usize,
+    /// Maximum length of any metadata string in bytes (default 1 MiB)
+    pub max_string_size: usize,
+    /// Maximum total bytes of all metadata strings (default 100 MiB)
+    pub max_total_string_data: usize,
+    /// Maximum element count of a metadata array (default [`MAX_ARRAY_LEN`])
+    pub max_array_len: usize,
+}
+
+impl Default for LoaderOptions {
+    fn default() -> Self {
+        Self {
+            max_vocab_size: 1_000_000,
+            max_token_length: 1024,
+            max_merge_count: 1_000_000, // Issue R3#14
+            max_string_size: 1024 * 1024,
+            max_total_string_data: 100 * 1024 * 1024,
+            max_array_len: MAX_ARRAY_LEN,
+        }
+    }
+}
+
 /// Special token IDs loaded from GGUF metadata.
 ///
 /// Groups the full set of llama.cpp special tokens so they can be passed
@@ -39,8 +106,10 @@ pub struct SpecialTokenIds {
     pub unk: Option<u32>,
     pub pad: Option<u32>,
     pub eot: Option<u32>,
+    pub eom: Option<u32>,
     pub eog: Option<u32>,
     pub sep: Option<u32>,
+    pub cls: Option<u32>,
     pub nl: Option<u32>,
     pub fim_pre: Option<u32>,
     pub fim_suf: Option<u32>,
@@ -92,9 +161,26 @@ pub struct GGUFMetadata {
     ///
     /// [`shimmyjinja`]: https://crates.io/crates/shimmyjinja
     pub chat_template: Option<String>,
+    /// Named chat template variants (`tokenizer.chat_template.<name>`), keyed
+    /// by `<name>`, e.g. `tool_use` or `rag`.
+    pub chat_templates: HashMap<String, String>,
     pub special: SpecialTokenIds,
     pub flags: TokenizationFlags,
     pub merges: Option<Vec<(String, String)>>,
+    /// SentencePiece normalization rules (`tokenizer.ggml.precompiled_charsmap`)
+    /// used by UGM models, as the raw serialized blob.
+    pub precompiled_charsmap: Option<Vec<u8>>,
+    /// Declared embedding row count (`tokenizer.ggml.token_count`), for models
+    /// whose embedding matrix is padded past the token list.
+    pub token_count: Option<u64>,
+    /// Score of the UGM unknown-token path (`tokenizer.ggml.unknown_token_score`);
+    /// absent in most files, where it is derived from the vocabulary.
+    pub unknown_token_score: Option<f64>,
+    /// `general.*` metadata (architecture, name, quantization version, ...).
+    ///
+    /// Tokenizer keys are extracted into the typed fields above; all other
+    /// non-general keys are skipped during parsing.
+    pub metadata: HashMap<String, MetadataValue>,
 }
 
 /// Loads tokenizer metadata from a GGUF file at the given path.
@@ -108,8 +194,72 @@ pub struct GGUFMetadata {
 /// for malformed or unsupported GGUF data, and [`Error::VocabularyError`] if
 /// the tokenizer section is missing or inconsistent.
 pub fn load_metadata<P: AsRef<Path>>(path: P) -> Result<GGUFMetadata, Error> {
+    load_metadata_with_options(path, &LoaderOptions::default())
+}
+
+/// Loads tokenizer metadata from a GGUF file with custom size limits.
+///
+/// # Errors
+///
+/// Same as [`load_metadata`], with the limits taken from `options`.
+pub fn load_metadata_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &LoaderOptions,
+) -> Result<GGUFMetadata, Error> {
     let file = File::open(path)?;
-    load_metadata_from_reader(BufReader::new(file))
+    let size = file.metadata()?.len();
+    parse_metadata(BufReader::new(file), Some(size), options)
+}
+
+/// Loads tokenizer metadata from a memory-mapped GGUF file.
+///
+/// The file is mapped read-only and parsed straight from the mapped slice, so
+/// metadata bytes are copied once (into the owned token strings) rather than
+/// first through a `BufReader` buffer. Only the metadata pages at the start of
+/// the file are touched; tensor data is never faulted in.
+///
+/// Tradeoffs versus [`load_metadata`]: the parsed vocabulary is still owned,
+/// so peak memory is dominated by the token strings either way; the gain is
+/// fewer copies and syscalls. Mapping fails on sources that cannot be mapped
+/// (some network filesystems, pipes), where [`load_metadata`] still works.
+///
+/// # Concurrent modification
+///
+/// This is the crate's only `unsafe` code. The mapping is live only while
+/// this function parses, but the file must not be truncated or rewritten by
+/// another process during that time: a truncated mapping makes the read
+/// fault (`SIGBUS` on Unix), and bytes changing under the parser are
+/// undefined behavior. Use [`load_metadata`] for files that may be modified
+/// while they are loaded.
+///
+/// # Errors
+///
+/// Same as [`load_metadata`], plus [`Error::Io`] if the file cannot be mapped.
+#[cfg(feature = "mmap")]
+pub fn load_metadata_mmap<P: AsRef<Path>>(path: P) -> Result<GGUFMetadata, Error> {
+    load_metadata_mmap_with_options(path, &LoaderOptions::default())
+}
+
+/// Loads tokenizer metadata from a memory-mapped GGUF file with custom size
+/// limits.
+///
+/// The same caveat about concurrent modification as [`load_metadata_mmap`]
+/// applies.
+///
+/// # Errors
+///
+/// Same as [`load_metadata_mmap`], with the limits taken from `options`.
+#[cfg(feature = "mmap")]
+pub fn load_metadata_mmap_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &LoaderOptions,
+) -> Result<GGUFMetadata, Error> {
+    let file = File::open(path)?;
+    // SAFETY: the map is read-only and dropped before this function returns;
+    // every string is copied out of it during parsing. The file must not be
+    // truncated or modified concurrently, as documented above.
+    let map = unsafe { memmap2::Mmap::map(&file)? };
+    parse_metadata(&map[..], Some(map.len() as u64), options)
 }
 
 /// Loads tokenizer metadata from any [`Read`] source.
@@ -120,12 +270,44 @@ pub fn load_metadata<P: AsRef<Path>>(path: P) -> Result<GGUFMetadata, Error> {
 /// # Errors
 ///
 /// Same as [`load_metadata`].
-pub fn load_metadata_from_reader<R: Read>(mut reader: R) -> Result<GGUFMetadata, Error> {
+pub fn load_metadata_from_reader<R: Read>(reader: R) -> Result<GGUFMetadata, Error> {
+    parse_metadata(reader, None, &LoaderOptions::default())
+}
+
+/// Loads tokenizer metadata from a seekable source, starting at its current
+/// position.
+///
+/// Like [`load_metadata_from_reader`], but the bytes left in the source are
+/// measured up front so that declared array lengths can be checked against
+/// them: a header claiming more elements than the data could hold fails
+/// immediately instead of reserving memory for them.
+///
+/// # Errors
+///
+/// Same as [`load_metadata`].
+pub fn load_metadata_from_seekable<R: Read + Seek>(mut reader: R) -> Result<GGUFMetadata, Error> {
+    let start = reader.stream_position()?;
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(start))?;
+    parse_metadata(
+        reader,
+        Some(end.saturating_sub(start)),
+        &LoaderOptions::default(),
+    )
+}
+
+/// Parse GGUF metadata; `size` is the number of bytes left in `reader`, when
+/// known.
+fn parse_metadata<R: Read>(
+    reader: R,
+    size: Option<u64>,
+    options: &LoaderOptions,
+) -> Result<GGUFMetadata, Error> {
     /// Extract an optional `u32` from a kv-pair map.
     macro_rules! kv_u32 {
         ($map:expr, $key:expr) => {
             match $map.get($key) {
-                Some(Value::U32(v)) => Some(*v),
+                Some(MetadataValue::U32(v)) => Some(*v),
                 _ => None,
             }
         };
@@ -135,13 +317,17 @@ pub fn load_metadata_from_reader<R: Read>(mut reader: R) -> Result<GGUFMetadata,
     macro_rules! kv_bool {
         ($map:expr, $key:expr) => {
             match $map.get($key) {
-                Some(Value::Bool(v)) => Some(*v),
+                Some(MetadataValue::Bool(v)) => Some(*v),
                 _ => None,
             }
         };
     }
 
     let mut total_string_bytes: usize = 0;
+    let mut reader = Bounded {
+        inner: reader,
+        remaining: size,
+    };
 
     let mut magic = [0u8; 4];
     reader.read_exact(&mut magic)?;
@@ -151,25 +337,31 @@ pub fn load_metadata_from_reader<R: Read>(mut reader: R) -> Result<GGUFMetadata,
     }
 
     let version = read_u32(&mut reader)?;
-    if !(2..=3).contains(&version) {
+    if !(1..=3).contains(&version) {
         return Err(Error::InvalidMetadata(format!(
-            "Unsupported GGUF version: {version} (only versions 2-3 are supported)"
+            "Unsupported GGUF version: {version} (only versions 1-3 are supported)"
         )));
     }
 
-    let _tensor_count = read_u64(&mut reader)?;
-    let metadata_count = read_u64(&mut reader)?;
+    let _tensor_count = read_len(&mut reader, version)?;
+    let metadata_count = read_len(&mut reader, version)?;
 
     let mut kv_pairs = HashMap::new();
     for _ in 0..metadata_count {
-        let key = read_string(&mut reader, &mut total_string_bytes)?;
-        let value = read_value(&mut reader, &mut total_string_bytes)?;
-        kv_pairs.insert(key, value);
+        let key = read_string(&mut reader, version, &mut total_string_bytes, options)?;
+        if is_retained_key(&key) {
+            let value = read_value(&mut reader, version, &mut total_string_bytes, options)?;
+            kv_pairs.insert(key, value);
+        } else {
+            // Architecture-specific and tensor-layout keys are irrelevant here;
+            // step over their payload without materialising it.
+            skip_value(&mut reader, version)?;
+        }
     }
 
     // Extract tokenizer metadata
     let tokens = match kv_pairs.get("tokenizer.ggml.tokens") {
-        Some(Value::StringArray(arr)) => arr.clone(),
+        Some(MetadataValue::StringArray(arr)) => arr.clone(),
         _ => {
             return Err(Error::InvalidMetadata(
                 "Missing tokenizer.ggml.tokens".into(),
@@ -178,30 +370,42 @@ pub fn load_metadata_from_reader<R: Read>(mut reader: R) -> Result<GGUFMetadata,
     };
 
     let scores = match kv_pairs.get("tokenizer.ggml.scores") {
-        Some(Value::F32Array(arr)) => Some(arr.clone()),
+        Some(MetadataValue::F32Array(arr)) => Some(arr.clone()),
         _ => None,
     };
 
     let token_types = match kv_pairs.get("tokenizer.ggml.token_type") {
-        Some(Value::I32Array(arr)) => Some(arr.iter().map(|&t| TokenType::from(t)).collect()),
+        Some(MetadataValue::I32Array(arr)) => {
+            Some(arr.iter().map(|&t| TokenType::from(t)).collect())
+        }
         _ => None,
     };
 
     let model_type = match kv_pairs.get("tokenizer.ggml.model") {
-        Some(Value::String(s)) => s.clone(),
+        Some(MetadataValue::String(s)) => s.clone(),
         _ => "llama".to_string(), // Default
     };
 
     let pre_type = match kv_pairs.get("tokenizer.ggml.pre") {
-        Some(Value::String(s)) => Some(s.clone()),
+        Some(MetadataValue::String(s)) => Some(s.clone()),
         _ => None,
     };
 
     // Chat template — raw Jinja2 string, pass to shimmyjinja to render prompts
     let chat_template = match kv_pairs.get("tokenizer.chat_template") {
-        Some(Value::String(s)) => Some(s.clone()),
+        Some(MetadataValue::String(s)) => Some(s.clone()),
         _ => None,
     };
+    let chat_templates = kv_pairs
+        .iter()
+        .filter_map(|(key, value)| {
+            let name = key.strip_prefix("tokenizer.chat_template.")?;
+            match value {
+                MetadataValue::String(s) => Some((name.to_string(), s.clone())),
+                _ => None,
+            }
+        })
+        .collect();
 
     // Special tokens
     let special = SpecialTokenIds {
@@ -210,12 +414,21 @@ pub fn load_metadata_from_reader<R: Read>(mut reader: R) -> Result<GGUFMetadata,
         unk: kv_u32!(kv_pairs, "tokenizer.ggml.unknown_token_id"),
         pad: kv_u32!(kv_pairs, "tokenizer.ggml.padding_token_id"),
         eot: kv_u32!(kv_pairs, "tokenizer.ggml.eot_token_id"),
+        eom: kv_u32!(kv_pairs, "tokenizer.ggml.eom_token_id"),
         eog: kv_u32!(kv_pairs, "tokenizer.ggml.eog_token_id"),
-        sep: kv_u32!(kv_pairs, "tokenizer.ggml.sep_token_id"),
+        // llama.cpp's converter writes the misspelled `seperator_token_id`.
+        sep: kv_u32!(kv_pairs, "tokenizer.ggml.seperator_token_id")
+            .or(kv_u32!(kv_pairs, "tokenizer.ggml.separator_token_id"))
+            .or(kv_u32!(kv_pairs, "tokenizer.ggml.sep_token_id")),
+        cls: kv_u32!(kv_pairs, "tokenizer.ggml.cls_token_id"),
         nl: kv_u32!(kv_pairs, "tokenizer.ggml.nl_token_id"),
-        fim_pre: kv_u32!(kv_pairs, "tokenizer.ggml.fim_pre_token_id"),
-        fim_suf: kv_u32!(kv_pairs, "tokenizer.ggml.fim_suf_token_id"),
-        fim_mid: kv_u32!(kv_pairs, "tokenizer.ggml.fim_mid_token_id"),
+        // Older converters wrote the FIM IDs as prefix/suffix/middle.
+        fim_pre: kv_u32!(kv_pairs, "tokenizer.ggml.fim_pre_token_id")
+            .or(kv_u32!(kv_pairs, "tokenizer.ggml.prefix_token_id")),
+        fim_suf: kv_u32!(kv_pairs, "tokenizer.ggml.fim_suf_token_id")
+            .or(kv_u32!(kv_pairs, "tokenizer.ggml.suffix_token_id")),
+        fim_mid: kv_u32!(kv_pairs, "tokenizer.ggml.fim_mid_token_id")
+            .or(kv_u32!(kv_pairs, "tokenizer.ggml.middle_token_id")),
         mask: kv_u32!(kv_pairs, "tokenizer.ggml.mask_token_id"),
     };
 
@@ -235,7 +448,7 @@ pub fn load_metadata_from_reader<R: Read>(mut reader: R) -> Result<GGUFMetadata,
 
     // BPE merges
     let merges = match kv_pairs.get("tokenizer.ggml.merges") {
-        Some(Value::StringArray(arr)) => {
+        Some(MetadataValue::StringArray(arr)) => {
             let mut result = Vec::new();
             for merge_str in arr {
                 let parts: Vec<&str> = merge_str.split(' ').collect();
@@ -248,6 +461,27 @@ pub fn load_metadata_from_reader<R: Read>(mut reader: R) -> Result<GGUFMetadata,
         _ => None,
     };
 
+    // UGM normalization map; stored as u8 or i8 depending on the converter
+    let precompiled_charsmap = match kv_pairs.remove("tokenizer.ggml.precompiled_charsmap") {
+        Some(MetadataValue::U8Array(bytes)) => Some(bytes),
+        Some(MetadataValue::I8Array(bytes)) => Some(bytes.into_iter().map(|b| b as u8).collect()),
+        _ => None,
+    };
+
+    let token_count = kv_pairs
+        .get("tokenizer.ggml.token_count")
+        .and_then(MetadataValue::as_u64);
+
+    let unknown_token_score = kv_pairs
+        .get("tokenizer.ggml.unknown_token_score")
+        .and_then(MetadataValue::as_f64)
+        .filter(|score| score.is_finite());
+
+    let metadata = kv_pairs
+        .into_iter()
+        .filter(|(key, _)| key.starts_with("general."))
+        .collect();
+
     Ok(GGUFMetadata {
         tokens,
         scores,
@@ -255,32 +489,122 @@ pub fn load_metadata_from_reader<R: Read>(mut reader: R) -> Result<GGUFMetadata,
         model_type,
         pre_type,
         chat_template,
+        chat_templates,
         special,
         flags,
         merges,
+        precompiled_charsmap,
+        token_count,
+        unknown_token_score,
+        metadata,
     })
 }
 
 /// A typed value read from a GGUF metadata key-value pair.
 ///
-/// Only the variants needed to reconstruct a tokenizer are surfaced here.
+/// Every GGUF value type is parsed so that unrelated metadata (e.g.
+/// `general.file_type` or 64-bit counts) never aborts a load. Variants mirror
+/// the GGUF type table one-to-one; arrays are stored as typed vectors.
 /// Unrecognised type IDs return [`Error::InvalidMetadata`].
-#[derive(Debug)]
-enum Value {
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
     U32(u32),
-    #[allow(dead_code)] // read via pattern-match in read_value; Debug confuses the lint
     I32(i32),
-    #[allow(dead_code)] // read via pattern-match in read_value; Debug confuses the lint
     F32(f32),
     Bool(bool),
-    #[allow(dead_code)] // Retained to consume GGUF BOOL arrays not used by tokenization.
-    BoolArray(Vec<bool>),
     String(String),
-    StringArray(Vec<String>),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    U8Array(Vec<u8>),
+    I8Array(Vec<i8>),
+    U16Array(Vec<u16>),
+    I16Array(Vec<i16>),
+    U32Array(Vec<u32>),
     I32Array(Vec<i32>),
     F32Array(Vec<f32>),
-    #[allow(dead_code)]
-    U8Array(Vec<u8>),
+    BoolArray(Vec<bool>),
+    StringArray(Vec<String>),
+    U64Array(Vec<u64>),
+    I64Array(Vec<i64>),
+    F64Array(Vec<f64>),
+}
+
+impl MetadataValue {
+    /// The string payload, if this is a [`MetadataValue::String`].
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Any integer scalar widened to `u64`, or `None` for negative values and
+    /// non-integer types.
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Self::U8(v) => Some(u64::from(v)),
+            Self::U16(v) => Some(u64::from(v)),
+            Self::U32(v) => Some(u64::from(v)),
+            Self::U64(v) => Some(v),
+            Self::I8(v) => u64::try_from(v).ok(),
+            Self::I16(v) => u64::try_from(v).ok(),
+            Self::I32(v) => u64::try_from(v).ok(),
+            Self::I64(v) => u64::try_from(v).ok(),
+            _ => None,
+        }
+    }
+
+    /// A float scalar widened to `f64`, or `None` for non-float types.
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::F32(v) => Some(f64::from(v)),
+            Self::F64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// The boolean payload, if this is a [`MetadataValue::Bool`].
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+/// GGUF value-type IDs, as defined by the GGUF specification.
+mod value_type {
+    pub const U8: u32 = 0;
+    pub const I8: u32 = 1;
+    pub const U16: u32 = 2;
+    pub const I16: u32 = 3;
+    pub const U32: u32 = 4;
+    pub const I32: u32 = 5;
+    pub const F32: u32 = 6;
+    pub const BOOL: u32 = 7;
+    pub const STRING: u32 = 8;
+    pub const ARRAY: u32 = 9;
+    pub const U64: u32 = 10;
+    pub const I64: u32 = 11;
+    pub const F64: u32 = 12;
+}
+
+/// Read a fixed-size little-endian primitive.
+macro_rules! read_le {
+    ($reader:expr, $ty:ty) => {{
+        let mut buf = [0u8; std::mem::size_of::<$ty>()];
+        $reader.read_exact(&mut buf)?;
+        <$ty>::from_le_bytes(buf)
+    }};
 }
 
 fn read_u32<R: Read>(reader: &mut R) -> Result<u32, Error> {
@@ -307,10 +631,25 @@ fn read_f32<R: Read>(reader: &mut R) -> Result<f32, Error> {
     Ok(f32::from_le_bytes(buf))
 }
 
-fn read_string<R: Read>(reader: &mut R, total_bytes: &mut usize) -> Result<String, Error> {
-    const MAX_STRING_SIZE: usize = 1024 * 1024; // 1MB max per string
-    const MAX_TOTAL_STRING_DATA: usize = 100 * 1024 * 1024; // 100MB total
-    let len_u64 = read_u64(reader)?;
+/// Read a header count or a string/array length.
+///
+/// GGUF v1 stores these as `u32`; v2 widened them to `u64` without changing
+/// anything else in the metadata layout.
+fn read_len<R: Read>(reader: &mut R, version: u32) -> Result<u64, Error> {
+    if version == 1 {
+        read_u32(reader).map(u64::from)
+    } else {
+        read_u64(reader)
+    }
+}
+
+fn read_string<R: Read>(
+    reader: &mut R,
+    version: u32,
+    total_bytes: &mut usize,
+    options: &LoaderOptions,
+) -> Result<String, Error> {
+    let len_u64 = read_len(reader, version)?;
 
     // Prevent truncation on 32-bit systems (Issue R4#12)
     if len_u64 > usize::MAX as u64 {
@@ -320,9 +659,10 @@ fn read_string<R: Read>(reader: &mut R, total_bytes: &mut usize) -> Result<Strin
     }
     let len = len_u64 as usize;
 
-    if len > MAX_STRING_SIZE {
+    if len > options.max_string_size {
         return Err(Error::InvalidMetadata(format!(
-            "String too large: {len} bytes (max: {MAX_STRING_SIZE})"
+            "String too large: {len} bytes (max: {})",
+            options.max_string_size
         )));
     }
 
@@ -330,10 +670,10 @@ fn read_string<R: Read>(reader: &mut R, total_bytes: &mut usize) -> Result<Strin
     *total_bytes = total_bytes
         .checked_add(len)
         .ok_or_else(|| Error::InvalidMetadata("Total string data overflow".to_string()))?;
-    if *total_bytes > MAX_TOTAL_STRING_DATA {
+    if *total_bytes > options.max_total_string_data {
         return Err(Error::InvalidMetadata(format!(
             "Total string data too large: {} bytes (max: {})",
-            *total_bytes, MAX_TOTAL_STRING_DATA
+            *total_bytes, options.max_total_string_data
         )));
     }
 
@@ -342,72 +682,232 @@ fn read_string<R: Read>(reader: &mut R, total_bytes: &mut usize) -> Result<Strin
     String::from_utf8(buf).map_err(|e| Error::InvalidMetadata(format!("Invalid UTF-8: {e}")))
 }
 
-fn read_value<R: Read>(reader: &mut R, total_bytes: &mut usize) -> Result<Value, Error> {
-    let type_id = read_u32(reader)?;
+/// A reader that tracks how many bytes are left in the source, when known.
+struct Bounded<R> {
+    inner: R,
+    remaining: Option<u64>,
+}
 
+impl<R: Read> Read for Bounded<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(remaining) = &mut self.remaining {
+            *remaining = remaining.saturating_sub(n as u64);
+        }
+        Ok(n)
+    }
+}
+
+/// Validate a declared array length before anything is allocated for it.
+///
+/// Rejects lengths above [`LoaderOptions::max_array_len`], then defers to
+/// [`check_array_fits`].
+fn check_array_len(
+    array_len: u64,
+    array_type: u32,
+    version: u32,
+    remaining: Option<u64>,
+    max_array_len: usize,
+) -> Result<usize, Error> {
+    if array_len > max_array_len as u64 {
+        return Err(Error::InvalidMetadata(format!(
+            "Array too large: {array_len} elements (max: {max_array_len})"
+        )));
+    }
+    check_array_fits(array_len, array_type, version, remaining)?;
+    Ok(array_len as usize)
+}
+
+/// Reject an array whose smallest possible encoding (fixed-size elements, or
+/// one length prefix per string) would not fit in the bytes left in the
+/// source, so a truncated file fails before its elements are read.
+fn check_array_fits(
+    array_len: u64,
+    array_type: u32,
+    version: u32,
+    remaining: Option<u64>,
+) -> Result<(), Error> {
+    let min_elem_size = match array_type {
+        value_type::STRING if version == 1 => 4,
+        value_type::STRING => 8,
+        _ => fixed_size(array_type).unwrap_or(0),
+    };
+    if let Some(remaining) = remaining {
+        let min_bytes = array_len.saturating_mul(min_elem_size);
+        if min_bytes > remaining {
+            return Err(Error::InvalidMetadata(format!(
+                "Array of {array_len} elements needs at least {min_bytes} bytes, \
+                 but only {remaining} remain"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Whether a metadata key is parsed and kept, rather than skipped.
+fn is_retained_key(key: &str) -> bool {
+    key.starts_with("tokenizer.") || key.starts_with("general.")
+}
+
+/// Wire size in bytes of a fixed-size GGUF value type, or `None` for strings,
+/// arrays, and unknown type IDs.
+fn fixed_size(type_id: u32) -> Option<u64> {
+    use value_type as t;
     match type_id {
-        4 => Ok(Value::U32(read_u32(reader)?)),
-        5 => Ok(Value::I32(read_i32(reader)?)),
-        6 => Ok(Value::F32(read_f32(reader)?)),
-        7 => {
-            let mut byte = [0u8; 1];
-            reader.read_exact(&mut byte)?;
-            Ok(Value::Bool(byte[0] != 0))
+        t::U8 | t::I8 | t::BOOL => Some(1),
+        t::U16 | t::I16 => Some(2),
+        t::U32 | t::I32 | t::F32 => Some(4),
+        t::U64 | t::I64 | t::F64 => Some(8),
+        _ => None,
+    }
+}
+
+/// Discard exactly `n` bytes from the reader.
+fn skip_bytes<R: Read>(reader: &mut R, n: u64) -> Result<(), Error> {
+    let skipped = std::io::copy(&mut reader.by_ref().take(n), &mut std::io::sink())?;
+    if skipped < n {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            format!("metadata value truncated: expected {n} bytes, found {skipped}"),
+        )));
+    }
+    Ok(())
+}
+
+/// Skip one metadata value (type ID included) without allocating its payload.
+///
+/// The byte length of every known type is computable from the stream, so a
+/// value can be stepped over even though it is never decoded. Only unknown
+/// type IDs are an error, since their length cannot be determined. Array
+/// lengths are still checked against the bytes remaining.
+fn skip_value<R: Read>(reader: &mut Bounded<R>, version: u32) -> Result<(), Error> {
+    use value_type as t;
+
+    let type_id = read_u32(reader)?;
+    match type_id {
+        t::STRING => {
+            let len = read_len(reader, version)?;
+            skip_bytes(reader, len)
+        }
+        t::ARRAY => {
+            let array_type = read_u32(reader)?;
+            let array_len = read_len(reader, version)?;
+            check_array_fits(array_len, array_type, version, reader.remaining)?;
+            if array_type == t::STRING {
+                for _ in 0..array_len {
+                    let len = read_len(reader, version)?;
+                    skip_bytes(reader, len)?;
+                }
+                Ok(())
+            } else if let Some(size) = fixed_size(array_type) {
+                let total = array_len.checked_mul(size).ok_or_else(|| {
+                    Error::InvalidMetadata(format!(
+                        "Array byte length overflows: {array_len} elements"
+                    ))
+                })?;
+                skip_bytes(reader, total)
+            } else {
+                Err(Error::InvalidMetadata(format!(
+                    "Unsupported array type: {array_type}"
+                )))
+            }
         }
-        8 => Ok(Value::String(read_string(reader, total_bytes)?)),
-        9 => {
-            // Array
+        _ => match fixed_size(type_id) {
+            Some(size) => skip_bytes(reader, size),
+            None => Err(Error::InvalidMetadata(format!(
+                "Unsupported value type: {type_id}"
+            ))),
+        },
+    }
+}
+
+fn read_value<R: Read>(
+    reader: &mut Bounded<R>,
+    version: u32,
+    total_bytes: &mut usize,
+    options: &LoaderOptions,
+) -> Result<MetadataValue, Error> {
+    use value_type as t;
+
+    /// Read `$len` elements with `$read` into a typed array variant.
+    macro_rules! read_array {
+        ($len:expr, $variant:ident, $read:expr) => {{
+            let mut arr = Vec::with_capacity($len.min(INITIAL_ARRAY_CAPACITY));
+            for _ in 0..$len {
+                arr.push($read);
+            }
+            MetadataValue::$variant(arr)
+        }};
+    }
+
+    let type_id = read_u32(reader)?;
+
+    Ok(match type_id {
+        t::U8 => MetadataValue::U8(read_le!(reader, u8)),
+        t::I8 => MetadataValue::I8(read_le!(reader, i8)),
+        t::U16 => MetadataValue::U16(read_le!(reader, u16)),
+        t::I16 => MetadataValue::I16(read_le!(reader, i16)),
+        t::U32 => MetadataValue::U32(read_u32(reader)?),
+        t::I32 => MetadataValue::I32(read_i32(reader)?),
+        t::F32 => MetadataValue::F32(read_f32(reader)?),
+        t::BOOL => MetadataValue::Bool(read_le!(reader, u8) != 0),
+        t::STRING => MetadataValue::String(read_string(reader, version, total_bytes, options)?),
+        t::U64 => MetadataValue::U64(read_u64(reader)?),
+        t::I64 => MetadataValue::I64(read_le!(reader, i64)),
+        t::F64 => MetadataValue::F64(read_le!(reader, f64)),
+        t::ARRAY => {
             let array_type = read_u32(reader)?;
-            let array_len = read_u64(reader)? as usize;
+            let array_len = read_len(reader, version)?;
+            let array_len = check_array_len(
+                array_len,
+                array_type,
+                version,
+                reader.remaining,
+                options.max_array_len,
+            )?;
 
             match array_type {
-                0 => {
+                t::U8 => {
                     // U8 array (used for precompiled_charsmap in T5/mT5 models)
-                    let mut buf = vec![0u8; array_len];
-                    reader.read_exact(&mut buf)?;
-                    Ok(Value::U8Array(buf))
-                }
-                7 => {
-                    // BOOL array: one byte per boolean in the GGUF wire format.
-                    let mut arr = Vec::with_capacity(array_len);
-                    for _ in 0..array_len {
-                        let mut byte = [0u8; 1];
-                        reader.read_exact(&mut byte)?;
-                        arr.push(byte[0] != 0);
+                    let mut buf = Vec::with_capacity(array_len.min(INITIAL_ARRAY_CAPACITY));
+                    reader
+                        .by_ref()
+                        .take(array_len as u64)
+                        .read_to_end(&mut buf)?;
+                    if buf.len() < array_len {
+                        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
                     }
-                    Ok(Value::BoolArray(arr))
+                    MetadataValue::U8Array(buf)
                 }
-                5 => {
-                    // I32 array
-                    let mut arr = Vec::with_capacity(array_len);
-                    for _ in 0..array_len {
-                        arr.push(read_i32(reader)?);
-                    }
-                    Ok(Value::I32Array(arr))
+                t::I8 => read_array!(array_len, I8Array, read_le!(reader, i8)),
+                t::U16 => read_array!(array_len, U16Array, read_le!(reader, u16)),
+                t::I16 => read_array!(array_len, I16Array, read_le!(reader, i16)),
+                t::U32 => read_array!(array_len, U32Array, read_u32(reader)?),
+                t::I32 => read_array!(array_len, I32Array, read_i32(reader)?),
+                t::F32 => read_array!(array_len, F32Array, read_f32(reader)?),
+                // BOOL array: one byte per boolean in the GGUF wire format.
+                t::BOOL => read_array!(array_len, BoolArray, read_le!(reader, u8) != 0),
+                t::STRING => {
+                    read_array!(
+                        array_len,
+                        StringArray,
+                        read_string(reader, version, total_bytes, options)?
+                    )
                 }
-                6 => {
-                    // F32 array
-                    let mut arr = Vec::with_capacity(array_len);
-                    for _ in 0..array_len {
-                        arr.push(read_f32(reader)?);
-                    }
-                    Ok(Value::F32Array(arr))
-                }
-                8 => {
-                    // String array
-                    let mut arr = Vec::with_capacity(array_len);
-                    for _ in 0..array_len {
-                        arr.push(read_string(reader, total_bytes)?);
-                    }
-                    Ok(Value::StringArray(arr))
+                t::U64 => read_array!(array_len, U64Array, read_u64(reader)?),
+                t::I64 => read_array!(array_len, I64Array, read_le!(reader, i64)),
+                t::F64 => read_array!(array_len, F64Array, read_le!(reader, f64)),
+                _ => {
+                    return Err(Error::InvalidMetadata(format!(
+                        "Unsupported array type: {array_type}"
+                    )))
                 }
-                _ => Err(Error::InvalidMetadata(format!(
-                    "Unsupported array type: {array_type}"
-                ))),
             }
         }
-        _ => Err(Error::InvalidMetadata(format!(
-            "Unsupported value type: {type_id}"
-        ))),
-    }
+        _ => {
+            return Err(Error::InvalidMetadata(format!(
+                "Unsupported value type: {type_id}"
+            )))
+        }
+    })
 }
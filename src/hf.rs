@@ -0,0 +1,528 @@
+//! HuggingFace `tokenizer.json` import.
+//!
+//! Translates the HF `tokenizers` serialization into the same
+//! [`GGUFMetadata`] the GGUF loader produces, so the result goes through the
+//! identical validation and backend dispatch as a GGUF file.
+//!
+//! # Supported model kinds
+//!
+//! - **Byte-level BPE** (GPT-2, Llama-3, Qwen2, ...): mapped to the `gpt2`
+//!   backend. The pre-tokenizer type is inferred from the `Split` regex in
+//!   `pre_tokenizer`; override it with
+//!   [`TokenizerBuilder::pre_type`](crate::TokenizerBuilder::pre_type) if the
+//!   guess is wrong.
+//! - **Unigram** (T5, mT5, ...): mapped to the UGM backend, with
+//!   `add_space_prefix` taken from the `Metaspace` pre-tokenizer.
+//!
+//! SentencePiece-style BPE (`Metaspace` + `byte_fallback`, e.g. Llama-2's
+//! `tokenizer.json`) and WordPiece are rejected with
+//! [`Error::UnsupportedModel`].
+//!
+//! Legacy GPT-2/RoBERTa distributions that ship `vocab.json` + `merges.txt`
+//! instead of a `tokenizer.json` are loaded by [`load_vocab_merges`] as
+//! byte-level BPE with the `gpt-2` pre-tokenizer.
+//!
+//! # Special tokens
+//!
+//! `added_tokens` marked `special` become control tokens, the rest
+//! user-defined tokens. BOS/EOS and whether they are added come from a
+//! `TemplateProcessing`, `BertProcessing` or `RobertaProcessing`
+//! post-processor; without one, BOS/EOS are recognised by common names
+//! (`<s>`, `<|endoftext|>`, ...) and not added automatically.
+
+use crate::gguf::{GGUFMetadata, SpecialTokenIds, TokenizationFlags};
+use crate::vocab::TokenType;
+use crate::{Error, TokenId};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Largest `tokenizer.json` accepted (256 MB). Real files are 1–20 MB.
+const MAX_JSON_SIZE: u64 = 256 * 1024 * 1024;
+
+const BOS_NAMES: &[&str] = &["<s>", "<|begin_of_text|>", "<|startoftext|>", "<bos>"];
+const EOS_NAMES: &[&str] = &["</s>", "<|end_of_text|>", "<|endoftext|>", "<eos>"];
+const PAD_NAMES: &[&str] = &["<pad>", "[PAD]", "<|pad|>"];
+const UNK_NAMES: &[&str] = &["<unk>", "[UNK]"];
+
+/// Loads tokenizer metadata from a HuggingFace `tokenizer.json` file.
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if the file cannot be read,
+/// [`Error::InvalidMetadata`] for malformed JSON or a missing/ill-typed
+/// `model` section, [`Error::UnsupportedModel`] for model kinds other than
+/// byte-level BPE and Unigram, and [`Error::VocabularyError`] if token IDs are
+/// not contiguous.
+pub fn load_metadata<P: AsRef<Path>>(path: P) -> Result<GGUFMetadata, Error> {
+    let path = path.as_ref();
+    let size = std::fs::metadata(path)?.len();
+    if size > MAX_JSON_SIZE {
+        return Err(Error::InvalidMetadata(format!(
+            "tokenizer.json too large: {size} bytes (max: {MAX_JSON_SIZE})"
+        )));
+    }
+    parse_metadata(&std::fs::read(path)?)
+}
+
+/// Loads byte-level BPE metadata from a `vocab.json` + `merges.txt` pair.
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if either file cannot be read,
+/// [`Error::InvalidMetadata`] if `vocab.json` is not a piece → ID object or
+/// `merges.txt` has a malformed line, and [`Error::VocabularyError`] if token
+/// IDs are not contiguous.
+pub fn load_vocab_merges<P: AsRef<Path>, Q: AsRef<Path>>(
+    vocab_json: P,
+    merges_txt: Q,
+) -> Result<GGUFMetadata, Error> {
+    let read = |path: &Path, name: &str| -> Result<Vec<u8>, Error> {
+        let size = std::fs::metadata(path)?.len();
+        if size > MAX_JSON_SIZE {
+            return Err(Error::InvalidMetadata(format!(
+                "{name} too large: {size} bytes (max: {MAX_JSON_SIZE})"
+            )));
+        }
+        Ok(std::fs::read(path)?)
+    };
+    let vocab = read(vocab_json.as_ref(), "vocab.json")?;
+    let merges = read(merges_txt.as_ref(), "merges.txt")?;
+    parse_vocab_merges(&vocab, &merges)
+}
+
+/// Parses byte-level BPE metadata from the bytes of `vocab.json` and
+/// `merges.txt`.
+///
+/// The optional `#version` header of `merges.txt` and blank lines are
+/// skipped; every other line must be `left right`. BOS, EOS, padding and
+/// unknown tokens are recognised by name (`<s>`, `</s>`, `<pad>`, `<unk>`,
+/// ...) and marked as special; GPT-2's `<|endoftext|>` serves as BOS, EOS
+/// and unknown token, as in its HF config. BOS/EOS are not added
+/// automatically.
+///
+/// # Errors
+///
+/// Same as [`load_vocab_merges`], except for I/O errors.
+pub fn parse_vocab_merges(vocab_json: &[u8], merges_txt: &[u8]) -> Result<GGUFMetadata, Error> {
+    let vocab: Value = serde_json::from_slice(vocab_json)
+        .map_err(|e| Error::InvalidMetadata(format!("vocab.json: {e}")))?;
+    let vocab = vocab.as_object().ok_or_else(|| {
+        Error::InvalidMetadata("vocab.json: must be an object of piece → ID".to_string())
+    })?;
+    let mut pieces = BTreeMap::new();
+    for (piece, id) in vocab {
+        let id = as_token_id(id).ok_or_else(|| {
+            Error::InvalidMetadata("vocab.json: IDs must be integers".to_string())
+        })?;
+        insert_piece(&mut pieces, id, piece)?;
+    }
+
+    let merges_txt = std::str::from_utf8(merges_txt)
+        .map_err(|e| Error::InvalidMetadata(format!("merges.txt: {e}")))?;
+    let mut merges = Vec::new();
+    for (i, line) in merges_txt.lines().enumerate() {
+        if line.is_empty() || (i == 0 && line.starts_with("#version")) {
+            continue;
+        }
+        let (left, right) = line
+            .split_once(' ')
+            .filter(|(l, r)| !l.is_empty() && !r.is_empty() && !r.contains(' '))
+            .ok_or_else(|| {
+                Error::InvalidMetadata(format!(
+                    "merges.txt: line {} is not `left right`: {line:?}",
+                    i + 1
+                ))
+            })?;
+        merges.push((left.to_string(), right.to_string()));
+    }
+
+    // GPT-2 has no `<unk>`; its HF config uses `<|endoftext|>` instead.
+    let unk = [UNK_NAMES, &["<|endoftext|>"]]
+        .concat()
+        .into_iter()
+        .find(|name| vocab.contains_key(*name));
+    let mut meta = build_metadata(&Value::Null, pieces, None, unk, false)?;
+    if let Some(types) = meta.token_types.as_mut() {
+        let special = &meta.special;
+        for id in [special.bos, special.eos, special.pad]
+            .into_iter()
+            .flatten()
+        {
+            if types[id as usize] == TokenType::Normal {
+                types[id as usize] = TokenType::Control;
+            }
+        }
+    }
+    meta.model_type = "gpt2".to_string();
+    meta.pre_type = Some("gpt-2".to_string());
+    meta.merges = Some(merges);
+    Ok(meta)
+}
+
+/// Parses tokenizer metadata from the bytes of a `tokenizer.json` file.
+///
+/// # Errors
+///
+/// Same as [`load_metadata`], except for I/O errors.
+pub fn parse_metadata(bytes: &[u8]) -> Result<GGUFMetadata, Error> {
+    let root: Value = serde_json::from_slice(bytes)
+        .map_err(|e| Error::InvalidMetadata(format!("tokenizer.json: {e}")))?;
+    let model = root
+        .get("model")
+        .ok_or_else(|| invalid("missing `model` section"))?;
+
+    let kind = match model.get("type").and_then(Value::as_str) {
+        Some(kind) => kind,
+        // Older serializations omit the tag; infer it from the vocab shape.
+        None if model.get("merges").is_some() => "BPE",
+        None if model.get("vocab").is_some_and(Value::is_array) => "Unigram",
+        None => return Err(invalid("`model.type` is missing")),
+    };
+
+    match kind {
+        "BPE" => parse_bpe(&root, model),
+        "Unigram" => parse_unigram(&root, model),
+        other => Err(Error::UnsupportedModel(format!(
+            "tokenizer.json model type {other}"
+        ))),
+    }
+}
+
+fn parse_bpe(root: &Value, model: &Value) -> Result<GGUFMetadata, Error> {
+    if !contains_type(root.get("pre_tokenizer"), "ByteLevel")
+        && !contains_type(root.get("decoder"), "ByteLevel")
+    {
+        return Err(Error::UnsupportedModel(
+            "tokenizer.json BPE without a ByteLevel pre-tokenizer (SentencePiece-style BPE)"
+                .to_string(),
+        ));
+    }
+
+    let vocab = model
+        .get("vocab")
+        .and_then(Value::as_object)
+        .ok_or_else(|| invalid("`model.vocab` must be an object for BPE"))?;
+    let mut pieces = BTreeMap::new();
+    for (piece, id) in vocab {
+        let id = as_token_id(id).ok_or_else(|| invalid("`model.vocab` IDs must be integers"))?;
+        insert_piece(&mut pieces, id, piece)?;
+    }
+
+    let merges = model
+        .get("merges")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("`model.merges` must be an array for BPE"))?
+        .iter()
+        .map(parse_merge)
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let unk = model.get("unk_token").and_then(Value::as_str);
+    let mut meta = build_metadata(root, pieces, None, unk, false)?;
+    meta.model_type = "gpt2".to_string();
+    meta.pre_type = Some(bpe_pre_type(root.get("pre_tokenizer")).to_string());
+    meta.merges = Some(merges);
+    Ok(meta)
+}
+
+fn parse_unigram(root: &Value, model: &Value) -> Result<GGUFMetadata, Error> {
+    let entries = model
+        .get("vocab")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("`model.vocab` must be an array for Unigram"))?;
+
+    let mut pieces = BTreeMap::new();
+    let mut scores = Vec::with_capacity(entries.len());
+    for (id, entry) in entries.iter().enumerate() {
+        let (piece, score) = match entry.as_array().map(Vec::as_slice) {
+            Some([piece, score]) => (piece.as_str(), score.as_f64()),
+            _ => (None, None),
+        };
+        let (Some(piece), Some(score)) = (piece, score) else {
+            return Err(invalid(
+                "Unigram `model.vocab` entries must be [piece, score]",
+            ));
+        };
+        insert_piece(&mut pieces, id as TokenId, piece)?;
+        scores.push(score as f32);
+    }
+
+    let unk = model
+        .get("unk_id")
+        .and_then(as_token_id)
+        .and_then(|id| pieces.get(&id).cloned());
+    let byte_fallback = model
+        .get("byte_fallback")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let mut meta = build_metadata(root, pieces, Some(scores), unk.as_deref(), byte_fallback)?;
+    meta.model_type = "t5".to_string();
+    meta.flags.add_space_prefix = metaspace_prefix(root.get("pre_tokenizer")).unwrap_or(true);
+    Ok(meta)
+}
+
+/// Shared tail of both model kinds: merge in `added_tokens`, assign token
+/// types and resolve the special-token IDs.
+fn build_metadata(
+    root: &Value,
+    mut pieces: BTreeMap<TokenId, String>,
+    mut scores: Option<Vec<f32>>,
+    unk: Option<&str>,
+    byte_fallback: bool,
+) -> Result<GGUFMetadata, Error> {
+    let mut added_types = HashMap::new();
+    for added in root
+        .get("added_tokens")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let id = added.get("id").and_then(as_token_id);
+        let content = added.get("content").and_then(Value::as_str);
+        let (Some(id), Some(content)) = (id, content) else {
+            return Err(invalid("`added_tokens` entries need `id` and `content`"));
+        };
+        insert_piece(&mut pieces, id, content)?;
+        let special = added.get("special").and_then(Value::as_bool) == Some(true);
+        let ttype = if special {
+            TokenType::Control
+        } else {
+            TokenType::UserDefined
+        };
+        added_types.insert(id, ttype);
+    }
+
+    // IDs must be exactly 0..n — the vocabulary is indexed by position.
+    if let Some((&last, _)) = pieces.last_key_value() {
+        if last as usize + 1 != pieces.len() {
+            return Err(Error::VocabularyError(format!(
+                "Token IDs are not contiguous: {} tokens but highest ID is {last}",
+                pieces.len()
+            )));
+        }
+    }
+    let tokens: Vec<String> = pieces.into_values().collect();
+    let n_tokens = tokens.len();
+    if let Some(scores) = scores.as_mut() {
+        // Added tokens beyond the Unigram table carry no score.
+        scores.resize(n_tokens, 0.0);
+    }
+
+    let token_to_id: HashMap<&str, TokenId> = tokens
+        .iter()
+        .enumerate()
+        .map(|(id, piece)| (piece.as_str(), id as TokenId))
+        .collect();
+    let unk_id = unk.and_then(|piece| token_to_id.get(piece).copied());
+
+    let token_types = tokens
+        .iter()
+        .enumerate()
+        .map(|(id, piece)| {
+            let id = id as TokenId;
+            if let Some(&ttype) = added_types.get(&id) {
+                ttype
+            } else if Some(id) == unk_id {
+                TokenType::Unknown
+            } else if byte_fallback && is_byte_piece(piece) {
+                TokenType::Byte
+            } else {
+                TokenType::Normal
+            }
+        })
+        .collect();
+
+    let by_name = |names: &[&str]| names.iter().find_map(|n| token_to_id.get(n).copied());
+    let mut special = SpecialTokenIds {
+        unk: unk_id,
+        pad: root
+            .pointer("/padding/pad_id")
+            .and_then(as_token_id)
+            .or_else(|| by_name(PAD_NAMES)),
+        ..SpecialTokenIds::default()
+    };
+    let mut flags = TokenizationFlags {
+        add_bos_token: false,
+        add_eos_token: false,
+        ..TokenizationFlags::default()
+    };
+
+    match post_processor_specials(root.get("post_processor"), &token_to_id) {
+        Some((bos, eos)) => {
+            special.bos = bos;
+            special.eos = eos;
+            flags.add_bos_token = bos.is_some();
+            flags.add_eos_token = eos.is_some();
+        }
+        None => {
+            special.bos = by_name(BOS_NAMES);
+            special.eos = by_name(EOS_NAMES);
+        }
+    }
+    special.bos = special.bos.or(special.eos);
+    special.eos = special.eos.or(special.bos);
+
+    Ok(GGUFMetadata {
+        tokens,
+        scores,
+        token_types: Some(token_types),
+        model_type: String::new(),
+        pre_type: None,
+        chat_template: None,
+        chat_templates: HashMap::new(),
+        special,
+        flags,
+        merges: None,
+        precompiled_charsmap: None,
+        token_count: None,
+        unknown_token_score: None,
+        metadata: HashMap::new(),
+    })
+}
+
+/// Insert `piece` at `id`, rejecting two different pieces for one ID.
+fn insert_piece(
+    pieces: &mut BTreeMap<TokenId, String>,
+    id: TokenId,
+    piece: &str,
+) -> Result<(), Error> {
+    match pieces.get(&id) {
+        Some(existing) if existing != piece => Err(Error::VocabularyError(format!(
+            "Token ID {id} is assigned to both '{existing}' and '{piece}'"
+        ))),
+        Some(_) => Ok(()),
+        None => {
+            pieces.insert(id, piece.to_string());
+            Ok(())
+        }
+    }
+}
+
+fn parse_merge(merge: &Value) -> Result<(String, String), Error> {
+    let pair = match merge {
+        // Classic form: "left right"
+        Value::String(s) => s
+            .split_once(' ')
+            .map(|(l, r)| (l.to_string(), r.to_string())),
+        // tokenizers >= 0.20 form: ["left", "right"]
+        Value::Array(parts) => match parts.as_slice() {
+            [Value::String(l), Value::String(r)] => Some((l.clone(), r.clone())),
+            _ => None,
+        },
+        _ => None,
+    };
+    pair.ok_or_else(|| invalid(&format!("malformed merge rule {merge}")))
+}
+
+/// BOS/EOS from a post-processor, or `None` if there is no recognised one.
+fn post_processor_specials(
+    processor: Option<&Value>,
+    token_to_id: &HashMap<&str, TokenId>,
+) -> Option<(Option<TokenId>, Option<TokenId>)> {
+    let processor = processor?;
+    match processor.get("type").and_then(Value::as_str)? {
+        "TemplateProcessing" => {
+            let single = processor.get("single")?.as_array()?;
+            let resolve = |item: &Value| -> Option<TokenId> {
+                let name = item.pointer("/SpecialToken/id")?.as_str()?;
+                processor
+                    .get("special_tokens")
+                    .and_then(|s| s.get(name))
+                    .and_then(|s| s.pointer("/ids/0"))
+                    .and_then(as_token_id)
+                    .or_else(|| token_to_id.get(name).copied())
+            };
+            let seq = single.iter().position(|i| i.get("Sequence").is_some())?;
+            let bos = single[..seq].iter().find_map(resolve);
+            let eos = single[seq + 1..].iter().rev().find_map(resolve);
+            Some((bos, eos))
+        }
+        "BertProcessing" | "RobertaProcessing" => {
+            let id = |key: &str| {
+                processor
+                    .pointer(&format!("/{key}/1"))
+                    .and_then(as_token_id)
+            };
+            Some((id("cls"), id("sep")))
+        }
+        "Sequence" => processor
+            .get("processors")?
+            .as_array()?
+            .iter()
+            .find_map(|p| post_processor_specials(Some(p), token_to_id)),
+        _ => None,
+    }
+}
+
+/// Guess the llama.cpp pre-tokenizer type from a byte-level BPE
+/// `pre_tokenizer`'s `Split` regex.
+fn bpe_pre_type(pre_tokenizer: Option<&Value>) -> &'static str {
+    let mut regexes = Vec::new();
+    collect_split_regexes(pre_tokenizer, &mut regexes);
+    match regexes.first() {
+        None => "gpt-2",
+        Some(re) if re.contains(r"\p{N}{1,3}") => "llama3",
+        Some(re) if re.contains(r"[^\r\n\p{L}\p{N}]?\p{L}+") => "qwen2",
+        Some(_) => "default",
+    }
+}
+
+fn collect_split_regexes<'a>(value: Option<&'a Value>, out: &mut Vec<&'a str>) {
+    match value {
+        Some(Value::Object(map)) => {
+            if map.get("type").and_then(Value::as_str) == Some("Split") {
+                if let Some(re) = map.get("pattern").and_then(|p| p.get("Regex")) {
+                    out.extend(re.as_str());
+                }
+            }
+            map.values()
+                .for_each(|v| collect_split_regexes(Some(v), out));
+        }
+        Some(Value::Array(items)) => items
+            .iter()
+            .for_each(|v| collect_split_regexes(Some(v), out)),
+        _ => {}
+    }
+}
+
+/// `add_space_prefix` from a (possibly nested) `Metaspace` pre-tokenizer.
+fn metaspace_prefix(value: Option<&Value>) -> Option<bool> {
+    match value? {
+        Value::Object(map) => {
+            if map.get("type").and_then(Value::as_str) == Some("Metaspace") {
+                if let Some(scheme) = map.get("prepend_scheme").and_then(Value::as_str) {
+                    return Some(scheme != "never");
+                }
+                return Some(map.get("add_prefix_space").and_then(Value::as_bool) != Some(false));
+            }
+            map.values().find_map(|v| metaspace_prefix(Some(v)))
+        }
+        Value::Array(items) => items.iter().find_map(|v| metaspace_prefix(Some(v))),
+        _ => None,
+    }
+}
+
+/// Whether `value` (or anything nested in it) is a component of type `kind`.
+fn contains_type(value: Option<&Value>, kind: &str) -> bool {
+    match value {
+        Some(Value::Object(map)) => {
+            map.get("type").and_then(Value::as_str) == Some(kind)
+                || map.values().any(|v| contains_type(Some(v), kind))
+        }
+        Some(Value::Array(items)) => items.iter().any(|v| contains_type(Some(v), kind)),
+        _ => false,
+    }
+}
+
+fn is_byte_piece(piece: &str) -> bool {
+    crate::byte_encoder::parse_hex_byte_token(piece).is_some()
+}
+
+fn as_token_id(value: &Value) -> Option<TokenId> {
+    value.as_u64().and_then(|id| TokenId::try_from(id).ok())
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::InvalidMetadata(format!("tokenizer.json: {msg}"))
+}
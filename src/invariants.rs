@@ -10,6 +10,7 @@
 //! 2. **Vocabulary consistency**: BOS/EOS tokens exist and are valid
 //! 3. **Encode postconditions**: Output tokens are valid
 //! 4. **Decode preconditions**: Input tokens are valid before decoding
+//! 5. **Slice boundaries**: Symbol byte ranges fall on UTF-8 char boundaries
 //!
 //! # Usage
 //!
@@ -151,6 +152,36 @@ pub fn assert_vocabulary_consistent(tokenizer: &Tokenizer) {
     }
 }
 
+/// Asserts that `start..end` is a valid slice range of `text`: in bounds
+/// and on UTF-8 char boundaries.
+///
+/// Backends that track symbols as byte ranges check this before slicing, and
+/// then slice with `str::get` so a mis-tracked range is an error rather than
+/// a panic in release builds.
+///
+/// # Panics
+///
+/// Panics in debug builds if the range is out of bounds or splits a char.
+#[inline]
+pub fn assert_char_boundary_range(text: &str, start: usize, end: usize) {
+    #[cfg(debug_assertions)]
+    {
+        debug_assert!(
+            start <= end && end <= text.len(),
+            "Invariant violation: range {start}..{end} out of bounds for {} bytes",
+            text.len()
+        );
+        debug_assert!(
+            text.is_char_boundary(start) && text.is_char_boundary(end),
+            "Invariant violation: range {start}..{end} splits a UTF-8 character"
+        );
+    }
+    #[cfg(not(debug_assertions))]
+    {
+        let _ = (text, start, end);
+    }
+}
+
 /// Asserts that a single token ID is valid.
 ///
 /// # Panics
@@ -189,6 +220,27 @@ mod tests {
         assert_tokens_in_bounds(&tokens, 1000);
     }
 
+    #[test]
+    fn test_char_boundary_range_valid() {
+        assert_char_boundary_range("a▁b", 0, 0);
+        assert_char_boundary_range("a▁b", 1, 4);
+        assert_char_boundary_range("a▁b", 0, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "splits a UTF-8 character")]
+    #[cfg(debug_assertions)]
+    fn test_char_boundary_range_mid_char() {
+        assert_char_boundary_range("a▁b", 1, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    #[cfg(debug_assertions)]
+    fn test_char_boundary_range_out_of_bounds() {
+        assert_char_boundary_range("ab", 1, 3);
+    }
+
     #[test]
     fn test_encode_postconditions_valid() {
         let tokens = vec![1, 2, 3];
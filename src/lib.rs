@@ -59,22 +59,36 @@
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Seek};
 use std::path::Path;
+use std::sync::Arc;
 
 pub mod bpe;
+mod builder;
 pub mod byte_encoder;
+#[cfg(feature = "chat")]
+pub mod chat;
 pub mod gguf;
+#[cfg(feature = "hf")]
+pub mod hf;
 pub mod invariants;
+mod offsets;
 pub mod plamo2;
 pub mod rwkv;
 pub mod sentencepiece;
+pub mod stream;
+pub mod tiktoken;
 pub mod ugm;
 pub mod vocab;
 pub mod wpm;
 
+pub use builder::TokenizerBuilder;
+#[cfg(feature = "chat")]
+pub use chat::ChatMessage;
+pub use gguf::{LoaderOptions, MetadataValue};
 pub use plamo2::Plamo2Tokenizer;
 pub use rwkv::RwkvTokenizer;
+pub use stream::StreamDecoder;
 pub use ugm::UgmTokenizer;
 pub use vocab::{TokenType, Vocabulary};
 pub use wpm::WpmTokenizer;
@@ -119,6 +133,19 @@ const PARALLEL_BATCH_MIN_ITEMS: usize = 2;
 /// See [`PARALLEL_BATCH_MIN_ITEMS`].
 #[cfg(feature = "parallel")]
 const PARALLEL_BATCH_MIN_BYTES: usize = 2048;
+/// Minimum total token count before [`Tokenizer::decode_batch`] goes parallel.
+///
+/// Decoding is a per-token table lookup and much cheaper than encoding, so the
+/// gate is in tokens rather than bytes: at the ~4 bytes/token typical of BPE
+/// vocabularies this is several times the encode byte gate, keeping short
+/// beam-search batches on the sequential path.
+#[cfg(feature = "parallel")]
+const PARALLEL_DECODE_MIN_TOKENS: usize = 4096;
+
+/// Texts [`Tokenizer::token_histogram`] encodes per
+/// [`encode_batch`](Tokenizer::encode_batch) call; bounds how many encoded
+/// sequences are alive at once.
+const HISTOGRAM_CHUNK_TEXTS: usize = 1024;
 
 /// Reduce per-input batch results into a single `Result`, preserving order and
 /// returning the error at the **lowest failing input index**.
@@ -126,7 +153,7 @@ const PARALLEL_BATCH_MIN_BYTES: usize = 2048;
 /// Because inputs are collected into an order-preserving `Vec` first, scanning
 /// in order yields the smallest failing index deterministically — identical for
 /// the sequential and parallel backends.
-fn finalize_batch(results: Vec<Result<Vec<TokenId>, Error>>) -> Result<Vec<Vec<TokenId>>, Error> {
+fn finalize_batch<T>(results: Vec<Result<T, Error>>) -> Result<Vec<T>, Error> {
     let mut out = Vec::with_capacity(results.len());
     for result in results {
         out.push(result?);
@@ -138,13 +165,99 @@ fn finalize_batch(results: Vec<Result<Vec<TokenId>, Error>>) -> Result<Vec<Vec<T
 ///
 /// Construct with [`EncodeOptions::with_special_tokens`] for the common case or
 /// [`EncodeOptions::with_parse_special`] when the input may contain literal
-/// special-token strings such as `<|eot_id|>`.
+/// special-token strings such as `<|eot_id|>`, then adjust with the `with_*`
+/// builders. The struct is `#[non_exhaustive]`: new options can be added
+/// without breaking callers, so it cannot be built with a struct literal
+/// outside this crate.
 #[derive(Debug, Clone, Default)]
+#[non_exhaustive]
 pub struct EncodeOptions {
     /// Add BOS/EOS tokens according to model configuration
     pub add_special_tokens: bool,
+    /// Add (`Some(true)`) or omit (`Some(false)`) the leading BOS token
+    /// regardless of `add_special_tokens` and the model's `add_bos_token`
+    /// flag; `None` follows them. See [`EncodeOptions::with_added_bos`].
+    pub force_bos: Option<bool>,
+    /// Add (`Some(true)`) or omit (`Some(false)`) the trailing EOS token
+    /// regardless of `add_special_tokens` and the model's `add_eos_token`
+    /// flag; `None` follows them. See [`EncodeOptions::with_added_eos`].
+    pub force_eos: Option<bool>,
     /// Parse special token strings in input (e.g., `<|eot_id|>`) and emit as tokens
     pub parse_special: bool,
+    /// Tokens inserted verbatim after BOS and before the encoded text. See
+    /// [`EncodeOptions::with_prefix_tokens`].
+    pub prefix_tokens: Vec<TokenId>,
+    /// Tokens inserted verbatim after the encoded text and before EOS. See
+    /// [`EncodeOptions::with_suffix_tokens`].
+    pub suffix_tokens: Vec<TokenId>,
+    /// Cap the output at this many tokens (including BOS/EOS); `None` keeps
+    /// every token. See [`EncodeOptions::with_truncation`].
+    pub max_length: Option<usize>,
+    /// Which end of the sequence `max_length` removes tokens from
+    pub truncation_side: TruncationSide,
+    /// Drop a leading byte-order mark (U+FEFF) before encoding. See
+    /// [`EncodeOptions::with_strip_bom`].
+    pub strip_bom: bool,
+    /// Drop zero-width characters (U+200B, U+200C, U+200D, U+2060, U+FEFF)
+    /// anywhere in the input. See [`EncodeOptions::with_strip_zero_width`].
+    pub strip_zero_width: bool,
+    /// Convert `\r\n` and lone `\r` line endings to `\n` before encoding.
+    /// See [`EncodeOptions::with_normalize_newlines`].
+    pub normalize_newlines: bool,
+    /// Prepend a space to text that does not start with one, for byte-level
+    /// BPE models. See [`EncodeOptions::with_leading_space`].
+    pub add_leading_space: bool,
+    /// Unicode normalization applied to the input before any backend runs;
+    /// `None` (the default) leaves it untouched. See
+    /// [`EncodeOptions::with_normalization`].
+    pub normalization: Option<NormalizationForm>,
+}
+
+/// Unicode normalization form for [`EncodeOptions::normalization`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizationForm {
+    /// Canonical composition (`e` + U+0301 → `é`)
+    Nfc,
+    /// Canonical decomposition (`é` → `e` + U+0301)
+    Nfd,
+    /// Compatibility composition (also folds e.g. `ﬁ` → `fi`, `①` → `1`)
+    Nfkc,
+    /// Compatibility decomposition
+    Nfkd,
+}
+
+impl NormalizationForm {
+    fn apply(self, text: &str) -> String {
+        use unicode_normalization::UnicodeNormalization;
+        match self {
+            Self::Nfc => text.nfc().collect(),
+            Self::Nfd => text.nfd().collect(),
+            Self::Nfkc => text.nfkc().collect(),
+            Self::Nfkd => text.nfkd().collect(),
+        }
+    }
+}
+
+/// Segment order of a fill-in-the-middle prompt built by
+/// [`Tokenizer::encode_infill_with_order`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InfillOrder {
+    /// Prefix-suffix-middle: `<PRE> prefix <SUF> suffix <MID>`
+    #[default]
+    Psm,
+    /// Suffix-prefix-middle: `<SUF> suffix <PRE> prefix <MID>`, as with
+    /// llama.cpp's `--spm-infill`
+    Spm,
+}
+
+/// Which end of an encoded sequence truncation removes tokens from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TruncationSide {
+    /// Drop tokens from the end, keeping the prefix of the text
+    #[default]
+    Right,
+    /// Drop tokens from the start, keeping the suffix of the text
+    Left,
 }
 
 impl EncodeOptions {
@@ -153,7 +266,7 @@ impl EncodeOptions {
     pub fn with_special_tokens(add_special_tokens: bool) -> Self {
         Self {
             add_special_tokens,
-            parse_special: false,
+            ..Self::default()
         }
     }
 
@@ -163,6 +276,377 @@ impl EncodeOptions {
         Self {
             add_special_tokens,
             parse_special,
+            ..Self::default()
+        }
+    }
+
+    /// Always add (`true`) or never add (`false`) the leading BOS token
+    ///
+    /// Overrides both `add_special_tokens` and the GGUF `add_bos_token`
+    /// flag, e.g. to encode a continuation segment without BOS on a model
+    /// that adds it by default. BERT-style models lead with CLS instead, and
+    /// this controls that token.
+    #[must_use]
+    pub fn with_added_bos(mut self, add: bool) -> Self {
+        self.force_bos = Some(add);
+        self
+    }
+
+    /// Always add (`true`) or never add (`false`) the trailing EOS token
+    ///
+    /// Overrides both `add_special_tokens` and the GGUF `add_eos_token`
+    /// flag, e.g. to terminate a training example on a model that does not
+    /// add EOS by default. BERT-style models end with SEP instead, and this
+    /// controls that token.
+    #[must_use]
+    pub fn with_added_eos(mut self, add: bool) -> Self {
+        self.force_eos = Some(add);
+        self
+    }
+
+    /// Insert `tokens` verbatim between BOS and the encoded text
+    ///
+    /// For fixed prompt wrapping (system prompt, instruction markers):
+    /// encode the wrapper once and reuse its tokens instead of concatenating
+    /// strings, which can merge across the boundary and change how both the
+    /// wrapper and the start of the text tokenize. The text is encoded
+    /// exactly as it would be alone. Truncation never removes these tokens
+    /// while text tokens remain.
+    #[must_use]
+    pub fn with_prefix_tokens(mut self, tokens: Vec<TokenId>) -> Self {
+        self.prefix_tokens = tokens;
+        self
+    }
+
+    /// Insert `tokens` verbatim between the encoded text and EOS
+    ///
+    /// The counterpart of [`with_prefix_tokens`](Self::with_prefix_tokens).
+    #[must_use]
+    pub fn with_suffix_tokens(mut self, tokens: Vec<TokenId>) -> Self {
+        self.suffix_tokens = tokens;
+        self
+    }
+
+    /// Truncate the encoded output to at most `max_length` tokens
+    ///
+    /// Truncation runs after BOS/EOS are added, and the BOS/EOS tokens added
+    /// by `add_special_tokens` are preserved: only text tokens are removed, so
+    /// right truncation still ends with EOS and left truncation still starts
+    /// with BOS. If `max_length` is smaller than the number of added special
+    /// tokens, no text tokens remain and the special tokens themselves are cut
+    /// from the same side (right keeps BOS, left keeps EOS).
+    #[must_use]
+    pub fn with_truncation(mut self, max_length: usize, side: TruncationSide) -> Self {
+        self.max_length = Some(max_length);
+        self.truncation_side = side;
+        self
+    }
+
+    /// Drop a leading UTF-8 byte-order mark before encoding
+    ///
+    /// Text read from files or pasted from the web often starts with U+FEFF,
+    /// which otherwise encodes to extra byte tokens in front of the first
+    /// word. Off by default for llama.cpp parity, which encodes the BOM.
+    #[must_use]
+    pub fn with_strip_bom(mut self, strip: bool) -> Self {
+        self.strip_bom = strip;
+        self
+    }
+
+    /// Drop zero-width characters anywhere in the input before encoding
+    ///
+    /// Removes zero-width space, non-joiner and joiner (U+200B-U+200D), word
+    /// joiner (U+2060) and U+FEFF, which are invisible but change how the
+    /// surrounding text is split. Note that the joiner is meaningful in emoji
+    /// sequences and some scripts. Off by default for llama.cpp parity.
+    #[must_use]
+    pub fn with_strip_zero_width(mut self, strip: bool) -> Self {
+        self.strip_zero_width = strip;
+        self
+    }
+
+    /// Convert Windows (`\r\n`) and old Mac (`\r`) line endings to `\n`
+    /// before encoding
+    ///
+    /// The same text saved on different platforms then encodes to the same
+    /// tokens, which suits models trained on LF-normalized data. llama.cpp
+    /// tokenizes `\r` as its own character (BPE pre-tokenizers usually
+    /// split `\r\n` as one whitespace run, SentencePiece falls back to its
+    /// byte token), so this is off by default for parity.
+    #[must_use]
+    pub fn with_normalize_newlines(mut self, normalize: bool) -> Self {
+        self.normalize_newlines = normalize;
+        self
+    }
+
+    /// Encode a fragment as if it followed a space (byte-level BPE only)
+    ///
+    /// GPT-2 style vocabularies fold a word's leading space into its first
+    /// token, so `"world"` and `" world"` encode differently (`world` vs
+    /// `Ġworld`). With this set, non-empty text that does not already start
+    /// with a space gets one prepended, so a mid-sentence fragment encodes
+    /// the way it would inside a longer text. This mirrors HuggingFace's
+    /// `ByteLevel` `add_prefix_space`. The space is not part of the input:
+    /// [`Tokenizer::encode_with_options_and_offsets`] excludes it from the
+    /// first token's span.
+    ///
+    /// SentencePiece-style models have their own `add_space_prefix` flag and
+    /// ignore this option, as do the other backends. Off by default for
+    /// llama.cpp parity.
+    #[must_use]
+    pub fn with_leading_space(mut self, add: bool) -> Self {
+        self.add_leading_space = add;
+        self
+    }
+
+    /// Normalize the input to `form` before encoding
+    ///
+    /// Useful for stabilizing token IDs across differently composed Unicode
+    /// (e.g. precomposed `é` vs `e` + combining acute). This changes the
+    /// token IDs a model would otherwise see, so it is off by default for
+    /// llama.cpp parity; llama.cpp never normalizes BPE/SPM input.
+    #[must_use]
+    pub fn with_normalization(mut self, form: NormalizationForm) -> Self {
+        self.normalization = Some(form);
+        self
+    }
+
+    /// These options minus the input rewriting llama.cpp does not do, for
+    /// [`Tokenizer::strict_llamacpp_parity`].
+    fn for_llamacpp_parity(&self) -> Self {
+        Self {
+            strip_bom: false,
+            strip_zero_width: false,
+            normalize_newlines: false,
+            add_leading_space: false,
+            normalization: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// Zero-width characters removed by [`EncodeOptions::strip_zero_width`].
+const ZERO_WIDTH_CHARS: [char; 5] = ['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}'];
+
+/// Apply [`EncodeOptions::strip_bom`] and [`EncodeOptions::strip_zero_width`].
+fn strip_invisible<'a>(text: &'a str, options: &EncodeOptions) -> std::borrow::Cow<'a, str> {
+    let text = if options.strip_bom {
+        text.strip_prefix('\u{FEFF}').unwrap_or(text)
+    } else {
+        text
+    };
+    if options.strip_zero_width && text.contains(ZERO_WIDTH_CHARS) {
+        text.replace(ZERO_WIDTH_CHARS, "").into()
+    } else {
+        text.into()
+    }
+}
+
+/// Target width for [`Tokenizer::encode_batch_padded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadStrategy {
+    /// Pad every row to the length of the longest row in the batch
+    Longest,
+    /// Pad (or truncate) every row to exactly this many tokens
+    Fixed(usize),
+}
+
+/// Result of [`Tokenizer::roundtrip_report`]: one encode → decode → encode
+/// cycle over a piece of text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundtripReport {
+    /// `encode(text, false)`
+    pub tokens: Vec<TokenId>,
+    /// `decode(&tokens, false)`
+    pub decoded: String,
+    /// `encode(&decoded, false)`
+    pub reencoded: Vec<TokenId>,
+    /// Whether `decoded` equals the input text
+    pub text_matches: bool,
+    /// Whether `reencoded` equals `tokens`
+    pub tokens_stable: bool,
+}
+
+/// The model's special tokens by role, as in HuggingFace's
+/// `special_tokens_map.json`. Built by [`Tokenizer::special_tokens_map`].
+///
+/// Each role holds the token ID and its vocabulary piece. With the `serde`
+/// feature the map serializes to HuggingFace's JSON shape: each role as its
+/// piece string, with unset roles and an empty
+/// `additional_special_tokens` omitted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpecialTokensMap {
+    /// Beginning-of-sequence token
+    pub bos_token: Option<(TokenId, String)>,
+    /// End-of-sequence token
+    pub eos_token: Option<(TokenId, String)>,
+    /// Unknown token
+    pub unk_token: Option<(TokenId, String)>,
+    /// Padding token
+    pub pad_token: Option<(TokenId, String)>,
+    /// Classifier token (BERT-style models)
+    pub cls_token: Option<(TokenId, String)>,
+    /// Separator token (BERT-style models)
+    pub sep_token: Option<(TokenId, String)>,
+    /// Mask token
+    pub mask_token: Option<(TokenId, String)>,
+    /// Every other special token, in ID order
+    pub additional_special_tokens: Vec<(TokenId, String)>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SpecialTokensMap {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let roles = [
+            ("bos_token", &self.bos_token),
+            ("eos_token", &self.eos_token),
+            ("unk_token", &self.unk_token),
+            ("pad_token", &self.pad_token),
+            ("cls_token", &self.cls_token),
+            ("sep_token", &self.sep_token),
+            ("mask_token", &self.mask_token),
+        ];
+        let mut map = serializer.serialize_map(None)?;
+        for (key, token) in roles {
+            if let Some((_, piece)) = token {
+                map.serialize_entry(key, piece)?;
+            }
+        }
+        if !self.additional_special_tokens.is_empty() {
+            let pieces: Vec<&str> = self
+                .additional_special_tokens
+                .iter()
+                .map(|(_, piece)| piece.as_str())
+                .collect();
+            map.serialize_entry("additional_special_tokens", &pieces)?;
+        }
+        map.end()
+    }
+}
+
+/// The first difference [`Tokenizer::diff`] found between two tokenizers.
+///
+/// Each variant holds the value from `self` (`left`) and from the other
+/// tokenizer (`right`). `Display` renders a one-line description for test
+/// and CI output.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenizerDiff {
+    /// The model types (`tokenizer.ggml.model`) differ
+    ModelType { left: String, right: String },
+    /// The pre-tokenizer types (`tokenizer.ggml.pre`) differ
+    PreType {
+        left: Option<String>,
+        right: Option<String>,
+    },
+    /// The vocabularies have different sizes
+    VocabSize { left: usize, right: usize },
+    /// Token `id` has a different piece
+    Token {
+        id: TokenId,
+        left: String,
+        right: String,
+    },
+    /// Token `id` has a different type
+    TokenType {
+        id: TokenId,
+        left: TokenType,
+        right: TokenType,
+    },
+    /// Token `id` has a different score
+    TokenScore { id: TokenId, left: f32, right: f32 },
+    /// Merge rule `index` differs, or exists on one side only
+    Merge {
+        index: usize,
+        left: Option<(String, String)>,
+        right: Option<(String, String)>,
+    },
+    /// A special-token role (`"bos"`, `"eos"`, `"pad"`, ...) has a different ID
+    SpecialToken {
+        role: &'static str,
+        left: Option<TokenId>,
+        right: Option<TokenId>,
+    },
+    /// A special-token string maps to a different ID (or is special on one
+    /// side only), e.g. after [`Tokenizer::add_special_tokens`]
+    SpecialTokenText {
+        text: String,
+        left: Option<TokenId>,
+        right: Option<TokenId>,
+    },
+    /// An encoding flag (`"add_bos_token"`, `"add_space_prefix"`, ...) differs
+    Flag {
+        name: &'static str,
+        left: bool,
+        right: bool,
+    },
+}
+
+impl std::fmt::Display for TokenizerDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ModelType { left, right } => {
+                write!(f, "model type differs: {left:?} vs {right:?}")
+            }
+            Self::PreType { left, right } => {
+                write!(f, "pre-tokenizer type differs: {left:?} vs {right:?}")
+            }
+            Self::VocabSize { left, right } => {
+                write!(f, "vocabulary size differs: {left} vs {right}")
+            }
+            Self::Token { id, left, right } => {
+                write!(f, "token {id} differs: {left:?} vs {right:?}")
+            }
+            Self::TokenType { id, left, right } => {
+                write!(f, "type of token {id} differs: {left:?} vs {right:?}")
+            }
+            Self::TokenScore { id, left, right } => {
+                write!(f, "score of token {id} differs: {left} vs {right}")
+            }
+            Self::Merge { index, left, right } => {
+                write!(f, "merge {index} differs: {left:?} vs {right:?}")
+            }
+            Self::SpecialToken { role, left, right } => {
+                write!(f, "{role} token ID differs: {left:?} vs {right:?}")
+            }
+            Self::SpecialTokenText { text, left, right } => {
+                write!(f, "special token {text:?} differs: {left:?} vs {right:?}")
+            }
+            Self::Flag { name, left, right } => {
+                write!(f, "{name} differs: {left} vs {right}")
+            }
+        }
+    }
+}
+
+/// Apply [`EncodeOptions::max_length`] to `tokens`, which start with `lead`
+/// and end with `trail` added special tokens.
+fn truncate_tokens<T>(
+    tokens: &mut Vec<T>,
+    max_length: usize,
+    side: TruncationSide,
+    lead: usize,
+    trail: usize,
+) {
+    let len = tokens.len();
+    if len <= max_length {
+        return;
+    }
+
+    let excess = len - max_length;
+    if max_length >= lead + trail {
+        // Remove only text tokens, between the added specials.
+        match side {
+            TruncationSide::Right => tokens.drain(len - trail - excess..len - trail),
+            TruncationSide::Left => tokens.drain(lead..lead + excess),
+        };
+    } else {
+        match side {
+            TruncationSide::Right => tokens.truncate(max_length),
+            TruncationSide::Left => {
+                tokens.drain(..excess);
+            }
         }
     }
 }
@@ -171,8 +655,10 @@ impl EncodeOptions {
 ///
 /// Construct with [`DecodeOptions::with_skip_special`] for the common case, or
 /// [`DecodeOptions::new`] for full control over whitespace stripping and
-/// special-token text emission.
+/// special-token text emission, then adjust with the `with_*` builders. Like
+/// [`EncodeOptions`], the struct is `#[non_exhaustive]`.
 #[derive(Debug, Clone, Default)]
+#[non_exhaustive]
 pub struct DecodeOptions {
     /// Skip special tokens (BOS, EOS, etc.) in output
     pub skip_special_tokens: bool,
@@ -180,6 +666,30 @@ pub struct DecodeOptions {
     pub lstrip: bool,
     /// If false, emit empty string for special/control tokens instead of their text
     pub include_special_text: bool,
+    /// How special tokens that survive `skip_special_tokens` are rendered.
+    /// The default, [`SpecialRender::Raw`], defers to `include_special_text`;
+    /// see [`DecodeOptions::with_special_render`].
+    pub special_token_render: SpecialRender,
+    /// Return [`Error::InvalidUtf8`] when the decoded bytes are not valid UTF-8,
+    /// instead of replacing invalid sequences with U+FFFD
+    pub strict_utf8: bool,
+    /// Skip token IDs outside the vocabulary instead of returning
+    /// [`Error::InvalidToken`], so one corrupt ID from a sampler does not
+    /// abort decoding the whole sequence
+    pub ignore_invalid_tokens: bool,
+    /// With `ignore_invalid_tokens`, decode each invalid ID as the UNK token
+    /// instead of dropping it. The substituted UNK is then treated like any
+    /// other, so `skip_special_tokens` removes it again.
+    pub replace_invalid_with_unk: bool,
+    /// Drop the single leading space that a SentencePiece-style model's
+    /// `add_space_prefix` put in front of the text. Unlike `lstrip`, this
+    /// removes exactly one space from the start of the output and leaves
+    /// every other piece alone. No effect on other models.
+    pub strip_prefix_space: bool,
+    /// Run [`clean_spaces`] on the output even if the model's
+    /// `tokenizer.ggml.clean_spaces` flag is off. Models with the flag set
+    /// always get it.
+    pub clean_spaces: bool,
 }
 
 impl DecodeOptions {
@@ -188,8 +698,8 @@ impl DecodeOptions {
     pub fn with_skip_special(skip_special_tokens: bool) -> Self {
         Self {
             skip_special_tokens,
-            lstrip: false,
             include_special_text: true,
+            ..Self::default()
         }
     }
 
@@ -200,6 +710,166 @@ impl DecodeOptions {
             skip_special_tokens,
             lstrip,
             include_special_text,
+            ..Self::default()
+        }
+    }
+
+    /// Return [`Error::InvalidUtf8`] for invalid UTF-8 instead of replacing it
+    ///
+    /// See [`strict_utf8`](Self::strict_utf8).
+    #[must_use]
+    pub fn with_strict_utf8(mut self, strict: bool) -> Self {
+        self.strict_utf8 = strict;
+        self
+    }
+
+    /// Skip out-of-vocabulary token IDs instead of failing, optionally
+    /// rendering each as the UNK token
+    ///
+    /// See [`ignore_invalid_tokens`](Self::ignore_invalid_tokens) and
+    /// [`replace_invalid_with_unk`](Self::replace_invalid_with_unk).
+    #[must_use]
+    pub fn with_ignore_invalid_tokens(mut self, replace_with_unk: bool) -> Self {
+        self.ignore_invalid_tokens = true;
+        self.replace_invalid_with_unk = replace_with_unk;
+        self
+    }
+
+    /// Drop the synthetic leading space from `add_space_prefix`
+    ///
+    /// See [`strip_prefix_space`](Self::strip_prefix_space).
+    #[must_use]
+    pub fn with_strip_prefix_space(mut self, strip: bool) -> Self {
+        self.strip_prefix_space = strip;
+        self
+    }
+
+    /// Apply [`clean_spaces`] regardless of the model's flag
+    ///
+    /// See [`clean_spaces`](Self::clean_spaces).
+    #[must_use]
+    pub fn with_clean_spaces(mut self, clean: bool) -> Self {
+        self.clean_spaces = clean;
+        self
+    }
+
+    /// Choose how special tokens appear in the output
+    ///
+    /// [`SpecialRender::Hidden`] and [`SpecialRender::Named`] take precedence
+    /// over `include_special_text`.
+    #[must_use]
+    pub fn with_special_render(mut self, render: SpecialRender) -> Self {
+        self.special_token_render = render;
+        self
+    }
+
+    /// The rendering actually applied, folding in `include_special_text`.
+    fn effective_special_render(&self) -> SpecialRender {
+        match self.special_token_render {
+            SpecialRender::Raw if !self.include_special_text => SpecialRender::Hidden,
+            render => render,
+        }
+    }
+
+    /// These options minus the output rewriting llama.cpp does not do, for
+    /// [`Tokenizer::strict_llamacpp_parity`].
+    fn for_llamacpp_parity(&self) -> Self {
+        Self {
+            special_token_render: match self.special_token_render {
+                SpecialRender::Named => SpecialRender::Raw,
+                render => render,
+            },
+            strip_prefix_space: false,
+            clean_spaces: false,
+            ..self.clone()
+        }
+    }
+}
+
+/// How [`Tokenizer::decode_with_options`] renders special tokens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpecialRender {
+    /// Emit nothing for special tokens
+    Hidden,
+    /// Emit the token's own piece text, e.g. `<|endoftext|>` or `</s>`
+    #[default]
+    Raw,
+    /// Emit a stable name for the token's role (`<bos>`, `<eos>`, `<eot>`,
+    /// ...) regardless of its piece text, so logs read the same across
+    /// models. Specials without a known role fall back to their piece text;
+    /// see [`Vocabulary::special_token_name`].
+    Named,
+}
+
+/// Tokenizer family of a loaded model.
+///
+/// Classified from `tokenizer.ggml.model` and, for byte-level BPE models,
+/// `tokenizer.ggml.pre`. Each variant maps to exactly one backend, so this is
+/// also the table [`Tokenizer`] dispatches on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ModelFamily {
+    /// SentencePiece (`llama`, `mistral`)
+    Llama,
+    /// SentencePiece (`gemma`)
+    Gemma,
+    /// Byte-level BPE with the GPT-2 pre-tokenizer, or no `pre` key
+    Gpt2,
+    /// Byte-level BPE with the Llama-3 pre-tokenizer
+    Llama3,
+    /// Byte-level BPE with the Qwen2 pre-tokenizer, or a `qwen`/`qwen2` model
+    /// without a `pre` key
+    Qwen2,
+    /// Byte-level BPE with one of the `deepseek-*` pre-tokenizers
+    DeepSeek,
+    /// Byte-level BPE with the StarCoder pre-tokenizer
+    StarCoder,
+    /// Byte-level BPE with any other pre-tokenizer (holds the `pre` name)
+    OtherBpe(String),
+    /// WordPiece (`bert`, `wpm`)
+    Bert,
+    /// Unigram (`t5`, `ugm`, `unigram`)
+    T5,
+    /// RWKV greedy trie (`rwkv`)
+    Rwkv,
+    /// PLaMo-2 table-driven DP (`plamo2`)
+    Plamo2,
+    /// A model type no backend handles (holds `tokenizer.ggml.model`)
+    Unknown(String),
+}
+
+impl ModelFamily {
+    /// Classify a model from its GGUF `tokenizer.ggml.model` and
+    /// `tokenizer.ggml.pre` values.
+    ///
+    /// `pre_type` only matters for byte-level BPE models.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shimmytok::ModelFamily;
+    ///
+    /// assert_eq!(ModelFamily::detect("gpt2", Some("llama-bpe")), ModelFamily::Llama3);
+    /// assert_eq!(ModelFamily::detect("t5", None), ModelFamily::T5);
+    /// ```
+    #[must_use]
+    pub fn detect(model_type: &str, pre_type: Option<&str>) -> Self {
+        match model_type {
+            "llama" | "mistral" => Self::Llama,
+            "gemma" => Self::Gemma,
+            "gpt2" | "qwen" | "qwen2" => match pre_type {
+                None if model_type == "gpt2" => Self::Gpt2,
+                None | Some("qwen2") => Self::Qwen2,
+                Some("gpt-2") => Self::Gpt2,
+                Some("llama3" | "llama-v3" | "llama-bpe") => Self::Llama3,
+                Some("starcoder") => Self::StarCoder,
+                Some(pre) if pre.starts_with("deepseek-") => Self::DeepSeek,
+                Some(pre) => Self::OtherBpe(pre.to_string()),
+            },
+            "bert" | "wpm" => Self::Bert,
+            "t5" | "ugm" | "unigram" => Self::T5,
+            "rwkv" => Self::Rwkv,
+            "plamo2" => Self::Plamo2,
+            model => Self::Unknown(model.to_string()),
         }
     }
 }
@@ -211,6 +881,14 @@ impl DecodeOptions {
 /// 1 M-token limit, well below `u32::MAX` (4.2 B).
 pub type TokenId = u32;
 
+/// Half-open `(start, end)` byte span into the text passed to
+/// [`Tokenizer::encode_with_offsets`].
+pub type Offset = (usize, usize);
+
+/// Token rows and their attention mask from
+/// [`Tokenizer::encode_batch_padded`].
+pub type PaddedBatch = (Vec<Vec<TokenId>>, Vec<Vec<u8>>);
+
 /// Main tokenizer interface for encoding and decoding text
 ///
 /// The tokenizer loads vocabulary and configuration from GGUF files and provides
@@ -228,40 +906,146 @@ pub type TokenId = u32;
 /// # Ok(())
 /// # }
 /// ```
+///
+/// # Cloning
+///
+/// `Tokenizer` implements [`Clone`], so each worker thread can own a handle
+/// without reloading the GGUF file. All state is immutable after loading, and
+/// both the vocabulary and the prepared backend state (compiled regexes,
+/// merge ranks, tries) live behind an [`Arc`](std::sync::Arc): a clone is two
+/// reference-count bumps and never copies vocabulary strings. Wrapping the
+/// tokenizer in your own `Arc<Tokenizer>` works equally well.
+#[derive(Clone)]
 pub struct Tokenizer {
-    vocab: Vocabulary,
-    tokenizer_impl: Box<dyn TokenizerImpl>,
+    vocab: Arc<Vocabulary>,
+    tokenizer_impl: Arc<dyn TokenizerImpl>,
+    /// See [`Tokenizer::strict_llamacpp_parity`].
+    strict_parity: bool,
+}
+
+/// A summary rather than the whole vocabulary, like [`Vocabulary`]'s.
+impl std::fmt::Debug for Tokenizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tokenizer")
+            .field("model_type", &self.vocab.model_type())
+            .field("pre_type", &self.vocab.pre_type())
+            .field("algorithm", &self.algorithm())
+            .field("vocab_size", &self.vocab_size())
+            .field("bos_token", &self.vocab.bos_token_id())
+            .field("eos_token", &self.vocab.eos_token_id())
+            .field("unk_token", &self.vocab.unk_token_id())
+            .field("pad_token", &self.vocab.pad_token_id())
+            .field("strict_llamacpp_parity", &self.strict_parity)
+            .finish_non_exhaustive()
+    }
 }
 
+/// Lazily encoded runs of tokens, from `TokenizerImpl::encode_chunks`.
+type TokenChunks<'a> = Box<dyn Iterator<Item = Result<Vec<TokenId>, Error>> + 'a>;
+
 trait TokenizerImpl: Send + Sync {
+    /// Short name of the algorithm, reported by [`Tokenizer::algorithm`].
+    fn algorithm(&self) -> &'static str;
     fn encode(&self, text: &str, vocab: &Vocabulary) -> Result<Vec<TokenId>, Error>;
     fn decode(&self, tokens: &[TokenId], vocab: &Vocabulary) -> Result<String, Error>;
+
+    /// Encode one large document, possibly in parallel. Must return exactly
+    /// what `encode` does; backends without a safe split stay serial.
+    fn encode_large(&self, text: &str, vocab: &Vocabulary) -> Result<Vec<TokenId>, Error> {
+        self.encode(text, vocab)
+    }
+
+    /// Encode lazily, one run of tokens per `next`; the runs concatenate to
+    /// what `encode` returns. Backends without a safe split produce a single
+    /// run, computed on the first `next`.
+    fn encode_chunks<'a>(&'a self, text: &'a str, vocab: &'a Vocabulary) -> TokenChunks<'a> {
+        Box::new(std::iter::once_with(move || self.encode(text, vocab)))
+    }
+
+    /// The text as the backend sees it just before segmentation, for
+    /// [`Tokenizer::normalize`]. Backends without a preprocessing step return
+    /// it unchanged.
+    fn normalize(&self, text: &str, _vocab: &Vocabulary) -> String {
+        text.to_string()
+    }
+
+    /// Whether pieces are GPT-2 byte-encoded: each input byte maps to one
+    /// printable character (a space becomes `Ġ`) before segmentation.
+    fn is_byte_level(&self) -> bool {
+        false
+    }
+
+    /// Whether a space after a non-whitespace character always starts a new
+    /// pre-tokenizer fragment, so merges never cross it. Gates the reuse of
+    /// previous tokens in [`Tokenizer::encode_incremental`].
+    fn splits_before_space(&self) -> bool {
+        false
+    }
+
+    /// Byte ranges of the regex pre-tokenizer fragments, for
+    /// [`Tokenizer::pre_tokenize_debug`]. `None` for backends without a
+    /// regex pre-tokenizer.
+    fn fragments(&self, _text: &str) -> Option<Vec<(usize, usize)>> {
+        None
+    }
+
+    /// Raw decoded bytes before UTF-8 validation. Backends whose pieces are
+    /// always valid UTF-8 can rely on the default.
+    fn decode_bytes(&self, tokens: &[TokenId], vocab: &Vocabulary) -> Result<Vec<u8>, Error> {
+        self.decode(tokens, vocab).map(String::into_bytes)
+    }
 }
 
 /// Generate a `TokenizerImpl` wrapper struct for tokenizers whose `encode`/`decode`
 /// methods match the trait signature. This avoids repeating the same forwarding
-/// boilerplate for every algorithm variant.
+/// boilerplate for every algorithm variant. `$algorithm` is the name reported
+/// by [`Tokenizer::algorithm`]. List any optional trait methods the inner
+/// tokenizer implements itself (`decode_bytes`, `normalize`) after it.
 macro_rules! impl_tokenizer_wrapper {
-    ($wrapper:ident, $inner:path) => {
+    ($wrapper:ident, $inner:path, $algorithm:literal $(, $method:ident)*) => {
         struct $wrapper {
             inner: $inner,
         }
 
         impl TokenizerImpl for $wrapper {
+            fn algorithm(&self) -> &'static str {
+                $algorithm
+            }
             fn encode(&self, text: &str, vocab: &Vocabulary) -> Result<Vec<TokenId>, Error> {
                 self.inner.encode(text, vocab)
             }
             fn decode(&self, tokens: &[TokenId], vocab: &Vocabulary) -> Result<String, Error> {
                 self.inner.decode(tokens, vocab)
             }
+            $(forward_to_inner!($method);)*
+        }
+    };
+}
+
+/// One optional `TokenizerImpl` method forwarded to `self.inner`, for
+/// [`impl_tokenizer_wrapper`].
+macro_rules! forward_to_inner {
+    (decode_bytes) => {
+        fn decode_bytes(&self, tokens: &[TokenId], vocab: &Vocabulary) -> Result<Vec<u8>, Error> {
+            self.inner.decode_bytes(tokens, vocab)
+        }
+    };
+    (normalize) => {
+        fn normalize(&self, text: &str, vocab: &Vocabulary) -> String {
+            self.inner.normalize(text, vocab)
         }
     };
 }
 
-impl_tokenizer_wrapper!(WpmWrapper, wpm::WpmTokenizer);
-impl_tokenizer_wrapper!(RwkvWrapper, rwkv::RwkvTokenizer);
-impl_tokenizer_wrapper!(UgmWrapper, ugm::UgmTokenizer);
-impl_tokenizer_wrapper!(Plamo2Wrapper, plamo2::Plamo2Tokenizer);
+impl_tokenizer_wrapper!(WpmWrapper, wpm::WpmTokenizer, "wpm", normalize);
+impl_tokenizer_wrapper!(RwkvWrapper, rwkv::RwkvTokenizer, "rwkv", decode_bytes);
+impl_tokenizer_wrapper!(UgmWrapper, ugm::UgmTokenizer, "ugm", normalize);
+impl_tokenizer_wrapper!(
+    Plamo2Wrapper,
+    plamo2::Plamo2Tokenizer,
+    "plamo2",
+    decode_bytes
+);
 
 impl Tokenizer {
     /// Load a tokenizer from a GGUF model file
@@ -291,44 +1075,60 @@ impl Tokenizer {
         Self::from_vocab(vocab)
     }
 
-    /// Load a tokenizer from any [`Read`] source.
+    /// Load a tokenizer from a GGUF model file with custom size limits
     ///
-    /// Useful for loading from network streams, embedded assets, or any
-    /// in-memory byte source. For loading from a file path prefer
-    /// [`from_gguf_file`](Self::from_gguf_file).
+    /// [`from_gguf_file`](Self::from_gguf_file) rejects vocabularies, tokens,
+    /// merge lists and metadata strings beyond the [`LoaderOptions`]
+    /// defaults. Raise the limits here for trusted files that exceed them.
     ///
     /// # Errors
     ///
-    /// Same as [`from_gguf_file`](Self::from_gguf_file).
+    /// Same as [`from_gguf_file`](Self::from_gguf_file), with the limits
+    /// taken from `options`.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use shimmytok::Tokenizer;
-    /// use std::fs::File;
-    /// use std::io::BufReader;
+    /// use shimmytok::{LoaderOptions, Tokenizer};
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let reader = BufReader::new(File::open("model.gguf")?);
-    /// let tokenizer = Tokenizer::from_reader(reader)?;
+    /// let options = LoaderOptions {
+    ///     max_token_length: 16 * 1024,
+    ///     ..LoaderOptions::default()
+    /// };
+    /// let tokenizer = Tokenizer::from_gguf_file_with_options("model.gguf", &options)?;
     /// # Ok(())
     /// # }
     /// ```
-    #[must_use = "from_reader returns a Result that must be handled"]
-    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
-        let vocab = Vocabulary::from_reader(reader)?;
+    #[must_use = "from_gguf_file_with_options returns a Result that must be handled"]
+    pub fn from_gguf_file_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &LoaderOptions,
+    ) -> Result<Self, Error> {
+        let vocab = Vocabulary::from_gguf_file_with_options(path, options)?;
         Self::from_vocab(vocab)
     }
 
-    /// Load a tokenizer from a byte slice containing a GGUF file.
+    /// Load a tokenizer from a memory-mapped GGUF model file
     ///
-    /// Convenience wrapper around [`from_reader`](Self::from_reader) for the
-    /// common case where the model is already loaded into memory — for example
-    /// in WASM applications or embedded systems.
+    /// Requires the `mmap` feature. Parses metadata directly from the mapped
+    /// file instead of through a `BufReader`, avoiding one copy of the
+    /// vocabulary bytes. Results are identical to
+    /// [`from_gguf_file`](Self::from_gguf_file); see
+    /// [`gguf::load_metadata_mmap`] for the tradeoffs.
+    ///
+    /// # Concurrent modification
+    ///
+    /// The file is memory-mapped while it is parsed, using the crate's only
+    /// `unsafe` code. Another process must not truncate or rewrite it during
+    /// loading: that faults the read or is undefined behavior. Use
+    /// [`from_gguf_file`](Self::from_gguf_file) for files that may change
+    /// while they are loaded.
     ///
     /// # Errors
     ///
-    /// Same as [`from_gguf_file`](Self::from_gguf_file).
+    /// Same as [`from_gguf_file`](Self::from_gguf_file), plus [`Error::Io`] if
+    /// the file cannot be mapped.
     ///
     /// # Example
     ///
@@ -336,64 +1136,50 @@ impl Tokenizer {
     /// use shimmytok::Tokenizer;
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bytes: Vec<u8> = std::fs::read("model.gguf")?;
-    /// let tokenizer = Tokenizer::from_bytes(&bytes)?;
+    /// let tokenizer = Tokenizer::from_gguf_file_mmap("model.gguf")?;
     /// # Ok(())
     /// # }
     /// ```
-    #[must_use = "from_bytes returns a Result that must be handled"]
-    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
-        Self::from_reader(Cursor::new(bytes))
+    #[cfg(feature = "mmap")]
+    #[must_use = "from_gguf_file_mmap returns a Result that must be handled"]
+    pub fn from_gguf_file_mmap<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let vocab = Vocabulary::from_gguf_file_mmap(path)?;
+        Self::from_vocab(vocab)
     }
 
-    /// Shared construction logic — builds a `Tokenizer` from an already-loaded
-    /// `Vocabulary`. All public constructors funnel through here.
-    fn from_vocab(vocab: Vocabulary) -> Result<Self, Error> {
-        let tokenizer_impl: Box<dyn TokenizerImpl> = match vocab.model_type() {
-            // SentencePiece models
-            "llama" | "mistral" | "gemma" => Box::new(sentencepiece::SentencePieceTokenizer::new()),
-            // BPE models
-            "gpt2" | "qwen" | "qwen2" => Box::new(bpe::BPETokenizer::new(&vocab)?),
-            // WPM (WordPiece) models — BERT-style
-            "bert" | "wpm" => Box::new(WpmWrapper {
-                inner: wpm::WpmTokenizer::new(&vocab),
-            }),
-            // RWKV models — trie-based greedy
-            "rwkv" => Box::new(RwkvWrapper {
-                inner: rwkv::RwkvTokenizer::new(&vocab),
-            }),
-            // UGM (Unigram) models — T5-style Viterbi
-            "t5" | "ugm" => Box::new(UgmWrapper {
-                inner: ugm::UgmTokenizer::new(&vocab),
-            }),
-            // PLaMo-2 models — table-driven DP
-            "plamo2" => Box::new(Plamo2Wrapper {
-                inner: plamo2::Plamo2Tokenizer::new(&vocab)?,
-            }),
-            model => return Err(Error::UnsupportedModel(model.to_string())),
-        };
-
-        let tokenizer = Self {
-            vocab,
-            tokenizer_impl,
-        };
-
-        // Verify vocabulary consistency in debug builds
-        invariants::assert_vocabulary_consistent(&tokenizer);
-
-        Ok(tokenizer)
+    /// [`from_gguf_file_mmap`](Self::from_gguf_file_mmap) with custom size
+    /// limits
+    ///
+    /// Requires the `mmap` feature. The limits work as in
+    /// [`from_gguf_file_with_options`](Self::from_gguf_file_with_options),
+    /// and the same caveat about files modified during loading applies.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`from_gguf_file_mmap`](Self::from_gguf_file_mmap), with the
+    /// limits taken from `options`.
+    #[cfg(feature = "mmap")]
+    #[must_use = "from_gguf_file_mmap_with_options returns a Result that must be handled"]
+    pub fn from_gguf_file_mmap_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &LoaderOptions,
+    ) -> Result<Self, Error> {
+        let vocab = Vocabulary::from_gguf_file_mmap_with_options(path, options)?;
+        Self::from_vocab(vocab)
     }
 
-    /// Get the raw Jinja2 chat template string embedded in the GGUF file.
+    /// Load a tokenizer from a HuggingFace `tokenizer.json` file
     ///
-    /// Most GGUF models include a `tokenizer.chat_template` field — a Jinja2
-    /// template that formats a conversation into the exact prompt format the
-    /// model was trained on.
+    /// Requires the `hf` feature. Byte-level BPE and Unigram models are
+    /// mapped onto the same backends GGUF files use, so the result can be
+    /// compared directly against a GGUF-derived tokenizer. See [`hf`] for the
+    /// supported model kinds and how special tokens are resolved; use
+    /// [`TokenizerBuilder::from_hf_json`] to correct anything inferred wrongly.
     ///
-    /// Pass the returned string to a Jinja renderer such as
-    /// [`shimmyjinja`](https://crates.io/crates/shimmyjinja) to produce a
-    /// correctly formatted prompt, then encode the result with
-    /// [`encode`](Self::encode).
+    /// # Errors
+    ///
+    /// See [`hf::load_metadata`], plus the validation errors of
+    /// [`from_gguf_file`](Self::from_gguf_file).
     ///
     /// # Example
     ///
@@ -401,30 +1187,28 @@ impl Tokenizer {
     /// use shimmytok::Tokenizer;
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
-    ///
-    /// if let Some(template) = tokenizer.chat_template() {
-    ///     // Pass `template` to shimmyjinja::render_chat_template(...)
-    ///     println!("Chat template: {} chars", template.len());
-    /// }
+    /// let tokenizer = Tokenizer::from_hf_json("tokenizer.json")?;
+    /// let tokens = tokenizer.encode("Hello world", false)?;
     /// # Ok(())
     /// # }
     /// ```
-    #[must_use]
-    pub fn chat_template(&self) -> Option<&str> {
-        self.vocab.chat_template()
+    #[cfg(feature = "hf")]
+    #[must_use = "from_hf_json returns a Result that must be handled"]
+    pub fn from_hf_json<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let vocab = Vocabulary::from_metadata(hf::load_metadata(path)?)?;
+        Self::from_vocab(vocab)
     }
 
-    /// Encode text into a sequence of token IDs
-    ///
-    /// # Arguments
+    /// Load a byte-level BPE tokenizer from a `vocab.json` + `merges.txt`
+    /// pair
     ///
-    /// * `text` - The input text to tokenize
-    /// * `add_special_tokens` - If true, adds BOS/EOS tokens according to model configuration
+    /// Requires the `hf` feature. For legacy GPT-2/RoBERTa distributions
+    /// that predate `tokenizer.json`; see [`Vocabulary::from_hf_files`].
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// Returns a vector of token IDs representing the input text.
+    /// See [`hf::load_vocab_merges`], plus the validation errors of
+    /// [`from_gguf_file`](Self::from_gguf_file).
     ///
     /// # Example
     ///
@@ -432,239 +1216,306 @@ impl Tokenizer {
     /// use shimmytok::Tokenizer;
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
-    /// let tokens = tokenizer.encode("Hello world", true)?;
-    /// println!("Tokens: {:?}", tokens);
+    /// let tokenizer = Tokenizer::from_hf_files("gpt2/vocab.json", "gpt2/merges.txt")?;
+    /// let tokens = tokenizer.encode("Hello world", false)?;
     /// # Ok(())
     /// # }
     /// ```
-    #[must_use = "encode returns a Result that must be handled"]
-    pub fn encode(&self, text: &str, add_special_tokens: bool) -> Result<Vec<TokenId>, Error> {
-        self.encode_with_options(
-            text,
-            &EncodeOptions::with_special_tokens(add_special_tokens),
-        )
+    #[cfg(feature = "hf")]
+    #[must_use = "from_hf_files returns a Result that must be handled"]
+    pub fn from_hf_files<P: AsRef<Path>, Q: AsRef<Path>>(
+        vocab_json: P,
+        merges_txt: Q,
+    ) -> Result<Self, Error> {
+        Self::from_vocab(Vocabulary::from_hf_files(vocab_json, merges_txt)?)
     }
 
-    /// Encode text into a sequence of token IDs with full options
+    /// Load a tokenizer from an OpenAI `.tiktoken` vocabulary file
     ///
-    /// # Arguments
-    ///
-    /// * `text` - The input text to tokenize
-    /// * `options` - Encoding options (add_special_tokens, parse_special)
+    /// Each `base64 rank` line becomes a byte-level BPE token with the rank as
+    /// its ID; merge rules are reconstructed from the ranks, so the result
+    /// runs on the same backend as GGUF GPT-2 style models. `special_tokens`
+    /// are registered as control tokens (at least one is required; a token
+    /// named `<|endoftext|>` becomes EOS). See [`tiktoken`] for how the
+    /// pre-tokenizer is chosen; use [`TokenizerBuilder::from_tiktoken`] to
+    /// override it.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// Returns a vector of token IDs representing the input text.
+    /// See [`tiktoken::load_metadata`], plus the validation errors of
+    /// [`from_gguf_file`](Self::from_gguf_file).
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use shimmytok::{Tokenizer, EncodeOptions};
+    /// use shimmytok::Tokenizer;
+    /// use std::path::Path;
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
-    ///
-    /// // Parse special tokens like <|eot_id|> in input
-    /// let opts = EncodeOptions::with_parse_special(true, true);
-    /// let tokens = tokenizer.encode_with_options("Hello<|eot_id|>World", &opts)?;
+    /// let specials = [("<|endoftext|>".to_string(), 100257)];
+    /// let tokenizer = Tokenizer::from_tiktoken(Path::new("cl100k_base.tiktoken"), &specials)?;
+    /// let tokens = tokenizer.encode("Hello world", false)?;
     /// # Ok(())
     /// # }
     /// ```
-    #[must_use = "encode_with_options returns a Result that must be handled"]
-    pub fn encode_with_options(
-        &self,
-        text: &str,
-        options: &EncodeOptions,
-    ) -> Result<Vec<TokenId>, Error> {
-        let mut tokens = Vec::new();
-
-        if options.add_special_tokens && self.vocab.add_bos_token() {
-            tokens.push(self.vocab.bos_token_id());
-        }
-
-        if options.parse_special {
-            // Build special token map and find occurrences in text
-            let special_map = self.vocab.special_token_map();
-            let fragments = split_on_special_tokens(text, &special_map);
-
-            for fragment in fragments {
-                match fragment {
-                    TextFragment::Special(token_id) => {
-                        tokens.push(token_id);
-                    }
-                    TextFragment::Text(t) => {
-                        if !t.is_empty() {
-                            let encoded = self.tokenizer_impl.encode(&t, &self.vocab)?;
-                            tokens.extend(encoded);
-                        }
-                    }
-                }
-            }
-        } else {
-            let encoded = self.tokenizer_impl.encode(text, &self.vocab)?;
-            tokens.extend(encoded);
-        }
-
-        if options.add_special_tokens && self.vocab.add_eos_token() {
-            tokens.push(self.vocab.eos_token_id());
-        }
-
-        // Verify postconditions in debug builds
-        invariants::assert_encode_postconditions(&tokens, self.vocab_size());
+    #[must_use = "from_tiktoken returns a Result that must be handled"]
+    pub fn from_tiktoken<P: AsRef<Path>>(
+        vocab_bpe: P,
+        special_tokens: &[(String, TokenId)],
+    ) -> Result<Self, Error> {
+        let vocab = Vocabulary::from_metadata(tiktoken::load_metadata(vocab_bpe, special_tokens)?)?;
+        Self::from_vocab(vocab)
+    }
 
-        Ok(tokens)
+    /// Load a tokenizer from any [`Read`] source.
+    ///
+    /// Useful for loading from network streams, embedded assets, or any
+    /// in-memory byte source. For loading from a file path prefer
+    /// [`from_gguf_file`](Self::from_gguf_file).
+    ///
+    /// # Errors
+    ///
+    /// Same as [`from_gguf_file`](Self::from_gguf_file).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    /// use std::fs::File;
+    /// use std::io::BufReader;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let reader = BufReader::new(File::open("model.gguf")?);
+    /// let tokenizer = Tokenizer::from_reader(reader)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "from_reader returns a Result that must be handled"]
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let vocab = Vocabulary::from_reader(reader)?;
+        Self::from_vocab(vocab)
     }
 
-    /// Decode a sequence of token IDs back into text
+    /// Load a tokenizer from a seekable GGUF source such as a
+    /// [`Cursor`] over an in-memory buffer or an open [`std::fs::File`].
     ///
-    /// # Arguments
+    /// This is the reader counterpart of [`from_gguf_file`](Self::from_gguf_file)
+    /// for callers that embed models in their binary or download them into
+    /// memory and do not want to write a temporary file. Parsing starts at the
+    /// reader's current position.
     ///
-    /// * `tokens` - Slice of token IDs to decode
-    /// * `skip_special_tokens` - If true, filters out special tokens (BOS, EOS, etc.)
+    /// Prefer this over [`from_reader`](Self::from_reader) whenever the source
+    /// can seek: the bytes left in it are measured up front, so a header
+    /// declaring more array elements than the data could hold is rejected
+    /// before any memory is reserved for them. Use `from_reader` only for
+    /// sources that cannot seek, such as network streams and pipes.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// Returns the decoded text as a String.
+    /// Same as [`from_gguf_file`](Self::from_gguf_file).
     ///
     /// # Example
     ///
     /// ```no_run
     /// use shimmytok::Tokenizer;
+    /// use std::io::Cursor;
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
-    /// let tokens = vec![15043, 3186]; // "Hello world"
-    /// let text = tokenizer.decode(&tokens, true)?;
-    /// println!("Text: {}", text);
+    /// let bytes: Vec<u8> = std::fs::read("model.gguf")?;
+    /// let tokenizer = Tokenizer::from_gguf_reader(Cursor::new(bytes))?;
     /// # Ok(())
     /// # }
     /// ```
-    #[must_use = "decode returns a Result that must be handled"]
-    pub fn decode(&self, tokens: &[TokenId], skip_special_tokens: bool) -> Result<String, Error> {
-        self.decode_with_options(
-            tokens,
-            &DecodeOptions::with_skip_special(skip_special_tokens),
-        )
+    #[must_use = "from_gguf_reader returns a Result that must be handled"]
+    pub fn from_gguf_reader<R: Read + Seek>(reader: R) -> Result<Self, Error> {
+        let vocab = Vocabulary::from_gguf_reader(reader)?;
+        Self::from_vocab(vocab)
     }
 
-    /// Decode a sequence of token IDs back into text with full options
+    /// Load a tokenizer from a byte slice containing a GGUF file.
     ///
-    /// # Arguments
+    /// Alias of [`from_bytes`](Self::from_bytes), named to match
+    /// [`from_gguf_file`](Self::from_gguf_file) and
+    /// [`from_gguf_reader`](Self::from_gguf_reader); the two behave
+    /// identically.
     ///
-    /// * `tokens` - Slice of token IDs to decode
-    /// * `options` - Decoding options (skip_special_tokens, lstrip, include_special_text)
+    /// # Errors
     ///
-    /// # Returns
+    /// Same as [`from_gguf_file`](Self::from_gguf_file).
+    #[must_use = "from_gguf_bytes returns a Result that must be handled"]
+    pub fn from_gguf_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes(bytes)
+    }
+
+    /// Load a tokenizer from a byte slice containing a GGUF file (an mmap,
+    /// embedded asset, or network buffer).
     ///
-    /// Returns the decoded text as a String.
+    /// Convenience wrapper around [`from_gguf_reader`](Self::from_gguf_reader) for the
+    /// common case where the model is already loaded into memory — for example
+    /// in WASM applications or embedded systems. The metadata parser's
+    /// per-string and total string-data limits apply unchanged, so a crafted
+    /// blob cannot force oversized allocations.
+    /// [`from_gguf_bytes`](Self::from_gguf_bytes) is an alias of this method.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`from_gguf_file`](Self::from_gguf_file).
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use shimmytok::{Tokenizer, DecodeOptions};
+    /// use shimmytok::Tokenizer;
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
-    /// let tokens = vec![15043, 3186];
+    /// let bytes: Vec<u8> = std::fs::read("model.gguf")?;
+    /// let tokenizer = Tokenizer::from_bytes(&bytes)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "from_gguf_bytes")]
+    #[must_use = "from_bytes returns a Result that must be handled"]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_gguf_reader(Cursor::new(bytes))
+    }
+
+    /// Build a tokenizer over an already-loaded [`Vocabulary`]
     ///
-    /// // Decode with lstrip to remove leading whitespace from tokens
-    /// let opts = DecodeOptions::new(true, true, false);
-    /// let text = tokenizer.decode_with_options(&tokens, &opts)?;
+    /// The backend tables (tries, score tables, merge ranks) are built the
+    /// first time a tokenizer is constructed over a vocabulary and cached in
+    /// it, so a tokenizer over a clone of [`vocab`](Self::vocab) reuses them
+    /// instead of walking the vocabulary again.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnsupportedModel`] if the vocabulary's model type has
+    /// no backend, or the backend's own construction errors.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::{Tokenizer, Vocabulary};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let vocab = Vocabulary::from_gguf_file("model.gguf")?;
+    /// let a = Tokenizer::from_vocabulary(vocab.clone())?;
+    /// let b = Tokenizer::from_vocabulary(vocab)?; // no rebuild
     /// # Ok(())
     /// # }
     /// ```
-    #[must_use = "decode_with_options returns a Result that must be handled"]
-    pub fn decode_with_options(
-        &self,
-        tokens: &[TokenId],
-        options: &DecodeOptions,
-    ) -> Result<String, Error> {
-        // NOTE: We intentionally do NOT assert preconditions here because
-        // tokens are user input that may be invalid. The code below handles
-        // invalid tokens by returning Error::InvalidToken.
+    #[must_use = "from_vocabulary returns a Result that must be handled"]
+    pub fn from_vocabulary(vocab: Vocabulary) -> Result<Self, Error> {
+        Self::from_vocab(vocab)
+    }
 
-        let filtered: Vec<TokenId>;
-        let filtered_tokens: &[TokenId] = if options.skip_special_tokens {
-            filtered = tokens
-                .iter()
-                .copied()
-                .filter(|&id| !self.vocab.is_special_token(id))
-                .collect();
-            &filtered
-        } else {
-            tokens
+    /// Shared construction logic — builds a `Tokenizer` from an already-loaded
+    /// `Vocabulary`. All public constructors funnel through here.
+    ///
+    /// Backends that need vocabulary-derived state (tries, score tables, merge
+    /// ranks) borrow the vocabulary only while they are constructed and keep
+    /// their own prepared copy of that state, cached in the vocabulary by
+    /// [`select_backend`](Self::select_backend). The `Vocabulary` itself is
+    /// then moved into the `Tokenizer` (behind an `Arc` shared by clones) and
+    /// handed back to the backend on every `encode`/`decode` call, so no
+    /// backend ever holds a self-referential borrow.
+    fn from_vocab(vocab: Vocabulary) -> Result<Self, Error> {
+        let tokenizer_impl = Self::select_backend(&vocab)?;
+        let tokenizer = Self {
+            vocab: Arc::new(vocab),
+            tokenizer_impl,
+            strict_parity: false,
         };
 
-        // If we need special handling (lstrip or include_special_text=false),
-        // we need to decode token by token
-        let mut result = if options.lstrip || !options.include_special_text {
-            let mut result = String::new();
-            for &token_id in filtered_tokens {
-                if !options.include_special_text && self.vocab.is_special_token(token_id) {
-                    continue;
-                }
-
-                let piece = self.tokenizer_impl.decode(&[token_id], &self.vocab)?;
-
-                let piece = if options.lstrip {
-                    piece.trim_start().to_string()
-                } else {
-                    piece
-                };
+        // Verify vocabulary consistency in debug builds
+        invariants::assert_vocabulary_consistent(&tokenizer);
 
-                result.push_str(&piece);
-            }
-            result
-        } else {
-            self.tokenizer_impl.decode(filtered_tokens, &self.vocab)?
-        };
+        Ok(tokenizer)
+    }
 
-        // Apply clean_spaces post-processing if enabled in vocab (llama.cpp parity)
-        if self.vocab.clean_spaces() {
-            result = apply_clean_spaces(&result);
+    /// The backend for `vocab`'s model type, built once per vocabulary.
+    ///
+    /// Building a backend walks the whole vocabulary (tries for UGM and RWKV,
+    /// score tables for PLaMo-2, merge ranks for BPE), so the result is cached
+    /// in the vocabulary and shared by its clones: constructing another
+    /// tokenizer over an already-loaded vocabulary only bumps a reference
+    /// count.
+    fn select_backend(vocab: &Vocabulary) -> Result<Arc<dyn TokenizerImpl>, Error> {
+        let cache = vocab.backend_cache();
+        if let Some(backend) = cache.get() {
+            return Ok(Arc::clone(backend));
         }
+        let backend = Self::build_backend(vocab)?;
+        Ok(Arc::clone(cache.get_or_init(|| backend)))
+    }
 
-        Ok(result)
+    /// Construct the backend for `vocab`'s model type.
+    fn build_backend(vocab: &Vocabulary) -> Result<Arc<dyn TokenizerImpl>, Error> {
+        use ModelFamily as F;
+        let tokenizer_impl: Arc<dyn TokenizerImpl> =
+            match F::detect(vocab.model_type(), vocab.pre_type()) {
+                // SentencePiece models
+                F::Llama | F::Gemma => Arc::new(sentencepiece::SentencePieceTokenizer::new()),
+                // BPE models
+                F::Gpt2 | F::Llama3 | F::Qwen2 | F::DeepSeek | F::StarCoder | F::OtherBpe(_) => {
+                    Arc::new(bpe::BPETokenizer::new(vocab)?)
+                }
+                // WPM (WordPiece) models — BERT-style
+                F::Bert => Arc::new(WpmWrapper {
+                    inner: wpm::WpmTokenizer::new(vocab),
+                }),
+                // RWKV models — trie-based greedy
+                F::Rwkv => Arc::new(RwkvWrapper {
+                    inner: rwkv::RwkvTokenizer::new(vocab),
+                }),
+                // UGM (Unigram) models — T5-style Viterbi
+                F::T5 => Arc::new(UgmWrapper {
+                    inner: ugm::UgmTokenizer::new(vocab),
+                }),
+                // PLaMo-2 models — table-driven DP
+                F::Plamo2 => Arc::new(Plamo2Wrapper {
+                    inner: plamo2::Plamo2Tokenizer::new(vocab)?,
+                }),
+                F::Unknown(model) => return Err(Error::UnsupportedModel(model)),
+            };
+        Ok(tokenizer_impl)
     }
 
-    /// Get the vocabulary size
+    /// Get the raw Jinja2 chat template string embedded in the GGUF file.
     ///
-    /// # Returns
+    /// Most GGUF models include a `tokenizer.chat_template` field — a Jinja2
+    /// template that formats a conversation into the exact prompt format the
+    /// model was trained on.
     ///
-    /// The total number of tokens in the vocabulary.
-    #[must_use]
-    pub fn vocab_size(&self) -> usize {
-        self.vocab.n_tokens()
-    }
-
-    /// Get the Beginning-of-Sequence (BOS) token ID
+    /// Pass the returned string to a Jinja renderer such as
+    /// [`shimmyjinja`](https://crates.io/crates/shimmyjinja) to produce a
+    /// correctly formatted prompt, then encode the result with
+    /// [`encode`](Self::encode). With the `chat` feature,
+    /// `encode_chat` does both steps.
     ///
-    /// # Returns
+    /// # Example
     ///
-    /// The token ID used to mark the beginning of a sequence.
-    #[must_use]
-    pub fn bos_token(&self) -> TokenId {
-        self.vocab.bos_token_id()
-    }
-
-    /// Get the End-of-Sequence (EOS) token ID
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
     ///
-    /// # Returns
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
     ///
-    /// The token ID used to mark the end of a sequence.
+    /// if let Some(template) = tokenizer.chat_template() {
+    ///     // Pass `template` to shimmyjinja::render_chat_template(...)
+    ///     println!("Chat template: {} chars", template.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
     #[must_use]
-    pub fn eos_token(&self) -> TokenId {
-        self.vocab.eos_token_id()
+    pub fn chat_template(&self) -> Option<&str> {
+        self.vocab.chat_template()
     }
 
-    /// Get the tokenizer model type
-    ///
-    /// Returns the model type identifier from the GGUF metadata.
-    ///
-    /// # Returns
+    /// Get the named chat template variants embedded in the GGUF file
     ///
-    /// The model type string, such as "llama", "mistral", "gpt2", "qwen", "qwen2", or "gemma".
+    /// Some models ship extra templates next to the default one, stored as
+    /// `tokenizer.chat_template.<name>` (e.g. `tool_use` or `rag`). The map is
+    /// keyed by `<name>` and is empty when the model has none.
     ///
     /// # Example
     ///
@@ -673,29 +1524,2182 @@ impl Tokenizer {
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
-    /// println!("Model type: {}", tokenizer.model_type());
+    /// let template = tokenizer
+    ///     .chat_templates()
+    ///     .get("tool_use")
+    ///     .map(String::as_str)
+    ///     .or(tokenizer.chat_template());
     /// # Ok(())
     /// # }
     /// ```
     #[must_use]
-    pub fn model_type(&self) -> &str {
-        self.vocab.model_type()
+    pub fn chat_templates(&self) -> &std::collections::HashMap<String, String> {
+        self.vocab.chat_templates()
     }
 
-    /// Get the pre-tokenization type (for BPE models)
+    /// Render the model's chat template over `messages`
     ///
-    /// Returns the pre-tokenizer identifier from GGUF metadata.
-    /// Used internally to select the correct regex patterns for pre-tokenization.
+    /// Requires the `chat` feature. See [`chat`] for the supported Jinja
+    /// environment. A BOS or EOS piece that the template prints at the very
+    /// start or end is removed when the model adds that token itself, so
+    /// [`encode_chat`](Self::encode_chat) never doubles it (llama.cpp does
+    /// the same).
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// The pre-type string like "llama3", "gpt-2", "deepseek-coder", etc., or None if not set.
-    #[must_use]
-    pub fn pre_type(&self) -> Option<&str> {
-        self.vocab.pre_type()
-    }
+    /// - [`Error::InvalidMetadata`] if the model has no chat template or it
+    ///   does not parse
+    /// - [`Error::TokenizationFailed`] if rendering fails
+    #[cfg(feature = "chat")]
+    #[must_use = "apply_chat_template returns a Result that must be handled"]
+    pub fn apply_chat_template(
+        &self,
+        messages: &[ChatMessage],
+        add_generation_prompt: bool,
+    ) -> Result<String, Error> {
+        let template = self
+            .chat_template()
+            .ok_or_else(|| Error::InvalidMetadata("Model has no chat template".to_string()))?;
+        let bos = self.vocab.get_token_text(self.bos_token()).unwrap_or("");
+        let eos = self.vocab.get_token_text(self.eos_token()).unwrap_or("");
 
-    /// Encode multiple texts, returning one token sequence per input.
+        let mut prompt =
+            chat::render_chat_template(template, messages, add_generation_prompt, bos, eos)?;
+        let (lead, trail) = self.special_affixes();
+        if lead.is_some() && !bos.is_empty() && prompt.starts_with(bos) {
+            prompt.drain(..bos.len());
+        }
+        if trail.is_some() && !eos.is_empty() && prompt.ends_with(eos) {
+            prompt.truncate(prompt.len() - eos.len());
+        }
+        Ok(prompt)
+    }
+
+    /// Render the model's chat template over `messages` and encode the result
+    ///
+    /// Requires the `chat` feature. The prompt from
+    /// [`apply_chat_template`](Self::apply_chat_template) is encoded with
+    /// special tokens added and `parse_special` on, so role markers such as
+    /// `<|start_header_id|>` become single control tokens.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`apply_chat_template`](Self::apply_chat_template) and
+    /// [`encode`](Self::encode).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::{ChatMessage, Tokenizer};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("llama-3-instruct.gguf")?;
+    /// let messages = [
+    ///     ChatMessage::new("system", "You are terse."),
+    ///     ChatMessage::new("user", "Hi!"),
+    /// ];
+    /// let prompt = tokenizer.encode_chat(&messages, true)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "chat")]
+    #[must_use = "encode_chat returns a Result that must be handled"]
+    pub fn encode_chat(
+        &self,
+        messages: &[ChatMessage],
+        add_generation_prompt: bool,
+    ) -> Result<Vec<TokenId>, Error> {
+        let prompt = self.apply_chat_template(messages, add_generation_prompt)?;
+        let options = EncodeOptions::with_parse_special(true, true);
+        self.encode_with_options(&prompt, &options)
+    }
+
+    /// Get the `general.*` metadata from the GGUF file
+    ///
+    /// Exposes keys such as `general.architecture`, `general.name`, and
+    /// `general.quantization_version` without a second parse of the file.
+    /// Tokenizer keys are available through dedicated accessors instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// if let Some(arch) = tokenizer.metadata().get("general.architecture") {
+    ///     println!("Architecture: {}", arch.as_str().unwrap_or("?"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn metadata(&self) -> &std::collections::HashMap<String, MetadataValue> {
+        self.vocab.metadata()
+    }
+
+    /// Encode text into a sequence of token IDs
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The input text to tokenize
+    /// * `add_special_tokens` - If true, adds BOS/EOS tokens according to model configuration
+    ///
+    /// # Returns
+    ///
+    /// Returns a vector of token IDs representing the input text.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let tokens = tokenizer.encode("Hello world", true)?;
+    /// println!("Tokens: {:?}", tokens);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "encode returns a Result that must be handled"]
+    pub fn encode(&self, text: &str, add_special_tokens: bool) -> Result<Vec<TokenId>, Error> {
+        self.encode_with_options(
+            text,
+            &EncodeOptions::with_special_tokens(add_special_tokens),
+        )
+    }
+
+    /// Encode text into a sequence of token IDs with full options
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The input text to tokenize
+    /// * `options` - Encoding options (add_special_tokens, parse_special, truncation)
+    ///
+    /// # Returns
+    ///
+    /// Returns a vector of token IDs representing the input text.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::{Tokenizer, EncodeOptions};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    ///
+    /// // Parse special tokens like <|eot_id|> in input
+    /// let opts = EncodeOptions::with_parse_special(true, true);
+    /// let tokens = tokenizer.encode_with_options("Hello<|eot_id|>World", &opts)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "encode_with_options returns a Result that must be handled"]
+    pub fn encode_with_options(
+        &self,
+        text: &str,
+        options: &EncodeOptions,
+    ) -> Result<Vec<TokenId>, Error> {
+        let mut tokens = Vec::new();
+        self.for_each_encoded(text, options, |chunk| tokens.extend_from_slice(chunk))?;
+
+        if let Some(max_length) = options.max_length {
+            let (prefix, suffix) = self.encode_affixes(options);
+            let lead = usize::from(prefix.is_some()) + options.prefix_tokens.len();
+            let trail = usize::from(suffix.is_some()) + options.suffix_tokens.len();
+            truncate_tokens(
+                &mut tokens,
+                max_length,
+                options.truncation_side,
+                lead,
+                trail,
+            );
+        }
+
+        // Verify postconditions in debug builds
+        invariants::assert_encode_postconditions(&tokens, self.vocab_size());
+
+        Ok(tokens)
+    }
+
+    /// Encode text and flag which output tokens are special
+    ///
+    /// Returns the same tokens as [`encode_with_options`](Self::encode_with_options)
+    /// together with a parallel vector that is `true` wherever
+    /// [`is_special_token`](Self::is_special_token) holds — BOS/EOS added by
+    /// `add_special_tokens`, special strings split out by `parse_special`,
+    /// control tokens and UNK. Handy for chat renderers that style special
+    /// tokens differently.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`encode_with_options`](Self::encode_with_options).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::{EncodeOptions, Tokenizer};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let opts = EncodeOptions::with_parse_special(true, true);
+    /// let (tokens, special) = tokenizer.encode_with_special_flags("Hi<|eot_id|>", &opts)?;
+    /// for (token, is_special) in tokens.iter().zip(special) {
+    ///     println!("{token}{}", if is_special { " (special)" } else { "" });
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "encode_with_special_flags returns a Result that must be handled"]
+    pub fn encode_with_special_flags(
+        &self,
+        text: &str,
+        options: &EncodeOptions,
+    ) -> Result<(Vec<TokenId>, Vec<bool>), Error> {
+        let tokens = self.encode_with_options(text, options)?;
+        let special = tokens
+            .iter()
+            .map(|&id| self.vocab.is_special_token(id))
+            .collect();
+        Ok((tokens, special))
+    }
+
+    /// Count the tokens `text` encodes to, without collecting them
+    ///
+    /// Runs exactly the same pipeline as [`encode`](Self::encode) but only
+    /// tallies chunk lengths, so no combined `Vec<TokenId>` is built. Useful
+    /// for context-window budgeting and cost estimation.
+    ///
+    /// The result is always equal to `encode(text, add_special_tokens)?.len()`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`encode`](Self::encode).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let n = tokenizer.count_tokens("Hello world", true)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "count_tokens returns a Result that must be handled"]
+    pub fn count_tokens(&self, text: &str, add_special_tokens: bool) -> Result<usize, Error> {
+        let mut count = 0;
+        self.for_each_encoded(
+            text,
+            &EncodeOptions::with_special_tokens(add_special_tokens),
+            |chunk| count += chunk.len(),
+        )?;
+        Ok(count)
+    }
+
+    /// Re-encode an edited text, reusing the tokens of its previous version
+    ///
+    /// For editors that re-tokenize a growing buffer on every keystroke.
+    /// `prev_tokens` must be `encode(prev_text, false)`; the result is always
+    /// equal to `encode(new_text, false)`, only cheaper to compute.
+    ///
+    /// Byte-level BPE models keep every previous token before the last split
+    /// point inside the prefix both texts share, and encode only the rest of
+    /// `new_text`. A split point is a non-whitespace character followed by a
+    /// space: for most pre-tokenizers no pattern matches across it, so merges
+    /// on either side of it are independent. Pre-tokenizers that can keep such
+    /// a space inside a fragment (`superbpe`, `viking`, `deepseek-llm`,
+    /// `deepseek-coder`) and other model families can merge across spaces and
+    /// fall back to a full encode, as does a `prev_tokens` that does not spell
+    /// out `prev_text` byte for byte.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`encode`](Self::encode).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let prev = "fn main() { let x";
+    /// let prev_tokens = tokenizer.encode(prev, false)?;
+    /// let tokens = tokenizer.encode_incremental(prev, &prev_tokens, "fn main() { let xs")?;
+    /// assert_eq!(tokens, tokenizer.encode("fn main() { let xs", false)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "encode_incremental returns a Result that must be handled"]
+    pub fn encode_incremental(
+        &self,
+        prev_text: &str,
+        prev_tokens: &[TokenId],
+        new_text: &str,
+    ) -> Result<Vec<TokenId>, Error> {
+        if !self.tokenizer_impl.splits_before_space() || new_text.len() > MAX_INPUT_SIZE {
+            return self.encode(new_text, false);
+        }
+
+        let shared = prev_text
+            .bytes()
+            .zip(new_text.bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        // The space at `split` must itself be shared, so that lookaheads in
+        // the pre-tokenizer see the same character in both texts.
+        let split = (1..shared).rev().find(|&i| {
+            prev_text.as_bytes()[i] == b' '
+                && prev_text[..i]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| !c.is_whitespace())
+        });
+        let Some(split) = split else {
+            return self.encode(new_text, false);
+        };
+
+        // Count the previous tokens that spell out `prev_text[..split]`.
+        let n_tokens = self.vocab.n_tokens();
+        let mut offset = 0;
+        let mut kept = 0;
+        while offset < split {
+            let Some(&id) = prev_tokens.get(kept) else {
+                return self.encode(new_text, false);
+            };
+            if id as usize >= n_tokens {
+                return self.encode(new_text, false);
+            }
+            let piece = self.tokenizer_impl.decode_bytes(&[id], &self.vocab)?;
+            if prev_text.as_bytes().get(offset..offset + piece.len()) != Some(&piece[..]) {
+                return self.encode(new_text, false);
+            }
+            offset += piece.len();
+            kept += 1;
+        }
+        if offset != split {
+            return self.encode(new_text, false);
+        }
+
+        let tail = self.encode(&new_text[split..], false)?;
+        if kept + tail.len() > MAX_OUTPUT_TOKENS {
+            return self.encode(new_text, false);
+        }
+        let mut tokens = Vec::with_capacity(kept + tail.len());
+        tokens.extend_from_slice(&prev_tokens[..kept]);
+        tokens.extend(tail);
+        Ok(tokens)
+    }
+
+    /// Encode text lazily, one token at a time
+    ///
+    /// Yields exactly the tokens of [`encode`](Self::encode), but does the
+    /// work on demand, so a caller that stops after N tokens (a prompt
+    /// budget check, a preview) skips encoding the rest of the input.
+    /// Byte-level BPE models encode one pre-tokenizer fragment at a time;
+    /// other model families encode the whole text on the first call to
+    /// `next`, since their merges can span any split.
+    ///
+    /// An error is yielded once, after which the iterator ends. Producing
+    /// more than [`MAX_OUTPUT_TOKENS`] text tokens is an error, as in
+    /// [`encode`](Self::encode).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let document = std::fs::read_to_string("book.txt")?;
+    /// let preview = tokenizer
+    ///     .encode_iter(&document, true)
+    ///     .take(512)
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encode_iter<'a>(
+        &'a self,
+        text: &'a str,
+        add_special_tokens: bool,
+    ) -> impl Iterator<Item = Result<TokenId, Error>> + 'a {
+        let (prefix, suffix) = if add_special_tokens {
+            self.special_affixes()
+        } else {
+            (None, None)
+        };
+        EncodeIter {
+            prefix,
+            chunks: self.tokenizer_impl.encode_chunks(text, &self.vocab),
+            current: Vec::new().into_iter(),
+            produced: 0,
+            suffix,
+            done: false,
+        }
+    }
+
+    /// Encode one large document, spreading the work across threads
+    ///
+    /// Returns exactly the tokens of [`encode`](Self::encode). For byte-level
+    /// BPE models built with the default `parallel` feature, the text is
+    /// pre-tokenized once and then split into chunks at pre-tokenizer
+    /// fragment boundaries — never inside a fragment, so no merge can span a
+    /// seam — and the chunks are encoded on the Rayon thread pool. Short
+    /// inputs, other model families and builds without `parallel` encode
+    /// serially.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`encode`](Self::encode).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let book = std::fs::read_to_string("book.txt")?;
+    /// let tokens = tokenizer.encode_large(&book, true)?;
+    /// assert_eq!(tokens, tokenizer.encode(&book, true)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "encode_large returns a Result that must be handled"]
+    pub fn encode_large(
+        &self,
+        text: &str,
+        add_special_tokens: bool,
+    ) -> Result<Vec<TokenId>, Error> {
+        let (prefix, suffix) = if add_special_tokens {
+            self.special_affixes()
+        } else {
+            (None, None)
+        };
+        let body = self.tokenizer_impl.encode_large(text, &self.vocab)?;
+
+        let mut tokens = Vec::with_capacity(body.len() + 2);
+        tokens.extend(prefix);
+        tokens.extend(body);
+        tokens.extend(suffix);
+
+        invariants::assert_encode_postconditions(&tokens, self.vocab_size());
+        Ok(tokens)
+    }
+
+    /// Encode text and report the byte span of the input each token covers
+    ///
+    /// Returns the token IDs together with a parallel vector of
+    /// `(start, end)` byte offsets into `text`, so `&text[start..end]` is the
+    /// substring a token was produced from. The tokens are identical to
+    /// [`encode`](Self::encode).
+    ///
+    /// # Offset conventions
+    ///
+    /// - BOS/EOS tokens added by `add_special_tokens` get zero-width spans:
+    ///   `(0, 0)` for BOS and `(text.len(), text.len())` for EOS.
+    /// - A synthetic leading space (SentencePiece `add_space_prefix`) is not
+    ///   part of the input, so it is excluded from the span; a token that is
+    ///   only that space gets a zero-width span.
+    /// - Spans always fall on character boundaries: byte-fallback tokens for
+    ///   one multi-byte character each report the whole character.
+    /// - `<unk>` tokens cover the input between their aligned neighbours.
+    ///
+    /// Offsets are recovered by aligning each token's decoded bytes with the
+    /// input, which is exact for byte-level BPE and SentencePiece and
+    /// best-effort for backends that normalize text (e.g. WPM accent
+    /// stripping).
+    ///
+    /// # Errors
+    ///
+    /// Same as [`encode`](Self::encode).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let text = "Hello world";
+    /// let (tokens, offsets) = tokenizer.encode_with_offsets(text, false)?;
+    /// for (token, (start, end)) in tokens.iter().zip(offsets) {
+    ///     println!("{token}: {:?}", &text[start..end]);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "encode_with_offsets returns a Result that must be handled"]
+    pub fn encode_with_offsets(
+        &self,
+        text: &str,
+        add_special_tokens: bool,
+    ) -> Result<(Vec<TokenId>, Vec<Offset>), Error> {
+        let mut body = Vec::new();
+        self.for_each_encoded(text, &EncodeOptions::with_special_tokens(false), |chunk| {
+            body.extend_from_slice(chunk)
+        })?;
+
+        let pieces = body
+            .iter()
+            .map(|&token| self.offset_piece(token))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let body_offsets = offsets::align(text, &pieces);
+
+        let mut tokens = Vec::with_capacity(body.len() + 2);
+        let mut spans = Vec::with_capacity(body.len() + 2);
+        let (prefix, suffix) = if add_special_tokens {
+            self.special_affixes()
+        } else {
+            (None, None)
+        };
+        if let Some(prefix) = prefix {
+            tokens.push(prefix);
+            spans.push((0, 0));
+        }
+        tokens.extend(body);
+        spans.extend(body_offsets);
+        if let Some(suffix) = suffix {
+            tokens.push(suffix);
+            spans.push((text.len(), text.len()));
+        }
+
+        invariants::assert_encode_postconditions(&tokens, self.vocab_size());
+
+        Ok((tokens, spans))
+    }
+
+    /// [`encode_with_offsets`](Self::encode_with_offsets) with the full
+    /// [`EncodeOptions`]
+    ///
+    /// Returns the same tokens as
+    /// [`encode_with_options`](Self::encode_with_options). Spans follow the
+    /// conventions of `encode_with_offsets`; in addition, the space added by
+    /// [`EncodeOptions::add_leading_space`] is excluded like a SentencePiece
+    /// prefix space, and tokens from `prefix_tokens` / `suffix_tokens` get
+    /// zero-width spans at the start / end of `text`. Options that rewrite the
+    /// input (`strip_bom`, `normalize_newlines`, `normalization`, ...) make
+    /// the alignment best-effort, as for normalizing backends.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`encode_with_options`](Self::encode_with_options).
+    #[must_use = "encode_with_options_and_offsets returns a Result that must be handled"]
+    pub fn encode_with_options_and_offsets(
+        &self,
+        text: &str,
+        options: &EncodeOptions,
+    ) -> Result<(Vec<TokenId>, Vec<Offset>), Error> {
+        let body_options = EncodeOptions {
+            add_special_tokens: false,
+            force_bos: None,
+            force_eos: None,
+            prefix_tokens: Vec::new(),
+            suffix_tokens: Vec::new(),
+            max_length: None,
+            ..options.clone()
+        };
+        let mut body = Vec::new();
+        self.for_each_encoded(text, &body_options, |chunk| body.extend_from_slice(chunk))?;
+
+        let pieces = body
+            .iter()
+            .map(|&token| self.offset_piece(token))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let body_offsets = offsets::align(text, &pieces);
+
+        let n_tokens = self.vocab.n_tokens();
+        let mut wrapping = options.prefix_tokens.iter().chain(&options.suffix_tokens);
+        if let Some(&bad) = wrapping.find(|&&id| id as usize >= n_tokens) {
+            return Err(Error::InvalidToken(format!(
+                "Token ID {bad} not found in vocabulary of {n_tokens} tokens"
+            )));
+        }
+
+        let (prefix, suffix) = self.encode_affixes(options);
+        let lead: Vec<TokenId> = prefix
+            .into_iter()
+            .chain(options.prefix_tokens.iter().copied())
+            .collect();
+        let trail: Vec<TokenId> = options
+            .suffix_tokens
+            .iter()
+            .copied()
+            .chain(suffix)
+            .collect();
+
+        let mut tokens: Vec<TokenId> = lead.iter().chain(&body).chain(&trail).copied().collect();
+        let mut spans: Vec<Offset> = std::iter::repeat((0, 0))
+            .take(lead.len())
+            .chain(body_offsets)
+            .chain(std::iter::repeat((text.len(), text.len())).take(trail.len()))
+            .collect();
+        if let Some(max_length) = options.max_length {
+            let side = options.truncation_side;
+            truncate_tokens(&mut tokens, max_length, side, lead.len(), trail.len());
+            truncate_tokens(&mut spans, max_length, side, lead.len(), trail.len());
+        }
+
+        invariants::assert_encode_postconditions(&tokens, self.vocab_size());
+
+        Ok((tokens, spans))
+    }
+
+    /// Encode text and pair each token with its vocabulary piece
+    ///
+    /// The pieces are the raw stored form, exactly as
+    /// [`token_to_piece`](Self::token_to_piece) returns them: `▁Hello` for
+    /// SentencePiece, `ĠHello` for byte-level BPE, `<0x0A>` for byte
+    /// fallback. Use [`encode_with_display_pieces`](Self::encode_with_display_pieces)
+    /// for human-readable text instead.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`encode`](Self::encode).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// for (id, piece) in tokenizer.encode_with_pieces("Hello world", false)? {
+    ///     println!("{id:>6} {piece}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "encode_with_pieces returns a Result that must be handled"]
+    pub fn encode_with_pieces(
+        &self,
+        text: &str,
+        add_special_tokens: bool,
+    ) -> Result<Vec<(TokenId, String)>, Error> {
+        let tokens = self.encode(text, add_special_tokens)?;
+        Ok(tokens
+            .into_iter()
+            .map(|id| {
+                let piece = self.vocab.get_token_text(id).unwrap_or_default();
+                (id, piece.to_string())
+            })
+            .collect())
+    }
+
+    /// Encode text and pair each token with its decoded text
+    ///
+    /// Like [`encode_with_pieces`](Self::encode_with_pieces), but each
+    /// piece is decoded on its own: byte-level BPE pieces lose their GPT-2
+    /// byte encoding (`Ġ` becomes a space), `▁` becomes a space and byte
+    /// fallback tokens become their byte. Special tokens keep their
+    /// vocabulary text so they stay visible.
+    ///
+    /// A character split across several tokens cannot be shown by any one
+    /// of them, so those tokens display as U+FFFD; concatenating the
+    /// display strings therefore reproduces the input only when every
+    /// token ends on a character boundary.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`encode`](Self::encode).
+    #[must_use = "encode_with_display_pieces returns a Result that must be handled"]
+    pub fn encode_with_display_pieces(
+        &self,
+        text: &str,
+        add_special_tokens: bool,
+    ) -> Result<Vec<(TokenId, String)>, Error> {
+        let tokens = self.encode(text, add_special_tokens)?;
+        tokens
+            .into_iter()
+            .map(|id| {
+                let display = if self.vocab.is_special_token(id) {
+                    self.vocab
+                        .get_token_text(id)
+                        .unwrap_or_default()
+                        .to_string()
+                } else {
+                    String::from_utf8_lossy(&self.decode_token_bytes(id)?).into_owned()
+                };
+                Ok((id, display))
+            })
+            .collect()
+    }
+
+    /// Encode a pair of texts as one sequence, with segment IDs
+    ///
+    /// With `add_special_tokens`, each text is followed by a separator and
+    /// the pair gets the model's usual prefix:
+    ///
+    /// - BERT-style (WPM) models: `[CLS] A [SEP] B [SEP]`. A WPM vocabulary
+    ///   without a SEP token (see [`sep_token`](Self::sep_token)) uses EOS
+    ///   as the separator, and one without CLS uses BOS as the prefix.
+    /// - Every other model: `A SEP B SEP` with the model's SEP token if it
+    ///   defines one, otherwise `A EOS B EOS` if its `add_eos_token` flag is
+    ///   set, otherwise `A B` with no separator: a pair never gets a special
+    ///   token the model would not add to a single sequence. BOS comes first
+    ///   if the model's `add_bos_token` flag is set.
+    ///
+    /// Without `add_special_tokens` the two encodings are simply concatenated.
+    ///
+    /// The second vector is parallel to the tokens: `0` for the prefix, `A`
+    /// and its separator, `1` for `B` and its separator — the
+    /// `token_type_ids` sentence-pair models expect.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`encode`](Self::encode).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("bert.gguf")?;
+    /// let (tokens, segments) = tokenizer.encode_pair("How old are you?", "I am six.", true)?;
+    /// assert_eq!(tokens.len(), segments.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "encode_pair returns a Result that must be handled"]
+    pub fn encode_pair(
+        &self,
+        text_a: &str,
+        text_b: &str,
+        add_special_tokens: bool,
+    ) -> Result<(Vec<TokenId>, Vec<u8>), Error> {
+        let (prefix, separator) = if add_special_tokens {
+            let (prefix, suffix) = self.special_affixes();
+            (prefix, self.vocab.sep_token_id().or(suffix))
+        } else {
+            (None, None)
+        };
+        let body = EncodeOptions::with_special_tokens(false);
+
+        let mut tokens = Vec::new();
+        tokens.extend(prefix);
+        self.for_each_encoded(text_a, &body, |chunk| tokens.extend_from_slice(chunk))?;
+        tokens.extend(separator);
+        let first_segment = tokens.len();
+        self.for_each_encoded(text_b, &body, |chunk| tokens.extend_from_slice(chunk))?;
+        tokens.extend(separator);
+
+        let mut segments = vec![0u8; first_segment];
+        segments.resize(tokens.len(), 1);
+
+        invariants::assert_encode_postconditions(&tokens, self.vocab_size());
+
+        Ok((tokens, segments))
+    }
+
+    /// Encode a fill-in-the-middle (code infilling) prompt
+    ///
+    /// Produces `<PRE> prefix <SUF> suffix <MID>` using the model's FIM
+    /// tokens (see [`fim_pre_token`](Self::fim_pre_token)), preceded by BOS
+    /// if the model's `add_bos_token` flag is set — the layout llama.cpp's
+    /// infill endpoint sends. The model's completion is the middle.
+    ///
+    /// Equivalent to [`encode_infill_with_order`](Self::encode_infill_with_order)
+    /// with [`InfillOrder::Psm`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::VocabularyError`] if the model lacks any of the three FIM tokens
+    /// - Same as [`encode`](Self::encode) otherwise
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("starcoder.gguf")?;
+    /// let prompt = tokenizer.encode_infill("fn add(a: i32, b: i32) -> i32 {\n", "\n}")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "encode_infill returns a Result that must be handled"]
+    pub fn encode_infill(&self, prefix: &str, suffix: &str) -> Result<Vec<TokenId>, Error> {
+        self.encode_infill_with_order(prefix, suffix, InfillOrder::Psm)
+    }
+
+    /// Encode a fill-in-the-middle prompt with an explicit segment order
+    ///
+    /// See [`encode_infill`](Self::encode_infill) and [`InfillOrder`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`encode_infill`](Self::encode_infill).
+    #[must_use = "encode_infill_with_order returns a Result that must be handled"]
+    pub fn encode_infill_with_order(
+        &self,
+        prefix: &str,
+        suffix: &str,
+        order: InfillOrder,
+    ) -> Result<Vec<TokenId>, Error> {
+        let (Some(pre), Some(suf), Some(mid)) = (
+            self.fim_pre_token(),
+            self.fim_suf_token(),
+            self.fim_mid_token(),
+        ) else {
+            return Err(Error::VocabularyError(
+                "Infill requested but model does not define FIM prefix/suffix/middle tokens"
+                    .to_string(),
+            ));
+        };
+        let body = EncodeOptions::with_special_tokens(false);
+
+        let mut tokens = Vec::new();
+        tokens.extend(
+            self.vocab
+                .add_bos_token()
+                .then_some(self.vocab.bos_token_id()),
+        );
+        let segments = match order {
+            InfillOrder::Psm => [(pre, prefix), (suf, suffix)],
+            InfillOrder::Spm => [(suf, suffix), (pre, prefix)],
+        };
+        for (marker, text) in segments {
+            tokens.push(marker);
+            self.for_each_encoded(text, &body, |chunk| tokens.extend_from_slice(chunk))?;
+        }
+        tokens.push(mid);
+
+        invariants::assert_encode_postconditions(&tokens, self.vocab_size());
+
+        Ok(tokens)
+    }
+
+    /// How `token` takes part in offset alignment.
+    fn offset_piece(&self, token: TokenId) -> Result<offsets::Piece, Error> {
+        if token == self.vocab.unk_token_id() || self.token_type(token) == TokenType::Unknown {
+            Ok(offsets::Piece::Unknown)
+        } else if self.is_special_token(token) {
+            Ok(offsets::Piece::Bytes(Vec::new()))
+        } else {
+            self.decode_token_bytes(token).map(offsets::Piece::Bytes)
+        }
+    }
+
+    /// Shared encode pipeline: hands each produced run of tokens to `sink` in
+    /// order (BOS, prefix tokens, then text/special fragments, then suffix
+    /// tokens and EOS).
+    fn for_each_encoded(
+        &self,
+        text: &str,
+        options: &EncodeOptions,
+        mut sink: impl FnMut(&[TokenId]),
+    ) -> Result<(), Error> {
+        let parity_options;
+        let options = if self.strict_parity {
+            parity_options = options.for_llamacpp_parity();
+            &parity_options
+        } else {
+            options
+        };
+
+        let stripped = strip_invisible(text, options);
+        let text = stripped.as_ref();
+        let unified = (options.normalize_newlines && text.contains('\r'))
+            .then(|| text.replace("\r\n", "\n").replace('\r', "\n"));
+        let text = unified.as_deref().unwrap_or(text);
+        let normalized = options.normalization.map(|form| form.apply(text));
+        let text = normalized.as_deref().unwrap_or(text);
+        let spaced = (options.add_leading_space
+            && self.tokenizer_impl.is_byte_level()
+            && !text.is_empty()
+            && !text.starts_with(' '))
+        .then(|| format!(" {text}"));
+        let text = spaced.as_deref().unwrap_or(text);
+
+        let n_tokens = self.vocab.n_tokens();
+        let mut wrapping = options.prefix_tokens.iter().chain(&options.suffix_tokens);
+        if let Some(&bad) = wrapping.find(|&&id| id as usize >= n_tokens) {
+            return Err(Error::InvalidToken(format!(
+                "Token ID {bad} not found in vocabulary of {n_tokens} tokens"
+            )));
+        }
+
+        let (prefix, suffix) = self.encode_affixes(options);
+
+        if let Some(prefix) = prefix {
+            sink(&[prefix]);
+        }
+        if !options.prefix_tokens.is_empty() {
+            sink(&options.prefix_tokens);
+        }
+
+        if options.parse_special {
+            // Build special token map and find occurrences in text
+            let special_map = self.vocab.special_token_map();
+            let fragments = split_on_special_tokens(text, &special_map);
+
+            for fragment in fragments {
+                match fragment {
+                    TextFragment::Special(token_id) => {
+                        sink(&[token_id]);
+                    }
+                    TextFragment::Text(t) => {
+                        if !t.is_empty() {
+                            sink(&self.tokenizer_impl.encode(&t, &self.vocab)?);
+                        }
+                    }
+                }
+            }
+        } else {
+            sink(&self.tokenizer_impl.encode(text, &self.vocab)?);
+        }
+
+        if !options.suffix_tokens.is_empty() {
+            sink(&options.suffix_tokens);
+        }
+        if let Some(suffix) = suffix {
+            sink(&[suffix]);
+        }
+
+        Ok(())
+    }
+
+    /// Whether this is a BERT-style (WPM) model, which uses `[CLS]`/`[SEP]`
+    /// instead of BOS/EOS.
+    fn is_wpm(&self) -> bool {
+        matches!(self.model_family(), ModelFamily::Bert)
+    }
+
+    /// Whether encoding prepends a synthetic `▁` to the text: only the
+    /// SentencePiece-style backends honour `add_space_prefix`. The match is
+    /// exhaustive so that a new family has to decide.
+    fn adds_space_prefix(&self) -> bool {
+        let honoured = match self.model_family() {
+            ModelFamily::Llama | ModelFamily::Gemma | ModelFamily::T5 => true,
+            ModelFamily::Gpt2
+            | ModelFamily::Llama3
+            | ModelFamily::Qwen2
+            | ModelFamily::DeepSeek
+            | ModelFamily::StarCoder
+            | ModelFamily::OtherBpe(_)
+            | ModelFamily::Bert
+            | ModelFamily::Rwkv
+            | ModelFamily::Plamo2
+            | ModelFamily::Unknown(_) => false,
+        };
+        honoured && self.vocab.add_space_prefix()
+    }
+
+    /// The tokens `add_special_tokens` puts before and after the text.
+    ///
+    /// BERT-style (WPM) models are always wrapped as `[CLS] ... [SEP]`, like
+    /// llama.cpp does, falling back to BOS/EOS when the vocabulary has no
+    /// CLS/SEP token. Every other model gets BOS/EOS as its `add_bos_token` /
+    /// `add_eos_token` flags say.
+    fn special_affixes(&self) -> (Option<TokenId>, Option<TokenId>) {
+        let vocab = &self.vocab;
+        if self.is_wpm() {
+            (
+                Some(vocab.cls_token_id().unwrap_or(vocab.bos_token_id())),
+                Some(vocab.sep_token_id().unwrap_or(vocab.eos_token_id())),
+            )
+        } else {
+            (
+                vocab.add_bos_token().then_some(vocab.bos_token_id()),
+                vocab.add_eos_token().then_some(vocab.eos_token_id()),
+            )
+        }
+    }
+
+    /// [`special_affixes`](Self::special_affixes) as `options` selects them:
+    /// `add_special_tokens` gates both, and `force_bos`/`force_eos` override
+    /// each side.
+    fn encode_affixes(&self, options: &EncodeOptions) -> (Option<TokenId>, Option<TokenId>) {
+        let (lead, trail) = self.special_affixes();
+        let lead = match options.force_bos {
+            Some(true) => Some(lead.unwrap_or(self.vocab.bos_token_id())),
+            Some(false) => None,
+            None => lead.filter(|_| options.add_special_tokens),
+        };
+        let trail = match options.force_eos {
+            Some(true) => Some(trail.unwrap_or(self.vocab.eos_token_id())),
+            Some(false) => None,
+            None => trail.filter(|_| options.add_special_tokens),
+        };
+        (lead, trail)
+    }
+
+    /// Decode a sequence of token IDs back into text
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - Slice of token IDs to decode
+    /// * `skip_special_tokens` - If true, filters out special tokens (BOS, EOS, etc.)
+    ///
+    /// # Returns
+    ///
+    /// Returns the decoded text as a String.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let tokens = vec![15043, 3186]; // "Hello world"
+    /// let text = tokenizer.decode(&tokens, true)?;
+    /// println!("Text: {}", text);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "decode returns a Result that must be handled"]
+    pub fn decode(&self, tokens: &[TokenId], skip_special_tokens: bool) -> Result<String, Error> {
+        self.decode_with_options(
+            tokens,
+            &DecodeOptions::with_skip_special(skip_special_tokens),
+        )
+    }
+
+    /// Decode token IDs, borrowing from the vocabulary when possible
+    ///
+    /// Returns the same text as [`decode`](Self::decode), but skips the
+    /// byte-level and `▁` rewriting when every piece is already its own
+    /// output: plain printable ASCII (no `Ġ`, `▁` or space) from a byte-level
+    /// BPE or SentencePiece vocabulary, with no special tokens. Such pieces
+    /// are joined directly, and a single token is returned as a
+    /// [`Cow::Borrowed`](std::borrow::Cow::Borrowed) slice of the vocabulary
+    /// without allocating. Anything else falls back to `decode`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`decode`](Self::decode).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// for id in [15043, 3186] {
+    ///     print!("{}", tokenizer.decode_cow(&[id], true)?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "decode_cow returns a Result that must be handled"]
+    pub fn decode_cow(
+        &self,
+        tokens: &[TokenId],
+        skip_special_tokens: bool,
+    ) -> Result<std::borrow::Cow<'_, str>, Error> {
+        use std::borrow::Cow;
+        use ModelFamily as F;
+        // Larger outputs go through `decode` for its size limit.
+        const MAX_LITERAL_SIZE: usize = 100 * 1024 * 1024;
+
+        let literal_family = matches!(
+            self.model_family(),
+            F::Gpt2
+                | F::Llama3
+                | F::Qwen2
+                | F::DeepSeek
+                | F::StarCoder
+                | F::OtherBpe(_)
+                | F::Llama
+                | F::Gemma
+        ) && !self.vocab.clean_spaces();
+        let n_tokens = self.vocab.n_tokens();
+        let literal_piece = |id: TokenId| {
+            if id as usize >= n_tokens || self.vocab.is_special_token(id) {
+                return None;
+            }
+            let piece = self.vocab.get_token_text(id)?;
+            // `<0xNN>` is a SentencePiece byte token, not literal text.
+            let byte_token = byte_encoder::parse_hex_byte_token(piece).is_some();
+            (piece.bytes().all(|b| b.is_ascii_graphic()) && !byte_token).then_some(piece)
+        };
+
+        if literal_family {
+            let pieces: Option<Vec<&str>> = tokens.iter().map(|&id| literal_piece(id)).collect();
+            if let Some(pieces) = pieces {
+                match pieces[..] {
+                    [] => return Ok(Cow::Borrowed("")),
+                    [piece] => return Ok(Cow::Borrowed(piece)),
+                    _ => {
+                        let len = pieces.iter().map(|p| p.len()).sum();
+                        if len <= MAX_LITERAL_SIZE {
+                            let mut text = String::with_capacity(len);
+                            pieces.iter().for_each(|p| text.push_str(p));
+                            return Ok(Cow::Owned(text));
+                        }
+                    }
+                }
+            }
+        }
+
+        self.decode(tokens, skip_special_tokens).map(Cow::Owned)
+    }
+
+    /// Decode a sequence of token IDs back into text with full options
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - Slice of token IDs to decode
+    /// * `options` - Decoding options (skip_special_tokens, lstrip, include_special_text)
+    ///
+    /// # Returns
+    ///
+    /// Returns the decoded text as a String.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::{Tokenizer, DecodeOptions};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let tokens = vec![15043, 3186];
+    ///
+    /// // Decode with lstrip to remove leading whitespace from tokens
+    /// let opts = DecodeOptions::new(true, true, false);
+    /// let text = tokenizer.decode_with_options(&tokens, &opts)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "decode_with_options returns a Result that must be handled"]
+    pub fn decode_with_options(
+        &self,
+        tokens: &[TokenId],
+        options: &DecodeOptions,
+    ) -> Result<String, Error> {
+        let parity_options;
+        let options = if self.strict_parity {
+            parity_options = options.for_llamacpp_parity();
+            &parity_options
+        } else {
+            options
+        };
+
+        // NOTE: We intentionally do NOT assert preconditions here because
+        // tokens are user input that may be invalid. Out-of-range IDs are
+        // dropped, replaced or reported here, before anything looks them up.
+        let n_tokens = self.vocab.n_tokens();
+        let valid: Vec<TokenId>;
+        let tokens: &[TokenId] =
+            if let Some(&bad) = tokens.iter().find(|&&id| id as usize >= n_tokens) {
+                if !options.ignore_invalid_tokens {
+                    return Err(Error::InvalidToken(format!(
+                        "Token ID {bad} not found in vocabulary of {n_tokens} tokens"
+                    )));
+                }
+                let unk = self.vocab.unk_token_id();
+                valid = tokens
+                    .iter()
+                    .filter_map(|&id| {
+                        if (id as usize) < n_tokens {
+                            Some(id)
+                        } else {
+                            options.replace_invalid_with_unk.then_some(unk)
+                        }
+                    })
+                    .collect();
+                &valid
+            } else {
+                tokens
+            };
+
+        let filtered: Vec<TokenId>;
+        let filtered_tokens: &[TokenId] = if options.skip_special_tokens {
+            filtered = tokens
+                .iter()
+                .copied()
+                .filter(|&id| !self.vocab.is_special_token(id))
+                .collect();
+            &filtered
+        } else {
+            tokens
+        };
+
+        let render = options.effective_special_render();
+        // Name of the marker that replaces `token_id` under `Named` rendering.
+        let special_name = |token_id| {
+            if render == SpecialRender::Named && self.vocab.is_special_token(token_id) {
+                self.vocab.special_token_name(token_id)
+            } else {
+                None
+            }
+        };
+
+        let mut result = if options.strict_utf8 {
+            // Work on raw bytes so that multi-byte sequences split across
+            // tokens still validate as a whole.
+            let mut bytes = Vec::new();
+            if options.lstrip || render != SpecialRender::Raw {
+                for &token_id in filtered_tokens {
+                    if render == SpecialRender::Hidden && self.vocab.is_special_token(token_id) {
+                        continue;
+                    }
+                    if let Some(name) = special_name(token_id) {
+                        bytes.extend_from_slice(name.as_bytes());
+                        continue;
+                    }
+                    let piece = self.tokenizer_impl.decode_bytes(&[token_id], &self.vocab)?;
+                    if options.lstrip {
+                        bytes.extend_from_slice(trim_start_bytes(&piece));
+                    } else {
+                        bytes.extend_from_slice(&piece);
+                    }
+                }
+            } else {
+                bytes = self
+                    .tokenizer_impl
+                    .decode_bytes(filtered_tokens, &self.vocab)?;
+            }
+            String::from_utf8(bytes).map_err(|e| Error::InvalidUtf8(e.to_string()))?
+        } else if options.lstrip || render != SpecialRender::Raw {
+            // If we need special handling (lstrip or hidden/named specials),
+            // we need to decode token by token
+            let mut result = String::new();
+            for &token_id in filtered_tokens {
+                if render == SpecialRender::Hidden && self.vocab.is_special_token(token_id) {
+                    continue;
+                }
+                if let Some(name) = special_name(token_id) {
+                    result.push_str(name);
+                    continue;
+                }
+
+                let piece = self.tokenizer_impl.decode(&[token_id], &self.vocab)?;
+
+                let piece = if options.lstrip {
+                    piece.trim_start().to_string()
+                } else {
+                    piece
+                };
+
+                result.push_str(&piece);
+            }
+            result
+        } else {
+            self.tokenizer_impl.decode(filtered_tokens, &self.vocab)?
+        };
+
+        if options.strip_prefix_space && self.adds_space_prefix() && result.starts_with(' ') {
+            result.remove(0);
+        }
+
+        // Apply clean_spaces post-processing if enabled in vocab (llama.cpp parity)
+        // or requested by the caller
+        if self.vocab.clean_spaces() || options.clean_spaces {
+            result = clean_spaces(&result);
+        }
+
+        Ok(result)
+    }
+
+    /// Decode a sequence of token IDs into the raw bytes they represent
+    ///
+    /// Returns the concatenated bytes of every piece before any UTF-8
+    /// validation, so a multi-byte character split across tokens, or a
+    /// byte-fallback token on its own, comes back exactly as the model
+    /// produced it rather than as U+FFFD. No text post-processing is applied
+    /// ([`clean_spaces`] and the [`DecodeOptions`] text options only affect
+    /// [`decode`](Self::decode)).
+    ///
+    /// For valid UTF-8 output, `String::from_utf8(decode_to_bytes(..))`
+    /// equals [`decode`](Self::decode) on models without `clean_spaces`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidToken`] if any token ID is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let bytes = tokenizer.decode_to_bytes(&[15043, 3186], true)?;
+    /// let text = String::from_utf8_lossy(&bytes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "decode_to_bytes returns a Result that must be handled"]
+    pub fn decode_to_bytes(
+        &self,
+        tokens: &[TokenId],
+        skip_special_tokens: bool,
+    ) -> Result<Vec<u8>, Error> {
+        let n_tokens = self.vocab.n_tokens();
+        if let Some(&bad) = tokens.iter().find(|&&id| id as usize >= n_tokens) {
+            return Err(Error::InvalidToken(format!(
+                "Token ID {bad} not found in vocabulary of {n_tokens} tokens"
+            )));
+        }
+
+        if skip_special_tokens {
+            let filtered: Vec<TokenId> = tokens
+                .iter()
+                .copied()
+                .filter(|&id| !self.vocab.is_special_token(id))
+                .collect();
+            self.tokenizer_impl.decode_bytes(&filtered, &self.vocab)
+        } else {
+            self.tokenizer_impl.decode_bytes(tokens, &self.vocab)
+        }
+    }
+
+    /// Encode, decode and re-encode `text`, reporting where it diverges
+    ///
+    /// A diagnostic for validating GGUF conversions and new tokenizer
+    /// configurations. `text_matches` is false when decoding loses or alters
+    /// characters, e.g. CJK mapped to UNK by a vocabulary without byte
+    /// tokens; `tokens_stable` is false when the decoded text does not encode
+    /// back to the same IDs. No special tokens are added or skipped.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`encode`](Self::encode) and [`decode`](Self::decode).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let report = tokenizer.roundtrip_report("Hello, 世界")?;
+    /// if !report.text_matches {
+    ///     eprintln!("lossy: {:?} -> {:?}", "Hello, 世界", report.decoded);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "roundtrip_report returns a Result that must be handled"]
+    pub fn roundtrip_report(&self, text: &str) -> Result<RoundtripReport, Error> {
+        let tokens = self.encode(text, false)?;
+        let decoded = self.decode(&tokens, false)?;
+        let reencoded = self.encode(&decoded, false)?;
+        Ok(RoundtripReport {
+            text_matches: decoded == text,
+            tokens_stable: reencoded == tokens,
+            tokens,
+            decoded,
+            reencoded,
+        })
+    }
+
+    /// Get the vocabulary size
+    ///
+    /// # Returns
+    ///
+    /// The total number of tokens in the vocabulary, including reserved
+    /// slots. See [`padded_vocab_size`](Self::padded_vocab_size) for the
+    /// embedding row count and [`usable_vocab_size`](Self::usable_vocab_size)
+    /// for the tokens that can actually be produced.
+    #[must_use]
+    pub fn vocab_size(&self) -> usize {
+        self.vocab.n_tokens()
+    }
+
+    /// Byte length of the longest token piece in the vocabulary
+    ///
+    /// Useful for sizing lookahead buffers or bounding greedy matches; the
+    /// value is computed once when the model is loaded.
+    #[must_use]
+    pub fn longest_token_len(&self) -> usize {
+        self.vocab.longest_token_byte_len()
+    }
+
+    /// Get the embedding row count the model's logits are laid out over
+    ///
+    /// [`vocab_size`](Self::vocab_size) counts the token list. Some models
+    /// pad their embedding matrix past it to a convenient multiple and record
+    /// the padded size in `tokenizer.ggml.token_count`; this returns that
+    /// value when it is present and larger, and `vocab_size()` otherwise. Use
+    /// it to size or align logit tensors.
+    #[must_use]
+    pub fn padded_vocab_size(&self) -> usize {
+        self.vocab.padded_n_tokens()
+    }
+
+    /// Get the largest valid token ID, `vocab_size() - 1`
+    ///
+    /// Loading rejects empty vocabularies, so this is always a real token.
+    #[must_use]
+    pub fn max_token_id(&self) -> TokenId {
+        (self.vocab.n_tokens() - 1) as TokenId
+    }
+
+    /// Check that a logits vector lines up with this vocabulary
+    ///
+    /// Samplers index logits by token ID, so a tensor sized for a different
+    /// vocabulary silently picks the wrong tokens. Accepts
+    /// [`vocab_size`](Self::vocab_size) and
+    /// [`padded_vocab_size`](Self::padded_vocab_size).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::VocabularyError`] if `len` matches neither size.
+    #[must_use = "validate_logits_len returns a Result that must be handled"]
+    pub fn validate_logits_len(&self, len: usize) -> Result<(), Error> {
+        let n_tokens = self.vocab.n_tokens();
+        let padded = self.vocab.padded_n_tokens();
+        if len == n_tokens || len == padded {
+            return Ok(());
+        }
+        let expected = if padded == n_tokens {
+            n_tokens.to_string()
+        } else {
+            format!("{n_tokens} or {padded} (padded)")
+        };
+        Err(Error::VocabularyError(format!(
+            "Logits length {len} does not match vocabulary size {expected}"
+        )))
+    }
+
+    /// Get the number of tokens that can actually be produced
+    ///
+    /// Excludes reserved slots typed [`TokenType::Unused`] (often named
+    /// `[PAD32000]` or `<|reserved_special_token_N|>`), which stay in the token
+    /// list so IDs line up with the embedding but never appear in output.
+    /// Always at most [`vocab_size`](Self::vocab_size).
+    #[must_use]
+    pub fn usable_vocab_size(&self) -> usize {
+        self.vocab.n_usable_tokens()
+    }
+
+    /// Number of BPE merge rules loaded from the model (0 for non-BPE models)
+    #[must_use]
+    pub fn merge_count(&self) -> usize {
+        self.vocab.merge_count()
+    }
+
+    /// The BPE merge rules in priority order, highest first (empty for
+    /// non-BPE models)
+    ///
+    /// Pieces are in their stored GPT-2 byte-level form, e.g. `("Ġ", "t")`.
+    #[must_use]
+    pub fn merges(&self) -> &[(String, String)] {
+        self.vocab.get_merges()
+    }
+
+    /// The BPE merge rules as `"left right"` lines, in priority order
+    ///
+    /// This is the format of a HuggingFace `merges.txt` (and of the GGUF
+    /// `tokenizer.ggml.merges` array), so loading the lines back yields the
+    /// same [`merges`](Self::merges). `merges.txt` files conventionally start
+    /// with a `#version: 0.2` line, which is not included.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let mut merges_txt = String::from("#version: 0.2\n");
+    /// for line in tokenizer.export_merges() {
+    ///     merges_txt.push_str(&line);
+    ///     merges_txt.push('\n');
+    /// }
+    /// std::fs::write("merges.txt", merges_txt)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn export_merges(&self) -> Vec<String> {
+        self.merges()
+            .iter()
+            .map(|(left, right)| format!("{left} {right}"))
+            .collect()
+    }
+
+    /// Whether `other` tokenizes identically to `self`
+    ///
+    /// Shorthand for [`diff`](Self::diff) finding nothing. Useful for
+    /// asserting that a converted GGUF matches its reference tokenizer.
+    #[must_use]
+    pub fn equivalent(&self, other: &Tokenizer) -> bool {
+        self.diff(other).is_none()
+    }
+
+    /// The first difference between `self` and `other`, or `None` if they
+    /// are equivalent
+    ///
+    /// Compares the loaded vocabularies, not object identity, in this order:
+    /// model and pre-tokenizer type, vocabulary size, then per token its
+    /// piece, type and score, the merge rules, special-token IDs and strings
+    /// (including ones registered with
+    /// [`add_special_tokens`](Self::add_special_tokens)) and the encoding
+    /// flags (`add_bos_token`, `add_space_prefix`, byte fallback, ...). Chat
+    /// templates and other metadata are ignored, as is the per-tokenizer
+    /// [`strict_llamacpp_parity`](Self::strict_llamacpp_parity) setting.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let converted = Tokenizer::from_gguf_file("converted.gguf")?;
+    /// let reference = Tokenizer::from_gguf_file("reference.gguf")?;
+    /// if let Some(diff) = converted.diff(&reference) {
+    ///     panic!("conversion diverges: {diff}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn diff(&self, other: &Tokenizer) -> Option<TokenizerDiff> {
+        if Arc::ptr_eq(&self.vocab, &other.vocab) {
+            return None;
+        }
+        let (a, b) = (&*self.vocab, &*other.vocab);
+
+        if a.model_type() != b.model_type() {
+            return Some(TokenizerDiff::ModelType {
+                left: a.model_type().to_string(),
+                right: b.model_type().to_string(),
+            });
+        }
+        if a.pre_type() != b.pre_type() {
+            return Some(TokenizerDiff::PreType {
+                left: a.pre_type().map(str::to_string),
+                right: b.pre_type().map(str::to_string),
+            });
+        }
+        if a.n_tokens() != b.n_tokens() {
+            return Some(TokenizerDiff::VocabSize {
+                left: a.n_tokens(),
+                right: b.n_tokens(),
+            });
+        }
+
+        for id in 0..a.n_tokens() as TokenId {
+            let (left, right) = (a.get_token_text(id), b.get_token_text(id));
+            if left != right {
+                return Some(TokenizerDiff::Token {
+                    id,
+                    left: left.unwrap_or_default().to_string(),
+                    right: right.unwrap_or_default().to_string(),
+                });
+            }
+            let (left, right) = (a.get_token_type(id), b.get_token_type(id));
+            if left != right {
+                return Some(TokenizerDiff::TokenType { id, left, right });
+            }
+            let (left, right) = (a.get_token_score(id), b.get_token_score(id));
+            if left.to_bits() != right.to_bits() {
+                return Some(TokenizerDiff::TokenScore { id, left, right });
+            }
+        }
+
+        let (left, right) = (a.get_merges(), b.get_merges());
+        for index in 0..left.len().max(right.len()) {
+            if left.get(index) != right.get(index) {
+                return Some(TokenizerDiff::Merge {
+                    index,
+                    left: left.get(index).cloned(),
+                    right: right.get(index).cloned(),
+                });
+            }
+        }
+
+        let special_ids = |v: &Vocabulary| {
+            [
+                ("bos", Some(v.bos_token_id())),
+                ("eos", Some(v.eos_token_id())),
+                ("unk", Some(v.unk_token_id())),
+                ("pad", v.pad_token_id()),
+                ("cls", v.cls_token_id()),
+                ("sep", v.sep_token_id()),
+                ("mask", v.mask_token_id()),
+                ("eot", v.eot_token_id()),
+                ("eom", v.eom_token_id()),
+                ("eog", v.eog_token_id()),
+                ("nl", v.nl_token_id()),
+                ("fim_pre", v.fim_pre_token_id()),
+                ("fim_suf", v.fim_suf_token_id()),
+                ("fim_mid", v.fim_mid_token_id()),
+            ]
+        };
+        for ((role, left), (_, right)) in special_ids(a).into_iter().zip(special_ids(b)) {
+            if left != right {
+                return Some(TokenizerDiff::SpecialToken { role, left, right });
+            }
+        }
+
+        let (left, right) = (a.special_token_map(), b.special_token_map());
+        if left != right {
+            let mut texts: Vec<&String> = left.keys().chain(right.keys()).collect();
+            texts.sort_unstable();
+            for text in texts {
+                let (l, r) = (left.get(text).copied(), right.get(text).copied());
+                if l != r {
+                    return Some(TokenizerDiff::SpecialTokenText {
+                        text: text.clone(),
+                        left: l,
+                        right: r,
+                    });
+                }
+            }
+        }
+
+        let flags = |v: &Vocabulary| {
+            [
+                ("add_bos_token", v.add_bos_token()),
+                ("add_eos_token", v.add_eos_token()),
+                ("add_space_prefix", v.add_space_prefix()),
+                ("clean_spaces", v.clean_spaces()),
+                ("remove_extra_whitespaces", v.remove_extra_whitespaces()),
+                ("escape_whitespaces", v.escape_whitespaces()),
+                ("treat_whitespace_as_suffix", v.treat_whitespace_as_suffix()),
+                ("byte_fallback", v.byte_fallback()),
+            ]
+        };
+        for ((name, left), (_, right)) in flags(a).into_iter().zip(flags(b)) {
+            if left != right {
+                return Some(TokenizerDiff::Flag { name, left, right });
+            }
+        }
+
+        None
+    }
+
+    /// Borrow the underlying [`Vocabulary`]
+    ///
+    /// Gives access to token text, scores, types and merges without parsing
+    /// the GGUF file a second time. The reference is shared, so the loaded
+    /// vocabulary cannot be modified through it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let vocab = tokenizer.vocab();
+    /// println!("{} tokens, {} merges", vocab.n_tokens(), vocab.get_merges().len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn vocab(&self) -> &Vocabulary {
+        &self.vocab
+    }
+
+    /// Get the Beginning-of-Sequence (BOS) token ID
+    ///
+    /// # Returns
+    ///
+    /// The token ID used to mark the beginning of a sequence.
+    #[must_use]
+    pub fn bos_token(&self) -> TokenId {
+        self.vocab.bos_token_id()
+    }
+
+    /// Get the End-of-Sequence (EOS) token ID
+    ///
+    /// # Returns
+    ///
+    /// The token ID used to mark the end of a sequence.
+    #[must_use]
+    pub fn eos_token(&self) -> TokenId {
+        self.vocab.eos_token_id()
+    }
+
+    /// Check whether a token ends generation
+    ///
+    /// True for EOS and for every other end-of-generation token the model
+    /// has: `tokenizer.ggml.eot_token_id` / `eom_token_id`, and pieces such
+    /// as `<|eot_id|>`, `<|im_end|>` or `<end_of_turn>` (see
+    /// [`vocab::EOG_PIECES`]). Sampling loops should stop on any of them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// # let sampled = 0;
+    /// if tokenizer.is_eog_token(sampled) {
+    ///     println!("done");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn is_eog_token(&self, token: TokenId) -> bool {
+        self.vocab.is_eog_token(token)
+    }
+
+    /// All end-of-generation token IDs, sorted ascending
+    ///
+    /// See [`is_eog_token`](Self::is_eog_token).
+    #[must_use]
+    pub fn eog_token_ids(&self) -> &[TokenId] {
+        self.vocab.eog_token_ids()
+    }
+
+    /// Get the padding token ID, if the model defines one
+    ///
+    /// # Returns
+    ///
+    /// The ID from `tokenizer.ggml.padding_token_id`, or `None` for models
+    /// without a padding token (common for decoder-only LLMs).
+    #[must_use]
+    pub fn pad_token(&self) -> Option<TokenId> {
+        self.vocab.pad_token_id()
+    }
+
+    /// Check whether the model defines a padding token
+    ///
+    /// [`encode_batch_padded`](Self::encode_batch_padded) requires one.
+    #[must_use]
+    pub fn has_pad_token(&self) -> bool {
+        self.pad_token().is_some()
+    }
+
+    /// Get the classifier (CLS) token ID, if the model defines one
+    ///
+    /// Read from `tokenizer.ggml.cls_token_id`; for BERT-style (WPM) models
+    /// without that key, the `[CLS]` piece is used. WPM models start every
+    /// sequence encoded with `add_special_tokens` with this token.
+    #[must_use]
+    pub fn cls_token(&self) -> Option<TokenId> {
+        self.vocab.cls_token_id()
+    }
+
+    /// Get the separator (SEP) token ID, if the model defines one
+    ///
+    /// Read from `tokenizer.ggml.seperator_token_id` (llama.cpp's spelling)
+    /// or `tokenizer.ggml.sep_token_id`; for BERT-style (WPM) models without
+    /// either key, the `[SEP]` piece is used. WPM models end every sequence
+    /// encoded with `add_special_tokens` with this token.
+    #[must_use]
+    pub fn sep_token(&self) -> Option<TokenId> {
+        self.vocab.sep_token_id()
+    }
+
+    /// Get the end-of-turn (EOT) token ID, if the model defines one
+    ///
+    /// Read from `tokenizer.ggml.eot_token_id`. To stop generation, prefer
+    /// [`is_eog_token`](Self::is_eog_token), which also covers models that
+    /// only mark the end of a turn by piece text.
+    #[must_use]
+    pub fn eot_token(&self) -> Option<TokenId> {
+        self.vocab.eot_token_id()
+    }
+
+    /// Get the end-of-message (EOM) token ID, if the model defines one
+    ///
+    /// Read from `tokenizer.ggml.eom_token_id` (Llama-3.1 tool calls).
+    #[must_use]
+    pub fn eom_token(&self) -> Option<TokenId> {
+        self.vocab.eom_token_id()
+    }
+
+    /// Get the mask token ID, if the model defines one
+    ///
+    /// Read from `tokenizer.ggml.mask_token_id`.
+    #[must_use]
+    pub fn mask_token(&self) -> Option<TokenId> {
+        self.vocab.mask_token_id()
+    }
+
+    /// Get the fill-in-the-middle prefix token ID, if the model defines one
+    ///
+    /// Read from `tokenizer.ggml.fim_pre_token_id`, or the older
+    /// `tokenizer.ggml.prefix_token_id`.
+    #[must_use]
+    pub fn fim_pre_token(&self) -> Option<TokenId> {
+        self.vocab.fim_pre_token_id()
+    }
+
+    /// Get the fill-in-the-middle suffix token ID, if the model defines one
+    ///
+    /// Read from `tokenizer.ggml.fim_suf_token_id`, or the older
+    /// `tokenizer.ggml.suffix_token_id`.
+    #[must_use]
+    pub fn fim_suf_token(&self) -> Option<TokenId> {
+        self.vocab.fim_suf_token_id()
+    }
+
+    /// Get the fill-in-the-middle middle token ID, if the model defines one
+    ///
+    /// Read from `tokenizer.ggml.fim_mid_token_id`, or the older
+    /// `tokenizer.ggml.middle_token_id`.
+    #[must_use]
+    pub fn fim_mid_token(&self) -> Option<TokenId> {
+        self.vocab.fim_mid_token_id()
+    }
+
+    /// Get the unknown (UNK) token ID
+    ///
+    /// SPM, UGM, WPM and RWKV emit this ID for input they cannot represent.
+    /// Every vocabulary reports one (defaulting to 0 when the GGUF omits
+    /// `tokenizer.ggml.unknown_token_id`), so check [`has_unk`](Self::has_unk)
+    /// before treating it as a real UNK token.
+    #[must_use]
+    pub fn unk_token(&self) -> TokenId {
+        self.vocab.unk_token_id()
+    }
+
+    /// Check whether [`unk_token`](Self::unk_token) is an actual
+    /// [`TokenType::Unknown`] token
+    ///
+    /// Byte-level BPE vocabularies typically have no UNK token, in which case
+    /// the reported ID is just a default and may be an ordinary token.
+    #[must_use]
+    pub fn has_unk(&self) -> bool {
+        self.token_type(self.unk_token()) == TokenType::Unknown
+    }
+
+    /// Count the UNK tokens in an encoded sequence
+    ///
+    /// A non-zero count means part of the input was not representable by the
+    /// vocabulary. Always 0 for models without a real UNK token (see
+    /// [`has_unk`](Self::has_unk)), so ordinary tokens are never miscounted.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let tokens = tokenizer.encode("Hello world", false)?;
+    /// if tokenizer.unk_count(&tokens) > 0 {
+    ///     eprintln!("input contains characters the vocabulary cannot represent");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn unk_count(&self, tokens: &[TokenId]) -> usize {
+        if !self.has_unk() {
+            return 0;
+        }
+        let unk = self.unk_token();
+        tokens.iter().filter(|&&id| id == unk).count()
+    }
+
+    /// Get the tokenizer model type
+    ///
+    /// Returns the model type identifier from the GGUF metadata.
+    ///
+    /// # Returns
+    ///
+    /// The model type string, such as "llama", "mistral", "gpt2", "qwen", "qwen2", or "gemma".
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// println!("Model type: {}", tokenizer.model_type());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn model_type(&self) -> &str {
+        self.vocab.model_type()
+    }
+
+    /// Get the pre-tokenization type (for BPE models)
+    ///
+    /// Returns the pre-tokenizer identifier from GGUF metadata.
+    /// Used internally to select the correct regex patterns for pre-tokenization.
+    ///
+    /// # Returns
+    ///
+    /// The pre-type string like "llama3", "gpt-2", "deepseek-coder", etc., or None if not set.
+    #[must_use]
+    pub fn pre_type(&self) -> Option<&str> {
+        self.vocab.pre_type()
+    }
+
+    /// Classify the model into a [`ModelFamily`]
+    ///
+    /// A typed alternative to matching on [`model_type`](Self::model_type)
+    /// and [`pre_type`](Self::pre_type) strings.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::{ModelFamily, Tokenizer};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// if tokenizer.model_family() == ModelFamily::Llama3 {
+    ///     println!("Llama-3 style BPE");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn model_family(&self) -> ModelFamily {
+        ModelFamily::detect(self.vocab.model_type(), self.vocab.pre_type())
+    }
+
+    /// Name of the tokenization algorithm this tokenizer runs
+    ///
+    /// One of `"bpe"`, `"spm"`, `"ugm"`, `"wpm"`, `"rwkv"` or `"plamo2"`,
+    /// taken from the backend that was actually constructed. Unlike
+    /// [`model_type`](Self::model_type), which echoes the GGUF string, this
+    /// tells a `"gpt2"` model running BPE apart from a `"t5"` model running
+    /// Unigram.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// println!("{} via {}", tokenizer.model_type(), tokenizer.algorithm());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn algorithm(&self) -> &'static str {
+        self.tokenizer_impl.algorithm()
+    }
+
+    /// The text as the backend sees it right before segmentation
+    ///
+    /// Runs only the [`algorithm`](Self::algorithm)'s preprocessing, without
+    /// tokenizing, to help debug divergences from a reference tokenizer:
+    ///
+    /// - `"bpe"`: the pre-tokenizer fragments, byte-encoded (`Ġ` for a
+    ///   space) and separated by plain spaces, e.g. `"Hello , Ġworld"`.
+    /// - `"spm"`: spaces replaced by `▁`, with the `▁` prefix when
+    ///   `add_space_prefix` is set.
+    /// - `"ugm"`: the precompiled charsmap applied, then the same `▁`
+    ///   handling plus `remove_extra_whitespaces`.
+    /// - `"wpm"`: the lowercased, accent-stripped words with their phantom
+    ///   `▁` prefixes, e.g. `"▁hello▁,▁world"`.
+    /// - `"rwkv"`, `"plamo2"`: unchanged; these match the raw text.
+    ///
+    /// Encode options such as [`EncodeOptions::strip_bom`] or `normalization`
+    /// run before this step and are not applied here. Empty input stays empty.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// println!("{}", tokenizer.normalize("Hello world"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn normalize(&self, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+        self.tokenizer_impl.normalize(text, &self.vocab)
+    }
+
+    /// Split text into the BPE pre-tokenizer fragments, before any merging
+    ///
+    /// Returns each fragment the `tokenizer.ggml.pre` split patterns produce,
+    /// as raw input text, in order; merges never cross a fragment boundary.
+    /// When token IDs differ from llama.cpp, comparing these with llama.cpp's
+    /// `unicode_regex_split` output shows whether the split patterns or the
+    /// merges are at fault. [`normalize`](Self::normalize) shows the same
+    /// fragments byte-encoded.
+    ///
+    /// This is a debugging aid, not a stable contract: fragment boundaries
+    /// change whenever the split patterns are corrected to match llama.cpp.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::UnsupportedModel`] if the model is not BPE
+    ///   ([`algorithm`](Self::algorithm) is not `"bpe"`).
+    /// - [`Error::TokenizationFailed`] if `text` exceeds [`MAX_INPUT_SIZE`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("gpt2.gguf")?;
+    /// // ["Hello", ",", " world"]
+    /// println!("{:?}", tokenizer.pre_tokenize_debug("Hello, world")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "pre_tokenize_debug returns a Result that must be handled"]
+    pub fn pre_tokenize_debug(&self, text: &str) -> Result<Vec<String>, Error> {
+        if text.len() > MAX_INPUT_SIZE {
+            return Err(Error::TokenizationFailed(format!(
+                "Input text too large: {} bytes (max: {MAX_INPUT_SIZE})",
+                text.len()
+            )));
+        }
+        let spans = self.tokenizer_impl.fragments(text).ok_or_else(|| {
+            Error::UnsupportedModel(format!(
+                "pre_tokenize_debug requires a BPE model, not {:?}",
+                self.algorithm()
+            ))
+        })?;
+        Ok(spans
+            .into_iter()
+            .map(|(start, end)| text[start..end].to_string())
+            .collect())
+    }
+
+    /// Override the pre-tokenizer type and re-select the BPE split patterns.
+    ///
+    /// Useful for GGUF files that omit `tokenizer.ggml.pre` (and so get the
+    /// generic default patterns) or carry a wrong value from a buggy
+    /// converter. Unrecognised names fall back to the default patterns, as
+    /// they do at load time. Only the BPE backend uses the pre-tokenizer type.
+    ///
+    /// The vocabulary is always copied, so that a failed rebuild can leave
+    /// the tokenizer untouched; clones made before this call keep the old
+    /// patterns. To override it before the tokenizer is built, use
+    /// [`TokenizerBuilder::pre_type`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TokenizationFailed`] if the backend cannot be rebuilt
+    /// with the new patterns; the tokenizer is left unchanged in that case.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// if tokenizer.pre_type().is_none() {
+    ///     tokenizer.set_pre_type("llama3")?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_pre_type(&mut self, pre_type: &str) -> Result<(), Error> {
+        let mut vocab = (*self.vocab).clone();
+        vocab.set_pre_type(pre_type);
+        self.tokenizer_impl = Self::select_backend(&vocab)?;
+        self.vocab = Arc::new(vocab);
+        Ok(())
+    }
+
+    /// Whether BPE encoding splits unmatched symbols into byte tokens
+    ///
+    /// A BPE symbol that is not itself a vocabulary piece (a merge whose
+    /// result the vocabulary lacks, for instance) is encoded as its
+    /// single-byte pieces when this is true, with UNK only for bytes the
+    /// vocabulary has no piece for. When false, the whole symbol becomes one
+    /// UNK. Defaults to whether the vocabulary has all 256 byte pieces
+    /// ([`Vocabulary::has_byte_tokens`]). Only the BPE backend reads it.
+    #[must_use]
+    pub fn byte_fallback(&self) -> bool {
+        self.vocab.byte_fallback()
+    }
+
+    /// Whether every byte value has a token
+    ///
+    /// True when [`Vocabulary::byte_token_coverage`] reports all 256 bytes,
+    /// as `<0xNN>` pieces or GPT-2 byte-level characters. Without full
+    /// coverage, input containing an uncovered byte can encode to UNK.
+    #[must_use]
+    pub fn has_full_byte_fallback(&self) -> bool {
+        self.vocab.byte_token_coverage().0 == 256
+    }
+
+    /// Override [`byte_fallback`](Self::byte_fallback) and rebuild the backend
+    ///
+    /// Like [`set_pre_type`](Self::set_pre_type), this works on a fresh copy
+    /// of the vocabulary, so clones made before the call keep the old setting.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`set_pre_type`](Self::set_pre_type); the tokenizer is left
+    /// unchanged on error.
+    pub fn set_byte_fallback(&mut self, enabled: bool) -> Result<(), Error> {
+        let mut vocab = (*self.vocab).clone();
+        vocab.set_byte_fallback(enabled);
+        self.tokenizer_impl = Self::select_backend(&vocab)?;
+        self.vocab = Arc::new(vocab);
+        Ok(())
+    }
+
+    /// Make encoding and decoding mirror llama.cpp exactly
+    ///
+    /// With `enable`, the shimmytok-only conveniences in [`EncodeOptions`]
+    /// and [`DecodeOptions`] are ignored, so the same calls produce what
+    /// `llama_tokenize` / `llama_detokenize` would:
+    ///
+    /// - [`EncodeOptions::strip_bom`] and [`EncodeOptions::strip_zero_width`]
+    ///   are off: a BOM or zero-width character is tokenized like any other
+    ///   character.
+    /// - [`EncodeOptions::normalize_newlines`] is off: `\r` is tokenized as
+    ///   written.
+    /// - [`EncodeOptions::add_leading_space`] is off: BPE text is encoded
+    ///   without a prepended space.
+    /// - [`EncodeOptions::normalization`] is off: the input reaches the
+    ///   backend unnormalized.
+    /// - [`DecodeOptions::clean_spaces`] is off: [`clean_spaces`] only runs
+    ///   when the model's `tokenizer.ggml.clean_spaces` flag is set.
+    /// - [`DecodeOptions::strip_prefix_space`] is off: the `add_space_prefix`
+    ///   space is kept, as llama.cpp's per-token pieces do.
+    /// - [`SpecialRender::Named`] renders as [`SpecialRender::Raw`]: special
+    ///   tokens come out as their vocabulary text, or nothing without
+    ///   `include_special_text`.
+    ///
+    /// Everything llama.cpp itself does (BOS/EOS from the model flags,
+    /// `parse_special`, `lstrip`, `skip_special_tokens`) is unaffected. The
+    /// setting applies to this handle only; clones made before the call keep
+    /// their own.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::{EncodeOptions, Tokenizer};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// tokenizer.strict_llamacpp_parity(true);
+    /// // The BOM is tokenized, exactly as `llama-tokenize` would.
+    /// let opts = EncodeOptions::with_special_tokens(false).with_strip_bom(true);
+    /// let tokens = tokenizer.encode_with_options("\u{FEFF}Hello", &opts)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn strict_llamacpp_parity(&mut self, enable: bool) {
+        self.strict_parity = enable;
+    }
+
+    /// Whether [`strict_llamacpp_parity`](Self::strict_llamacpp_parity) is on
+    #[must_use]
+    pub fn is_strict_llamacpp_parity(&self) -> bool {
+        self.strict_parity
+    }
+
+    /// Whether the SentencePiece-style encoders prepend a `▁` word boundary
+    ///
+    /// Read from `tokenizer.ggml.add_space_prefix` (defaults to `true`). When
+    /// false, interior spaces are still mapped to `▁` but no leading one is added.
+    #[must_use]
+    pub fn add_space_prefix(&self) -> bool {
+        self.vocab.add_space_prefix()
+    }
+
+    /// Encode multiple texts, returning one token sequence per input.
     ///
     /// This method is available in every build configuration — native, WASM/WASI,
     /// and `--no-default-features`. The result order always matches the input
@@ -707,58 +3711,241 @@ impl Tokenizer {
     /// all other configurations run sequentially. This is an internal
     /// implementation detail and never changes the results.
     ///
-    /// # Arguments
+    /// # Arguments
+    ///
+    /// * `texts` - Slice of text strings to tokenize
+    /// * `add_special_tokens` - If true, adds BOS/EOS tokens according to model configuration
+    ///
+    /// # Returns
+    ///
+    /// Returns a vector of token ID vectors, one for each input text, in input
+    /// order.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let texts = vec!["Hello world", "Goodbye world"];
+    /// let batch = tokenizer.encode_batch(&texts, true)?;
+    /// for (text, tokens) in texts.iter().zip(batch.iter()) {
+    ///     println!("{}: {:?}", text, tokens);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "encode_batch returns a Result that must be handled"]
+    pub fn encode_batch(
+        &self,
+        texts: &[&str],
+        add_special_tokens: bool,
+    ) -> Result<Vec<Vec<TokenId>>, Error> {
+        // Both backends collect per-input results into an order-preserving Vec,
+        // then `finalize_batch` selects the lowest-index error (if any). This
+        // makes the error deterministic and identical regardless of whether the
+        // work ran sequentially or across the Rayon thread pool.
+        #[cfg(feature = "parallel")]
+        {
+            let total_bytes: usize = texts.iter().map(|t| t.len()).sum();
+            if texts.len() >= PARALLEL_BATCH_MIN_ITEMS && total_bytes >= PARALLEL_BATCH_MIN_BYTES {
+                let results: Vec<Result<Vec<TokenId>, Error>> = texts
+                    .par_iter()
+                    .map(|text| self.encode(text, add_special_tokens))
+                    .collect();
+                return finalize_batch(results);
+            }
+        }
+
+        self.encode_batch_sequential(texts, add_special_tokens)
+    }
+
+    /// The serial batch backend: the only one without the `parallel`
+    /// feature (e.g. on `wasm32-unknown-unknown`), and the small-batch path
+    /// with it.
+    fn encode_batch_sequential(
+        &self,
+        texts: &[&str],
+        add_special_tokens: bool,
+    ) -> Result<Vec<Vec<TokenId>>, Error> {
+        let results: Vec<Result<Vec<TokenId>, Error>> = texts
+            .iter()
+            .map(|text| self.encode(text, add_special_tokens))
+            .collect();
+        finalize_batch(results)
+    }
+
+    /// Count how often each token occurs across `texts`
+    ///
+    /// Encodes without BOS/EOS through [`encode_batch`](Self::encode_batch)
+    /// (so in parallel with the default `parallel` feature) and sums the
+    /// occurrences of every token ID. Tokens that never occur are absent. Use
+    /// [`top_k`] to list the most frequent ones.
+    ///
+    /// Memory stays bounded for large corpora: texts are encoded 1024 at a
+    /// time and each chunk's sequences are dropped once counted, so beyond
+    /// one chunk only the map itself is kept — at most one entry per
+    /// vocabulary token. The caller still holds `texts`; for corpora that do
+    /// not fit in memory, call this per shard and add the maps together.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`encode_batch`](Self::encode_batch); the first failing text
+    /// aborts the count.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::{top_k, Tokenizer};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let histogram = tokenizer.token_histogram(&["the cat", "the dog"])?;
+    /// for (token, count) in top_k(&histogram, 10) {
+    ///     println!("{:?}: {count}", tokenizer.token_to_piece(token)?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "token_histogram returns a Result that must be handled"]
+    pub fn token_histogram(
+        &self,
+        texts: &[&str],
+    ) -> Result<std::collections::HashMap<TokenId, u64>, Error> {
+        let mut histogram = std::collections::HashMap::new();
+        for chunk in texts.chunks(HISTOGRAM_CHUNK_TEXTS) {
+            for tokens in self.encode_batch(chunk, false)? {
+                for token in tokens {
+                    *histogram.entry(token).or_insert(0) += 1;
+                }
+            }
+        }
+        Ok(histogram)
+    }
+
+    /// Decode multiple token sequences, returning one string per input.
+    ///
+    /// The counterpart of [`encode_batch`](Self::encode_batch): the result
+    /// order always matches the input order, each element is identical to
+    /// calling [`decode`](Self::decode) on the corresponding sequence, and if
+    /// several sequences fail the error for the lowest index is returned. With
+    /// the default `parallel` feature, large batches are decoded across a
+    /// Rayon thread pool.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`decode`](Self::decode).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let beams = tokenizer.encode_batch(&["Hello world", "Hello there"], false)?;
+    /// let sequences: Vec<&[u32]> = beams.iter().map(Vec::as_slice).collect();
+    /// let texts = tokenizer.decode_batch(&sequences, true)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "decode_batch returns a Result that must be handled"]
+    pub fn decode_batch(
+        &self,
+        sequences: &[&[TokenId]],
+        skip_special_tokens: bool,
+    ) -> Result<Vec<String>, Error> {
+        #[cfg(feature = "parallel")]
+        {
+            let total_tokens: usize = sequences.iter().map(|s| s.len()).sum();
+            if sequences.len() >= PARALLEL_BATCH_MIN_ITEMS
+                && total_tokens >= PARALLEL_DECODE_MIN_TOKENS
+            {
+                let results: Vec<Result<String, Error>> = sequences
+                    .par_iter()
+                    .map(|tokens| self.decode(tokens, skip_special_tokens))
+                    .collect();
+                return finalize_batch(results);
+            }
+        }
+
+        let results: Vec<Result<String, Error>> = sequences
+            .iter()
+            .map(|tokens| self.decode(tokens, skip_special_tokens))
+            .collect();
+        finalize_batch(results)
+    }
+
+    /// Encode a batch into equal-length rows plus an attention mask
+    ///
+    /// Encodes like [`encode_batch`](Self::encode_batch), then right-pads every
+    /// row with the model's padding token to the width chosen by `pad_to`.
+    /// The mask has the same shape as the rows: `1` for a real token and `0`
+    /// for padding.
     ///
-    /// * `texts` - Slice of text strings to tokenize
-    /// * `add_special_tokens` - If true, adds BOS/EOS tokens according to model configuration
+    /// With [`PadStrategy::Fixed`], rows longer than the fixed width are
+    /// truncated from the right, preserving the added BOS/EOS tokens exactly
+    /// as [`EncodeOptions::with_truncation`] does.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// Returns a vector of token ID vectors, one for each input text, in input
-    /// order.
+    /// - [`Error::VocabularyError`] if the model defines no padding token
+    ///   (`tokenizer.ggml.padding_token_id`).
+    /// - [`Error::TokenizationFailed`] if a fixed width exceeds
+    ///   [`MAX_OUTPUT_TOKENS`].
+    /// - Otherwise the same errors as [`encode_batch`](Self::encode_batch).
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use shimmytok::Tokenizer;
+    /// use shimmytok::{PadStrategy, Tokenizer};
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
-    /// let texts = vec!["Hello world", "Goodbye world"];
-    /// let batch = tokenizer.encode_batch(&texts, true)?;
-    /// for (text, tokens) in texts.iter().zip(batch.iter()) {
-    ///     println!("{}: {:?}", text, tokens);
-    /// }
+    /// let (rows, mask) =
+    ///     tokenizer.encode_batch_padded(&["Hello", "Hello world"], true, PadStrategy::Longest)?;
+    /// assert_eq!(rows[0].len(), rows[1].len());
     /// # Ok(())
     /// # }
     /// ```
-    #[must_use = "encode_batch returns a Result that must be handled"]
-    pub fn encode_batch(
+    #[must_use = "encode_batch_padded returns a Result that must be handled"]
+    pub fn encode_batch_padded(
         &self,
         texts: &[&str],
         add_special_tokens: bool,
-    ) -> Result<Vec<Vec<TokenId>>, Error> {
-        // Both backends collect per-input results into an order-preserving Vec,
-        // then `finalize_batch` selects the lowest-index error (if any). This
-        // makes the error deterministic and identical regardless of whether the
-        // work ran sequentially or across the Rayon thread pool.
-        #[cfg(feature = "parallel")]
-        {
-            let total_bytes: usize = texts.iter().map(|t| t.len()).sum();
-            if texts.len() >= PARALLEL_BATCH_MIN_ITEMS && total_bytes >= PARALLEL_BATCH_MIN_BYTES {
-                let results: Vec<Result<Vec<TokenId>, Error>> = texts
-                    .par_iter()
-                    .map(|text| self.encode(text, add_special_tokens))
-                    .collect();
-                return finalize_batch(results);
+        pad_to: PadStrategy,
+    ) -> Result<PaddedBatch, Error> {
+        let pad_id = self.vocab.pad_token_id().ok_or_else(|| {
+            Error::VocabularyError("Padding requested but model defines no pad token".to_string())
+        })?;
+        if let PadStrategy::Fixed(width) = pad_to {
+            if width > MAX_OUTPUT_TOKENS {
+                return Err(Error::TokenizationFailed(format!(
+                    "Padding width {width} exceeds maximum output tokens ({MAX_OUTPUT_TOKENS})"
+                )));
             }
         }
 
-        let results: Vec<Result<Vec<TokenId>, Error>> = texts
-            .iter()
-            .map(|text| self.encode(text, add_special_tokens))
-            .collect();
-        finalize_batch(results)
+        let mut rows = self.encode_batch(texts, add_special_tokens)?;
+        let width = match pad_to {
+            PadStrategy::Longest => rows.iter().map(Vec::len).max().unwrap_or(0),
+            PadStrategy::Fixed(width) => width,
+        };
+        let (prefix, suffix) = self.special_affixes();
+        let lead = usize::from(add_special_tokens && prefix.is_some());
+        let trail = usize::from(add_special_tokens && suffix.is_some());
+
+        let mut masks = Vec::with_capacity(rows.len());
+        for row in &mut rows {
+            truncate_tokens(row, width, TruncationSide::Right, lead, trail);
+            let mut mask = vec![1u8; row.len()];
+            mask.resize(width, 0);
+            row.resize(width, pad_id);
+            masks.push(mask);
+        }
+
+        Ok((rows, masks))
     }
 
     /// Decode a single token to text
@@ -805,6 +3992,59 @@ impl Tokenizer {
         self.tokenizer_impl.decode(&[token], &self.vocab)
     }
 
+    /// Create a [`StreamDecoder`] for UTF-8-safe token-by-token decoding
+    ///
+    /// Unlike calling [`decode_single`](Self::decode_single) per token, the
+    /// stream decoder holds back bytes of a character that is split across
+    /// tokens (common for emoji and CJK with byte-level vocabularies), so no
+    /// `�` is emitted mid-character.
+    #[must_use]
+    pub fn stream_decoder(&self) -> StreamDecoder<'_> {
+        StreamDecoder::new(self)
+    }
+
+    /// Decode tokens whose tail may not be final yet
+    ///
+    /// For speculative decoding and beam search, where the last candidate
+    /// token can end partway through a character. Returns the text that is
+    /// renderable now together with the number of trailing bytes held back
+    /// because they start a character the next token may complete; those
+    /// bytes are not emitted as `�`. The text is what a fresh
+    /// [`StreamDecoder`] produces for `tokens` before
+    /// [`finish`](StreamDecoder::finish).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidToken`] if any token ID is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let tokens = tokenizer.encode("Rust 🦀", false)?;
+    /// let (text, pending) = tokenizer.try_decode_partial(&tokens[..tokens.len() - 1])?;
+    /// println!("{text} (+{pending} bytes pending)");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "try_decode_partial returns a Result that must be handled"]
+    pub fn try_decode_partial(&self, tokens: &[TokenId]) -> Result<(String, usize), Error> {
+        let mut stream = self.stream_decoder();
+        let mut text = String::new();
+        for &token in tokens {
+            text.push_str(&stream.push(token)?);
+        }
+        Ok((text, stream.pending_len()))
+    }
+
+    /// Raw bytes of a single token's decoded piece, without UTF-8 validation.
+    pub(crate) fn decode_token_bytes(&self, token: TokenId) -> Result<Vec<u8>, Error> {
+        self.tokenizer_impl.decode_bytes(&[token], &self.vocab)
+    }
+
     /// Get the text representation of a token
     ///
     /// Returns the raw token piece (vocabulary entry) for a given token ID.
@@ -879,6 +4119,75 @@ impl Tokenizer {
         self.vocab.get_token_id(text)
     }
 
+    /// Look up the ID of an exact vocabulary piece.
+    ///
+    /// Same exact-match semantics as [`get_token`](Self::get_token), named to
+    /// pair with [`id_to_token`](Self::id_to_token) for logit-bias and
+    /// grammar-constrained decoding code.
+    #[must_use]
+    pub fn token_to_id(&self, piece: &str) -> Option<TokenId> {
+        self.vocab.get_token_id(piece)
+    }
+
+    /// Get the full vocabulary as an owned piece → ID map.
+    ///
+    /// This clones every vocabulary string: for a 150k-token model that is
+    /// ~150k small heap allocations and several MB of memory. Prefer
+    /// [`vocab_iter`](Self::vocab_iter) when you only need to walk the
+    /// vocabulary once.
+    #[must_use]
+    pub fn get_vocab(&self) -> std::collections::HashMap<String, TokenId> {
+        self.vocab.token_to_id_map().clone()
+    }
+
+    /// Iterate over `(piece, id)` pairs in ID order, borrowing the pieces.
+    ///
+    /// The zero-copy alternative to [`get_vocab`](Self::get_vocab) for
+    /// building logit masks or vocabulary diffs.
+    pub fn vocab_iter(&self) -> impl Iterator<Item = (&str, TokenId)> + '_ {
+        self.vocab.iter()
+    }
+
+    /// Borrow the vocabulary piece for a token ID.
+    ///
+    /// Unlike [`token_to_piece`](Self::token_to_piece) this does not allocate
+    /// and returns `None` (rather than an error) for out-of-range IDs. The
+    /// piece is the raw stored form, e.g. `▁Hello` or `ĠHello`.
+    #[must_use]
+    pub fn id_to_token(&self, id: TokenId) -> Option<&str> {
+        self.vocab.get_token_text(id)
+    }
+
+    /// Get the score of a token
+    ///
+    /// For SentencePiece and Unigram models this is the piece's log
+    /// probability; BPE models typically store merge priority or zeros.
+    ///
+    /// # Returns
+    ///
+    /// The token's score, or `None` if the token ID is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// if let Some(score) = tokenizer.token_score(15043) {
+    ///     println!("Score: {score}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn token_score(&self, token: TokenId) -> Option<f32> {
+        if token >= self.vocab.n_tokens() as TokenId {
+            return None;
+        }
+        Some(self.vocab.get_token_score(token))
+    }
+
     /// Get the type of a token
     ///
     /// Returns the token type classification from the vocabulary.
@@ -942,6 +4251,282 @@ impl Tokenizer {
         }
         self.vocab.is_special_token(token)
     }
+
+    /// Check whether a token is a byte-fallback token
+    ///
+    /// True for tokens typed [`TokenType::Byte`], the `<0x00>`..`<0xFF>`
+    /// pieces SentencePiece-style models fall back to for characters outside
+    /// their vocabulary. Byte-level BPE models type their single-byte pieces
+    /// as ordinary tokens, so this is false for them; use
+    /// [`byte_token_value`](Self::byte_token_value) to cover both.
+    #[must_use]
+    pub fn is_byte_token(&self, token: TokenId) -> bool {
+        self.token_type(token) == TokenType::Byte
+    }
+
+    /// The single raw byte a token stands for, if it stands for exactly one
+    ///
+    /// Parses the `<0xNN>` piece of a [byte token](Self::is_byte_token), and
+    /// for byte-level BPE models maps a
+    /// one-character piece back through the GPT-2 byte encoder (`Ċ` is
+    /// `0x0A`, `a` is `0x61`). Returns `None` for multi-byte pieces, special
+    /// tokens and out-of-range IDs. Useful for building byte-level sampling
+    /// constraints.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let newline_tokens: Vec<u32> = (0..tokenizer.vocab_size() as u32)
+    ///     .filter(|&id| tokenizer.byte_token_value(id) == Some(b'\n'))
+    ///     .collect();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn byte_token_value(&self, token: TokenId) -> Option<u8> {
+        if self.is_special_token(token) {
+            return None;
+        }
+        let piece = self.vocab.get_token_text(token)?;
+        if self.is_byte_token(token) {
+            return byte_encoder::parse_hex_byte_token(piece);
+        }
+        if !self.tokenizer_impl.is_byte_level() {
+            return None;
+        }
+        let mut chars = piece.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => byte_encoder::char_to_byte(c),
+            _ => None,
+        }
+    }
+
+    /// Check whether a token begins a new word
+    ///
+    /// True when the token's piece starts with the model's word-boundary
+    /// marker: `Ġ` (an encoded space) for byte-level BPE, `▁` for
+    /// SentencePiece, Unigram and WordPiece, and a plain space for RWKV and
+    /// PLaMo-2, whose pieces are stored as text. Useful for word-level diffs
+    /// and whitespace-aware highlighting. Out-of-range IDs return false.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// let words = tokenizer
+    ///     .encode("Hello brave new world", false)?
+    ///     .into_iter()
+    ///     .filter(|&id| tokenizer.token_starts_word(id))
+    ///     .count();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn token_starts_word(&self, id: TokenId) -> bool {
+        use ModelFamily as F;
+        let Some(piece) = self.vocab.get_token_text(id) else {
+            return false;
+        };
+        let marker = match self.model_family() {
+            F::Gpt2 | F::Llama3 | F::Qwen2 | F::DeepSeek | F::StarCoder | F::OtherBpe(_) => {
+                '\u{0120}'
+            }
+            F::Rwkv | F::Plamo2 => ' ',
+            F::Llama | F::Gemma | F::Bert | F::T5 | F::Unknown(_) => '\u{2581}',
+        };
+        piece.starts_with(marker)
+    }
+
+    /// List the IDs of all special tokens, in ascending order
+    ///
+    /// Covers every ID for which [`is_special_token`](Self::is_special_token)
+    /// is true: control and unknown tokens, BOS/EOS/UNK/PAD and the model's
+    /// other special IDs, plus tokens registered with
+    /// [`add_special_tokens`](Self::add_special_tokens). Useful for building
+    /// stop-token sets.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// for (id, piece) in tokenizer.special_tokens() {
+    ///     println!("{id}: {piece}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn special_token_ids(&self) -> Vec<TokenId> {
+        self.vocab.special_token_ids()
+    }
+
+    /// Like [`special_token_ids`](Self::special_token_ids), with each token's
+    /// vocabulary piece
+    #[must_use]
+    pub fn special_tokens(&self) -> Vec<(TokenId, &str)> {
+        self.vocab.special_tokens()
+    }
+
+    /// Group the model's special tokens by role, like HuggingFace's
+    /// `special_tokens_map.json`
+    ///
+    /// BOS, EOS and UNK come from the GGUF IDs and are `None` only if the ID
+    /// is outside the vocabulary; PAD, CLS, SEP and MASK are set when the
+    /// model defines them (see [`pad_token`](Self::pad_token) and friends).
+    /// Every other [`special_tokens`](Self::special_tokens) entry goes in
+    /// `additional_special_tokens`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::Tokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// if let Some((id, piece)) = tokenizer.special_tokens_map().eos_token {
+    ///     println!("EOS is {piece} ({id})");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn special_tokens_map(&self) -> SpecialTokensMap {
+        let entry = |id: Option<TokenId>| {
+            let id = id?;
+            Some((id, self.vocab.get_token_text(id)?.to_string()))
+        };
+        let map = SpecialTokensMap {
+            bos_token: entry(Some(self.vocab.bos_token_id())),
+            eos_token: entry(Some(self.vocab.eos_token_id())),
+            unk_token: entry(Some(self.vocab.unk_token_id())),
+            pad_token: entry(self.vocab.pad_token_id()),
+            cls_token: entry(self.vocab.cls_token_id()),
+            sep_token: entry(self.vocab.sep_token_id()),
+            mask_token: entry(self.vocab.mask_token_id()),
+            additional_special_tokens: Vec::new(),
+        };
+        let roles: Vec<TokenId> = [
+            &map.bos_token,
+            &map.eos_token,
+            &map.unk_token,
+            &map.pad_token,
+            &map.cls_token,
+            &map.sep_token,
+            &map.mask_token,
+        ]
+        .into_iter()
+        .filter_map(|token| token.as_ref().map(|(id, _)| *id))
+        .collect();
+        let additional_special_tokens = self
+            .vocab
+            .special_tokens()
+            .into_iter()
+            .filter(|(id, _)| !roles.contains(id))
+            .map(|(id, piece)| (id, piece.to_string()))
+            .collect();
+        SpecialTokensMap {
+            additional_special_tokens,
+            ..map
+        }
+    }
+
+    /// Register special-token strings that are not marked special in the model
+    ///
+    /// Chat templates and tool-calling formats often rely on sentinels such as
+    /// `<|im_start|>` that a GGUF file stores as ordinary tokens, or not at
+    /// all. After registration, each `(text, id)` pair is split out of the
+    /// input as the single token `id` by
+    /// [`encode_with_options`](Self::encode_with_options) with
+    /// `parse_special`, and `id` counts as special for
+    /// [`is_special_token`](Self::is_special_token) and for skipping during
+    /// decode. Decoding `id` still yields the vocabulary's piece for it.
+    /// Registering the same text twice keeps the last ID; see
+    /// [`Vocabulary::add_special_tokens`].
+    ///
+    /// Clones made before this call are unaffected; the vocabulary is copied
+    /// on write if it is shared.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidToken`] if a text is empty or an ID is outside
+    /// the vocabulary; nothing is registered in that case.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shimmytok::{EncodeOptions, Tokenizer};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+    /// tokenizer.add_special_tokens(&[("<tool_call>".to_string(), 151657)])?;
+    /// let opts = EncodeOptions::with_parse_special(false, true);
+    /// let tokens = tokenizer.encode_with_options("<tool_call>{}", &opts)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_special_tokens(&mut self, tokens: &[(String, TokenId)]) -> Result<(), Error> {
+        Arc::make_mut(&mut self.vocab).add_special_tokens(tokens)
+    }
+}
+
+/// Iterator behind [`Tokenizer::encode_iter`].
+struct EncodeIter<'a> {
+    prefix: Option<TokenId>,
+    chunks: TokenChunks<'a>,
+    /// The rest of the current run of text tokens.
+    current: std::vec::IntoIter<TokenId>,
+    /// Text tokens loaded so far, checked against [`MAX_OUTPUT_TOKENS`].
+    produced: usize,
+    suffix: Option<TokenId>,
+    done: bool,
+}
+
+impl Iterator for EncodeIter<'_> {
+    type Item = Result<TokenId, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if let Some(prefix) = self.prefix.take() {
+            return Some(Ok(prefix));
+        }
+        loop {
+            if let Some(token) = self.current.next() {
+                return Some(Ok(token));
+            }
+            match self.chunks.next() {
+                Some(Ok(run)) => {
+                    self.produced += run.len();
+                    if self.produced > MAX_OUTPUT_TOKENS {
+                        self.done = true;
+                        return Some(Err(Error::TokenizationFailed(format!(
+                            "Output would exceed max tokens: {} (max: {})",
+                            self.produced, MAX_OUTPUT_TOKENS
+                        ))));
+                    }
+                    self.current = run.into_iter();
+                }
+                Some(Err(e)) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+                None => break,
+            }
+        }
+        self.done = true;
+        self.suffix.take().map(Ok)
+    }
 }
 
 // ============================================================================
@@ -1008,19 +4593,92 @@ fn split_on_special_tokens(
     result
 }
 
+/// Byte-level counterpart of [`str::trim_start`] for possibly-invalid UTF-8.
+///
+/// Only the leading valid UTF-8 prefix is inspected, so a piece that starts
+/// with a partial sequence is returned unchanged.
+fn trim_start_bytes(bytes: &[u8]) -> &[u8] {
+    let valid = match std::str::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(e) => std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or(""),
+    };
+    &bytes[valid.len() - valid.trim_start().len()..]
+}
+
+/// Contractions [`clean_spaces`] joins to the preceding word: ` 's`, ` 'm`,
+/// ` 've` and ` 're`.
+///
+/// llama.cpp deliberately leaves ` 't`, ` 'd` and ` 'll` spaced (the lines
+/// that would join them are commented out in its `detokenize`), so they are
+/// not in the default set. Pass a longer list to [`clean_spaces_with`] to
+/// join them too.
+pub const CLEAN_SPACES_CONTRACTIONS: &[&str] = &["s", "m", "ve", "re"];
+
+/// The `k` most frequent tokens of a [`Tokenizer::token_histogram`], most
+/// frequent first.
+///
+/// Ties are broken by ascending token ID, so the result is deterministic.
+/// Returns fewer than `k` entries when the histogram is smaller.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// let histogram = HashMap::from([(7, 3), (2, 5), (9, 3)]);
+/// assert_eq!(shimmytok::top_k(&histogram, 2), vec![(2, 5), (7, 3)]);
+/// ```
+#[must_use]
+pub fn top_k(histogram: &std::collections::HashMap<TokenId, u64>, k: usize) -> Vec<(TokenId, u64)> {
+    let mut entries: Vec<(TokenId, u64)> = histogram.iter().map(|(&t, &c)| (t, c)).collect();
+    entries.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    entries.truncate(k);
+    entries
+}
+
 /// Apply llama.cpp `clean_spaces` post-processing to decoded text.
 ///
-/// Implements the three cleanup passes from llama.cpp's `detokenize`:
+/// This is the step [`Tokenizer::decode`] runs for models with
+/// `tokenizer.ggml.clean_spaces`, and for any model when
+/// [`DecodeOptions::clean_spaces`] is set. It implements the three cleanup
+/// passes from llama.cpp's `detokenize`:
 ///
 /// | Pass | Transformation |
 /// |------|---------------|
 /// | 1 | Remove space before `?`, `!`, `.`, `,` |
 /// | 2 | Strip isolated apostrophe surrounded by spaces: ` ' ` → `'` |
-/// | 3 | Merge contractions: ` 'm`, ` 's`, ` 've`, ` 're` → drop the space |
+/// | 3 | Merge contractions ([`CLEAN_SPACES_CONTRACTIONS`]) → drop the space |
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(shimmytok::clean_spaces("I 'm here , it 's fine ."), "I'm here, it's fine.");
+/// // Matches llama.cpp: ` 't` keeps its space.
+/// assert_eq!(shimmytok::clean_spaces("don 't"), "don 't");
+/// ```
+#[must_use]
+pub fn clean_spaces(text: &str) -> String {
+    clean_spaces_with(text, CLEAN_SPACES_CONTRACTIONS)
+}
+
+/// [`clean_spaces`] with a custom set of contractions for pass 3.
+///
+/// Each entry is the text after the apostrophe (`"s"` for ` 's`); a space
+/// followed by `'` and any entry as a prefix is dropped.
+///
+/// # Example
+///
+/// ```
+/// use shimmytok::{clean_spaces_with, CLEAN_SPACES_CONTRACTIONS};
 ///
-/// The implementation collects to `Vec<char>` so that O(1) index access
-/// across all three passes avoids repeated UTF-8 scanning.
-fn apply_clean_spaces(text: &str) -> String {
+/// let mut contractions = CLEAN_SPACES_CONTRACTIONS.to_vec();
+/// contractions.extend(["t", "d", "ll"]);
+/// assert_eq!(clean_spaces_with("don 't , we 'll", &contractions), "don't, we'll");
+/// ```
+#[must_use]
+pub fn clean_spaces_with(text: &str, contractions: &[&str]) -> String {
+    // Collected to `Vec<char>` so that O(1) index access across all three
+    // passes avoids repeated UTF-8 scanning.
     let mut chars: Vec<char> = text.chars().collect();
     if chars.is_empty() {
         return String::new();
@@ -1048,18 +4706,16 @@ fn apply_clean_spaces(text: &str) -> String {
         }
     }
 
-    // Pass 3: remove the space before English contractions
-    //   ` 'm`, ` 's`, ` 've`, ` 're`
+    // Pass 3: remove the space before contractions
     let mut i = 1;
     while i + 1 < chars.len() {
         if chars[i - 1] == ' ' && chars[i] == '\'' {
-            let next = chars.get(i + 1);
-            let next2 = chars.get(i + 2);
-
-            let is_contraction = matches!(
-                (next, next2),
-                (Some('s' | 'm'), _) | (Some('v' | 'r'), Some('e'))
-            );
+            let rest = &chars[i + 1..];
+            let is_contraction = contractions.iter().any(|c| {
+                !c.is_empty()
+                    && c.chars().count() <= rest.len()
+                    && c.chars().zip(rest).all(|(a, &b)| a == b)
+            });
 
             if is_contraction {
                 chars.remove(i - 1);
@@ -1106,3 +4762,60 @@ pub enum Error {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gguf::{GGUFMetadata, SpecialTokenIds, TokenizationFlags};
+    use std::collections::HashMap;
+
+    /// A byte-level BPE tokenizer over `a`, `b`, `c` with merges
+    /// `a b` and `ab c`, built without going through GGUF.
+    fn tiny_bpe() -> Tokenizer {
+        let tokens = ["<unk>", "<s>", "</s>", "a", "b", "c", "ab", "abc", " "];
+        let metadata = GGUFMetadata {
+            tokens: tokens.iter().map(ToString::to_string).collect(),
+            scores: None,
+            token_types: None,
+            model_type: "gpt2".to_string(),
+            pre_type: Some("gpt-2".to_string()),
+            chat_template: None,
+            chat_templates: HashMap::new(),
+            special: SpecialTokenIds {
+                unk: Some(0),
+                bos: Some(1),
+                eos: Some(2),
+                ..SpecialTokenIds::default()
+            },
+            flags: TokenizationFlags::default(),
+            merges: Some(vec![
+                ("a".to_string(), "b".to_string()),
+                ("ab".to_string(), "c".to_string()),
+            ]),
+            precompiled_charsmap: None,
+            token_count: None,
+            unknown_token_score: None,
+            metadata: HashMap::new(),
+        };
+        Tokenizer::from_vocab(Vocabulary::from_metadata(metadata).unwrap()).unwrap()
+    }
+
+    /// The serial backend is called directly, so it is covered whether or not
+    /// the crate was built with `parallel`; the batch is large enough that,
+    /// with `parallel`, `encode_batch` takes the Rayon path for comparison.
+    #[test]
+    fn sequential_batch_matches_encode_batch() {
+        let tok = tiny_bpe();
+        let owned: Vec<String> = (0..512)
+            .map(|i| "abc acb ".repeat(i % 7 + 1) + &"b".repeat(i % 3))
+            .collect();
+        let texts: Vec<&str> = owned.iter().map(String::as_str).collect();
+
+        let sequential = tok.encode_batch_sequential(&texts, true).unwrap();
+        assert_eq!(sequential, tok.encode_batch(&texts, true).unwrap());
+        for (text, tokens) in texts.iter().zip(&sequential) {
+            assert_eq!(tokens, &tok.encode(text, true).unwrap());
+        }
+        assert!(tok.encode_batch_sequential(&[], false).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+//! Byte-offset recovery for encoded tokens.
+//!
+//! The backends transform text before matching it against the vocabulary
+//! (GPT-2 byte encoding, `▁` space escaping, lowercasing, whitespace
+//! normalization), so offsets are recovered after the fact: each token's
+//! decoded bytes are aligned against the original input with a forward-only
+//! cursor.
+//!
+//! # Alignment rules
+//!
+//! - A decoded space matches any ASCII whitespace byte, and ASCII letters
+//!   match case-insensitively (UGM whitespace folding, WPM lowercasing).
+//! - Pieces rewritten by normalization (WPM accent stripping, UGM charsmaps,
+//!   [`NormalizationForm`](crate::NormalizationForm)) are matched by
+//!   comparing both sides after compatibility decomposition with combining
+//!   marks removed, so `cafe` aligns with `café` and `AB` with `ＡＢ`.
+//! - A token is only looked for right after the previous one, past input
+//!   the backends drop (whitespace, combining marks, zero-width characters).
+//!   After unknown or unaligned tokens the search may skip ahead, but by at
+//!   most [`SEARCH_WINDOW`] bytes, so alignment stays linear in the input and
+//!   never jumps to a later occurrence of the same text.
+//! - A leading decoded space that is not present in the input is treated as
+//!   a synthetic prefix (SentencePiece `add_space_prefix`, WordPiece `▁`)
+//!   and excluded from the span; a token that is *only* such a space gets a
+//!   zero-width span.
+//! - Spans are widened to UTF-8 character boundaries, so byte-fallback
+//!   tokens covering part of a multi-byte character all report the span of
+//!   the whole character and `&text[start..end]` never panics.
+//! - Unknown tokens, and tokens whose bytes cannot be found, share the span
+//!   of the input between the previous and the next aligned token.
+
+use crate::Offset;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// How far past the skippable input after the last aligned token a token
+/// following unknown or unaligned tokens is looked for.
+const SEARCH_WINDOW: usize = 256;
+
+/// How a single token participates in alignment.
+pub(crate) enum Piece {
+    /// Decoded bytes to locate in the input.
+    Bytes(Vec<u8>),
+    /// Placeholder for input the vocabulary could not represent (`<unk>`).
+    Unknown,
+}
+
+/// Align `pieces` (one per token, in order) against `text`, returning one
+/// `(start, end)` byte span per piece.
+pub(crate) fn align(text: &str, pieces: &[Piece]) -> Vec<Offset> {
+    let input = text.as_bytes();
+    let mut spans = vec![(0, 0); pieces.len()];
+    let mut cursor = 0usize;
+    // Indices of tokens waiting for the next anchor to learn their span.
+    let mut pending: Vec<usize> = Vec::new();
+    // The most recent token that was aligned.
+    let mut last = None;
+
+    for (i, piece) in pieces.iter().enumerate() {
+        let bytes = match piece {
+            Piece::Bytes(bytes) => bytes,
+            Piece::Unknown => {
+                pending.push(i);
+                continue;
+            }
+        };
+
+        let next = skip_dropped(text, cursor);
+        let limit = if pending.is_empty() {
+            next
+        } else {
+            next.saturating_add(SEARCH_WINDOW).min(input.len())
+        };
+        let found = (cursor..=limit).find_map(|pos| match_at(text, pos, bytes));
+        match found {
+            Some((start, end)) => {
+                if pending.is_empty() {
+                    claim_marks(text, &mut spans, last, cursor, start);
+                }
+                for &p in &pending {
+                    spans[p] = widen(text, cursor, start);
+                }
+                pending.clear();
+                spans[i] = widen(text, start, end);
+                cursor = end;
+                last = Some(i);
+            }
+            None => pending.push(i),
+        }
+    }
+
+    if pending.is_empty() {
+        claim_marks(text, &mut spans, last, cursor, input.len());
+    }
+    for &p in &pending {
+        spans[p] = widen(text, cursor, input.len());
+    }
+
+    spans
+}
+
+/// Extend the span of the last aligned token over combining marks the
+/// backend dropped right after it (WPM accent stripping), stopping at
+/// `limit`, so it covers the whole accented character.
+fn claim_marks(text: &str, spans: &mut [Offset], last: Option<usize>, cursor: usize, limit: usize) {
+    let Some(span) = last.map(|l| &mut spans[l]) else {
+        return;
+    };
+    if span.0 == span.1 || !text.is_char_boundary(cursor) {
+        return;
+    }
+    let marks: usize = text[cursor..]
+        .chars()
+        .take_while(|&c| is_combining_mark(c))
+        .map(char::len_utf8)
+        .sum();
+    if marks > 0 && cursor + marks <= limit && span.1 == cursor {
+        span.1 = cursor + marks;
+    }
+}
+
+/// Try to match `piece` at `pos`, returning the raw byte span it covers.
+fn match_at(text: &str, pos: usize, piece: &[u8]) -> Option<Offset> {
+    let input = text.as_bytes();
+    // A leading space may be a synthetic prefix with no counterpart in the
+    // input.
+    let unprefixed = match piece.split_first() {
+        Some((b' ', rest)) => Some(rest),
+        _ => None,
+    };
+    let end = match_exact(input, pos, piece)
+        .or_else(|| unprefixed.and_then(|rest| match_exact(input, pos, rest)))
+        .or_else(|| {
+            let piece = std::str::from_utf8(piece).ok()?;
+            match_folded(text, pos, piece).or_else(|| {
+                let rest = piece.strip_prefix(' ')?;
+                match_folded(text, pos, rest)
+            })
+        })?;
+    Some((pos, end))
+}
+
+/// Match `piece` against the input at `pos` after folding both sides, for
+/// pieces the backend normalized.
+fn match_folded(text: &str, pos: usize, piece: &str) -> Option<usize> {
+    let first = text.get(pos..).and_then(|rest| rest.chars().next())?;
+    if piece.is_empty() || is_combining_mark(first) {
+        return None;
+    }
+    let mut want = piece.chars().flat_map(fold).peekable();
+    let mut chars = text[pos..].char_indices();
+    let mut end = pos;
+    while want.peek().is_some() {
+        let (i, c) = chars.next()?;
+        end = pos + i + c.len_utf8();
+        if c.is_whitespace() && want.peek() == Some(&' ') {
+            want.next();
+            continue;
+        }
+        for have in fold(c) {
+            if want.next() != Some(have) {
+                return None;
+            }
+        }
+    }
+    Some(end)
+}
+
+/// A character as normalizing backends may emit it: compatibility
+/// decomposed, without combining marks, lowercased.
+fn fold(c: char) -> impl Iterator<Item = char> {
+    std::iter::once(c)
+        .nfkd()
+        .filter(|&c| !is_combining_mark(c))
+        .flat_map(char::to_lowercase)
+}
+
+/// The first position at or after `pos` that is not input the backends drop
+/// without a trace: whitespace, combining marks and zero-width characters.
+fn skip_dropped(text: &str, mut pos: usize) -> usize {
+    let input = text.as_bytes();
+    while pos < input.len() {
+        if input[pos].is_ascii_whitespace() {
+            pos += 1;
+            continue;
+        }
+        let Some(c) = text.get(pos..).and_then(|rest| rest.chars().next()) else {
+            break;
+        };
+        if c.is_whitespace() || is_combining_mark(c) || crate::ZERO_WIDTH_CHARS.contains(&c) {
+            pos += c.len_utf8();
+        } else {
+            break;
+        }
+    }
+    pos
+}
+
+fn match_exact(input: &[u8], pos: usize, piece: &[u8]) -> Option<usize> {
+    let end = pos.checked_add(piece.len())?;
+    let window = input.get(pos..end)?;
+    let equal = window.iter().zip(piece).all(|(&have, &want)| {
+        have.eq_ignore_ascii_case(&want) || (want == b' ' && have.is_ascii_whitespace())
+    });
+    equal.then_some(end)
+}
+
+/// Widen a raw byte span outwards to the nearest character boundaries.
+fn widen(text: &str, mut start: usize, mut end: usize) -> Offset {
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+    (start, end)
+}
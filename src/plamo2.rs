@@ -24,6 +24,7 @@
 //! - Scaling scores exactly: `round(score * 1e4)`
 //! - Unknown sentinel score constants
 
+use crate::byte_encoder::parse_hex_byte_token;
 use crate::vocab::{TokenType, Vocabulary};
 use crate::Error;
 use std::collections::HashMap;
@@ -75,11 +76,8 @@ impl Plamo2Tokenizer {
             token_to_id.insert(tok.clone(), id);
 
             if matches!(vocab.get_token_type(id), TokenType::Byte) {
-                if tok.len() == 6 && tok.starts_with("<0x") && tok.ends_with('>') {
-                    let hex = &tok[3..5];
-                    if let Ok(b) = u8::from_str_radix(hex, 16) {
-                        byte_token[b as usize] = id;
-                    }
+                if let Some(b) = parse_hex_byte_token(&tok) {
+                    byte_token[b as usize] = id;
                 }
                 continue;
             }
@@ -271,8 +269,16 @@ impl Plamo2Tokenizer {
 
     /// Decode token IDs back to text.
     ///
-    /// Note: Full decode implementation requires mapping BYTE tokens back to bytes.
+    /// # Errors
+    /// Returns [`Error::InvalidUtf8`] if the BYTE tokens do not form valid UTF-8.
     pub fn decode(&self, tokens: &[u32], vocab: &Vocabulary) -> Result<String, Error> {
+        let bytes = self.decode_bytes(tokens, vocab)?;
+        String::from_utf8(bytes).map_err(|e| Error::InvalidUtf8(e.to_string()))
+    }
+
+    /// Decode token IDs to raw bytes, mapping BYTE tokens back to their byte
+    /// values, without UTF-8 validation.
+    pub fn decode_bytes(&self, tokens: &[u32], vocab: &Vocabulary) -> Result<Vec<u8>, Error> {
         let mut byte_to_token: HashMap<u32, u8> = HashMap::new();
         for (b, &tid) in self.byte_token.iter().enumerate() {
             byte_to_token.insert(tid, b as u8);
@@ -280,14 +286,23 @@ impl Plamo2Tokenizer {
 
         let mut bytes: Vec<u8> = Vec::new();
         for &t in tokens {
-            if let Some(&b) = byte_to_token.get(&t) {
+            // Only genuine BYTE tokens map back to raw bytes; models without a
+            // full byte block leave unset slots pointing at token 0.
+            let is_byte_token =
+                (t as usize) < vocab.n_tokens() && vocab.get_token_type(t) == TokenType::Byte;
+            let byte = if is_byte_token {
+                byte_to_token.get(&t).copied()
+            } else {
+                None
+            };
+            if let Some(b) = byte {
                 bytes.push(b);
             } else if let Some(txt) = vocab.get_token_text(t) {
                 bytes.extend_from_slice(txt.as_bytes());
             }
         }
 
-        String::from_utf8(bytes).map_err(|e| Error::InvalidUtf8(e.to_string()))
+        Ok(bytes)
     }
 }
 
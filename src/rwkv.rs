@@ -135,14 +135,26 @@ impl RwkvTokenizer {
     }
 
     /// Decode token IDs back to text.
+    ///
+    /// Pieces are unescaped with [`unescape_rwkv_token`] (as llama.cpp's
+    /// `token_to_piece` does) so `\n` decodes to a real newline.
     pub fn decode(&self, tokens: &[u32], vocab: &Vocabulary) -> Result<String, Error> {
-        let mut s = String::new();
+        let bytes = self.decode_bytes(tokens, vocab)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Decode token IDs to raw bytes without UTF-8 validation.
+    ///
+    /// `\xNN` escapes may produce partial UTF-8 sequences; [`decode`](Self::decode)
+    /// replaces those with U+FFFD.
+    pub fn decode_bytes(&self, tokens: &[u32], vocab: &Vocabulary) -> Result<Vec<u8>, Error> {
+        let mut bytes: Vec<u8> = Vec::new();
         for &t in tokens {
             if let Some(txt) = vocab.get_token_text(t) {
-                s.push_str(txt);
+                bytes.extend(unescape_rwkv_token(txt));
             }
         }
-        Ok(s)
+        Ok(bytes)
     }
 }
 
@@ -23,7 +23,7 @@
 //! # Reference
 //! llama.cpp `llm_tokenizer_spm_session::tokenize()` lines 821-1026
 
-use crate::{TokenId, TokenizerImpl, Vocabulary};
+use crate::{invariants, TokenId, TokenizerImpl, Vocabulary};
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap};
 
@@ -100,6 +100,25 @@ impl SentencePieceTokenizer {
 }
 
 impl TokenizerImpl for SentencePieceTokenizer {
+    fn algorithm(&self) -> &'static str {
+        "spm"
+    }
+
+    /// Add space prefix for SentencePiece (replacing spaces with ▁).
+    /// The vocabulary lookup uses get_token_id_any_space which handles both
+    /// ▁ (U+2581) and Ġ (U+0120) representations transparently.
+    fn normalize(&self, text: &str, vocab: &Vocabulary) -> String {
+        if vocab.add_space_prefix() {
+            if text.starts_with(' ') {
+                text.replace(' ', "▁")
+            } else {
+                format!("▁{}", text.replace(' ', "▁"))
+            }
+        } else {
+            text.replace(' ', "▁")
+        }
+    }
+
     fn encode(&self, text: &str, vocab: &Vocabulary) -> Result<Vec<TokenId>, crate::Error> {
         // Validate input size (Issue #10)
         if text.len() > crate::MAX_INPUT_SIZE {
@@ -114,18 +133,7 @@ impl TokenizerImpl for SentencePieceTokenizer {
             return Ok(Vec::new());
         }
 
-        // Add space prefix for SentencePiece (replacing spaces with ▁).
-        // The vocabulary lookup uses get_token_id_any_space which handles both
-        // ▁ (U+2581) and Ġ (U+0120) representations transparently.
-        let processed_text = if vocab.add_space_prefix() {
-            if text.starts_with(' ') {
-                text.replace(' ', "▁")
-            } else {
-                format!("▁{}", text.replace(' ', "▁"))
-            }
-        } else {
-            text.replace(' ', "▁")
-        };
+        let processed_text = self.normalize(text, vocab);
 
         // Validate processed size — ▁ is 3 bytes UTF-8
         if processed_text.len() > crate::MAX_INPUT_SIZE {
@@ -176,24 +184,23 @@ impl TokenizerImpl for SentencePieceTokenizer {
                 vocab,
                 &mut work_queue,
                 &mut rev_merge,
-            );
+            )?;
         }
 
-        // Process merges in priority order
-        // Each merge reduces symbol count by exactly 1, so total merges <= initial symbols.len().
-        // We allow 10x headroom for priority queue re-insertions of stale entries.
-        // The hard 100K cap has been removed — it fires on large prompts (>10K tokens) even
-        // when the BPE loop is making forward progress and would terminate correctly.
-        let max_iterations = 10 * symbols.len();
-        let mut iterations = 0;
+        // Process merges in priority order.
+        //
+        // Termination: the queue starts with at most `n - 1` bigrams and each
+        // successful merge (at most `n - 1` of them, since every merge removes
+        // a symbol) pushes at most two more, so at most `3 * (n - 1)` entries
+        // are ever popped. Stale entries — a side already merged away, the
+        // pair no longer adjacent, or either side grown since the push — are
+        // dropped by the checks below, exactly as llama.cpp does, so no
+        // iteration cap is needed.
+        let max_pushes = 3 * symbols.len();
+        let mut pops = 0;
         while let Some(bigram) = work_queue.pop() {
-            // Check limit BEFORE doing work (Issue R3#3)
-            if iterations >= max_iterations {
-                return Err(crate::Error::TokenizationFailed(format!(
-                    "SentencePiece merge iteration limit exceeded: {iterations} iterations (max: {max_iterations})"
-                )));
-            }
-            iterations += 1;
+            pops += 1;
+            debug_assert!(pops <= max_pushes, "SPM merge queue grew unbounded");
 
             if bigram.left >= symbols.len() || bigram.right >= symbols.len() {
                 continue;
@@ -232,7 +239,7 @@ impl TokenizerImpl for SentencePieceTokenizer {
                     vocab,
                     &mut work_queue,
                     &mut rev_merge,
-                );
+                )?;
             }
             if let Some(next) = symbols[bigram.left].next {
                 try_add_bigram(
@@ -243,7 +250,7 @@ impl TokenizerImpl for SentencePieceTokenizer {
                     vocab,
                     &mut work_queue,
                     &mut rev_merge,
-                );
+                )?;
             }
         }
 
@@ -267,7 +274,7 @@ impl TokenizerImpl for SentencePieceTokenizer {
 
             let symbol = &symbols[current];
             if symbol.len > 0 {
-                let text = &processed_text[symbol.pos..symbol.pos + symbol.len];
+                let text = symbol_text(&processed_text, symbol.pos, symbol.len)?;
                 resegment(
                     text,
                     &processed_text,
@@ -276,7 +283,7 @@ impl TokenizerImpl for SentencePieceTokenizer {
                     vocab,
                     &mut result,
                     0, // Initial depth
-                );
+                )?;
                 // Check output size after resegment
                 if result.len() > crate::MAX_OUTPUT_TOKENS {
                     return Err(crate::Error::TokenizationFailed(format!(
@@ -298,6 +305,16 @@ impl TokenizerImpl for SentencePieceTokenizer {
     }
 
     fn decode(&self, tokens: &[TokenId], vocab: &Vocabulary) -> Result<String, crate::Error> {
+        // Convert bytes to string (lossy for invalid UTF-8)
+        let bytes = self.decode_bytes(tokens, vocab)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    fn decode_bytes(
+        &self,
+        tokens: &[TokenId],
+        vocab: &Vocabulary,
+    ) -> Result<Vec<u8>, crate::Error> {
         // Validate all tokens exist
         for &token_id in tokens {
             if vocab.get_token_text(token_id).is_none() {
@@ -313,7 +330,7 @@ impl TokenizerImpl for SentencePieceTokenizer {
         for &token_id in tokens {
             if let Some(text) = vocab.get_token_text(token_id) {
                 // Check if this is a byte token like <0x0A>
-                if let Some(byte_val) = decode_byte_token(text) {
+                if let Some(byte_val) = crate::byte_encoder::parse_hex_byte_token(text) {
                     bytes.push(byte_val);
                 } else {
                     // Regular token - replace both ▁ and Ġ (space representations) with space
@@ -332,21 +349,27 @@ impl TokenizerImpl for SentencePieceTokenizer {
             }
         }
 
-        // Convert bytes to string (lossy for invalid UTF-8)
-        Ok(String::from_utf8_lossy(&bytes).into_owned())
+        Ok(bytes)
     }
 }
 
-/// Decode a byte token like `<0x0A>` to its byte value.
-/// Returns None if the token is not a valid byte token.
-fn decode_byte_token(text: &str) -> Option<u8> {
-    // Format: <0xXX> where XX is a hex value
-    if text.len() == 6 && text.starts_with("<0x") && text.ends_with('>') {
-        let hex = &text[3..5];
-        u8::from_str_radix(hex, 16).ok()
-    } else {
-        None
-    }
+/// `text[pos..pos + len]`, or [`crate::Error::TokenizationFailed`] if the
+/// range is out of bounds or splits a character.
+///
+/// Symbol ranges are built from `char_indices` and only ever grow by whole
+/// neighbours, so this cannot fail unless merge bookkeeping is wrong; debug
+/// builds assert instead of returning the error.
+fn symbol_text(text: &str, pos: usize, len: usize) -> Result<&str, crate::Error> {
+    invariants::assert_char_boundary_range(text, pos, pos.saturating_add(len));
+    pos.checked_add(len)
+        .and_then(|end| text.get(pos..end))
+        .ok_or_else(|| {
+            crate::Error::TokenizationFailed(format!(
+                "SPM symbol range {pos}..{} is not on a character boundary of {} bytes",
+                pos.saturating_add(len),
+                text.len()
+            ))
+        })
 }
 
 fn try_add_bigram(
@@ -357,20 +380,20 @@ fn try_add_bigram(
     vocab: &Vocabulary,
     work_queue: &mut BinaryHeap<Bigram>,
     rev_merge: &mut HashMap<String, (usize, usize)>,
-) {
+) -> Result<(), crate::Error> {
     if left >= symbols.len() || right >= symbols.len() {
-        return;
+        return Ok(());
     }
 
     let left_sym = &symbols[left];
     let right_sym = &symbols[right];
 
     if left_sym.len == 0 || right_sym.len == 0 {
-        return;
+        return Ok(());
     }
 
     // Get the combined text
-    let combined_text = &text[left_sym.pos..left_sym.pos + left_sym.len + right_sym.len];
+    let combined_text = symbol_text(text, left_sym.pos, left_sym.len + right_sym.len)?;
 
     // Check if this combination exists in vocabulary (try both ▁ and Ġ space forms)
     if let Some(token_id) = vocab.get_token_id_any_space(combined_text) {
@@ -386,6 +409,7 @@ fn try_add_bigram(
         // Track merge history
         rev_merge.insert(combined_text.to_string(), (left, right));
     }
+    Ok(())
 }
 
 /// Resegment function - the critical missing piece from llama.cpp
@@ -397,7 +421,7 @@ fn resegment(
     vocab: &Vocabulary,
     output: &mut Vec<TokenId>,
     depth: usize,
-) {
+) -> Result<(), crate::Error> {
     // Prevent stack overflow from deep recursion (Issue R3#4)
     const MAX_RECURSION_DEPTH: usize = 1000;
     if depth >= MAX_RECURSION_DEPTH {
@@ -405,13 +429,13 @@ fn resegment(
         for byte in text.bytes() {
             output.push(vocab.byte_to_token(byte));
         }
-        return;
+        return Ok(());
     }
 
     // Try to find the text as a complete token (try both ▁ and Ġ space forms)
     if let Some(token_id) = vocab.get_token_id_any_space(text) {
         output.push(token_id);
-        return;
+        return Ok(());
     }
 
     // If not found, check if we have merge history for this text
@@ -422,7 +446,7 @@ fn resegment(
             let right_sym = &symbols[right_idx];
 
             if left_sym.len > 0 {
-                let left_text = &full_text[left_sym.pos..left_sym.pos + left_sym.len];
+                let left_text = symbol_text(full_text, left_sym.pos, left_sym.len)?;
                 resegment(
                     left_text,
                     full_text,
@@ -431,10 +455,10 @@ fn resegment(
                     vocab,
                     output,
                     depth + 1,
-                );
+                )?;
             }
             if right_sym.len > 0 {
-                let right_text = &full_text[right_sym.pos..right_sym.pos + right_sym.len];
+                let right_text = symbol_text(full_text, right_sym.pos, right_sym.len)?;
                 resegment(
                     right_text,
                     full_text,
@@ -443,9 +467,9 @@ fn resegment(
                     vocab,
                     output,
                     depth + 1,
-                );
+                )?;
             }
-            return;
+            return Ok(());
         }
     }
 
@@ -453,4 +477,5 @@ fn resegment(
     for byte in text.bytes() {
         output.push(vocab.byte_to_token(byte));
     }
+    Ok(())
 }
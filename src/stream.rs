@@ -0,0 +1,119 @@
+//! Incremental, UTF-8-safe detokenization for streamed model output.
+//!
+//! Byte-level vocabularies (GPT-2 BPE, SentencePiece `<0xNN>` byte fallback,
+//! PLaMo-2) can split a single character across several tokens, e.g. 🦀 is
+//! four byte tokens. Decoding each token on its own then yields `�` for every
+//! fragment. [`StreamDecoder`] buffers the raw bytes between calls and only
+//! emits text once a character is complete, as llama.cpp's server does when
+//! streaming partial tokens.
+
+use crate::{Error, TokenId, Tokenizer};
+
+/// Stateful decoder that turns a token stream into complete UTF-8 text.
+///
+/// Created with [`Tokenizer::stream_decoder`].
+///
+/// # Example
+///
+/// ```no_run
+/// use shimmytok::Tokenizer;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let tokenizer = Tokenizer::from_gguf_file("model.gguf")?;
+/// let mut stream = tokenizer.stream_decoder().skip_special_tokens(true);
+/// for token in tokenizer.encode("Rust 🦀", false)? {
+///     print!("{}", stream.push(token)?);
+/// }
+/// print!("{}", stream.finish());
+/// # Ok(())
+/// # }
+/// ```
+pub struct StreamDecoder<'a> {
+    tokenizer: &'a Tokenizer,
+    pending: Vec<u8>,
+    skip_special_tokens: bool,
+}
+
+impl<'a> StreamDecoder<'a> {
+    pub(crate) fn new(tokenizer: &'a Tokenizer) -> Self {
+        Self {
+            tokenizer,
+            pending: Vec::new(),
+            skip_special_tokens: false,
+        }
+    }
+
+    /// Skip special tokens (BOS, EOS, etc.) instead of emitting their text.
+    #[must_use]
+    pub fn skip_special_tokens(mut self, skip: bool) -> Self {
+        self.skip_special_tokens = skip;
+        self
+    }
+
+    /// Feed one token and return the text that became complete.
+    ///
+    /// Returns an empty string while a multi-byte character is still
+    /// incomplete. Byte sequences that can never become valid UTF-8 are
+    /// emitted as U+FFFD immediately rather than held back.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidToken`] if the token ID is not in the vocabulary.
+    pub fn push(&mut self, token: TokenId) -> Result<String, Error> {
+        if token as usize >= self.tokenizer.vocab_size() {
+            return Err(Error::InvalidToken(format!(
+                "Token ID {token} not found in vocabulary"
+            )));
+        }
+        if self.skip_special_tokens && self.tokenizer.is_special_token(token) {
+            return Ok(String::new());
+        }
+        let bytes = self.tokenizer.decode_token_bytes(token)?;
+        self.pending.extend_from_slice(&bytes);
+        Ok(self.drain_complete())
+    }
+
+    /// Flush any buffered bytes, replacing an incomplete trailing sequence
+    /// with U+FFFD, and reset the decoder.
+    pub fn finish(&mut self) -> String {
+        let rest = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        rest
+    }
+
+    /// Number of bytes held back waiting for the rest of a character.
+    #[must_use]
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Move every complete character out of `pending`.
+    fn drain_complete(&mut self) -> String {
+        let mut out = String::new();
+        loop {
+            match std::str::from_utf8(&self.pending) {
+                Ok(s) => {
+                    out.push_str(s);
+                    self.pending.clear();
+                    return out;
+                }
+                Err(e) => {
+                    let valid = e.valid_up_to();
+                    out.push_str(&String::from_utf8_lossy(&self.pending[..valid]));
+                    match e.error_len() {
+                        // Invalid bytes: no continuation can fix them.
+                        Some(len) => {
+                            out.push('\u{FFFD}');
+                            self.pending.drain(..valid + len);
+                        }
+                        // Incomplete trailing character: wait for more bytes.
+                        None => {
+                            self.pending.drain(..valid);
+                            return out;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! OpenAI `tiktoken` vocabulary import.
+//!
+//! A `.tiktoken` file is a list of `base64(token_bytes) rank` lines; the rank
+//! doubles as the token ID and as the merge priority. This module turns such a
+//! file into the same [`GGUFMetadata`] the GGUF loader produces, so the result
+//! runs on the byte-level BPE backend:
+//!
+//! - Token bytes are stored GPT-2 byte-encoded (see
+//!   [`byte_encoder`](crate::byte_encoder)), like every GGUF BPE vocabulary.
+//! - Merge rules are recovered by re-running BPE on each multi-byte token
+//!   using only the tokens ranked below it; the two surviving parts form its
+//!   merge, and merges are ordered by the rank of the merged token. This is
+//!   the standard tiktoken → merges conversion and reproduces tiktoken's
+//!   lowest-rank-first merging.
+//! - The pre-tokenizer is picked from the vocabulary size: `o200k`-sized
+//!   vocabularies use the GPT-4o pattern, `cl100k`-sized ones the `cl100k`
+//!   pattern (shared with Llama-3), and smaller ones the GPT-2 pattern.
+//!   Override it with [`TokenizerBuilder::pre_type`](crate::TokenizerBuilder::pre_type)
+//!   if the guess is wrong.
+//! - Caller-supplied special tokens become control tokens. Rank gaps (e.g.
+//!   between the last ordinary rank and the first special token) are filled
+//!   with unused placeholder tokens so IDs stay positional.
+
+use crate::byte_encoder::byte_to_char;
+use crate::gguf::{GGUFMetadata, SpecialTokenIds, TokenizationFlags};
+use crate::vocab::TokenType;
+use crate::{Error, TokenId};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Largest `.tiktoken` file accepted (64 MB). `o200k_base` is under 4 MB.
+const MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Vocabulary sizes at which the `cl100k` / `o200k` patterns are assumed.
+const CL100K_MIN_RANKS: usize = 100_000;
+const O200K_MIN_RANKS: usize = 199_000;
+
+/// IDs at or above this are rejected before allocating the ID table; it
+/// matches the vocabulary's own size limit.
+const MAX_TOKEN_ID: TokenId = 1_000_000;
+
+const BOS_NAMES: &[&str] = &["<|begin_of_text|>", "<|startoftext|>"];
+const EOS_NAMES: &[&str] = &["<|endoftext|>", "<|end_of_text|>"];
+
+/// Loads tokenizer metadata from a `.tiktoken` file.
+///
+/// `special_tokens` are `(text, id)` pairs, e.g.
+/// `("<|endoftext|>", 100257)`; at least one is required so the tokenizer has
+/// an EOS token.
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if the file cannot be read, and otherwise the same
+/// errors as [`parse_metadata`].
+pub fn load_metadata<P: AsRef<Path>>(
+    path: P,
+    special_tokens: &[(String, TokenId)],
+) -> Result<GGUFMetadata, Error> {
+    let path = path.as_ref();
+    let size = std::fs::metadata(path)?.len();
+    if size > MAX_FILE_SIZE {
+        return Err(Error::InvalidMetadata(format!(
+            "tiktoken file too large: {size} bytes (max: {MAX_FILE_SIZE})"
+        )));
+    }
+    parse_metadata(&std::fs::read(path)?, special_tokens)
+}
+
+/// Parses tokenizer metadata from the contents of a `.tiktoken` file.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidMetadata`] for lines that are not
+/// `base64 rank`, and [`Error::VocabularyError`] for duplicate ranks or
+/// tokens, a special token whose ID collides with a ranked token, an empty
+/// `special_tokens` list, or a token that cannot be built by merging
+/// lower-ranked tokens.
+pub fn parse_metadata(
+    bytes: &[u8],
+    special_tokens: &[(String, TokenId)],
+) -> Result<GGUFMetadata, Error> {
+    let text = std::str::from_utf8(bytes)
+        .map_err(|e| Error::InvalidMetadata(format!("tiktoken file is not UTF-8: {e}")))?;
+
+    let mut ranked: Vec<(TokenId, Vec<u8>)> = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let malformed = || {
+            Error::InvalidMetadata(format!(
+                "tiktoken line {}: expected `base64 rank`",
+                line_no + 1
+            ))
+        };
+        let mut fields = line.split_whitespace();
+        let (Some(encoded), Some(rank), None) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(malformed());
+        };
+        let token = decode_base64(encoded).ok_or_else(malformed)?;
+        let rank: TokenId = rank.parse().map_err(|_| malformed())?;
+        ranked.push((rank, token));
+    }
+    if special_tokens.is_empty() {
+        return Err(Error::VocabularyError(
+            "tiktoken import needs at least one special token to serve as EOS".to_string(),
+        ));
+    }
+
+    ranked.sort_unstable_by_key(|(rank, _)| *rank);
+    let mut rank_of: HashMap<&[u8], TokenId> = HashMap::with_capacity(ranked.len());
+    for (rank, token) in &ranked {
+        if rank_of.insert(token.as_slice(), *rank).is_some() {
+            return Err(Error::VocabularyError(format!(
+                "tiktoken token with rank {rank} appears more than once"
+            )));
+        }
+    }
+
+    let max_id = ranked
+        .iter()
+        .map(|(rank, _)| *rank)
+        .chain(special_tokens.iter().map(|(_, id)| *id))
+        .max()
+        .unwrap_or(0);
+    if max_id >= MAX_TOKEN_ID {
+        return Err(Error::VocabularyError(format!(
+            "tiktoken token ID {max_id} too large (max: {})",
+            MAX_TOKEN_ID - 1
+        )));
+    }
+    let mut slots: Vec<Option<(String, TokenType)>> = vec![None; max_id as usize + 1];
+
+    let to_piece = |bytes: &[u8]| -> String { bytes.iter().copied().map(byte_to_char).collect() };
+
+    for (rank, token) in &ranked {
+        let slot = &mut slots[*rank as usize];
+        if slot.is_some() {
+            return Err(Error::VocabularyError(format!(
+                "tiktoken rank {rank} appears more than once"
+            )));
+        }
+        *slot = Some((to_piece(token), TokenType::Normal));
+    }
+    for (text, id) in special_tokens {
+        let slot = &mut slots[*id as usize];
+        if slot.is_some() {
+            return Err(Error::VocabularyError(format!(
+                "special token '{text}' uses ID {id}, which is already taken"
+            )));
+        }
+        *slot = Some((text.clone(), TokenType::Control));
+    }
+
+    // Merges in rank order: each multi-byte token is the merge of the two
+    // parts BPE leaves when only lower-ranked tokens may be used.
+    let mut merges = Vec::with_capacity(ranked.len());
+    for (rank, token) in ranked.iter().filter(|(_, token)| token.len() > 1) {
+        let split = merge_split(token, *rank, &rank_of).ok_or_else(|| {
+            Error::VocabularyError(format!(
+                "tiktoken token with rank {rank} cannot be built from lower-ranked tokens"
+            ))
+        })?;
+        merges.push((to_piece(&token[..split]), to_piece(&token[split..])));
+    }
+
+    let (tokens, token_types): (Vec<String>, Vec<TokenType>) = slots
+        .into_iter()
+        .enumerate()
+        .map(|(id, slot)| slot.unwrap_or_else(|| (format!("<|unused_{id}|>"), TokenType::Unused)))
+        .unzip();
+
+    let by_name = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|n| special_tokens.iter().find(|(text, _)| text == n))
+            .map(|(_, id)| *id)
+    };
+    let eos = by_name(EOS_NAMES).unwrap_or(special_tokens[0].1);
+    let bos = by_name(BOS_NAMES).unwrap_or(eos);
+
+    let pre_type = match ranked.len() {
+        n if n >= O200K_MIN_RANKS => "gpt-4o",
+        n if n >= CL100K_MIN_RANKS => "llama3",
+        _ => "gpt-2",
+    };
+
+    Ok(GGUFMetadata {
+        tokens,
+        scores: None,
+        token_types: Some(token_types),
+        model_type: "gpt2".to_string(),
+        pre_type: Some(pre_type.to_string()),
+        chat_template: None,
+        chat_templates: HashMap::new(),
+        special: SpecialTokenIds {
+            bos: Some(bos),
+            eos: Some(eos),
+            // Every byte has a rank, so nothing is ever unknown; point UNK at
+            // a special token rather than letting it default to ID 0.
+            unk: Some(eos),
+            ..SpecialTokenIds::default()
+        },
+        flags: TokenizationFlags {
+            add_bos_token: false,
+            add_eos_token: false,
+            ..TokenizationFlags::default()
+        },
+        merges: Some(merges),
+        precompiled_charsmap: None,
+        token_count: None,
+        unknown_token_score: None,
+        metadata: HashMap::new(),
+    })
+}
+
+/// Byte offset splitting `token` into the two parts BPE produces when only
+/// tokens ranked below `max_rank` may be merged, or `None` if it does not end
+/// in exactly two parts.
+fn merge_split(
+    token: &[u8],
+    max_rank: TokenId,
+    rank_of: &HashMap<&[u8], TokenId>,
+) -> Option<usize> {
+    // Start offsets of the current parts; part `i` spans `bounds[i]..bounds[i + 1]`.
+    let mut bounds: Vec<usize> = (0..=token.len()).collect();
+    loop {
+        let best = (0..bounds.len() - 2)
+            .filter_map(|i| {
+                let rank = *rank_of.get(&token[bounds[i]..bounds[i + 2]])?;
+                (rank < max_rank).then_some((rank, i))
+            })
+            .min();
+        match best {
+            Some((_, i)) => {
+                bounds.remove(i + 1);
+            }
+            None => break,
+        }
+    }
+    (bounds.len() == 3).then(|| bounds[1])
+}
+
+/// Decodes standard (RFC 4648) base64 with optional `=` padding.
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some(u32::from(c - b'A')),
+            b'a'..=b'z' => Some(u32::from(c - b'a') + 26),
+            b'0'..=b'9' => Some(u32::from(c - b'0') + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let data = encoded.trim_end_matches('=').as_bytes();
+    if data.len() % 4 == 1 || encoded.len() - data.len() > 2 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        let mut acc = 0u32;
+        for &c in chunk {
+            acc = (acc << 6) | value(c)?;
+        }
+        acc <<= 6 * (4 - chunk.len() as u32);
+        out.extend_from_slice(&acc.to_be_bytes()[1..chunk.len()]);
+    }
+    Some(out)
+}
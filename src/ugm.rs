@@ -11,7 +11,8 @@
 //! - Viterbi-style DP for optimal tokenization
 //! - Score-based selection between competing tokenizations
 //! - Unknown token handling with penalty score
-//! - Space prefix normalization (▁ U+2581) for T5/mT5 parity
+//! - SentencePiece normalization from the GGUF precompiled charsmap, with
+//!   space prefix (▁ U+2581) and whitespace collapsing for T5/mT5 parity
 
 use crate::vocab::{TokenType, Vocabulary};
 use crate::Error;
@@ -66,6 +67,90 @@ impl NaiveTrie {
     }
 }
 
+/// SentencePiece's precompiled normalization map
+/// (`tokenizer.ggml.precompiled_charsmap`).
+///
+/// Port of llama.cpp's `xcda_array_view` / `normalize_prefix`. The blob is a
+/// little-endian `u32` byte length, that many bytes of XOR-compressed compact
+/// double array (XCDA) nodes, then NUL-terminated replacement strings. Walking
+/// the XCDA over the input bytes finds the longest prefix with a replacement
+/// (e.g. full-width `Ａ` → `A`, U+3000 → space).
+#[derive(Clone)]
+pub(crate) struct Charsmap {
+    xcda: Vec<u32>,
+    replacements: Vec<u8>,
+}
+
+impl Charsmap {
+    /// Parse a precompiled charsmap blob.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::VocabularyError`] if the header is truncated or the
+    /// declared XCDA size does not fit in the blob.
+    pub(crate) fn parse(blob: &[u8]) -> Result<Self, Error> {
+        let malformed =
+            |what: &str| Error::VocabularyError(format!("Malformed precompiled charsmap: {what}"));
+        let (header, rest) = match blob {
+            [a, b, c, d, rest @ ..] => ([*a, *b, *c, *d], rest),
+            _ => return Err(malformed("missing XCDA size header")),
+        };
+        let xcda_size = u32::from_le_bytes(header) as usize;
+        if xcda_size % 4 != 0 || xcda_size >= rest.len() {
+            return Err(malformed(&format!(
+                "XCDA size {xcda_size} does not fit in {} bytes",
+                rest.len()
+            )));
+        }
+        let (xcda, replacements) = rest.split_at(xcda_size);
+        Ok(Self {
+            xcda: xcda
+                .chunks_exact(4)
+                .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                .collect(),
+            replacements: replacements.to_vec(),
+        })
+    }
+
+    fn base(packed: u32) -> u32 {
+        (packed >> 10) << ((packed & (1 << 9)) >> 6)
+    }
+
+    /// Longest prefix of `input` that has a replacement, as
+    /// `(bytes consumed, replacement)`. Out-of-range node or replacement
+    /// offsets end the match instead of panicking.
+    fn longest_match(&self, input: &[u8]) -> Option<(usize, &[u8])> {
+        let mut node = Self::base(*self.xcda.first()?);
+        let mut best = None;
+        for (i, &c) in input.iter().enumerate() {
+            if c == 0 {
+                break;
+            }
+            node ^= u32::from(c);
+            let Some(&packed) = self.xcda.get(node as usize) else {
+                break;
+            };
+            // LCHECK: label in the low byte; bit 31 marks value nodes.
+            if packed & ((1 << 31) | 0xFF) != u32::from(c) {
+                break;
+            }
+            let is_leaf = (packed >> 8) & 1 == 1;
+            node ^= Self::base(packed);
+            if is_leaf {
+                let Some(&value) = self.xcda.get(node as usize) else {
+                    break;
+                };
+                best = Some((i + 1, (value & ((1 << 31) - 1)) as usize));
+            }
+        }
+
+        let (len, offset) = best?;
+        let tail = self.replacements.get(offset..)?;
+        let end = tail.iter().position(|&b| b == 0).unwrap_or(tail.len());
+        Some((len, &tail[..end]))
+    }
+}
+
 /// Fragment types during user-defined token preprocessing.
 enum UgmFragment {
     /// A user-defined token that was matched
@@ -79,6 +164,7 @@ pub struct UgmTokenizer {
     trie: NaiveTrie,
     user_defined_trie: NaiveTrie,
     unknown_token_score: f64,
+    charsmap: Option<Charsmap>,
 }
 
 impl UgmTokenizer {
@@ -112,18 +198,26 @@ impl UgmTokenizer {
             }
         }
 
-        // Unknown token score = min_score - penalty (default 10.0)
+        // Unknown token score: declared by the model, otherwise
+        // min_score - penalty (llama.cpp's default of 10.0)
         let unknown_token_score_penalty = 10.0f64;
-        let unknown_token_score = if min_score.is_finite() {
-            min_score - unknown_token_score_penalty
-        } else {
-            -10.0 // Fallback if no normal tokens
+        let unknown_token_score = match vocab.unknown_token_score() {
+            Some(score) => score,
+            None if min_score.is_finite() => min_score - unknown_token_score_penalty,
+            None => -10.0, // Fallback if no normal tokens
         };
 
+        // Validated when the vocabulary was loaded.
+        let charsmap = vocab
+            .precompiled_charsmap()
+            .filter(|blob| !blob.is_empty())
+            .and_then(|blob| Charsmap::parse(blob).ok());
+
         Self {
             trie,
             user_defined_trie,
             unknown_token_score,
+            charsmap,
         }
     }
 
@@ -132,24 +226,10 @@ impl UgmTokenizer {
         if text.is_empty() {
             return Ok(Vec::new());
         }
-        // Normalize whitespace: newlines, tabs, carriage returns → spaces.
-        // This matches SentencePiece charsmap normalization for T5/mT5.
-        let normalized_text: String = text
-            .chars()
-            .map(|c| if c.is_ascii_whitespace() && c != ' ' { ' ' } else { c })
-            .collect();
-
-        // Add space prefix for SentencePiece-style tokenization (T5/mT5).
-        // When add_space_prefix is true, prepend ▁ (U+2581) and replace spaces.
-        let normalized = if vocab.add_space_prefix() {
-            if normalized_text.starts_with(' ') {
-                normalized_text.replace(' ', "▁")
-            } else {
-                format!("▁{}", normalized_text.replace(' ', "▁"))
-            }
-        } else {
-            normalized_text
-        };
+        let normalized = self.normalize(text, vocab);
+        if normalized.is_empty() {
+            return Ok(Vec::new());
+        }
 
         // Split on user-defined tokens first (greedy longest match)
         // so special tokens like <|endoftext|> are handled before Viterbi.
@@ -171,6 +251,106 @@ impl UgmTokenizer {
         Ok(result)
     }
 
+    /// Normalize text the way llama.cpp's UGM session does before Viterbi.
+    ///
+    /// Each input prefix is replaced via the precompiled charsmap (user-defined
+    /// tokens are kept verbatim), then spaces become `▁`: one is prepended
+    /// (or appended, with `treat_whitespace_as_suffix`) when
+    /// `add_space_prefix` is set, and with `remove_extra_whitespaces` runs of
+    /// spaces collapse to a single `▁` before each word, dropping leading and
+    /// trailing ones.
+    ///
+    /// Models without a charsmap only have ASCII whitespace (`\n`, `\t`,
+    /// ...) folded to spaces, approximating SentencePiece's default
+    /// `nmt_nfkc` rules.
+    #[must_use]
+    pub fn normalize(&self, text: &str, vocab: &Vocabulary) -> String {
+        const SPACE: &str = "\u{2581}";
+        let add_prefix = vocab.add_space_prefix();
+        let suffix = vocab.treat_whitespace_as_suffix();
+        let shall_prepend_space = !suffix && add_prefix;
+        let shall_append_space = suffix && add_prefix;
+        let shall_merge_spaces = vocab.remove_extra_whitespaces();
+
+        let mut out = String::with_capacity(text.len() * 3);
+        let mut is_space_prepended = false;
+        let mut processing_non_ws = false;
+
+        let mut pos = 0;
+        while pos < text.len() {
+            let (piece, consumed) = self.normalize_prefix(&text[pos..]);
+            for c in piece.chars() {
+                if c != ' ' {
+                    if !processing_non_ws {
+                        processing_non_ws = true;
+                        if (shall_prepend_space && !is_space_prepended) || shall_merge_spaces {
+                            out.push_str(SPACE);
+                            is_space_prepended = true;
+                        }
+                    }
+                    out.push(c);
+                } else {
+                    processing_non_ws = false;
+                    if !shall_merge_spaces {
+                        out.push_str(SPACE);
+                    }
+                }
+            }
+            pos += consumed;
+        }
+
+        if shall_append_space {
+            out.push_str(SPACE);
+        }
+        out
+    }
+
+    /// Normalized form of the longest handled prefix of `text`, and how many
+    /// bytes of `text` it consumed.
+    fn normalize_prefix<'a>(&'a self, text: &'a str) -> (std::borrow::Cow<'a, str>, usize) {
+        use std::borrow::Cow;
+
+        let bytes = text.as_bytes();
+        let user_defined = self.user_defined_prefix_len(bytes);
+        if user_defined > 0 {
+            return (Cow::Borrowed(&text[..user_defined]), user_defined);
+        }
+
+        if let Some(charsmap) = &self.charsmap {
+            if let Some((len, replacement)) = charsmap.longest_match(bytes) {
+                // Replacements are UTF-8 in well-formed maps; a match must also
+                // end on a char boundary for the rest of `text` to stay valid.
+                if text.is_char_boundary(len) {
+                    return (String::from_utf8_lossy(replacement), len);
+                }
+            }
+        }
+
+        let c = text.chars().next().unwrap_or(' ');
+        let piece = if self.charsmap.is_none() && c.is_ascii_whitespace() {
+            Cow::Borrowed(" ")
+        } else {
+            Cow::Borrowed(&text[..c.len_utf8()])
+        };
+        (piece, c.len_utf8())
+    }
+
+    /// Length of the longest user-defined token at the start of `bytes`.
+    fn user_defined_prefix_len(&self, bytes: &[u8]) -> usize {
+        let mut best = 0;
+        let mut node = 0;
+        for (i, &b) in bytes.iter().enumerate() {
+            match self.user_defined_trie.traverse(node, b) {
+                Some(next) => node = next,
+                None => break,
+            }
+            if self.user_defined_trie.value(node).is_some() {
+                best = i + 1;
+            }
+        }
+        best
+    }
+
     /// Split text on user-defined tokens using greedy longest match.
     fn split_on_user_defined(&self, text: &str) -> Vec<UgmFragment> {
         let bytes = text.as_bytes();
@@ -334,11 +514,14 @@ impl UgmTokenizer {
     }
 
     /// Decode token IDs back to text.
+    ///
+    /// Mirrors llama.cpp's `token_to_piece` for UGM: the `▁` (U+2581) space
+    /// marker is unescaped back to a plain space.
     pub fn decode(&self, tokens: &[u32], vocab: &Vocabulary) -> Result<String, Error> {
         let mut s = String::new();
         for &t in tokens {
             if let Some(txt) = vocab.get_token_text(t) {
-                s.push_str(txt);
+                s.push_str(&txt.replace('\u{2581}', " "));
             }
         }
         Ok(s)
@@ -18,16 +18,44 @@
 //! - `<unk>`: Unknown token fallback
 //! - Model-specific tokens via metadata
 
+use crate::gguf::{LoaderOptions, MetadataValue};
 use crate::{Error, TokenId};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+/// Pieces that end a turn in common chat formats. llama.cpp treats any of
+/// these as end-of-generation when present, regardless of metadata.
+pub const EOG_PIECES: &[&str] = &[
+    "<|eot_id|>",
+    "<|im_end|>",
+    "<|end|>",
+    "<|return|>",
+    "<|call|>",
+    "<end_of_turn>",
+    "<|endoftext|>",
+    "<|eom_id|>",
+    "<EOT>",
+    "_<EOT>",
+    "<|end_of_text|>",
+    "<end_of_utterance>",
+];
 
 /// Classification of a token in the vocabulary.
 ///
 /// These values match the integer codes stored in `tokenizer.ggml.token_type`
 /// arrays inside GGUF files and are identical to the constants used by
 /// llama.cpp's `LLAMA_TOKEN_TYPE_*` enum.
+///
+/// With the `serde` feature, `TokenType` serializes as that integer code, so
+/// it round-trips with GGUF's convention; out-of-range codes deserialize to
+/// [`TokenType::Undefined`], like [`From<i32>`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "i32", from = "i32")
+)]
 #[repr(i32)]
 pub enum TokenType {
     Undefined = 0,
@@ -53,6 +81,21 @@ impl From<i32> for TokenType {
     }
 }
 
+impl From<TokenType> for i32 {
+    fn from(value: TokenType) -> Self {
+        value.as_i32()
+    }
+}
+
+impl TokenType {
+    /// The GGUF integer code for this token type; the inverse of
+    /// [`From<i32>`].
+    #[must_use]
+    pub const fn as_i32(self) -> i32 {
+        self as i32
+    }
+}
+
 /// The vocabulary loaded from a GGUF model file.
 ///
 /// Holds all token strings, scores, type classifications, merge rules, and
@@ -61,17 +104,28 @@ impl From<i32> for TokenType {
 ///
 /// All index-based accessors (`get_token_text`, `get_token_score`, etc.) are
 /// O(1) via pre-built `Vec` and `HashMap` structures populated at load time.
+#[derive(Clone)]
 pub struct Vocabulary {
     tokens: Vec<String>,
     scores: Vec<f32>,
     token_types: Vec<TokenType>,
     token_to_id: HashMap<String, TokenId>,
+    /// Byte length of the longest token piece, computed at load time.
+    longest_token_len: usize,
+    /// Embedding row count: `tokenizer.ggml.token_count`, or `tokens.len()`.
+    padded_n_tokens: usize,
 
     // Model metadata
     model_type: String,
     pre_type: String,
+    /// UGM unknown-token score from `tokenizer.ggml.unknown_token_score`.
+    unknown_token_score: Option<f64>,
     /// Raw Jinja2 chat template string from the GGUF file, if present.
     chat_template: Option<String>,
+    /// Named chat template variants, keyed by the `tokenizer.chat_template.<name>` suffix.
+    chat_templates: HashMap<String, String>,
+    /// `general.*` GGUF metadata (architecture, name, quantization, ...).
+    metadata: HashMap<String, MetadataValue>,
 
     // Special tokens
     bos_token_id: TokenId,
@@ -80,13 +134,22 @@ pub struct Vocabulary {
     pad_token_id: Option<TokenId>,
     // Additional special tokens (llama.cpp parity)
     eot_token_id: Option<TokenId>,
+    eom_token_id: Option<TokenId>,
     eog_token_id: Option<TokenId>,
     sep_token_id: Option<TokenId>,
+    cls_token_id: Option<TokenId>,
     nl_token_id: Option<TokenId>,
     fim_pre_token_id: Option<TokenId>,
     fim_suf_token_id: Option<TokenId>,
     fim_mid_token_id: Option<TokenId>,
     mask_token_id: Option<TokenId>,
+    /// Every end-of-generation ID, sorted; see [`Vocabulary::eog_token_ids`].
+    eog_token_ids: Vec<TokenId>,
+    /// Special strings registered at runtime via
+    /// [`Vocabulary::add_special_tokens`], with the IDs they encode to.
+    added_special_tokens: HashMap<String, TokenId>,
+    /// The IDs in `added_special_tokens`, for O(1) `is_special_token`.
+    added_special_ids: HashSet<TokenId>,
 
     // Tokenization flags
     add_bos_token: bool,
@@ -100,6 +163,38 @@ pub struct Vocabulary {
 
     // For BPE models
     merges: Vec<(String, String)>,
+    /// Override for [`Vocabulary::byte_fallback`]; `None` auto-detects.
+    byte_fallback: Option<bool>,
+    // For UGM models
+    precompiled_charsmap: Option<Vec<u8>>,
+
+    /// The tokenizer backend (tries, score tables, merge ranks), built on
+    /// first use and shared by every clone of this vocabulary.
+    backend: Arc<OnceLock<Arc<dyn crate::TokenizerImpl>>>,
+}
+
+/// A summary rather than the whole vocabulary: sizes, the special-token IDs
+/// and flags that shape encoding, and the backend if one has been built.
+impl std::fmt::Debug for Vocabulary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Vocabulary")
+            .field("model_type", &self.model_type)
+            .field("pre_type", &self.pre_type)
+            .field("n_tokens", &self.n_tokens())
+            .field("n_merges", &self.merges.len())
+            .field("bos_token_id", &self.bos_token_id)
+            .field("eos_token_id", &self.eos_token_id)
+            .field("unk_token_id", &self.unk_token_id)
+            .field("pad_token_id", &self.pad_token_id)
+            .field("add_bos_token", &self.add_bos_token)
+            .field("add_eos_token", &self.add_eos_token)
+            .field("add_space_prefix", &self.add_space_prefix)
+            .field(
+                "backend",
+                &self.backend.get().map(|backend| backend.algorithm()),
+            )
+            .finish_non_exhaustive()
+    }
 }
 
 impl Vocabulary {
@@ -108,38 +203,108 @@ impl Vocabulary {
         Self::from_metadata(metadata)
     }
 
+    /// Load a vocabulary from a GGUF file with custom size limits.
+    ///
+    /// See [`LoaderOptions`] for the limits and their defaults.
+    pub fn from_gguf_file_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &LoaderOptions,
+    ) -> Result<Self, Error> {
+        let metadata = crate::gguf::load_metadata_with_options(path, options)?;
+        Self::from_metadata_with_options(metadata, options)
+    }
+
+    /// Load a vocabulary from a memory-mapped GGUF file.
+    ///
+    /// See [`crate::gguf::load_metadata_mmap`] for the tradeoffs and the
+    /// caveat about files modified while they load.
+    #[cfg(feature = "mmap")]
+    pub fn from_gguf_file_mmap<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_gguf_file_mmap_with_options(path, &LoaderOptions::default())
+    }
+
+    /// Load a vocabulary from a memory-mapped GGUF file with custom size
+    /// limits.
+    ///
+    /// See [`crate::gguf::load_metadata_mmap`] for the tradeoffs and the
+    /// caveat about files modified while they load.
+    #[cfg(feature = "mmap")]
+    pub fn from_gguf_file_mmap_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &LoaderOptions,
+    ) -> Result<Self, Error> {
+        let metadata = crate::gguf::load_metadata_mmap_with_options(path, options)?;
+        Self::from_metadata_with_options(metadata, options)
+    }
+
+    /// Load a byte-level BPE vocabulary from a legacy GPT-2/RoBERTa
+    /// `vocab.json` + `merges.txt` pair.
+    ///
+    /// Requires the `hf` feature. The result has `model_type` `gpt2` and the
+    /// `gpt-2` pre-tokenizer; see [`crate::hf::parse_vocab_merges`] for how
+    /// special tokens are resolved.
+    #[cfg(feature = "hf")]
+    pub fn from_hf_files<P: AsRef<Path>, Q: AsRef<Path>>(
+        vocab_json: P,
+        merges_txt: Q,
+    ) -> Result<Self, Error> {
+        let metadata = crate::hf::load_vocab_merges(vocab_json, merges_txt)?;
+        Self::from_metadata(metadata)
+    }
+
     /// Load a vocabulary from any [`std::io::Read`] source.
     pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, Error> {
         let metadata = crate::gguf::load_metadata_from_reader(reader)?;
         Self::from_metadata(metadata)
     }
 
-    fn from_metadata(metadata: crate::gguf::GGUFMetadata) -> Result<Self, Error> {
-        const MAX_VOCAB_SIZE: usize = 1_000_000; // 1M tokens max
-        const MAX_TOKEN_LENGTH: usize = 1024; // 1KB per token max
+    /// Load a vocabulary from a seekable GGUF source.
+    ///
+    /// See [`crate::gguf::load_metadata_from_seekable`].
+    pub fn from_gguf_reader<R: std::io::Read + std::io::Seek>(reader: R) -> Result<Self, Error> {
+        let metadata = crate::gguf::load_metadata_from_seekable(reader)?;
+        Self::from_metadata(metadata)
+    }
+
+    pub(crate) fn from_metadata(metadata: crate::gguf::GGUFMetadata) -> Result<Self, Error> {
+        Self::from_metadata_with_options(metadata, &LoaderOptions::default())
+    }
 
+    pub(crate) fn from_metadata_with_options(
+        metadata: crate::gguf::GGUFMetadata,
+        options: &LoaderOptions,
+    ) -> Result<Self, Error> {
         let num_tokens = metadata.tokens.len();
 
         if num_tokens == 0 {
             return Err(Error::VocabularyError("Vocabulary is empty".to_string()));
         }
-        if num_tokens > MAX_VOCAB_SIZE {
+        if num_tokens > options.max_vocab_size {
             return Err(Error::VocabularyError(format!(
-                "Vocabulary too large: {num_tokens} tokens (max: {MAX_VOCAB_SIZE})"
+                "Vocabulary too large: {num_tokens} tokens (max: {})",
+                options.max_vocab_size
             )));
         }
 
+        let mut longest_token_len = 0;
         for (i, token) in metadata.tokens.iter().enumerate() {
-            if token.len() > MAX_TOKEN_LENGTH {
+            longest_token_len = longest_token_len.max(token.len());
+            if token.len() > options.max_token_length {
                 return Err(Error::VocabularyError(format!(
                     "Token {} too large: {} bytes (max: {})",
                     i,
                     token.len(),
-                    MAX_TOKEN_LENGTH
+                    options.max_token_length
                 )));
             }
         }
 
+        // A declared count below the token list cannot be the embedding size.
+        let padded_n_tokens = metadata
+            .token_count
+            .and_then(|count| usize::try_from(count).ok())
+            .map_or(num_tokens, |count| count.max(num_tokens));
+
         // Build token_to_id with capacity hint (Issue #8)
         let mut token_to_id = HashMap::with_capacity(num_tokens);
         for (i, s) in metadata.tokens.iter().enumerate() {
@@ -154,29 +319,12 @@ impl Vocabulary {
             )));
         }
 
-        // Validate merge rules reference valid tokens (Issue #12)
         if let Some(ref merges) = metadata.merges {
-            const MAX_MERGE_COUNT: usize = 1_000_000; // 1M merges max (Issue R3#14)
-            if merges.len() > MAX_MERGE_COUNT {
-                return Err(Error::VocabularyError(format!(
-                    "Too many merge rules: {} (max: {})",
-                    merges.len(),
-                    MAX_MERGE_COUNT
-                )));
-            }
+            validate_merges(merges, &token_to_id, options.max_merge_count)?;
+        }
 
-            for (rank, (left, right)) in merges.iter().enumerate() {
-                if !token_to_id.contains_key(left) {
-                    return Err(Error::VocabularyError(format!(
-                        "Merge rule {rank} references unknown left token: '{left}'"
-                    )));
-                }
-                if !token_to_id.contains_key(right) {
-                    return Err(Error::VocabularyError(format!(
-                        "Merge rule {rank} references unknown right token: '{right}'"
-                    )));
-                }
-            }
+        if let Some(ref charsmap) = metadata.precompiled_charsmap {
+            crate::ugm::Charsmap::parse(charsmap)?;
         }
 
         let scores = metadata.scores.unwrap_or_else(|| vec![0.0; num_tokens]);
@@ -190,6 +338,35 @@ impl Vocabulary {
             )));
         }
 
+        // BERT vocabularies converted without CLS/SEP keys still use the
+        // standard `[CLS]`/`[SEP]` pieces.
+        let is_wpm = matches!(metadata.model_type.as_str(), "bert" | "wpm");
+        let wpm_by_name = |piece: &str| {
+            if is_wpm {
+                token_to_id.get(piece).copied()
+            } else {
+                None
+            }
+        };
+        let sep_token_id = metadata.special.sep.or_else(|| wpm_by_name("[SEP]"));
+        let cls_token_id = metadata.special.cls.or_else(|| wpm_by_name("[CLS]"));
+
+        let eos_token_id = metadata.special.eos.unwrap_or(2);
+
+        // End-of-generation set, as llama.cpp builds it: the EOS/EOT/EOM keys
+        // plus any piece whose text is a known end-of-turn marker.
+        let mut eog_token_ids: Vec<TokenId> = EOG_PIECES
+            .iter()
+            .filter_map(|piece| token_to_id.get(*piece).copied())
+            .chain([eos_token_id])
+            .chain(metadata.special.eot)
+            .chain(metadata.special.eom)
+            .chain(metadata.special.eog)
+            .filter(|&id| (id as usize) < num_tokens)
+            .collect();
+        eog_token_ids.sort_unstable();
+        eog_token_ids.dedup();
+
         Ok(Self {
             tokens: metadata.tokens,
             scores,
@@ -208,23 +385,33 @@ impl Vocabulary {
                 types
             },
             token_to_id,
+            longest_token_len,
+            padded_n_tokens,
 
             model_type: metadata.model_type,
             pre_type: metadata.pre_type.unwrap_or_else(|| "default".to_string()),
+            unknown_token_score: metadata.unknown_token_score,
             chat_template: metadata.chat_template,
+            chat_templates: metadata.chat_templates,
+            metadata: metadata.metadata,
 
             bos_token_id: metadata.special.bos.unwrap_or(1),
-            eos_token_id: metadata.special.eos.unwrap_or(2),
+            eos_token_id,
             unk_token_id: metadata.special.unk.unwrap_or(0),
             pad_token_id: metadata.special.pad,
             eot_token_id: metadata.special.eot,
+            eom_token_id: metadata.special.eom,
             eog_token_id: metadata.special.eog,
-            sep_token_id: metadata.special.sep,
+            sep_token_id,
+            cls_token_id,
             nl_token_id: metadata.special.nl,
             fim_pre_token_id: metadata.special.fim_pre,
             fim_suf_token_id: metadata.special.fim_suf,
             fim_mid_token_id: metadata.special.fim_mid,
             mask_token_id: metadata.special.mask,
+            eog_token_ids,
+            added_special_tokens: HashMap::new(),
+            added_special_ids: HashSet::new(),
 
             add_bos_token: metadata.flags.add_bos_token,
             add_eos_token: metadata.flags.add_eos_token,
@@ -235,6 +422,10 @@ impl Vocabulary {
             treat_whitespace_as_suffix: metadata.flags.treat_whitespace_as_suffix,
 
             merges: metadata.merges.unwrap_or_default(),
+            byte_fallback: None,
+            precompiled_charsmap: metadata.precompiled_charsmap,
+
+            backend: Arc::default(),
         })
     }
 
@@ -243,6 +434,13 @@ impl Vocabulary {
         &self.model_type
     }
 
+    /// Returns the `general.*` GGUF metadata, keyed by full key name
+    /// (e.g. `general.architecture`).
+    #[must_use]
+    pub fn metadata(&self) -> &HashMap<String, MetadataValue> {
+        &self.metadata
+    }
+
     /// Returns the raw Jinja2 chat template string from the GGUF file, if present.
     ///
     /// Pass this to a Jinja renderer such as
@@ -254,6 +452,13 @@ impl Vocabulary {
         self.chat_template.as_deref()
     }
 
+    /// Returns the named chat template variants (`tokenizer.chat_template.<name>`),
+    /// keyed by `<name>`. Empty when the GGUF file ships only the default template.
+    #[must_use]
+    pub fn chat_templates(&self) -> &HashMap<String, String> {
+        &self.chat_templates
+    }
+
     #[must_use]
     pub fn get_token_id(&self, text: &str) -> Option<TokenId> {
         self.token_to_id.get(text).copied()
@@ -284,6 +489,20 @@ impl Vocabulary {
         None
     }
 
+    /// The piece → ID map built at load time.
+    #[must_use]
+    pub fn token_to_id_map(&self) -> &HashMap<String, TokenId> {
+        &self.token_to_id
+    }
+
+    /// Iterate over `(piece, id)` pairs in ID order, without allocating.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, TokenId)> + '_ {
+        self.tokens
+            .iter()
+            .enumerate()
+            .map(|(id, piece)| (piece.as_str(), id as TokenId))
+    }
+
     #[must_use]
     pub fn get_token_text(&self, id: TokenId) -> Option<&str> {
         self.tokens
@@ -291,28 +510,25 @@ impl Vocabulary {
             .map(std::string::String::as_str)
     }
 
+    /// Score of a token, or `0.0` if `id` is out of range.
     #[must_use]
     pub fn get_token_score(&self, id: TokenId) -> f32 {
-        // scores length is validated to match tokens, so this is safe
-        self.scores
-            .get(id as usize)
-            .copied()
-            .expect("Token ID should be valid - scores validated at load time")
+        self.scores.get(id as usize).copied().unwrap_or(0.0)
     }
 
+    /// Type of a token, or [`TokenType::Undefined`] if `id` is out of range.
     #[must_use]
     pub fn get_token_type(&self, id: TokenId) -> TokenType {
-        // token_types length is validated to match tokens, so this is safe
         self.token_types
             .get(id as usize)
             .copied()
-            .expect("Token ID should be valid - token_types validated at load time")
+            .unwrap_or(TokenType::Undefined)
     }
 
     #[must_use]
     pub fn byte_to_token(&self, byte: u8) -> TokenId {
         // Try hex format <0xXX> first (SPM style)
-        let hex_str = format!("<0x{byte:02X}>");
+        let hex_str = crate::byte_encoder::format_hex_byte_token(byte);
         if let Some(id) = self.token_to_id.get(&hex_str) {
             return *id;
         }
@@ -334,9 +550,12 @@ impl Vocabulary {
             || id == self.unk_token_id
             || self.pad_token_id == Some(id)
             || self.eot_token_id == Some(id)
+            || self.eom_token_id == Some(id)
             || self.eog_token_id == Some(id)
             || self.sep_token_id == Some(id)
+            || self.cls_token_id == Some(id)
             || self.mask_token_id == Some(id)
+            || self.added_special_ids.contains(&id)
     }
 
     /// Build a map of special token strings to their IDs for parse_special mode.
@@ -351,8 +570,10 @@ impl Vocabulary {
             Some(self.unk_token_id),
             self.pad_token_id,
             self.eot_token_id,
+            self.eom_token_id,
             self.eog_token_id,
             self.sep_token_id,
+            self.cls_token_id,
             self.nl_token_id,
             self.fim_pre_token_id,
             self.fim_suf_token_id,
@@ -374,9 +595,71 @@ impl Vocabulary {
             }
         }
 
+        map.extend(
+            self.added_special_tokens
+                .iter()
+                .map(|(text, &id)| (text.clone(), id)),
+        );
+
         map
     }
 
+    /// IDs of every special token, in ascending order.
+    ///
+    /// A token is listed exactly when [`is_special_token`](Self::is_special_token)
+    /// holds: control and unknown tokens, BOS/EOS/UNK/PAD and the other
+    /// configured special IDs, and IDs registered with
+    /// [`add_special_tokens`](Self::add_special_tokens).
+    #[must_use]
+    pub fn special_token_ids(&self) -> Vec<TokenId> {
+        (0..self.n_tokens() as TokenId)
+            .filter(|&id| self.is_special_token(id))
+            .collect()
+    }
+
+    /// [`special_token_ids`](Self::special_token_ids) paired with each
+    /// token's vocabulary piece.
+    #[must_use]
+    pub fn special_tokens(&self) -> Vec<(TokenId, &str)> {
+        self.special_token_ids()
+            .into_iter()
+            .map(|id| (id, self.tokens[id as usize].as_str()))
+            .collect()
+    }
+
+    /// Register additional special-token strings.
+    ///
+    /// Each `(text, id)` pair makes `text` split out as the single token `id`
+    /// when special tokens are parsed, and makes `id` count as special. The
+    /// text need not match the vocabulary's own piece for `id`.
+    ///
+    /// Registering a string that is already registered, here or in an earlier
+    /// call, is not an error: the last ID given for it wins, and the ID it
+    /// replaces stops counting as special unless another string maps to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidToken`] if a text is empty or an ID is outside
+    /// the vocabulary; nothing is registered in that case.
+    pub fn add_special_tokens(&mut self, tokens: &[(String, TokenId)]) -> Result<(), Error> {
+        let n_tokens = self.n_tokens();
+        for (text, id) in tokens {
+            if text.is_empty() {
+                return Err(Error::InvalidToken(format!(
+                    "Special token text for ID {id} is empty"
+                )));
+            }
+            if *id as usize >= n_tokens {
+                return Err(Error::InvalidToken(format!(
+                    "Special token '{text}' has ID {id}, outside vocabulary of {n_tokens} tokens"
+                )));
+            }
+        }
+        self.added_special_tokens.extend(tokens.iter().cloned());
+        self.added_special_ids = self.added_special_tokens.values().copied().collect();
+        Ok(())
+    }
+
     #[must_use]
     pub fn add_bos_token(&self) -> bool {
         self.add_bos_token
@@ -402,11 +685,24 @@ impl Vocabulary {
         self.unk_token_id
     }
 
+    /// Padding token ID from `tokenizer.ggml.padding_token_id`, if the model
+    /// defines one.
+    #[must_use]
+    pub fn pad_token_id(&self) -> Option<TokenId> {
+        self.pad_token_id
+    }
+
     #[must_use]
     pub fn get_merges(&self) -> &[(String, String)] {
         &self.merges
     }
 
+    /// Number of BPE merge rules (0 for non-BPE models).
+    #[must_use]
+    pub fn merge_count(&self) -> usize {
+        self.merges.len()
+    }
+
     #[must_use]
     pub fn pre_type(&self) -> Option<&str> {
         if self.pre_type.is_empty() || self.pre_type == "default" {
@@ -416,11 +712,100 @@ impl Vocabulary {
         }
     }
 
+    /// Replace the pre-tokenizer type. Backends read it only at construction,
+    /// so this drops the cached backend and the caller must rebuild it.
+    pub(crate) fn set_pre_type(&mut self, pre_type: &str) {
+        self.pre_type = pre_type.to_string();
+        self.backend = Arc::default();
+    }
+
+    /// Whether the vocabulary has a token for every GPT-2 byte-level
+    /// character, i.e. all 256 single-byte BPE pieces.
+    #[must_use]
+    pub fn has_byte_tokens(&self) -> bool {
+        (0..=255u8).all(|byte| {
+            let mut buf = [0u8; 4];
+            let piece = crate::byte_encoder::byte_to_char(byte).encode_utf8(&mut buf);
+            self.token_to_id.contains_key(piece)
+        })
+    }
+
+    /// How many of the 256 byte values have a token, and which do.
+    ///
+    /// A byte counts as covered if the vocabulary has either its
+    /// SentencePiece `<0xNN>` piece or its GPT-2 byte-level character. Byte
+    /// fallback can only represent every input when the count is 256;
+    /// otherwise characters containing a missing byte degrade to UNK.
+    #[must_use]
+    pub fn byte_token_coverage(&self) -> (usize, [bool; 256]) {
+        let mut covered = [false; 256];
+        let mut buf = [0u8; 4];
+        for (byte, slot) in (0..=255u8).zip(covered.iter_mut()) {
+            let piece = crate::byte_encoder::byte_to_char(byte).encode_utf8(&mut buf);
+            *slot = self.token_to_id.contains_key(piece)
+                || self
+                    .token_to_id
+                    .contains_key(&crate::byte_encoder::format_hex_byte_token(byte));
+        }
+        (covered.iter().filter(|&&c| c).count(), covered)
+    }
+
+    /// Whether BPE splits a symbol missing from the vocabulary into its
+    /// single-byte pieces before falling back to UNK.
+    ///
+    /// Defaults to [`has_byte_tokens`](Self::has_byte_tokens) unless
+    /// overridden with `Tokenizer::set_byte_fallback`.
+    #[must_use]
+    pub fn byte_fallback(&self) -> bool {
+        self.byte_fallback.unwrap_or_else(|| self.has_byte_tokens())
+    }
+
+    /// Override [`byte_fallback`](Self::byte_fallback). Like
+    /// [`set_pre_type`](Self::set_pre_type), this drops the cached backend.
+    pub(crate) fn set_byte_fallback(&mut self, enabled: bool) {
+        self.byte_fallback = Some(enabled);
+        self.backend = Arc::default();
+    }
+
+    /// The cached backend for this vocabulary; see [`crate::Tokenizer`]'s
+    /// `select_backend`.
+    pub(crate) fn backend_cache(&self) -> &OnceLock<Arc<dyn crate::TokenizerImpl>> {
+        &self.backend
+    }
+
     #[must_use]
     pub fn n_tokens(&self) -> usize {
         self.tokens.len()
     }
 
+    /// Number of rows in the model's embedding matrix.
+    ///
+    /// Equal to [`n_tokens`](Self::n_tokens) unless the GGUF file declares a
+    /// larger `tokenizer.ggml.token_count`, as models whose embedding is padded
+    /// to a multiple (e.g. 32000 tokens in 32064 rows) may.
+    #[must_use]
+    pub fn padded_n_tokens(&self) -> usize {
+        self.padded_n_tokens
+    }
+
+    /// Number of tokens that are not reserved [`TokenType::Unused`] slots.
+    #[must_use]
+    pub fn n_usable_tokens(&self) -> usize {
+        self.token_types
+            .iter()
+            .filter(|&&t| t != TokenType::Unused)
+            .count()
+    }
+
+    /// Byte length of the longest token piece in the vocabulary.
+    ///
+    /// Computed once at load time, so greedy longest-match searches can bound
+    /// their window without scanning the vocabulary.
+    #[must_use]
+    pub fn longest_token_byte_len(&self) -> usize {
+        self.longest_token_len
+    }
+
     // Additional special token accessors (llama.cpp parity)
 
     #[must_use]
@@ -428,16 +813,41 @@ impl Vocabulary {
         self.eot_token_id
     }
 
+    #[must_use]
+    pub fn eom_token_id(&self) -> Option<TokenId> {
+        self.eom_token_id
+    }
+
     #[must_use]
     pub fn eog_token_id(&self) -> Option<TokenId> {
         self.eog_token_id
     }
 
+    /// All end-of-generation token IDs, sorted ascending.
+    ///
+    /// Includes EOS, the `eot`/`eom`/`eog` IDs from metadata, and every piece
+    /// listed in [`EOG_PIECES`] that exists in the vocabulary.
+    #[must_use]
+    pub fn eog_token_ids(&self) -> &[TokenId] {
+        &self.eog_token_ids
+    }
+
+    /// Whether generation should stop after `id`.
+    #[must_use]
+    pub fn is_eog_token(&self, id: TokenId) -> bool {
+        self.eog_token_ids.binary_search(&id).is_ok()
+    }
+
     #[must_use]
     pub fn sep_token_id(&self) -> Option<TokenId> {
         self.sep_token_id
     }
 
+    #[must_use]
+    pub fn cls_token_id(&self) -> Option<TokenId> {
+        self.cls_token_id
+    }
+
     #[must_use]
     pub fn nl_token_id(&self) -> Option<TokenId> {
         self.nl_token_id
@@ -463,8 +873,38 @@ impl Vocabulary {
         self.mask_token_id
     }
 
+    /// Stable name of a special token's role, such as `"<bos>"` or `"<eot>"`,
+    /// independent of the piece text the model uses for it.
+    ///
+    /// Returns `None` for tokens without a known role. When one ID fills
+    /// several roles (GPT-2 uses `<|endoftext|>` for both BOS and EOS), the
+    /// first of BOS, EOS, EOT, EOM, EOG, UNK, PAD, SEP, CLS, MASK, FIM
+    /// prefix/suffix/middle wins.
+    #[must_use]
+    pub fn special_token_name(&self, id: TokenId) -> Option<&'static str> {
+        [
+            (Some(self.bos_token_id), "<bos>"),
+            (Some(self.eos_token_id), "<eos>"),
+            (self.eot_token_id, "<eot>"),
+            (self.eom_token_id, "<eom>"),
+            (self.eog_token_id, "<eog>"),
+            (Some(self.unk_token_id), "<unk>"),
+            (self.pad_token_id, "<pad>"),
+            (self.sep_token_id, "<sep>"),
+            (self.cls_token_id, "<cls>"),
+            (self.mask_token_id, "<mask>"),
+            (self.fim_pre_token_id, "<fim_pre>"),
+            (self.fim_suf_token_id, "<fim_suf>"),
+            (self.fim_mid_token_id, "<fim_mid>"),
+        ]
+        .into_iter()
+        .find_map(|(role, name)| (role == Some(id)).then_some(name))
+    }
+
     // Cleanup/normalization flag accessors
 
+    /// Whether SPM/UGM encoding prepends `▁` to the input
+    /// (`tokenizer.ggml.add_space_prefix`, default `true`).
     #[must_use]
     pub fn add_space_prefix(&self) -> bool {
         self.add_space_prefix
@@ -489,4 +929,60 @@ impl Vocabulary {
     pub fn treat_whitespace_as_suffix(&self) -> bool {
         self.treat_whitespace_as_suffix
     }
+
+    /// The raw `tokenizer.ggml.precompiled_charsmap` blob, if the model has
+    /// one (UGM/T5 models converted from SentencePiece).
+    #[must_use]
+    pub fn precompiled_charsmap(&self) -> Option<&[u8]> {
+        self.precompiled_charsmap.as_deref()
+    }
+
+    /// The UGM unknown-token score declared by the model
+    /// (`tokenizer.ggml.unknown_token_score`), if any. Without it the UGM
+    /// backend uses the lowest normal-token score minus 10, like llama.cpp.
+    #[must_use]
+    pub fn unknown_token_score(&self) -> Option<f64> {
+        self.unknown_token_score
+    }
+}
+
+/// Validates BPE merge rules at load time.
+///
+/// Rejects oversized merge lists, rules that reference tokens missing from
+/// the vocabulary, and duplicate `(left, right)` pairs. A duplicate would
+/// silently take whichever rank the merge table kept, so the error names
+/// both the first offending rank and the rank it repeats.
+fn validate_merges(
+    merges: &[(String, String)],
+    token_to_id: &HashMap<String, TokenId>,
+    max_merge_count: usize,
+) -> Result<(), Error> {
+    if merges.len() > max_merge_count {
+        return Err(Error::VocabularyError(format!(
+            "Too many merge rules: {} (max: {})",
+            merges.len(),
+            max_merge_count
+        )));
+    }
+
+    // Merge rules must reference valid tokens (Issue #12)
+    let mut seen: HashMap<(&str, &str), usize> = HashMap::with_capacity(merges.len());
+    for (rank, (left, right)) in merges.iter().enumerate() {
+        if !token_to_id.contains_key(left) {
+            return Err(Error::VocabularyError(format!(
+                "Merge rule {rank} references unknown left token: '{left}'"
+            )));
+        }
+        if !token_to_id.contains_key(right) {
+            return Err(Error::VocabularyError(format!(
+                "Merge rule {rank} references unknown right token: '{right}'"
+            )));
+        }
+        if let Some(first) = seen.insert((left.as_str(), right.as_str()), rank) {
+            return Err(Error::VocabularyError(format!(
+                "Merge rule {rank} duplicates rule {first}: '{left} {right}'"
+            )));
+        }
+    }
+    Ok(())
 }
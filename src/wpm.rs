@@ -4,15 +4,18 @@
 //! Source reference: llm_tokenizer_wpm_session::tokenize + preprocess in llama-vocab.cpp
 //!
 //! Key behaviors:
-//! - Unicode NFD normalize, lowercase
-//! - Split into "words" on whitespace
-//! - Punctuation / certain symbols / Chinese chars become single-char "words"
+//! - Lowercase, Unicode NFD normalize, strip combining marks (accents)
+//! - Split into "words" on whitespace; drop control characters
+//! - Punctuation (any Unicode `P*`), ASCII symbols and Chinese chars become
+//!   single-char "words"
 //! - Each word is tokenized by greedy longest-match against vocab, with phantom space prefix U+2581 (▁)
 //! - If any character position in a word cannot be matched: discard all tokens produced for that word
 //! - If a word yields no tokens, emit unk
 
 use crate::vocab::Vocabulary;
 use crate::Error;
+use unicode_normalization::UnicodeNormalization;
+use unicode_properties::{GeneralCategory, GeneralCategoryGroup, UnicodeGeneralCategory};
 
 /// WPM tokenizer using phantom-space + greedy longest-match algorithm.
 pub struct WpmTokenizer {
@@ -23,14 +26,8 @@ pub struct WpmTokenizer {
 impl WpmTokenizer {
     /// Create a new WPM tokenizer from a vocabulary.
     pub fn new(vocab: &Vocabulary) -> Self {
-        let mut max_len = 0usize;
-        for id in 0..(vocab.n_tokens() as u32) {
-            if let Some(t) = vocab.get_token_text(id) {
-                max_len = max_len.max(t.len());
-            }
-        }
         Self {
-            max_token_len: max_len,
+            max_token_len: vocab.longest_token_byte_len(),
         }
     }
 
@@ -84,12 +81,26 @@ impl WpmTokenizer {
         Ok(out)
     }
 
+    /// The preprocessed words as matched against the vocabulary: each word
+    /// from `preprocess_wpm` with its phantom-space prefix, concatenated.
+    #[must_use]
+    pub fn normalize(&self, text: &str, _vocab: &Vocabulary) -> String {
+        preprocess_wpm(text)
+            .iter()
+            .filter(|w| !w.is_empty())
+            .flat_map(|w| ["\u{2581}", w.as_str()])
+            .collect()
+    }
+
     /// Decode token IDs back to text.
+    ///
+    /// The phantom-space prefix (`▁`, U+2581) on word-initial pieces is
+    /// unescaped back to a plain space, as in llama.cpp's `token_to_piece`.
     pub fn decode(&self, tokens: &[u32], vocab: &Vocabulary) -> Result<String, Error> {
         let mut s = String::new();
         for &t in tokens {
             if let Some(txt) = vocab.get_token_text(t) {
-                s.push_str(txt);
+                s.push_str(&txt.replace('\u{2581}', " "));
             }
         }
         Ok(s)
@@ -98,14 +109,20 @@ impl WpmTokenizer {
 
 /// Preprocess text for WPM tokenization.
 ///
-/// - Lowercase
-/// - Split on whitespace
-/// - Punctuation / symbols / Chinese chars => single-char word boundaries
+/// Matches llama.cpp's `llm_tokenizer_wpm_session::preprocess` and BERT's
+/// `BasicTokenizer`:
+///
+/// - Lowercase, then NFD and drop nonspacing marks (`Mn`), so `"Café"` and
+///   `"cafe\u{301}"` both become `"cafe"`
+/// - Split on whitespace; drop NUL, U+FFFD and control/format characters
+/// - Isolate punctuation (every Unicode `P*` category), ASCII symbols
+///   (`$`, `+`, `^`, ...) and CJK ideographs as single-char words. Non-ASCII
+///   symbols such as `€` stay inside words, as in BERT.
 fn preprocess_wpm(text: &str) -> Vec<String> {
     let lower = text.to_lowercase();
 
     let mut words: Vec<String> = vec![String::new()];
-    for ch in lower.chars() {
+    for ch in lower.nfd() {
         if ch.is_whitespace() {
             if !words.last().unwrap().is_empty() {
                 words.push(String::new());
@@ -113,8 +130,23 @@ fn preprocess_wpm(text: &str) -> Vec<String> {
             continue;
         }
 
+        let category = ch.general_category();
+        if ch == '\0'
+            || ch == '\u{FFFD}'
+            || matches!(
+                category,
+                GeneralCategory::NonspacingMark
+                    | GeneralCategory::Control
+                    | GeneralCategory::Format
+            )
+        {
+            continue;
+        }
+
         // Check if this is punctuation that should be isolated
-        let is_punct = ch.is_ascii_punctuation() || is_cjk_char(ch);
+        let is_punct = ch.general_category_group() == GeneralCategoryGroup::Punctuation
+            || ch.is_ascii_punctuation()
+            || is_cjk_char(ch);
         if is_punct {
             if !words.last().unwrap().is_empty() {
                 words.push(String::new());
@@ -173,6 +205,30 @@ mod tests {
         assert_eq!(words, vec!["hello", "世", "界"]);
     }
 
+    #[test]
+    fn test_preprocess_wpm_strips_accents() {
+        assert_eq!(preprocess_wpm("Café"), vec!["cafe"]);
+        assert_eq!(preprocess_wpm("cafe\u{301}"), vec!["cafe"]);
+        assert_eq!(preprocess_wpm("Ñandú Ångström"), vec!["nandu", "angstrom"]);
+    }
+
+    #[test]
+    fn test_preprocess_wpm_unicode_punctuation() {
+        // BERT BasicTokenizer: "«hello»—world…" -> « hello » — world …
+        assert_eq!(
+            preprocess_wpm("«hello»—world…"),
+            vec!["«", "hello", "»", "—", "world", "…"]
+        );
+        // ASCII symbols split, non-ASCII symbols stay in the word.
+        assert_eq!(preprocess_wpm("a+b"), vec!["a", "+", "b"]);
+        assert_eq!(preprocess_wpm("5€"), vec!["5€"]);
+    }
+
+    #[test]
+    fn test_preprocess_wpm_drops_control_chars() {
+        assert_eq!(preprocess_wpm("he\u{0}llo\u{200B}"), vec!["hello"]);
+    }
+
     #[test]
     fn test_is_cjk_char() {
         assert!(is_cjk_char('中'));
@@ -10,13 +10,21 @@
 
 #![allow(dead_code)]
 
-/// GGUF metadata value-type IDs (subset consumed by the loader).
-mod ty {
+/// GGUF metadata value-type IDs.
+pub mod ty {
+    pub const U8: u32 = 0;
+    pub const I8: u32 = 1;
+    pub const U16: u32 = 2;
+    pub const I16: u32 = 3;
     pub const U32: u32 = 4;
     pub const BOOL: u32 = 7;
     pub const STRING: u32 = 8;
     pub const ARRAY: u32 = 9;
     pub const I32: u32 = 5;
+    pub const F32: u32 = 6;
+    pub const U64: u32 = 10;
+    pub const I64: u32 = 11;
+    pub const F64: u32 = 12;
 }
 
 /// Incrementally builds a valid GGUF v3 byte payload with tokenizer metadata.
@@ -106,6 +114,54 @@ impl GgufBuilder {
         self
     }
 
+    /// Append an f32-array metadata value.
+    #[must_use]
+    pub fn with_f32_array(mut self, key: &str, values: &[f32]) -> Self {
+        self.push_key(key);
+        self.body.extend_from_slice(&ty::ARRAY.to_le_bytes());
+        self.body.extend_from_slice(&ty::F32.to_le_bytes());
+        self.body
+            .extend_from_slice(&(values.len() as u64).to_le_bytes());
+        for v in values {
+            self.body.extend_from_slice(&v.to_le_bytes());
+        }
+        self.kv_count += 1;
+        self
+    }
+
+    /// Append a `u64` metadata value.
+    #[must_use]
+    pub fn with_u64(mut self, key: &str, value: u64) -> Self {
+        self.push_key(key);
+        self.body.extend_from_slice(&ty::U64.to_le_bytes());
+        self.body.extend_from_slice(&value.to_le_bytes());
+        self.kv_count += 1;
+        self
+    }
+
+    /// Append a scalar of any type from its already-encoded payload bytes.
+    #[must_use]
+    pub fn with_raw(mut self, key: &str, type_id: u32, payload: &[u8]) -> Self {
+        self.push_key(key);
+        self.body.extend_from_slice(&type_id.to_le_bytes());
+        self.body.extend_from_slice(payload);
+        self.kv_count += 1;
+        self
+    }
+
+    /// Append an array of `count` elements of `elem_type` from its
+    /// already-encoded payload bytes.
+    #[must_use]
+    pub fn with_raw_array(mut self, key: &str, elem_type: u32, count: u64, payload: &[u8]) -> Self {
+        self.push_key(key);
+        self.body.extend_from_slice(&ty::ARRAY.to_le_bytes());
+        self.body.extend_from_slice(&elem_type.to_le_bytes());
+        self.body.extend_from_slice(&count.to_le_bytes());
+        self.body.extend_from_slice(payload);
+        self.kv_count += 1;
+        self
+    }
+
     /// Finalize the GGUF v3 payload: magic + version + counts + body.
     #[must_use]
     pub fn build(self) -> Vec<u8> {
@@ -137,6 +193,13 @@ impl GgufBuilder {
 /// - `""`    -> `[]`
 #[must_use]
 pub fn bpe_gpt2_fixture() -> Vec<u8> {
+    bpe_gpt2_fixture_with_flags(false, false)
+}
+
+/// [`bpe_gpt2_fixture`] with the given `add_bos_token` / `add_eos_token`
+/// flags.
+#[must_use]
+pub fn bpe_gpt2_fixture_with_flags(add_bos: bool, add_eos: bool) -> Vec<u8> {
     GgufBuilder::new()
         .with_string("tokenizer.ggml.model", "gpt2")
         .with_string("tokenizer.ggml.pre", "gpt-2")
@@ -148,11 +211,46 @@ pub fn bpe_gpt2_fixture() -> Vec<u8> {
         .with_u32("tokenizer.ggml.unknown_token_id", 0)
         .with_u32("tokenizer.ggml.bos_token_id", 1)
         .with_u32("tokenizer.ggml.eos_token_id", 2)
-        .with_bool("tokenizer.ggml.add_bos_token", false)
-        .with_bool("tokenizer.ggml.add_eos_token", false)
+        .with_bool("tokenizer.ggml.add_bos_token", add_bos)
+        .with_bool("tokenizer.ggml.add_eos_token", add_eos)
         .build()
 }
 
+/// A byte-level GPT-2 BPE vocabulary with a piece for every byte, so no input
+/// reaches UNK, as a [`GgufBuilder`] to which callers can add keys.
+///
+/// Vocabulary (id: piece):
+/// - 0: `<unk>`  1: `<s>`  2: `</s>`
+/// - 3..=258: the GPT-2 byte encoding of bytes `0x00..=0xFF` (byte `b` at
+///   id `3 + b`)
+/// - 259..: `extra`, in order
+///
+/// `merges` are the merge rules in rank order.
+#[must_use]
+pub fn bpe_full_bytes_builder(extra: &[&str], merges: &[&str]) -> GgufBuilder {
+    let tokens: Vec<String> = ["<unk>", "<s>", "</s>"]
+        .into_iter()
+        .map(str::to_string)
+        .chain((0..=255u8).map(|b| shimmytok::byte_encoder::byte_to_char(b).to_string()))
+        .chain(extra.iter().map(|piece| piece.to_string()))
+        .collect();
+    let token_refs: Vec<&str> = tokens.iter().map(String::as_str).collect();
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "gpt-2")
+        .with_string_array("tokenizer.ggml.tokens", &token_refs)
+        .with_string_array("tokenizer.ggml.merges", merges)
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .with_u32("tokenizer.ggml.bos_token_id", 1)
+        .with_u32("tokenizer.ggml.eos_token_id", 2)
+}
+
+/// [`bpe_full_bytes_builder`], built.
+#[must_use]
+pub fn bpe_full_bytes_fixture(extra: &[&str], merges: &[&str]) -> Vec<u8> {
+    bpe_full_bytes_builder(extra, merges).build()
+}
+
 /// A multi-pattern BPE fixture (StarCoder style). StarCoder applies two
 /// sequential patterns: the first (`\p{N}`) splits digits individually, the
 /// second handles letters/whitespace. This locks in multi-pattern splitting
@@ -184,3 +282,268 @@ pub fn bpe_starcoder_fixture() -> Vec<u8> {
         .with_bool("tokenizer.ggml.add_eos_token", false)
         .build()
 }
+
+/// A minimal T5-style Unigram (UGM) fixture.
+///
+/// Vocabulary (id: piece, type):
+/// - 0: `<pad>` (control)  1: `</s>` (control)  2: `<unk>` (unknown)
+/// - 3: `▁`  4: `▁Hello`  5: `▁world`  6: `Hello`  7: `world`
+/// - 8: `▁你好`  9: `你`  10: `好`  11: `世界`
+///
+/// Longer pieces carry higher (less negative) scores, so Viterbi prefers them.
+///
+/// Expected encodes (add_special = false):
+/// - `"Hello world"` -> `[4, 5]`
+/// - `"你好世界"`    -> `[8, 11]`
+#[must_use]
+pub fn ugm_t5_fixture() -> Vec<u8> {
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "t5")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &[
+                "<pad>",
+                "</s>",
+                "<unk>",
+                "\u{2581}",
+                "\u{2581}Hello",
+                "\u{2581}world",
+                "Hello",
+                "world",
+                "\u{2581}你好",
+                "你",
+                "好",
+                "世界",
+            ],
+        )
+        .with_f32_array(
+            "tokenizer.ggml.scores",
+            &[
+                0.0, 0.0, 0.0, -5.0, -2.0, -2.0, -6.0, -6.0, -2.0, -6.0, -6.0, -3.0,
+            ],
+        )
+        .with_i32_array(
+            "tokenizer.ggml.token_type",
+            &[3, 3, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+        )
+        .with_u32("tokenizer.ggml.unknown_token_id", 2)
+        .with_u32("tokenizer.ggml.eos_token_id", 1)
+        .with_u32("tokenizer.ggml.padding_token_id", 0)
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+        .with_bool("tokenizer.ggml.add_eos_token", false)
+        .build()
+}
+
+/// Serialize `(from, to)` normalization rules as a SentencePiece precompiled
+/// charsmap blob, in the layout llama.cpp reads from
+/// `tokenizer.ggml.precompiled_charsmap`: a `u32` XCDA byte length, the XCDA
+/// nodes, then NUL-terminated replacement strings.
+///
+/// Nodes are laid out naively — every trie node gets its own 256-entry block —
+/// which wastes space but is a valid double array.
+#[must_use]
+pub fn precompiled_charsmap(rules: &[(&str, &str)]) -> Vec<u8> {
+    use std::collections::BTreeMap;
+
+    // Byte trie over the `from` strings; values are replacement offsets.
+    let mut trie: Vec<(BTreeMap<u8, usize>, Option<u32>)> = vec![(BTreeMap::new(), None)];
+    let mut replacements = Vec::new();
+    for (from, to) in rules {
+        let mut node = 0;
+        for &b in from.as_bytes() {
+            let next = trie.len();
+            node = *trie[node].0.entry(b).or_insert(next);
+            if node == next {
+                trie.push((BTreeMap::new(), None));
+            }
+        }
+        trie[node].1 = Some(replacements.len() as u32);
+        replacements.extend_from_slice(to.as_bytes());
+        replacements.push(0);
+    }
+
+    // Place trie node `t` at XCDA index `x`; its children and value node live
+    // in a fresh block `B` at `B ^ label` (value: label 0), and `x` stores
+    // `x ^ B` as its base.
+    let mut xcda: Vec<u32> = vec![0; 256];
+    let mut queue = vec![(0usize, 0u32)];
+    while let Some((t, x)) = queue.pop() {
+        let block = xcda.len() as u32;
+        xcda.resize(xcda.len() + 256, 0);
+        let (children, value) = &trie[t];
+        let label = if x == 0 { 0 } else { xcda[x as usize] & 0xFF };
+        let leaf = u32::from(value.is_some()) << 8;
+        xcda[x as usize] = ((x ^ block) << 10) | leaf | label;
+        if let Some(offset) = value {
+            xcda[block as usize] = offset | (1 << 31);
+        }
+        for (&b, &child) in children {
+            let idx = block ^ u32::from(b);
+            xcda[idx as usize] = u32::from(b);
+            queue.push((child, idx));
+        }
+    }
+
+    let mut blob = ((xcda.len() * 4) as u32).to_le_bytes().to_vec();
+    for node in xcda {
+        blob.extend_from_slice(&node.to_le_bytes());
+    }
+    blob.extend_from_slice(&replacements);
+    blob
+}
+
+/// A minimal BERT-style WordPiece (WPM) fixture, laid out the way llama.cpp's
+/// converter stores BERT vocabularies: whole words carry the `▁` phantom-space
+/// prefix and `[CLS]` doubles as BOS.
+///
+/// Vocabulary (id: piece):
+/// - 0: `[PAD]` 1: `[UNK]` 2: `[CLS]` 3: `[SEP]` 4: `[MASK]`
+/// - 5: `▁hello` 6: `▁world` 7: `▁,` 8: `▁!` 9: `▁wor` 10: `ld`
+///
+/// Expected encodes (add_special = false):
+/// - `"Hello, world!"` -> `[5, 7, 6, 8]`
+#[must_use]
+pub fn wpm_bert_fixture() -> Vec<u8> {
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "bert")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &[
+                "[PAD]",
+                "[UNK]",
+                "[CLS]",
+                "[SEP]",
+                "[MASK]",
+                "\u{2581}hello",
+                "\u{2581}world",
+                "\u{2581},",
+                "\u{2581}!",
+                "\u{2581}wor",
+                "ld",
+            ],
+        )
+        .with_i32_array(
+            "tokenizer.ggml.token_type",
+            &[3, 2, 3, 3, 3, 1, 1, 1, 1, 1, 1],
+        )
+        .with_u32("tokenizer.ggml.padding_token_id", 0)
+        .with_u32("tokenizer.ggml.unknown_token_id", 1)
+        .with_u32("tokenizer.ggml.bos_token_id", 2)
+        .with_u32("tokenizer.ggml.eos_token_id", 3)
+        .with_bool("tokenizer.ggml.add_bos_token", true)
+        .with_bool("tokenizer.ggml.add_eos_token", true)
+        .build()
+}
+
+/// A minimal RWKV "World" fixture. RWKV vocabularies store control bytes in
+/// escaped form (`\n`, `\t`, `\xNN`), which the backend unescapes when it
+/// builds its trie.
+///
+/// Vocabulary (id: stored piece):
+/// - 0: `<s>` 1: `a` 2: `b` 3: `ab` 4: `\n` 5: `\t` 6: `\n\n` 7: `\x41`
+/// - 8: `\xe4` (a lone UTF-8 lead byte, invalid on its own)
+///
+/// Expected encodes (add_special = false):
+/// - `"ab\n\tab\n\n"` -> `[3, 4, 5, 3, 6]`
+/// - `"A"`          -> `[7]`
+#[must_use]
+pub fn rwkv_world_fixture() -> Vec<u8> {
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "rwkv")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &[
+                "<s>", "a", "b", "ab", "\\n", "\\t", "\\n\\n", "\\x41", "\\xe4",
+            ],
+        )
+        .with_u32("tokenizer.ggml.bos_token_id", 0)
+        .with_u32("tokenizer.ggml.eos_token_id", 0)
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+        .with_bool("tokenizer.ggml.add_eos_token", false)
+        .build()
+}
+
+/// A PLaMo-2 fixture with a complete `<0x00>`..`<0xFF>` BYTE-token block
+/// (ids 0–255) followed by a handful of Latin and Japanese pieces.
+///
+/// Normal pieces (id: piece):
+/// - 256: `Hello` 257: ` ` 258: `こんにちは` 259: `世界` 260: `こん` 261: `にちは`
+///
+/// Characters outside the vocabulary (e.g. `!`) fall back to their UTF-8 byte
+/// tokens, so `"Hello!"` encodes to `[256, 0x21]`.
+#[must_use]
+pub fn plamo2_fixture() -> Vec<u8> {
+    let mut tokens: Vec<String> = (0..=255u8).map(|b| format!("<0x{b:02X}>")).collect();
+    let mut types = vec![6i32; 256];
+    let mut scores = vec![0.0f32; 256];
+    for (piece, score) in [
+        ("Hello", -1.0f32),
+        (" ", -2.0),
+        ("こんにちは", -1.0),
+        ("世界", -1.0),
+        ("こん", -3.0),
+        ("にちは", -3.0),
+    ] {
+        tokens.push(piece.to_string());
+        types.push(1);
+        scores.push(score);
+    }
+    let token_refs: Vec<&str> = tokens.iter().map(String::as_str).collect();
+
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "plamo2")
+        .with_string_array("tokenizer.ggml.tokens", &token_refs)
+        .with_f32_array("tokenizer.ggml.scores", &scores)
+        .with_i32_array("tokenizer.ggml.token_type", &types)
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+        .with_bool("tokenizer.ggml.add_eos_token", false)
+        .build()
+}
+
+/// A LLaMA-style SentencePiece (SPM) fixture with a full `<0x00>`..`<0xFF>`
+/// byte-fallback block.
+///
+/// Vocabulary (id: piece):
+/// - 0: `<unk>` 1: `<s>` 2: `</s>`
+/// - 3..=258: `<0x00>`..`<0xFF>` (BYTE tokens; byte `b` has id `3 + b`)
+/// - 259: `▁` 260: `h` 261: `i` 262: `hi` 263: `▁hi`
+///
+/// Expected encodes (add_special = false, `add_space_prefix = true`):
+/// - `"hi\nhi"` -> `[263, 13, 262]` (newline falls back to `<0x0A>`)
+/// - `"é"`      -> `[259, 198, 172]` (`<0xC3><0xA9>`)
+#[must_use]
+pub fn spm_llama_fixture(add_space_prefix: bool) -> Vec<u8> {
+    let mut tokens: Vec<String> = vec!["<unk>".into(), "<s>".into(), "</s>".into()];
+    let mut types = vec![2i32, 3, 3];
+    let mut scores = vec![0.0f32; 3];
+    for b in 0..=255u8 {
+        tokens.push(format!("<0x{b:02X}>"));
+        types.push(6);
+        scores.push(0.0);
+    }
+    for (piece, score) in [
+        ("▁", -5.0f32),
+        ("h", -5.0),
+        ("i", -5.0),
+        ("hi", -1.0),
+        ("▁hi", -0.5),
+    ] {
+        tokens.push(piece.to_string());
+        types.push(1);
+        scores.push(score);
+    }
+    let token_refs: Vec<&str> = tokens.iter().map(String::as_str).collect();
+
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "llama")
+        .with_string_array("tokenizer.ggml.tokens", &token_refs)
+        .with_f32_array("tokenizer.ggml.scores", &scores)
+        .with_i32_array("tokenizer.ggml.token_type", &types)
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .with_u32("tokenizer.ggml.bos_token_id", 1)
+        .with_u32("tokenizer.ggml.eos_token_id", 2)
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+        .with_bool("tokenizer.ggml.add_eos_token", false)
+        .with_bool("tokenizer.ggml.add_space_prefix", add_space_prefix)
+        .build()
+}
@@ -0,0 +1,83 @@
+//! Tests for special tokens registered at runtime.
+
+mod common;
+
+use common::bpe_gpt2_fixture;
+use shimmytok::{EncodeOptions, Error, Tokenizer};
+
+/// The GPT-2 fixture with `abc` (id 7) registered as the sentinel `<tool>`.
+fn with_sentinel() -> Tokenizer {
+    let mut tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    tok.add_special_tokens(&[("<tool>".to_string(), 7)])
+        .unwrap();
+    tok
+}
+
+#[test]
+fn registered_sentinel_is_emitted_as_single_token() {
+    let tok = with_sentinel();
+    let opts = EncodeOptions::with_parse_special(false, true);
+    assert_eq!(
+        tok.encode_with_options("ab<tool>ab", &opts).unwrap(),
+        vec![6, 7, 6]
+    );
+    assert_eq!(
+        tok.encode_with_options("<tool><tool>", &opts).unwrap(),
+        vec![7, 7]
+    );
+
+    // Without parse_special the sentinel text is ordinary input.
+    let plain = EncodeOptions::with_parse_special(false, false);
+    assert_ne!(
+        tok.encode_with_options("ab<tool>ab", &plain).unwrap(),
+        vec![6, 7, 6]
+    );
+}
+
+#[test]
+fn registered_id_is_special_for_decode() {
+    let tok = with_sentinel();
+    assert!(tok.is_special_token(7));
+    assert_eq!(tok.decode(&[6, 7, 6], true).unwrap(), "abab");
+    assert_eq!(tok.decode(&[6, 7, 6], false).unwrap(), "ababcab");
+}
+
+#[test]
+fn clones_taken_earlier_are_unaffected() {
+    let mut tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let before = tok.clone();
+    tok.add_special_tokens(&[("<tool>".to_string(), 7)])
+        .unwrap();
+    assert!(tok.is_special_token(7));
+    assert!(!before.is_special_token(7));
+}
+
+#[test]
+fn out_of_range_ids_are_rejected_atomically() {
+    let mut tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let result = tok.add_special_tokens(&[("<ok>".to_string(), 7), ("<bad>".to_string(), 10)]);
+    assert!(matches!(result, Err(Error::InvalidToken(_))));
+    assert!(!tok.is_special_token(7));
+
+    let result = tok.add_special_tokens(&[(String::new(), 7)]);
+    assert!(matches!(result, Err(Error::InvalidToken(_))));
+}
+
+#[test]
+fn re_registering_text_keeps_the_last_id() {
+    let mut tok = with_sentinel();
+    tok.add_special_tokens(&[("<tool>".to_string(), 8)])
+        .unwrap();
+    let opts = EncodeOptions::with_parse_special(false, true);
+    assert_eq!(tok.encode_with_options("<tool>", &opts).unwrap(), vec![8]);
+    assert!(tok.is_special_token(8));
+    // The replaced ID is no longer special.
+    assert!(!tok.is_special_token(7));
+    assert!(!tok.special_token_ids().contains(&7));
+
+    // Within one call, too, the last pair wins.
+    tok.add_special_tokens(&[("<x>".to_string(), 7), ("<x>".to_string(), 9)])
+        .unwrap();
+    assert_eq!(tok.encode_with_options("<x>", &opts).unwrap(), vec![9]);
+    assert!(!tok.is_special_token(7));
+}
@@ -0,0 +1,156 @@
+//! Model-type dispatch for the non-SPM/BPE backends, and `Tokenizer::algorithm`.
+//!
+//! Every tokenizer here is built from an in-memory GGUF fixture (see
+//! `tests/common`), so these run on a clean checkout with no model files.
+
+mod common;
+
+use common::{plamo2_fixture, rwkv_world_fixture, ugm_t5_fixture, wpm_bert_fixture};
+use shimmytok::Tokenizer;
+
+// ── UGM (Unigram / T5) ──────────────────────────────────────────────────────
+
+#[test]
+fn ugm_t5_model_type_dispatches() {
+    let tok = Tokenizer::from_bytes(&ugm_t5_fixture()).expect("t5 model type should load");
+    assert_eq!(tok.model_type(), "t5");
+    assert_eq!(tok.encode("Hello world", false).unwrap(), vec![4, 5]);
+}
+
+#[test]
+fn ugm_round_trip_ascii_and_cjk() {
+    let tok = Tokenizer::from_bytes(&ugm_t5_fixture()).unwrap();
+
+    assert_eq!(tok.encode("你好世界", false).unwrap(), vec![8, 11]);
+
+    for text in ["Hello world", "你好世界", "Hello 你好"] {
+        let ids = tok.encode(text, false).unwrap();
+        let decoded = tok.decode(&ids, false).unwrap();
+        // The space prefix added on encode decodes to a single leading space.
+        assert_eq!(
+            decoded.strip_prefix(' '),
+            Some(text),
+            "round-trip mismatch for {text:?}"
+        );
+    }
+}
+
+// ── WPM (WordPiece / BERT) ──────────────────────────────────────────────────
+
+#[test]
+fn wpm_bert_isolates_punctuation() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).expect("bert model type should load");
+    // llama.cpp WPM: lowercase, split on whitespace, punctuation becomes its own
+    // phantom-space-prefixed word.
+    assert_eq!(
+        tok.encode("Hello, world!", false).unwrap(),
+        vec![5, 7, 6, 8]
+    );
+}
+
+#[test]
+fn wpm_bert_strips_accents_and_unicode_punctuation() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    // BERT uncased: "Héllo, wörld!" -> hello , world !
+    assert_eq!(
+        tok.encode("Héllo, wörld!", false).unwrap(),
+        vec![5, 7, 6, 8]
+    );
+    // Decomposed input strips the same way; "¡" is punctuation, so it is
+    // split off (and unknown) rather than glued onto "hello".
+    assert_eq!(
+        tok.encode("He\u{301}llo ¡world", false).unwrap(),
+        vec![5, 1, 6]
+    );
+}
+
+#[test]
+fn wpm_greedy_longest_match_within_word() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    // "▁world" exists as a whole token, so it wins over "▁wor" + "ld".
+    assert_eq!(tok.encode("world", false).unwrap(), vec![6]);
+    // An unmatched word collapses to a single [UNK].
+    assert_eq!(tok.encode("xyz", false).unwrap(), vec![1]);
+}
+
+#[test]
+fn wpm_special_tokens_and_decode_through_tokenizer() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+
+    let ids = tok.encode("Hello, world!", true).unwrap();
+    assert_eq!(ids, vec![2, 5, 7, 6, 8, 3]);
+    assert!(tok.is_special_token(2));
+    assert!(tok.is_special_token(3));
+    assert!(!tok.is_special_token(5));
+
+    assert_eq!(tok.decode(&ids, true).unwrap(), " hello , world !");
+    assert_eq!(
+        tok.decode(&ids, false).unwrap(),
+        "[CLS] hello , world ![SEP]"
+    );
+}
+
+// ── RWKV (trie-based greedy) ────────────────────────────────────────────────
+
+#[test]
+fn rwkv_escaped_vocab_matches_newline_and_tab() {
+    let tok = Tokenizer::from_bytes(&rwkv_world_fixture()).expect("rwkv model type should load");
+    // Greedy longest match: "ab" over "a"+"b", "\n\n" over two "\n".
+    assert_eq!(
+        tok.encode("ab\n\tab\n\n", false).unwrap(),
+        vec![3, 4, 5, 3, 6]
+    );
+    // `\x41` is unescaped to the byte 'A' when the trie is built.
+    assert_eq!(tok.encode("A", false).unwrap(), vec![7]);
+}
+
+#[test]
+fn rwkv_decode_unescapes_pieces() {
+    let tok = Tokenizer::from_bytes(&rwkv_world_fixture()).unwrap();
+    let text = "ab\n\tab\n\nA";
+    let ids = tok.encode(text, false).unwrap();
+    assert_eq!(tok.decode(&ids, false).unwrap(), text);
+}
+
+// ── PLaMo-2 (table-driven DP) ───────────────────────────────────────────────
+
+#[test]
+fn plamo2_model_type_dispatches() {
+    let tok = Tokenizer::from_bytes(&plamo2_fixture()).expect("plamo2 model type should load");
+    assert_eq!(tok.encode("こんにちは", false).unwrap(), vec![258]);
+    // `!` is not a piece, so it falls back to its byte token <0x21>.
+    assert_eq!(tok.encode("Hello!", false).unwrap(), vec![256, 0x21]);
+}
+
+#[test]
+fn plamo2_round_trip_latin_and_japanese() {
+    let tok = Tokenizer::from_bytes(&plamo2_fixture()).unwrap();
+    for text in ["Hello こんにちは", "こんにちは世界!", "Hello 世界 ü"] {
+        let ids = tok.encode(text, false).unwrap();
+        assert_eq!(
+            tok.decode(&ids, false).unwrap(),
+            text,
+            "round-trip mismatch for {text:?}"
+        );
+    }
+}
+
+// ── Algorithm introspection ─────────────────────────────────────────────────
+
+#[test]
+fn algorithm_names_the_constructed_backend() {
+    let cases = [
+        (common::bpe_gpt2_fixture(), "gpt2", "bpe"),
+        (common::bpe_starcoder_fixture(), "gpt2", "bpe"),
+        (common::spm_llama_fixture(true), "llama", "spm"),
+        (ugm_t5_fixture(), "t5", "ugm"),
+        (wpm_bert_fixture(), "bert", "wpm"),
+        (rwkv_world_fixture(), "rwkv", "rwkv"),
+        (plamo2_fixture(), "plamo2", "plamo2"),
+    ];
+    for (fixture, model_type, algorithm) in cases {
+        let tok = Tokenizer::from_bytes(&fixture).unwrap();
+        assert_eq!(tok.model_type(), model_type);
+        assert_eq!(tok.algorithm(), algorithm, "model type {model_type:?}");
+    }
+}
@@ -0,0 +1,70 @@
+//! Tests for BPE byte fallback (`Tokenizer::byte_fallback`).
+
+mod common;
+
+use common::{bpe_full_bytes_fixture, GgufBuilder};
+use shimmytok::Tokenizer;
+
+/// A BPE vocabulary with the merge `a b` but no `ab` piece, so merging
+/// leaves a symbol that is not a vocabulary token. With `all_bytes`, the
+/// 256 GPT-2 byte pieces follow `<unk> <s> </s>`; without, only `a` (3)
+/// and `b` (4) do.
+fn bpe_fixture(all_bytes: bool) -> Tokenizer {
+    let bytes = if all_bytes {
+        bpe_full_bytes_fixture(&[], &["a b"])
+    } else {
+        GgufBuilder::new()
+            .with_string("tokenizer.ggml.model", "gpt2")
+            .with_string("tokenizer.ggml.pre", "gpt-2")
+            .with_string_array("tokenizer.ggml.tokens", &["<unk>", "<s>", "</s>", "a", "b"])
+            .with_string_array("tokenizer.ggml.merges", &["a b"])
+            .with_u32("tokenizer.ggml.unknown_token_id", 0)
+            .build()
+    };
+    Tokenizer::from_bytes(&bytes).unwrap()
+}
+
+fn byte_id(tok: &Tokenizer, c: char) -> u32 {
+    tok.get_token(&c.to_string()).unwrap()
+}
+
+#[test]
+fn full_byte_vocabulary_falls_back_to_bytes() {
+    let mut tok = bpe_fixture(true);
+    assert!(tok.vocab().has_byte_tokens());
+    assert!(tok.byte_fallback());
+    let (a, b) = (byte_id(&tok, 'a'), byte_id(&tok, 'b'));
+    assert_eq!(tok.encode("ab", false).unwrap(), vec![a, b]);
+    // Every byte has a piece, so no input reaches UNK.
+    assert_eq!(tok.unk_count(&tok.encode("x\u{e9}!", false).unwrap()), 0);
+
+    tok.set_byte_fallback(false).unwrap();
+    assert!(!tok.byte_fallback());
+    assert_eq!(tok.encode("ab", false).unwrap(), vec![0]);
+}
+
+#[test]
+fn vocabulary_without_byte_tokens_emits_unk() {
+    let mut tok = bpe_fixture(false);
+    assert!(!tok.vocab().has_byte_tokens());
+    assert!(!tok.byte_fallback());
+    assert_eq!(tok.encode("ab", false).unwrap(), vec![0]);
+    assert_eq!(tok.encode("x", false).unwrap(), vec![0]);
+
+    tok.set_byte_fallback(true).unwrap();
+    assert_eq!(tok.encode("ab", false).unwrap(), vec![3, 4]);
+    // Bytes without a piece still become UNK.
+    assert_eq!(tok.encode("x", false).unwrap(), vec![0]);
+}
+
+#[test]
+fn override_is_per_handle() {
+    let tok = bpe_fixture(true);
+    let mut no_fallback = tok.clone();
+    no_fallback.set_byte_fallback(false).unwrap();
+    assert!(tok.byte_fallback());
+    assert_ne!(
+        tok.encode("ab", false).unwrap(),
+        no_fallback.encode("ab", false).unwrap()
+    );
+}
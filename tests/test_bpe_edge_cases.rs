@@ -21,7 +21,11 @@ fn test_empty_string() {
     let tok = Tokenizer::from_gguf_file(&model_path).expect("Failed to load tokenizer");
 
     let tokens = tok.encode("", false).expect("Failed to encode");
-    assert_eq!(tokens, vec![], "Empty string should produce empty tokens");
+    assert_eq!(
+        tokens,
+        Vec::<u32>::new(),
+        "Empty string should produce empty tokens"
+    );
 
     let decoded = tok.decode(&[], false).expect("Failed to decode");
     assert_eq!(decoded, "", "Empty tokens should decode to empty string");
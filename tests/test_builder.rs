@@ -0,0 +1,199 @@
+//! Tests for `TokenizerBuilder` metadata overrides, `set_pre_type` and `from_vocabulary`.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, GgufBuilder};
+use shimmytok::{Error, Tokenizer, TokenizerBuilder};
+
+/// The GPT-2 BPE fixture's vocabulary, mislabeled as a SentencePiece model
+/// and missing its pre-tokenizer tag.
+fn mislabeled_bpe_fixture() -> Vec<u8> {
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "llama")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["<unk>", "<s>", "</s>", "a", "b", "c", "ab", "abc", "1", "2"],
+        )
+        .with_string_array("tokenizer.ggml.merges", &["a b", "ab c"])
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .with_u32("tokenizer.ggml.bos_token_id", 1)
+        .with_u32("tokenizer.ggml.eos_token_id", 2)
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+        .with_bool("tokenizer.ggml.add_eos_token", false)
+        .build()
+}
+
+#[test]
+fn builder_forces_bpe_despite_wrong_model_tag() {
+    let bytes = mislabeled_bpe_fixture();
+
+    let zero_config = Tokenizer::from_bytes(&bytes).unwrap();
+    assert_eq!(zero_config.model_type(), "llama");
+    assert_ne!(zero_config.encode("abc", false).unwrap(), vec![7]);
+
+    let tok = TokenizerBuilder::from_bytes(&bytes)
+        .unwrap()
+        .model_type("gpt2")
+        .pre_type("gpt-2")
+        .build()
+        .unwrap();
+    assert_eq!(tok.model_type(), "gpt2");
+    assert_eq!(tok.pre_type(), Some("gpt-2"));
+
+    let reference = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    for text in ["abc", "acb", "ab", "abc 12"] {
+        assert_eq!(
+            tok.encode(text, false).unwrap(),
+            reference.encode(text, false).unwrap(),
+            "mismatch for {text:?}"
+        );
+    }
+}
+
+#[test]
+fn builder_overrides_special_tokens() {
+    let tok = TokenizerBuilder::from_bytes(&bpe_gpt2_fixture())
+        .unwrap()
+        .bos_token_id(2)
+        .eos_token_id(1)
+        .add_bos_token(true)
+        .add_eos_token(true)
+        .build()
+        .unwrap();
+    assert_eq!(tok.bos_token(), 2);
+    assert_eq!(tok.eos_token(), 1);
+    assert_eq!(tok.encode("abc", true).unwrap(), vec![2, 7, 1]);
+}
+
+#[test]
+fn builder_without_overrides_matches_zero_config() {
+    let bytes = bpe_gpt2_fixture();
+    let built = TokenizerBuilder::from_bytes(&bytes)
+        .unwrap()
+        .build()
+        .unwrap();
+    let plain = Tokenizer::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        built.encode("abc acb", true).unwrap(),
+        plain.encode("abc acb", true).unwrap()
+    );
+}
+
+#[test]
+fn builder_rejects_out_of_range_special_ids() {
+    let result = TokenizerBuilder::from_bytes(&bpe_gpt2_fixture())
+        .unwrap()
+        .bos_token_id(10)
+        .build();
+    assert!(matches!(result, Err(Error::VocabularyError(_))));
+
+    // IDs read from the metadata are checked too, not only the overrides.
+    for (key, name) in [
+        ("tokenizer.ggml.unknown_token_id", "UNK"),
+        ("tokenizer.ggml.padding_token_id", "PAD"),
+        ("tokenizer.ggml.seperator_token_id", "SEP"),
+        ("tokenizer.ggml.cls_token_id", "CLS"),
+        ("tokenizer.ggml.mask_token_id", "MASK"),
+        ("tokenizer.ggml.fim_pre_token_id", "FIM prefix"),
+    ] {
+        let bytes = GgufBuilder::new()
+            .with_string("tokenizer.ggml.model", "gpt2")
+            .with_string_array("tokenizer.ggml.tokens", &["<unk>", "a", "b", "ab"])
+            .with_string_array("tokenizer.ggml.merges", &["a b"])
+            .with_u32(key, 4)
+            .build();
+        match TokenizerBuilder::from_bytes(&bytes).unwrap().build() {
+            Err(Error::VocabularyError(msg)) => assert_eq!(
+                msg,
+                format!("{name} token ID 4 out of range for vocabulary of 4 tokens")
+            ),
+            other => panic!("{key}: expected VocabularyError, got {:?}", other.err()),
+        }
+    }
+
+    // Added special tokens are checked when they are registered.
+    let mut tok = TokenizerBuilder::from_bytes(&bpe_gpt2_fixture())
+        .unwrap()
+        .build()
+        .unwrap();
+    let result = tok.add_special_tokens(&[("<extra>".to_string(), 10)]);
+    assert!(matches!(result, Err(Error::InvalidToken(_))));
+}
+
+#[test]
+fn builder_reports_unknown_model_override() {
+    let result = TokenizerBuilder::from_bytes(&bpe_gpt2_fixture())
+        .unwrap()
+        .model_type("not-a-model")
+        .build();
+    assert!(matches!(result, Err(Error::UnsupportedModel(m)) if m == "not-a-model"));
+}
+
+/// A GPT-2 vocabulary with digit merges `1 2` -> `12` and `12 12` -> `1212`.
+fn digit_merges_fixture() -> Vec<u8> {
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "gpt-2")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["<unk>", "<s>", "</s>", "1", "2", "12", "1212"],
+        )
+        .with_string_array("tokenizer.ggml.merges", &["1 2", "12 12"])
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .with_u32("tokenizer.ggml.bos_token_id", 1)
+        .with_u32("tokenizer.ggml.eos_token_id", 2)
+        .build()
+}
+
+#[test]
+fn set_pre_type_reselects_split_patterns() {
+    let mut tok = Tokenizer::from_bytes(&digit_merges_fixture()).unwrap();
+    let before = tok.clone();
+
+    // GPT-2 keeps a digit run together: "1212" is one fragment.
+    assert_eq!(tok.encode("1212", false).unwrap(), vec![6]);
+
+    // Llama-3 splits digits into groups of three: "121" + "2".
+    tok.set_pre_type("llama3").unwrap();
+    assert_eq!(tok.pre_type(), Some("llama3"));
+    assert_eq!(tok.encode("1212", false).unwrap(), vec![5, 3, 4]);
+
+    // Earlier clones keep the original patterns.
+    assert_eq!(before.pre_type(), Some("gpt-2"));
+    assert_eq!(before.encode("1212", false).unwrap(), vec![6]);
+}
+
+#[test]
+fn from_vocabulary_reuses_the_loaded_vocabulary() {
+    for fixture in [
+        common::bpe_gpt2_fixture(),
+        common::spm_llama_fixture(true),
+        common::ugm_t5_fixture(),
+        common::wpm_bert_fixture(),
+        common::rwkv_world_fixture(),
+        common::plamo2_fixture(),
+    ] {
+        let tok = Tokenizer::from_bytes(&fixture).unwrap();
+        let copy = Tokenizer::from_vocabulary(tok.vocab().clone()).unwrap();
+        assert_eq!(copy.algorithm(), tok.algorithm());
+        for text in ["ab\n\tab", "Hello world", "こんにちは 世界"] {
+            assert_eq!(
+                copy.encode(text, true).unwrap(),
+                tok.encode(text, true).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn from_vocabulary_follows_set_pre_type() {
+    let mut tok = Tokenizer::from_bytes(&digit_merges_fixture()).unwrap();
+    let original = Tokenizer::from_vocabulary(tok.vocab().clone()).unwrap();
+    tok.set_pre_type("llama3").unwrap();
+
+    // The cached backend is dropped with the old pre-tokenizer, so a new
+    // tokenizer over the overridden vocabulary uses the new patterns.
+    let copy = Tokenizer::from_vocabulary(tok.vocab().clone()).unwrap();
+    assert_eq!(copy.encode("1212", false).unwrap(), vec![5, 3, 4]);
+    assert_eq!(original.encode("1212", false).unwrap(), vec![6]);
+}
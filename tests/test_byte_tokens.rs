@@ -0,0 +1,63 @@
+//! Tests for `Tokenizer::is_byte_token` and `Tokenizer::byte_token_value`.
+
+mod common;
+
+use common::{bpe_full_bytes_fixture, spm_llama_fixture};
+use shimmytok::Tokenizer;
+
+/// The full-byte GPT-2 fixture plus `ab` (259) and a literal `<0x41>` text
+/// piece (260).
+fn bpe_fixture() -> Tokenizer {
+    Tokenizer::from_bytes(&bpe_full_bytes_fixture(&["ab", "<0x41>"], &["a b"])).unwrap()
+}
+
+#[test]
+fn spm_byte_tokens_map_to_their_byte() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+
+    // `<0xNN>` sits at id 3 + NN.
+    for byte in [0x00u8, 0x0A, 0x41, 0xFF] {
+        let id = 3 + u32::from(byte);
+        assert!(tok.is_byte_token(id));
+        assert_eq!(tok.byte_token_value(id), Some(byte));
+    }
+
+    let hi = tok.get_token("hi").unwrap();
+    assert!(!tok.is_byte_token(hi));
+    assert_eq!(tok.byte_token_value(hi), None);
+
+    // Single-character normal pieces are not byte tokens in SPM.
+    let h = tok.get_token("h").unwrap();
+    assert_eq!(tok.byte_token_value(h), None);
+}
+
+#[test]
+fn specials_and_out_of_range_ids_have_no_value() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    for id in [0, 1, 2, tok.vocab_size() as u32] {
+        assert!(!tok.is_byte_token(id));
+        assert_eq!(tok.byte_token_value(id), None);
+    }
+}
+
+#[test]
+fn byte_level_bpe_pieces_map_through_byte_encoder() {
+    let tok = bpe_fixture();
+
+    let newline = tok.get_token("Ċ").unwrap();
+    assert!(!tok.is_byte_token(newline));
+    assert_eq!(tok.byte_token_value(newline), Some(b'\n'));
+    assert_eq!(
+        tok.byte_token_value(tok.get_token("a").unwrap()),
+        Some(b'a')
+    );
+    assert_eq!(
+        tok.byte_token_value(tok.get_token("Ġ").unwrap()),
+        Some(b' ')
+    );
+
+    assert_eq!(tok.byte_token_value(tok.get_token("ab").unwrap()), None);
+    // Outside SPM-style byte tokens, `<0xNN>` is ordinary text.
+    assert_eq!(tok.byte_token_value(tok.get_token("<0x41>").unwrap()), None);
+    assert_eq!(tok.byte_token_value(1), None);
+}
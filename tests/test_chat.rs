@@ -0,0 +1,113 @@
+//! Tests for chat template rendering (`chat` feature).
+
+#![cfg(feature = "chat")]
+
+mod common;
+
+use common::{bpe_gpt2_fixture, GgufBuilder};
+use shimmytok::{ChatMessage, Error, Tokenizer};
+
+/// The Llama-3 Instruct template as shipped in `tokenizer.chat_template`.
+const LLAMA3_TEMPLATE: &str = "{% set loop_messages = messages %}{% for message in loop_messages %}{% set content = '<|start_header_id|>' + message['role'] + '<|end_header_id|>\n\n'+ message['content'] | trim + '<|eot_id|>' %}{% if loop.index0 == 0 %}{% set content = bos_token + content %}{% endif %}{{ content }}{% endfor %}{% if add_generation_prompt %}{{ '<|start_header_id|>assistant<|end_header_id|>\n\n' }}{% endif %}";
+
+/// Llama-3 style byte-level vocabulary with just enough pieces for the
+/// conversations below.
+fn llama3_chat(template: &str) -> Vec<u8> {
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "llama-bpe")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &[
+                "<|begin_of_text|>",
+                "<|end_of_text|>",
+                "<|start_header_id|>",
+                "<|end_header_id|>",
+                "<|eot_id|>",
+                "user",
+                "assistant",
+                "Hi",
+                "ĊĊ",
+                "system",
+                "Be",
+                "Ġbrief",
+            ],
+        )
+        .with_i32_array(
+            "tokenizer.ggml.token_type",
+            &[3, 3, 3, 3, 3, 1, 1, 1, 1, 1, 1, 1],
+        )
+        .with_u32("tokenizer.ggml.bos_token_id", 0)
+        .with_u32("tokenizer.ggml.eos_token_id", 1)
+        .with_bool("tokenizer.ggml.add_bos_token", true)
+        .with_string("tokenizer.chat_template", template)
+        .build()
+}
+
+fn conversation() -> [ChatMessage; 2] {
+    [
+        ChatMessage::new("system", "Be brief"),
+        ChatMessage::new("user", " Hi\n"),
+    ]
+}
+
+#[test]
+fn llama3_layout() {
+    let tok = Tokenizer::from_bytes(&llama3_chat(LLAMA3_TEMPLATE)).unwrap();
+    let tokens = tok.encode_chat(&conversation(), true).unwrap();
+    assert_eq!(
+        tokens,
+        vec![
+            0, // BOS, once: the template's own bos_token text is removed
+            2, 9, 3, 8, 10, 11, 4, // system header, "Be brief", eot
+            2, 5, 3, 8, 7, 4, // user header, trimmed "Hi", eot
+            2, 6, 3, 8, // assistant header
+        ]
+    );
+}
+
+#[test]
+fn generation_prompt_is_optional() {
+    let tok = Tokenizer::from_bytes(&llama3_chat(LLAMA3_TEMPLATE)).unwrap();
+    let prompt = tok.apply_chat_template(&conversation(), false).unwrap();
+    assert_eq!(
+        prompt,
+        "<|start_header_id|>system<|end_header_id|>\n\nBe brief<|eot_id|>\
+         <|start_header_id|>user<|end_header_id|>\n\nHi<|eot_id|>"
+    );
+}
+
+#[test]
+fn python_string_methods_and_raise_exception() {
+    let template = "{% for m in messages %}{% if m.role not in ['user', 'system'] %}\
+                    {{ raise_exception('bad role: ' + m.role) }}{% endif %}\
+                    {{ m.content.strip() }}{% endfor %}";
+    let tok = Tokenizer::from_bytes(&llama3_chat(template)).unwrap();
+    assert_eq!(
+        tok.apply_chat_template(&conversation(), false).unwrap(),
+        "Be briefHi"
+    );
+
+    let err = tok
+        .apply_chat_template(&[ChatMessage::new("tool", "x")], false)
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::TokenizationFailed(msg) if msg.contains("bad role: tool")),
+        "unexpected error: {err}"
+    );
+}
+
+#[test]
+fn missing_or_broken_template_errors() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert!(matches!(
+        tok.encode_chat(&conversation(), true),
+        Err(Error::InvalidMetadata(msg)) if msg.contains("no chat template")
+    ));
+
+    let tok = Tokenizer::from_bytes(&llama3_chat("{% for %}")).unwrap();
+    assert!(matches!(
+        tok.encode_chat(&conversation(), true),
+        Err(Error::InvalidMetadata(msg)) if msg.contains("Invalid chat template")
+    ));
+}
@@ -1,5 +1,12 @@
 //! Tests for clean_spaces functionality (llama.cpp parity)
 
+mod common;
+
+use common::GgufBuilder;
+use shimmytok::{
+    clean_spaces, clean_spaces_with, DecodeOptions, Tokenizer, CLEAN_SPACES_CONTRACTIONS,
+};
+
 #[test]
 fn test_clean_spaces_punctuation() {
     // Test: space before punctuation is removed
@@ -8,9 +15,6 @@ fn test_clean_spaces_punctuation() {
     // " ." → "."
     // " ," → ","
 
-    // This tests the apply_clean_spaces function behavior
-    // We test through decode since that's where it's wired
-
     // Direct string transformation tests:
     let cases = vec![
         ("Hello ?", "Hello?"),
@@ -21,7 +25,7 @@ fn test_clean_spaces_punctuation() {
     ];
 
     for (input, expected) in cases {
-        let result = clean_spaces_helper(input);
+        let result = clean_spaces(input);
         assert_eq!(result, expected, "Failed for input: {:?}", input);
     }
 }
@@ -42,7 +46,7 @@ fn test_clean_spaces_apostrophe_contractions() {
     ];
 
     for (input, expected) in cases {
-        let result = clean_spaces_helper(input);
+        let result = clean_spaces(input);
         assert_eq!(result, expected, "Failed for input: {:?}", input);
     }
 }
@@ -53,7 +57,7 @@ fn test_clean_spaces_isolated_apostrophe() {
     let cases = vec![("word ' word", "word'word")];
 
     for (input, expected) in cases {
-        let result = clean_spaces_helper(input);
+        let result = clean_spaces(input);
         assert_eq!(result, expected, "Failed for input: {:?}", input);
     }
 }
@@ -67,61 +71,64 @@ fn test_clean_spaces_combined() {
     ];
 
     for (input, expected) in cases {
-        let result = clean_spaces_helper(input);
+        let result = clean_spaces(input);
         assert_eq!(result, expected, "Failed for input: {:?}", input);
     }
 }
 
-// Helper that mimics the apply_clean_spaces function
-// This is a copy to test in isolation without needing a tokenizer
-fn clean_spaces_helper(text: &str) -> String {
-    let mut chars: Vec<char> = text.chars().collect();
-    if chars.is_empty() {
-        return String::new();
-    }
+#[test]
+fn test_clean_spaces_keeps_t_d_ll_spaced_like_llama_cpp() {
+    // llama.cpp's detokenize has the lines joining these commented out.
+    let cases = vec![
+        ("don 't", "don 't"),
+        ("I 'd go", "I 'd go"),
+        ("we 'll see", "we 'll see"),
+    ];
 
-    // Pass 1: Remove space before punctuation ?!.,
-    let mut i = 1;
-    while i < chars.len() {
-        if chars[i - 1] == ' ' && matches!(chars[i], '?' | '!' | '.' | ',') {
-            chars.remove(i - 1);
-        } else {
-            i += 1;
-        }
+    for (input, expected) in cases {
+        let result = clean_spaces(input);
+        assert_eq!(result, expected, "Failed for input: {:?}", input);
     }
+}
 
-    // Pass 2: Strip single apostrophe between spaces: " ' " → "'"
-    let mut i = 1;
-    while i + 1 < chars.len() {
-        if chars[i] == '\'' && chars[i - 1] == ' ' && chars.get(i + 1) == Some(&' ') {
-            chars.remove(i - 1);
-            chars.remove(i);
-        } else {
-            i += 1;
-        }
-    }
+#[test]
+fn test_clean_spaces_with_extra_contractions() {
+    let mut contractions = CLEAN_SPACES_CONTRACTIONS.to_vec();
+    contractions.extend(["t", "d", "ll"]);
+
+    let cases = vec![
+        ("don 't", "don't"),
+        ("I 'd go", "I'd go"),
+        ("we 'll see", "we'll see"),
+        ("You 're here", "You're here"),
+    ];
 
-    // Pass 3: Apostrophe contractions
-    let mut i = 1;
-    while i + 1 < chars.len() {
-        if chars[i - 1] == ' ' && chars[i] == '\'' {
-            let next = chars.get(i + 1);
-            let next2 = chars.get(i + 2);
-
-            let should_remove_space = matches!(
-                (next, next2),
-                (Some('s'), _) | (Some('m'), _) | (Some('v'), Some('e')) | (Some('r'), Some('e'))
-            );
-
-            if should_remove_space {
-                chars.remove(i - 1);
-            } else {
-                i += 1;
-            }
-        } else {
-            i += 1;
-        }
+    for (input, expected) in cases {
+        let result = clean_spaces_with(input, &contractions);
+        assert_eq!(result, expected, "Failed for input: {:?}", input);
     }
 
-    chars.into_iter().collect()
+    // An empty set leaves every contraction alone.
+    assert_eq!(clean_spaces_with("I 'm", &[]), "I 'm");
+}
+
+#[test]
+fn test_decode_option_forces_clean_spaces() {
+    // Byte-level BPE without `tokenizer.ggml.clean_spaces`; `Ġ` is the
+    // GPT-2 byte encoding of a space.
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "gpt-2")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["<unk>", "<s>", "</s>", "a", "\u{120}", ",", "."],
+        )
+        .with_string_array("tokenizer.ggml.merges", &[])
+        .build();
+    let tok = Tokenizer::from_bytes(&bytes).unwrap();
+    let ids = tok.encode("a , a .", false).unwrap();
+    assert_eq!(tok.decode(&ids, false).unwrap(), "a , a .");
+
+    let opts = DecodeOptions::with_skip_special(false).with_clean_spaces(true);
+    assert_eq!(tok.decode_with_options(&ids, &opts).unwrap(), "a, a.");
 }
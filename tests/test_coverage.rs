@@ -65,11 +65,7 @@ fn test_decode_with_lstrip() {
     let tokens = tokenizer.encode("  hello world", false).unwrap();
 
     // Decode with lstrip to exercise that code path
-    let options = DecodeOptions {
-        skip_special_tokens: false,
-        include_special_text: true,
-        lstrip: true,
-    };
+    let options = DecodeOptions::new(false, true, true);
     let decoded = tokenizer.decode_with_options(&tokens, &options).unwrap();
     // lstrip should remove leading whitespace
     assert!(
@@ -91,11 +87,7 @@ fn test_decode_without_special_text() {
     let tokens = tokenizer.encode("hello", true).unwrap();
 
     // Decode excluding special text
-    let options = DecodeOptions {
-        skip_special_tokens: false,
-        include_special_text: false,
-        lstrip: false,
-    };
+    let options = DecodeOptions::new(false, false, false);
     let decoded = tokenizer.decode_with_options(&tokens, &options).unwrap();
     // Special tokens should not appear as text
     assert!(!decoded.contains("<|begin_of_text|>"));
@@ -0,0 +1,57 @@
+//! Tests for the `Debug` summaries of `Tokenizer` and `Vocabulary`.
+
+mod common;
+
+use common::{spm_llama_fixture, GgufBuilder};
+use shimmytok::Tokenizer;
+
+/// A 50 000-token BPE vocabulary, `t0` .. `t49999`.
+fn large_bpe() -> Tokenizer {
+    let tokens: Vec<String> = (0..50_000).map(|i| format!("t{i}")).collect();
+    let token_refs: Vec<&str> = tokens.iter().map(String::as_str).collect();
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "qwen2")
+        .with_string_array("tokenizer.ggml.tokens", &token_refs)
+        .with_string_array("tokenizer.ggml.merges", &[])
+        .with_u32("tokenizer.ggml.padding_token_id", 7)
+        .build();
+    Tokenizer::from_bytes(&bytes).unwrap()
+}
+
+#[test]
+fn tokenizer_debug_is_a_short_summary() {
+    let tok = large_bpe();
+    let debug = format!("{tok:?}");
+    assert!(debug.contains(r#"model_type: "gpt2""#), "{debug}");
+    assert!(debug.contains(r#"pre_type: Some("qwen2")"#), "{debug}");
+    assert!(debug.contains(r#"algorithm: "bpe""#), "{debug}");
+    assert!(debug.contains("vocab_size: 50000"), "{debug}");
+    assert!(debug.contains("pad_token: Some(7)"), "{debug}");
+    assert!(!debug.contains("t12345"), "{debug}");
+    assert!(debug.len() < 400, "{} bytes: {debug}", debug.len());
+
+    // Pretty-printing through `dbg!` stays short too.
+    assert!(format!("{tok:#?}").lines().count() < 20);
+}
+
+#[test]
+fn vocabulary_debug_is_a_short_summary() {
+    let tok = large_bpe();
+    let debug = format!("{:?}", tok.vocab());
+    assert!(debug.contains(r#"model_type: "gpt2""#), "{debug}");
+    assert!(debug.contains("n_tokens: 50000"), "{debug}");
+    assert!(debug.contains(r#"backend: Some("bpe")"#), "{debug}");
+    assert!(!debug.contains("t12345"), "{debug}");
+    assert!(debug.len() < 400, "{} bytes: {debug}", debug.len());
+}
+
+#[test]
+fn spm_debug_names_the_backend() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let debug = format!("{tok:?}");
+    assert!(debug.contains(r#"model_type: "llama""#), "{debug}");
+    assert!(debug.contains(r#"algorithm: "spm""#), "{debug}");
+    assert!(debug.contains("bos_token: 1"), "{debug}");
+    assert!(format!("{:?}", tok.vocab()).contains("add_space_prefix: true"));
+}
@@ -0,0 +1,108 @@
+//! Raw byte decoding (`Tokenizer::decode_to_bytes`) and encode/decode
+//! round-trip checks (`Tokenizer::roundtrip_report`).
+
+mod common;
+
+use common::{
+    bpe_gpt2_fixture, plamo2_fixture, rwkv_world_fixture, spm_llama_fixture, ugm_t5_fixture,
+    wpm_bert_fixture,
+};
+use shimmytok::{Error, Tokenizer};
+
+// ===== Decode to Bytes =====
+
+#[test]
+fn bytes_match_decode_for_valid_text() {
+    let cases = [
+        (bpe_gpt2_fixture(), "abcab12"),
+        (spm_llama_fixture(true), "hi hi\nhi é"),
+        (spm_llama_fixture(false), "hi hi"),
+        (ugm_t5_fixture(), "Hello world"),
+        (wpm_bert_fixture(), "hello, world!"),
+        (rwkv_world_fixture(), "hello world"),
+    ];
+    for (bytes, text) in cases {
+        let tok = Tokenizer::from_bytes(&bytes).unwrap();
+        let ids = tok.encode(text, true).unwrap();
+        for skip_special in [false, true] {
+            let raw = tok.decode_to_bytes(&ids, skip_special).unwrap();
+            assert_eq!(
+                String::from_utf8(raw).unwrap(),
+                tok.decode(&ids, skip_special).unwrap(),
+                "{} / {text:?}",
+                tok.model_type()
+            );
+        }
+    }
+}
+
+#[test]
+fn partial_utf8_is_returned_verbatim() {
+    // "é" is <0xC3><0xA9>; decode only the first byte token.
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(false)).unwrap();
+    let lead = 3 + 0xC3;
+    assert_eq!(tok.decode_to_bytes(&[lead], false).unwrap(), vec![0xC3]);
+    assert_eq!(tok.decode(&[lead], false).unwrap(), "\u{FFFD}");
+
+    // Concatenating per-token bytes reassembles the character.
+    let ids = tok.encode("é", false).unwrap();
+    let mut joined = Vec::new();
+    for id in &ids {
+        joined.extend(tok.decode_to_bytes(&[*id], false).unwrap());
+    }
+    assert_eq!(joined, "é".as_bytes());
+}
+
+#[test]
+fn special_tokens_are_skipped_on_request() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(false)).unwrap();
+    // <s> hi </s>
+    let ids = [1, 262, 2];
+    assert_eq!(tok.decode_to_bytes(&ids, true).unwrap(), b"hi");
+    assert_eq!(tok.decode_to_bytes(&ids, false).unwrap(), b"<s>hi</s>");
+}
+
+#[test]
+fn invalid_ids_are_rejected() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert!(matches!(
+        tok.decode_to_bytes(&[6, u32::MAX], false),
+        Err(Error::InvalidToken(msg)) if msg.contains(&u32::MAX.to_string())
+    ));
+}
+
+// ===== Round-Trip Report =====
+
+#[test]
+fn ascii_is_stable() {
+    for fixture in [
+        bpe_gpt2_fixture(),
+        spm_llama_fixture(false),
+        plamo2_fixture(),
+    ] {
+        let tok = Tokenizer::from_bytes(&fixture).unwrap();
+        let report = tok.roundtrip_report("abc").unwrap();
+        assert_eq!(report.tokens, report.reencoded);
+        assert!(report.tokens_stable, "{report:?}");
+        assert!(report.text_matches, "{report:?}");
+    }
+}
+
+#[test]
+fn cjk_without_byte_tokens_is_flagged() {
+    // The GPT-2 fixture has no byte-level pieces for CJK, so every byte
+    // falls back to UNK and decodes as `<unk>`.
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let report = tok.roundtrip_report("ab世界").unwrap();
+    assert!(!report.text_matches, "{report:?}");
+    assert!(!report.tokens_stable, "{report:?}");
+    assert_ne!(report.decoded, "ab世界");
+    assert_eq!(report.tokens[0], 6);
+}
+
+#[test]
+fn byte_fallback_round_trips_cjk() {
+    let tok = Tokenizer::from_bytes(&plamo2_fixture()).unwrap();
+    let report = tok.roundtrip_report("こんにちは世界").unwrap();
+    assert!(report.text_matches && report.tokens_stable, "{report:?}");
+}
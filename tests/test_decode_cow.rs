@@ -0,0 +1,72 @@
+//! Tests for `Tokenizer::decode_cow`.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, plamo2_fixture, spm_llama_fixture};
+use shimmytok::Tokenizer;
+use std::borrow::Cow;
+
+#[test]
+fn single_ascii_token_is_borrowed() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert!(matches!(
+        tok.decode_cow(&[7], false).unwrap(),
+        Cow::Borrowed("abc")
+    ));
+    assert!(matches!(
+        tok.decode_cow(&[], false).unwrap(),
+        Cow::Borrowed("")
+    ));
+    assert_eq!(tok.decode_cow(&[7, 8, 3], false).unwrap(), "abc1a");
+}
+
+#[test]
+fn rewritten_pieces_fall_back_to_decode() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(false)).unwrap();
+    let hi = tok.encode("hi", false).unwrap();
+    let space_hi = tok.token_to_id("▁hi").unwrap();
+    let byte_a = tok.token_to_id("<0x41>").unwrap();
+
+    assert!(matches!(
+        tok.decode_cow(&hi, false).unwrap(),
+        Cow::Borrowed("hi")
+    ));
+    assert!(matches!(
+        tok.decode_cow(&[space_hi], false).unwrap(),
+        Cow::Owned(s) if s == " hi"
+    ));
+    assert_eq!(tok.decode_cow(&[byte_a], false).unwrap(), "A");
+    // Special tokens honour `skip_special_tokens` exactly like `decode`.
+    assert_eq!(tok.decode_cow(&[1, byte_a, 2], true).unwrap(), "A");
+    assert_eq!(
+        tok.decode_cow(&[1, byte_a, 2], false).unwrap(),
+        tok.decode(&[1, byte_a, 2], false).unwrap()
+    );
+}
+
+#[test]
+fn matches_decode_on_fixture_tokens() {
+    for fixture in [
+        bpe_gpt2_fixture(),
+        spm_llama_fixture(true),
+        plamo2_fixture(),
+    ] {
+        let tok = Tokenizer::from_bytes(&fixture).unwrap();
+        let n = tok.vocab_size() as u32;
+        // Every single token, every pair among the leading (special and
+        // multi-char) IDs, and a pair pulling in each trailing ID.
+        let singles = (0..n).map(|a| vec![a]);
+        let leading = (0..n.min(12)).flat_map(|a| (0..n.min(12)).map(move |b| vec![a, b]));
+        let trailing = (0..n).map(|a| vec![a, n - 1 - a]);
+        for tokens in singles.chain(leading).chain(trailing) {
+            for skip in [false, true] {
+                assert_eq!(
+                    tok.decode_cow(&tokens, skip).ok().map(Cow::into_owned),
+                    tok.decode(&tokens, skip).ok(),
+                    "tokens {tokens:?}, skip {skip}"
+                );
+            }
+        }
+        assert!(tok.decode_cow(&[n], false).is_err());
+    }
+}
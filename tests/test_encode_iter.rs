@@ -0,0 +1,70 @@
+//! Tests for `Tokenizer::encode_iter`.
+
+mod common;
+
+use common::{
+    bpe_gpt2_fixture, bpe_gpt2_fixture_with_flags, bpe_starcoder_fixture, plamo2_fixture,
+    rwkv_world_fixture, spm_llama_fixture, ugm_t5_fixture, wpm_bert_fixture,
+};
+use shimmytok::{Error, Tokenizer, MAX_OUTPUT_TOKENS};
+
+fn collect(tok: &Tokenizer, text: &str, add_special: bool) -> Result<Vec<u32>, Error> {
+    tok.encode_iter(text, add_special).collect()
+}
+
+#[test]
+fn collect_equals_encode_for_every_backend() {
+    let cases: [(Vec<u8>, &str); 9] = [
+        (bpe_gpt2_fixture(), "abc ab 12 cab"),
+        (bpe_gpt2_fixture_with_flags(true, true), "abc ab 12 cab"),
+        (bpe_starcoder_fixture(), "12ab 1a ab"),
+        (spm_llama_fixture(true), "hi hi\nhi é"),
+        (spm_llama_fixture(false), "hi hi"),
+        (ugm_t5_fixture(), "Hello world"),
+        (wpm_bert_fixture(), "hello, world!"),
+        (rwkv_world_fixture(), "hello\nworld"),
+        (plamo2_fixture(), "hello 世界"),
+    ];
+    for (bytes, text) in cases {
+        let tok = Tokenizer::from_bytes(&bytes).unwrap();
+        for add_special in [false, true] {
+            assert_eq!(
+                collect(&tok, text, add_special).unwrap(),
+                tok.encode(text, add_special).unwrap(),
+                "{} {text:?} add_special={add_special}",
+                tok.algorithm()
+            );
+            assert_eq!(
+                collect(&tok, "", add_special).unwrap(),
+                tok.encode("", add_special).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn taking_a_prefix_matches_encode() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture_with_flags(true, true)).unwrap();
+    let text = "abc ab c ".repeat(1000);
+    let full = tok.encode(&text, true).unwrap();
+    let head: Vec<u32> = tok
+        .encode_iter(&text, true)
+        .take(5)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(head, full[..5]);
+}
+
+#[test]
+fn errors_are_yielded_once() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let text = "1".repeat(MAX_OUTPUT_TOKENS + 1);
+    assert!(tok.encode(&text, false).is_err());
+
+    let mut iter = tok.encode_iter(&text, false);
+    assert!(matches!(
+        iter.next(),
+        Some(Err(Error::TokenizationFailed(msg))) if msg.contains("max tokens")
+    ));
+    assert!(iter.next().is_none());
+}
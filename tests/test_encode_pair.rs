@@ -0,0 +1,105 @@
+//! Tests for `Tokenizer::encode_pair` on non-BERT models. The WPM layout
+//! (`[CLS] A [SEP] B [SEP]`) is covered in `test_wpm_special.rs`.
+
+mod common;
+
+use common::{
+    bpe_full_bytes_builder, bpe_gpt2_fixture, bpe_gpt2_fixture_with_flags, spm_llama_fixture,
+    ugm_t5_fixture, wpm_bert_fixture,
+};
+use shimmytok::{Tokenizer, TokenizerBuilder};
+
+/// Checks that `segments` is parallel to `tokens`, starts with zeros, ends
+/// with ones and switches exactly once.
+fn assert_segments_well_formed(tokens: &[u32], segments: &[u8]) {
+    assert_eq!(tokens.len(), segments.len());
+    let switch = segments.iter().position(|&s| s == 1).unwrap();
+    assert!(segments[..switch].iter().all(|&s| s == 0));
+    assert!(segments[switch..].iter().all(|&s| s == 1));
+}
+
+#[test]
+fn bpe_pair_is_separated_by_eos() {
+    // The fixture adds EOS, but not BOS, to single sequences.
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture_with_flags(false, true)).unwrap();
+    let (tokens, segments) = tok.encode_pair("abc", "ab", true).unwrap();
+    assert_eq!(tokens, vec![7, 2, 6, 2]);
+    assert_eq!(segments, vec![0, 0, 1, 1]);
+
+    let (tokens, segments) = tok.encode_pair("abc", "ab", false).unwrap();
+    assert_eq!(tokens, vec![7, 6]);
+    assert_eq!(segments, vec![0, 1]);
+}
+
+#[test]
+fn pair_without_add_eos_has_no_separator() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let (tokens, segments) = tok.encode_pair("abc", "ab", true).unwrap();
+    assert_eq!(tokens, vec![7, 6]);
+    assert_eq!(segments, vec![0, 1]);
+
+    // SPM: BOS still follows its own flag, and no EOS appears.
+    let tok = TokenizerBuilder::from_bytes(&spm_llama_fixture(true))
+        .unwrap()
+        .add_bos_token(true)
+        .build()
+        .unwrap();
+    let (tokens, segments) = tok.encode_pair("hi", "hi hi", true).unwrap();
+    let a = tok.encode("hi", false).unwrap();
+    let b = tok.encode("hi hi", false).unwrap();
+    assert_eq!(tokens, [&[1][..], &a, &b].concat());
+    assert!(!tokens.contains(&tok.eos_token()));
+    assert_eq!(segments.iter().filter(|&&s| s == 0).count(), 1 + a.len());
+    assert_eq!(segments.iter().filter(|&&s| s == 1).count(), b.len());
+}
+
+#[test]
+fn model_sep_token_is_the_separator() {
+    let bytes = bpe_full_bytes_builder(&["<sep>"], &[])
+        .with_u32("tokenizer.ggml.seperator_token_id", 259)
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+        .with_bool("tokenizer.ggml.add_eos_token", true)
+        .build();
+    let tok = Tokenizer::from_bytes(&bytes).unwrap();
+    let (a, b) = (3 + u32::from(b'a'), 3 + u32::from(b'b'));
+    let (tokens, segments) = tok.encode_pair("a", "b", true).unwrap();
+    assert_eq!(tokens, vec![a, 259, b, 259]);
+    assert_eq!(segments, vec![0, 0, 1, 1]);
+}
+
+#[test]
+fn bos_prefix_follows_the_model_flag() {
+    let tok = TokenizerBuilder::from_bytes(&bpe_gpt2_fixture_with_flags(false, true))
+        .unwrap()
+        .add_bos_token(true)
+        .build()
+        .unwrap();
+    let (tokens, segments) = tok.encode_pair("abc", "ab", true).unwrap();
+    assert_eq!(tokens, vec![1, 7, 2, 6, 2]);
+    assert_eq!(segments, vec![0, 0, 0, 1, 1]);
+}
+
+#[test]
+fn segments_align_across_backends() {
+    let cases = [
+        (spm_llama_fixture(true), "hi", "hi hi"),
+        (ugm_t5_fixture(), "Hello world", "你好世界"),
+        (wpm_bert_fixture(), "Hello, world", "world!"),
+    ];
+    for (bytes, a, b) in cases {
+        // EOS separates the pair only for models that add it.
+        let tok = TokenizerBuilder::from_bytes(&bytes)
+            .unwrap()
+            .add_eos_token(true)
+            .build()
+            .unwrap();
+        let (tokens, segments) = tok.encode_pair(a, b, true).unwrap();
+        assert_segments_well_formed(&tokens, &segments);
+
+        // The second segment is exactly `b` plus its separator.
+        let b_tokens = tok.encode(b, false).unwrap();
+        let switch = segments.iter().position(|&s| s == 1).unwrap();
+        assert_eq!(&tokens[switch..tokens.len() - 1], b_tokens.as_slice());
+        assert_eq!(tokens[switch - 1], *tokens.last().unwrap());
+    }
+}
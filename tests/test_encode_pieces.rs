@@ -0,0 +1,85 @@
+//! Tests for `Tokenizer::encode_with_pieces` and `encode_with_display_pieces`.
+
+mod common;
+
+use common::{bpe_full_bytes_builder, spm_llama_fixture};
+use shimmytok::Tokenizer;
+
+/// The full-byte GPT-2 fixture with BOS added and the merge `Ġ b`, where
+/// `Ġ` is the GPT-2 byte encoding of a space.
+fn byte_level_bpe() -> Tokenizer {
+    let bytes = bpe_full_bytes_builder(&["\u{120}b"], &["\u{120} b"])
+        .with_bool("tokenizer.ggml.add_bos_token", true)
+        .build();
+    Tokenizer::from_bytes(&bytes).unwrap()
+}
+
+fn ids(pairs: &[(u32, String)]) -> Vec<u32> {
+    pairs.iter().map(|(id, _)| *id).collect()
+}
+
+fn concat(pairs: &[(u32, String)]) -> String {
+    pairs.iter().map(|(_, piece)| piece.as_str()).collect()
+}
+
+#[test]
+fn bpe_pieces_are_raw_and_display_decodes() {
+    let tok = byte_level_bpe();
+    let text = "a b ab";
+
+    let raw = tok.encode_with_pieces(text, false).unwrap();
+    assert_eq!(ids(&raw), tok.encode(text, false).unwrap());
+    assert_eq!(concat(&raw), "a\u{120}b\u{120}ab");
+    for (id, piece) in &raw {
+        assert_eq!(&tok.token_to_piece(*id).unwrap(), piece);
+    }
+
+    let display = tok.encode_with_display_pieces(text, false).unwrap();
+    assert_eq!(ids(&display), ids(&raw));
+    assert_eq!(concat(&display), text);
+    assert_eq!(display[1].1, " b");
+}
+
+#[test]
+fn special_tokens_keep_their_text() {
+    let tok = byte_level_bpe();
+    let raw = tok.encode_with_pieces("ab", true).unwrap();
+    let display = tok.encode_with_display_pieces("ab", true).unwrap();
+    assert_eq!(raw[0], (1, "<s>".to_string()));
+    assert_eq!(display[0], (1, "<s>".to_string()));
+    assert_eq!(concat(&display[1..]), "ab");
+}
+
+#[test]
+fn split_characters_display_as_replacement() {
+    let tok = byte_level_bpe();
+    let display = tok.encode_with_display_pieces("é", false).unwrap();
+    // `é` is the bytes C3 A9, one byte piece each.
+    assert_eq!(ids(&display), vec![3 + 0xC3, 3 + 0xA9]);
+    assert_eq!(concat(&display), "\u{FFFD}\u{FFFD}");
+    // The raw pieces still decode back to the input as a whole.
+    assert_eq!(tok.decode(&ids(&display), false).unwrap(), "é");
+}
+
+#[test]
+fn spm_pieces_round_trip() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let text = "hi\nhi";
+
+    let raw = tok.encode_with_pieces(text, false).unwrap();
+    assert_eq!(concat(&raw), "▁hi<0x0A>hi");
+
+    let display = tok.encode_with_display_pieces(text, false).unwrap();
+    assert_eq!(concat(&display), format!(" {text}"));
+    assert_eq!(tok.decode(&ids(&display), false).unwrap(), concat(&display));
+}
+
+#[test]
+fn empty_input_has_no_pieces() {
+    let tok = byte_level_bpe();
+    assert!(tok.encode_with_pieces("", false).unwrap().is_empty());
+    assert!(tok
+        .encode_with_display_pieces("", false)
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,101 @@
+//! Tests for `EncodeOptions::force_bos` / `force_eos`.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, bpe_gpt2_fixture_with_flags, wpm_bert_fixture};
+use shimmytok::{EncodeOptions, Tokenizer, TruncationSide};
+
+const BOS: u32 = 1;
+const EOS: u32 = 2;
+/// `ab` in both GPT-2 fixtures.
+const AB: u32 = 6;
+
+/// `bpe_gpt2_fixture` with `add_bos_token` and `add_eos_token` on.
+fn bpe_adds_both() -> Tokenizer {
+    Tokenizer::from_bytes(&bpe_gpt2_fixture_with_flags(true, true)).unwrap()
+}
+
+fn encode(tok: &Tokenizer, options: &EncodeOptions) -> Vec<u32> {
+    tok.encode_with_options("ab", options).unwrap()
+}
+
+#[test]
+fn force_bos_on_model_that_defaults_off() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let on = EncodeOptions::with_special_tokens(true);
+    assert_eq!(encode(&tok, &on), vec![AB]);
+
+    assert_eq!(
+        encode(&tok, &on.clone().with_added_bos(true)),
+        vec![BOS, AB]
+    );
+    assert_eq!(encode(&tok, &on.with_added_eos(true)), vec![AB, EOS]);
+
+    // Forcing applies even with add_special_tokens off.
+    let off = EncodeOptions::with_special_tokens(false)
+        .with_added_bos(true)
+        .with_added_eos(true);
+    assert_eq!(encode(&tok, &off), vec![BOS, AB, EOS]);
+}
+
+#[test]
+fn suppress_eos_on_model_that_defaults_on() {
+    let tok = bpe_adds_both();
+    let on = EncodeOptions::with_special_tokens(true);
+    assert_eq!(encode(&tok, &on), vec![BOS, AB, EOS]);
+
+    assert_eq!(
+        encode(&tok, &on.clone().with_added_eos(false)),
+        vec![BOS, AB]
+    );
+    assert_eq!(
+        encode(&tok, &on.clone().with_added_bos(false)),
+        vec![AB, EOS]
+    );
+    assert_eq!(
+        encode(&tok, &on.with_added_bos(false).with_added_eos(false)),
+        vec![AB]
+    );
+
+    // `Some(true)` on a side the model already adds changes nothing.
+    let forced = EncodeOptions::with_special_tokens(true).with_added_bos(true);
+    assert_eq!(encode(&tok, &forced), vec![BOS, AB, EOS]);
+}
+
+#[test]
+fn none_follows_add_special_tokens_and_model_flags() {
+    let tok = bpe_adds_both();
+    let off = EncodeOptions::with_special_tokens(false);
+    assert_eq!(off.force_bos, None);
+    assert_eq!(off.force_eos, None);
+    assert_eq!(encode(&tok, &off), vec![AB]);
+    assert_eq!(encode(&tok, &off.with_added_eos(true)), vec![AB, EOS]);
+}
+
+#[test]
+fn bert_overrides_control_cls_and_sep() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    let (cls, sep) = (2, 3);
+    let on = EncodeOptions::with_special_tokens(true);
+    let text = tok.encode("hello", false).unwrap();
+
+    let plain = tok.encode_with_options("hello", &on).unwrap();
+    assert_eq!(plain, [vec![cls], text.clone(), vec![sep]].concat());
+    let no_sep = tok
+        .encode_with_options("hello", &on.with_added_eos(false))
+        .unwrap();
+    assert_eq!(no_sep, [vec![cls], text].concat());
+}
+
+#[test]
+fn truncation_keeps_forced_specials() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let options = EncodeOptions::with_special_tokens(false)
+        .with_added_bos(true)
+        .with_added_eos(true)
+        .with_truncation(3, TruncationSide::Right);
+    assert_eq!(
+        tok.encode_with_options("ab ab", &options).unwrap(),
+        vec![BOS, AB, EOS]
+    );
+}
@@ -0,0 +1,266 @@
+//! Model-free GGUF loader tests: metadata value types and stream robustness.
+
+mod common;
+
+use common::{ty, GgufBuilder};
+use shimmytok::{Error, MetadataValue, Tokenizer};
+
+/// Start a tiny GPT-2 vocabulary so the loaded tokenizer is fully usable.
+fn with_tokenizer_keys(builder: GgufBuilder) -> GgufBuilder {
+    builder
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string_array("tokenizer.ggml.tokens", &["<unk>", "a", "b", "ab"])
+        .with_string_array("tokenizer.ggml.merges", &["a b"])
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+}
+
+#[test]
+fn loads_with_u64_metadata_before_tokenizer_keys() {
+    let bytes = with_tokenizer_keys(
+        GgufBuilder::new()
+            .with_u64("general.parameter_count", 7_000_000_000)
+            .with_u32("general.file_type", 15),
+    )
+    .build();
+    let tok = Tokenizer::from_bytes(&bytes).expect("U64 metadata must not abort loading");
+    assert_eq!(tok.encode("ab", false).unwrap(), vec![3]);
+}
+
+#[test]
+fn loads_with_every_scalar_type() {
+    let bytes = with_tokenizer_keys(
+        GgufBuilder::new()
+            .with_raw("test.u8", ty::U8, &[7])
+            .with_raw("test.i8", ty::I8, &(-7i8).to_le_bytes())
+            .with_raw("test.u16", ty::U16, &700u16.to_le_bytes())
+            .with_raw("test.i16", ty::I16, &(-700i16).to_le_bytes())
+            .with_raw("test.i64", ty::I64, &(-7i64).to_le_bytes())
+            .with_raw("test.f64", ty::F64, &0.5f64.to_le_bytes()),
+    )
+    .build();
+    let tok = Tokenizer::from_bytes(&bytes).expect("all scalar types should parse");
+    assert_eq!(tok.vocab_size(), 4);
+}
+
+#[test]
+fn loads_with_every_array_type() {
+    let bytes = with_tokenizer_keys(
+        GgufBuilder::new()
+            .with_raw_array("test.i8s", ty::I8, 2, &[0xFF, 0x01])
+            .with_raw_array("test.u16s", ty::U16, 1, &9u16.to_le_bytes())
+            .with_raw_array("test.i16s", ty::I16, 1, &(-9i16).to_le_bytes())
+            .with_raw_array("test.u32s", ty::U32, 1, &9u32.to_le_bytes())
+            .with_raw_array("test.u64s", ty::U64, 1, &9u64.to_le_bytes())
+            .with_raw_array("test.i64s", ty::I64, 1, &(-9i64).to_le_bytes())
+            .with_raw_array("test.f64s", ty::F64, 1, &1.5f64.to_le_bytes()),
+    )
+    .build();
+    let tok = Tokenizer::from_bytes(&bytes).expect("all array types should parse");
+    assert_eq!(tok.encode("ab", false).unwrap(), vec![3]);
+}
+
+#[test]
+fn skips_mystery_keys_of_known_types_before_tokens() {
+    let junk: Vec<String> = (0..1000).map(|i| format!("tensor.{i}")).collect();
+    let junk: Vec<&str> = junk.iter().map(String::as_str).collect();
+    let bytes = with_tokenizer_keys(
+        GgufBuilder::new()
+            .with_string("mystery.name", "not a tokenizer key")
+            .with_string_array("mystery.names", &junk)
+            .with_raw_array("mystery.f64s", ty::F64, 2, &[0u8; 16])
+            .with_raw("mystery.i16", ty::I16, &3i16.to_le_bytes()),
+    )
+    .build();
+    let tok = Tokenizer::from_bytes(&bytes).expect("mystery keys should be skipped");
+    assert_eq!(tok.encode("ab", false).unwrap(), vec![3]);
+}
+
+#[test]
+fn unknown_value_type_still_errors() {
+    let bytes =
+        with_tokenizer_keys(GgufBuilder::new().with_raw("mystery.future", 99, &[0; 8])).build();
+    match Tokenizer::from_bytes(&bytes) {
+        Err(Error::InvalidMetadata(msg)) => {
+            assert!(msg.contains("Unsupported value type: 99"), "{msg}");
+        }
+        Err(e) => panic!("wrong error: {e:?}"),
+        Ok(_) => panic!("unknown type IDs have no computable length"),
+    }
+}
+
+#[test]
+fn truncated_skipped_value_errors() {
+    let mut bytes = GgufBuilder::new()
+        .with_raw_array("mystery.u64s", ty::U64, 4, &[0u8; 8])
+        .build();
+    bytes.truncate(bytes.len() - 1);
+    // Seekable sources catch the short array before reading it; plain
+    // readers find out when the stream runs dry.
+    assert!(matches!(
+        Tokenizer::from_bytes(&bytes),
+        Err(Error::InvalidMetadata(_))
+    ));
+    assert!(matches!(
+        Tokenizer::from_reader(&bytes[..]),
+        Err(Error::Io(_))
+    ));
+}
+
+#[test]
+fn general_metadata_is_exposed() {
+    let bytes = with_tokenizer_keys(
+        GgufBuilder::new()
+            .with_string("general.architecture", "llama")
+            .with_string("general.name", "Tiny Llama")
+            .with_u32("general.quantization_version", 2)
+            .with_u32("llama.context_length", 4096),
+    )
+    .build();
+    let tok = Tokenizer::from_bytes(&bytes).unwrap();
+    let meta = tok.metadata();
+
+    assert_eq!(
+        meta.get("general.architecture"),
+        Some(&MetadataValue::String("llama".into()))
+    );
+    assert_eq!(
+        meta.get("general.name").and_then(MetadataValue::as_str),
+        Some("Tiny Llama")
+    );
+    assert_eq!(
+        meta.get("general.quantization_version")
+            .and_then(MetadataValue::as_u64),
+        Some(2)
+    );
+    // Only general keys are surfaced; tokenizer and architecture keys are not.
+    assert!(meta.keys().all(|k| k.starts_with("general.")));
+}
+
+/// Hand-encode a GGUF v1 file: counts and string/array lengths are `u32`.
+fn gguf_v1(tokens: &[&str], merges: &[&str]) -> Vec<u8> {
+    fn push_str(buf: &mut Vec<u8>, s: &str) {
+        buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+        buf.extend_from_slice(s.as_bytes());
+    }
+    fn push_str_array(buf: &mut Vec<u8>, key: &str, values: &[&str]) {
+        push_str(buf, key);
+        buf.extend_from_slice(&ty::ARRAY.to_le_bytes());
+        buf.extend_from_slice(&ty::STRING.to_le_bytes());
+        buf.extend_from_slice(&(values.len() as u32).to_le_bytes());
+        for v in values {
+            push_str(buf, v);
+        }
+    }
+
+    let mut buf = Vec::new();
+    buf.extend_from_slice(b"GGUF");
+    buf.extend_from_slice(&1u32.to_le_bytes()); // version
+    buf.extend_from_slice(&0u32.to_le_bytes()); // tensor count
+    buf.extend_from_slice(&5u32.to_le_bytes()); // kv count
+
+    // A skipped key first, so the skip path must also use 32-bit lengths.
+    push_str_array(&mut buf, "llama.unused", &["x", "yz"]);
+    push_str(&mut buf, "tokenizer.ggml.model");
+    buf.extend_from_slice(&ty::STRING.to_le_bytes());
+    push_str(&mut buf, "gpt2");
+    push_str_array(&mut buf, "tokenizer.ggml.tokens", tokens);
+    push_str_array(&mut buf, "tokenizer.ggml.merges", merges);
+    push_str(&mut buf, "tokenizer.ggml.add_bos_token");
+    buf.extend_from_slice(&ty::BOOL.to_le_bytes());
+    buf.push(0);
+    buf
+}
+
+#[test]
+fn loads_gguf_v1() {
+    let bytes = gguf_v1(&["<unk>", "a", "b", "ab"], &["a b"]);
+    let tok = Tokenizer::from_bytes(&bytes).expect("GGUF v1 must load");
+    assert_eq!(tok.vocab_size(), 4);
+    assert_eq!(tok.merge_count(), 1);
+    assert_eq!(tok.encode("abab", false).unwrap(), vec![3, 3]);
+}
+
+#[test]
+fn unsupported_version_error_names_the_version() {
+    let mut bytes = with_tokenizer_keys(GgufBuilder::new()).build();
+    bytes[4..8].copy_from_slice(&4u32.to_le_bytes());
+    assert!(matches!(
+        Tokenizer::from_bytes(&bytes),
+        Err(Error::InvalidMetadata(msg))
+            if msg == "Unsupported GGUF version: 4 (only versions 1-3 are supported)"
+    ));
+}
+
+#[test]
+fn huge_array_len_is_rejected_before_allocating() {
+    for array_type in [ty::U8, ty::I32, ty::STRING] {
+        let bytes = GgufBuilder::new()
+            .with_raw_array("tokenizer.ggml.tokens", array_type, u64::MAX, &[])
+            .build();
+        // Seekable and plain readers both fail cleanly instead of aborting on OOM.
+        for result in [
+            Tokenizer::from_bytes(&bytes),
+            Tokenizer::from_reader(&bytes[..]),
+        ] {
+            match result {
+                Err(Error::InvalidMetadata(msg)) => {
+                    assert!(msg.contains("Array too large"), "{msg}");
+                }
+                other => panic!("expected InvalidMetadata, got {:?}", other.err()),
+            }
+        }
+    }
+}
+
+#[test]
+fn truncated_skipped_array_reports_declared_and_available_bytes() {
+    // An architecture key the loader never keeps: 1000 declared F32 values,
+    // 8 bytes present, and nothing after it.
+    let bytes = with_tokenizer_keys(GgufBuilder::new())
+        .with_raw_array("llama.rope.freqs", ty::F32, 1000, &[0; 8])
+        .build();
+    match Tokenizer::from_bytes(&bytes) {
+        Err(Error::InvalidMetadata(msg)) => {
+            assert_eq!(
+                msg,
+                "Array of 1000 elements needs at least 4000 bytes, but only 8 remain"
+            );
+        }
+        other => panic!("expected InvalidMetadata, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn truncated_string_array_is_rejected_up_front() {
+    // 100 declared strings need at least one 8-byte length prefix each.
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_raw_array("tokenizer.ggml.tokens", ty::STRING, 100, &[0; 16])
+        .build();
+    match Tokenizer::from_bytes(&bytes) {
+        Err(Error::InvalidMetadata(msg)) => {
+            assert!(
+                msg.contains("needs at least 800 bytes, but only 16 remain"),
+                "{msg}"
+            );
+        }
+        other => panic!("expected InvalidMetadata, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn array_len_is_checked_against_remaining_bytes() {
+    let bytes = with_tokenizer_keys(GgufBuilder::new().with_raw_array(
+        "tokenizer.ggml.scores",
+        ty::F32,
+        1 << 20,
+        &[0; 8],
+    ))
+    .build();
+    match Tokenizer::from_bytes(&bytes) {
+        Err(Error::InvalidMetadata(msg)) => {
+            assert!(msg.contains("needs at least 4194304 bytes"), "{msg}");
+        }
+        other => panic!("expected InvalidMetadata, got {:?}", other.err()),
+    }
+}
@@ -0,0 +1,53 @@
+//! GGUF loader allocation test. It lives in its own test binary because it
+//! installs a global allocator that records the largest single request.
+
+mod common;
+
+use common::{ty, GgufBuilder};
+use shimmytok::Tokenizer;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct LargestRequest;
+
+static LARGEST: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for LargestRequest {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LARGEST.fetch_max(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: LargestRequest = LargestRequest;
+
+#[test]
+fn plain_reader_does_not_reserve_declared_array_len() {
+    // A plain reader cannot check the declared length against the bytes left,
+    // so only the default element cap applies: 16 Mi elements, up to 384 MiB
+    // of `String`s, declared with 8 bytes behind them.
+    for array_type in [ty::U8, ty::U64, ty::STRING] {
+        let bytes = GgufBuilder::new()
+            .with_string("tokenizer.ggml.model", "gpt2")
+            .with_raw_array(
+                "tokenizer.ggml.tokens",
+                array_type,
+                shimmytok::gguf::MAX_ARRAY_LEN as u64,
+                &[0; 8],
+            )
+            .build();
+
+        LARGEST.store(0, Ordering::Relaxed);
+        assert!(Tokenizer::from_reader(&bytes[..]).is_err());
+        let largest = LARGEST.load(Ordering::Relaxed);
+        assert!(
+            largest < 1 << 20,
+            "array type {array_type}: reserved {largest} bytes up front"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Tests for loading legacy `vocab.json` + `merges.txt` pairs (`hf` feature).
+
+#![cfg(feature = "hf")]
+
+mod common;
+
+use common::bpe_gpt2_fixture;
+use shimmytok::{EncodeOptions, Error, Tokenizer, Vocabulary};
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+/// The vocabulary and merges of `bpe_gpt2_fixture`.
+const VOCAB_JSON: &str = r#"{"<unk>": 0, "<s>": 1, "</s>": 2, "a": 3, "b": 4, "c": 5, "ab": 6, "abc": 7, "1": 8, "2": 9}"#;
+const MERGES_TXT: &str = "#version: 0.2\na b\nab c\n";
+
+/// A GPT-2 style pair: `Ġ` pieces and `<|endoftext|>` as the last token.
+const GPT2_VOCAB_JSON: &str =
+    r#"{"a": 0, "b": 1, "Ġ": 2, "ab": 3, "Ġa": 4, "Ġab": 5, "<|endoftext|>": 6}"#;
+const GPT2_MERGES_TXT: &str = "#version: 0.2\r\nĠ a\r\nĠa b\r\na b\r\n";
+
+fn write(contents: &str) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(contents.as_bytes()).unwrap();
+    file
+}
+
+fn load(vocab: &str, merges: &str) -> Result<Tokenizer, Error> {
+    let (vocab, merges) = (write(vocab), write(merges));
+    Tokenizer::from_hf_files(vocab.path(), merges.path())
+}
+
+#[test]
+fn files_match_gguf_fixture() {
+    let files = load(VOCAB_JSON, MERGES_TXT).unwrap();
+    let gguf = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+
+    assert_eq!(files.model_type(), "gpt2");
+    assert_eq!(files.pre_type(), Some("gpt-2"));
+    assert_eq!(files.vocab_size(), gguf.vocab_size());
+    assert_eq!(files.merges(), gguf.merges());
+    for text in ["abc", "acb", "ab", "abcab12", ""] {
+        assert_eq!(
+            files.encode(text, false).unwrap(),
+            gguf.encode(text, false).unwrap(),
+            "{text:?}"
+        );
+    }
+    assert_eq!(files.bos_token(), 1);
+    assert_eq!(files.eos_token(), 2);
+    assert_eq!(files.unk_token(), 0);
+}
+
+#[test]
+fn gpt2_style_files_encode_and_mark_endoftext() {
+    let tok = load(GPT2_VOCAB_JSON, GPT2_MERGES_TXT).unwrap();
+    assert_eq!(tok.merge_count(), 3);
+    assert_eq!(tok.encode("ab ab a", false).unwrap(), vec![3, 5, 4]);
+    assert_eq!(tok.decode(&[3, 5, 4], false).unwrap(), "ab ab a");
+
+    // `<|endoftext|>` is BOS, EOS and UNK, as in GPT-2's HF config, and is
+    // not added by default.
+    assert_eq!(tok.eos_token(), 6);
+    assert_eq!(tok.bos_token(), 6);
+    assert_eq!(tok.unk_token(), 6);
+    assert!(tok.is_special_token(6));
+    assert_eq!(tok.encode("ab", true).unwrap(), vec![3]);
+    let options = EncodeOptions::with_parse_special(false, true);
+    assert_eq!(
+        tok.encode_with_options("ab<|endoftext|>", &options)
+            .unwrap(),
+        vec![3, 6]
+    );
+}
+
+#[test]
+fn vocabulary_from_hf_files() {
+    let (vocab, merges) = (write(VOCAB_JSON), write("a b\nab c"));
+    let vocab = Vocabulary::from_hf_files(vocab.path(), merges.path()).unwrap();
+    assert_eq!(vocab.model_type(), "gpt2");
+    assert_eq!(vocab.n_tokens(), 10);
+    let tok = Tokenizer::from_vocabulary(vocab).unwrap();
+    assert_eq!(tok.encode("abc", false).unwrap(), vec![7]);
+}
+
+#[test]
+fn malformed_inputs_are_rejected() {
+    assert!(matches!(
+        load(VOCAB_JSON, "#version: 0.2\na b\nabc\n"),
+        Err(Error::InvalidMetadata(msg)) if msg == r#"merges.txt: line 3 is not `left right`: "abc""#
+    ));
+    assert!(matches!(
+        load(VOCAB_JSON, "a b c\n"),
+        Err(Error::InvalidMetadata(msg)) if msg.contains("line 1")
+    ));
+    assert!(matches!(
+        load(r#"["a", "b"]"#, MERGES_TXT),
+        Err(Error::InvalidMetadata(msg)) if msg.starts_with("vocab.json:")
+    ));
+    assert!(matches!(
+        load(r#"{"a": 0, "b": 2}"#, ""),
+        Err(Error::VocabularyError(msg)) if msg.contains("not contiguous")
+    ));
+
+    let merges = write(MERGES_TXT);
+    assert!(matches!(
+        Tokenizer::from_hf_files("/nonexistent/vocab.json", merges.path()),
+        Err(Error::Io(_))
+    ));
+}
@@ -0,0 +1,176 @@
+//! Tests for HuggingFace `tokenizer.json` import (`hf` feature). Each JSON
+//! fixture mirrors a GGUF fixture in [`common`], so the two loaders can be
+//! compared token-for-token.
+
+#![cfg(feature = "hf")]
+
+mod common;
+
+use common::{bpe_gpt2_fixture, ugm_t5_fixture};
+use shimmytok::{Error, Tokenizer, TokenizerBuilder};
+use std::io::Write;
+
+/// Byte-level BPE with the same vocabulary and merges as `bpe_gpt2_fixture`.
+/// Uses both merge encodings (`"a b"` and `["ab", "c"]`).
+const BPE_JSON: &str = r#"{
+  "version": "1.0",
+  "added_tokens": [
+    {"id": 0, "content": "<unk>", "special": true},
+    {"id": 1, "content": "<s>", "special": true},
+    {"id": 2, "content": "</s>", "special": true}
+  ],
+  "normalizer": null,
+  "pre_tokenizer": {"type": "ByteLevel", "add_prefix_space": false, "trim_offsets": true, "use_regex": true},
+  "post_processor": null,
+  "decoder": {"type": "ByteLevel", "add_prefix_space": true, "trim_offsets": true, "use_regex": true},
+  "model": {
+    "type": "BPE",
+    "unk_token": "<unk>",
+    "vocab": {"<unk>": 0, "<s>": 1, "</s>": 2, "a": 3, "b": 4, "c": 5, "ab": 6, "abc": 7, "1": 8, "2": 9},
+    "merges": ["a b", ["ab", "c"]]
+  }
+}"#;
+
+/// Unigram with the same pieces and scores as `ugm_t5_fixture`, plus a T5
+/// style post-processor that appends `</s>`.
+const UNIGRAM_JSON: &str = r#"{
+  "version": "1.0",
+  "added_tokens": [
+    {"id": 0, "content": "<pad>", "special": true},
+    {"id": 1, "content": "</s>", "special": true},
+    {"id": 2, "content": "<unk>", "special": true}
+  ],
+  "pre_tokenizer": {"type": "Sequence", "pretokenizers": [
+    {"type": "WhitespaceSplit"},
+    {"type": "Metaspace", "replacement": "▁", "prepend_scheme": "always", "split": true}
+  ]},
+  "post_processor": {
+    "type": "TemplateProcessing",
+    "single": [{"Sequence": {"id": "A", "type_id": 0}}, {"SpecialToken": {"id": "</s>", "type_id": 0}}],
+    "pair": [],
+    "special_tokens": {"</s>": {"id": "</s>", "ids": [1], "tokens": ["</s>"]}}
+  },
+  "model": {
+    "type": "Unigram",
+    "unk_id": 2,
+    "vocab": [
+      ["<pad>", 0.0], ["</s>", 0.0], ["<unk>", 0.0], ["▁", -5.0],
+      ["▁Hello", -2.0], ["▁world", -2.0], ["Hello", -6.0], ["world", -6.0],
+      ["▁你好", -2.0], ["你", -6.0], ["好", -6.0], ["世界", -3.0]
+    ]
+  }
+}"#;
+
+fn write_json(json: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(json.as_bytes()).unwrap();
+    file
+}
+
+#[test]
+fn bpe_json_matches_gguf_fixture() {
+    let file = write_json(BPE_JSON);
+    let hf = Tokenizer::from_hf_json(file.path()).unwrap();
+    let gguf = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+
+    assert_eq!(hf.model_type(), "gpt2");
+    assert_eq!(hf.pre_type(), Some("gpt-2"));
+    assert_eq!(hf.vocab_size(), gguf.vocab_size());
+    assert_eq!(hf.encode("abc", false).unwrap(), vec![7]);
+    for text in ["abc acb ab 12", "acb", "ab", ""] {
+        assert_eq!(
+            hf.encode(text, false).unwrap(),
+            gguf.encode(text, false).unwrap(),
+            "mismatch for {text:?}"
+        );
+    }
+
+    // No post-processor: BOS/EOS found by name but not added.
+    assert_eq!(hf.bos_token(), 1);
+    assert_eq!(hf.eos_token(), 2);
+    assert_eq!(hf.encode("abc", true).unwrap(), vec![7]);
+    assert!(hf.is_special_token(1));
+}
+
+#[test]
+fn unigram_json_matches_gguf_fixture() {
+    let file = write_json(UNIGRAM_JSON);
+    let hf = Tokenizer::from_hf_json(file.path()).unwrap();
+    let gguf = Tokenizer::from_bytes(&ugm_t5_fixture()).unwrap();
+
+    assert_eq!(hf.encode("Hello world", false).unwrap(), vec![4, 5]);
+    assert_eq!(hf.encode("你好世界", false).unwrap(), vec![8, 11]);
+    assert_eq!(
+        hf.encode("Hello 你好 world", false).unwrap(),
+        gguf.encode("Hello 你好 world", false).unwrap()
+    );
+
+    // TemplateProcessing appends </s>.
+    assert_eq!(hf.eos_token(), 1);
+    assert_eq!(hf.encode("Hello world", true).unwrap(), vec![4, 5, 1]);
+    assert_eq!(hf.pad_token(), Some(0));
+}
+
+#[test]
+fn builder_can_correct_inferred_fields() {
+    let file = write_json(BPE_JSON);
+    let tok = TokenizerBuilder::from_hf_json(file.path())
+        .unwrap()
+        .add_eos_token(true)
+        .build()
+        .unwrap();
+    assert_eq!(tok.encode("abc", true).unwrap(), vec![7, 2]);
+}
+
+#[test]
+fn sentencepiece_style_bpe_is_rejected() {
+    let json = BPE_JSON
+        .replace(r#""type": "ByteLevel""#, r#""type": "Metaspace""#)
+        .replace(
+            r#""type": "BPE","#,
+            r#""type": "BPE", "byte_fallback": true,"#,
+        );
+    let file = write_json(&json);
+    let result = Tokenizer::from_hf_json(file.path());
+    assert!(matches!(result, Err(Error::UnsupportedModel(_))));
+}
+
+#[test]
+fn malformed_json_is_rejected() {
+    let file = write_json("{ not json");
+    assert!(matches!(
+        Tokenizer::from_hf_json(file.path()),
+        Err(Error::InvalidMetadata(_))
+    ));
+
+    let file = write_json(r#"{"model": {"type": "WordLevel", "vocab": {}}}"#);
+    assert!(matches!(
+        Tokenizer::from_hf_json(file.path()),
+        Err(Error::UnsupportedModel(_))
+    ));
+}
+
+#[test]
+fn non_contiguous_ids_are_rejected() {
+    let json = BPE_JSON.replace(r#""2": 9"#, r#""2": 42"#);
+    let file = write_json(&json);
+    assert!(matches!(
+        Tokenizer::from_hf_json(file.path()),
+        Err(Error::VocabularyError(_))
+    ));
+}
+
+#[test]
+fn pre_type_inferred_from_split_regex() {
+    let llama3_pre = r#""pre_tokenizer": {"type": "Sequence", "pretokenizers": [
+        {"type": "Split", "pattern": {"Regex": "(?i:'s|'t|'re|'ve|'m|'ll|'d)|[^\\r\\n\\p{L}\\p{N}]?\\p{L}+|\\p{N}{1,3}| ?[^\\s\\p{L}\\p{N}]+[\\r\\n]*|\\s*[\\r\\n]+|\\s+(?!\\S)|\\s+"}, "behavior": "Isolated", "invert": false},
+        {"type": "ByteLevel", "add_prefix_space": false, "trim_offsets": true, "use_regex": false}
+    ]},"#;
+    let json = BPE_JSON.replace(
+        r#""pre_tokenizer": {"type": "ByteLevel", "add_prefix_space": false, "trim_offsets": true, "use_regex": true},"#,
+        llama3_pre,
+    );
+    let file = write_json(&json);
+    let tok = Tokenizer::from_hf_json(file.path()).unwrap();
+    assert_eq!(tok.pre_type(), Some("llama3"));
+}
@@ -0,0 +1,93 @@
+//! Fill-in-the-middle prompt assembly (`Tokenizer::encode_infill`).
+
+mod common;
+
+use common::{bpe_gpt2_fixture, GgufBuilder};
+use shimmytok::{Error, InfillOrder, Tokenizer, TokenizerBuilder};
+
+/// StarCoder-style vocabulary: `<fim_prefix>`=1, `<fim_middle>`=2,
+/// `<fim_suffix>`=3; `a`=5, `b`=6, `ab`=7.
+fn starcoder_fim() -> Vec<u8> {
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "starcoder")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &[
+                "<|endoftext|>",
+                "<fim_prefix>",
+                "<fim_middle>",
+                "<fim_suffix>",
+                "<fim_pad>",
+                "a",
+                "b",
+                "ab",
+            ],
+        )
+        .with_i32_array("tokenizer.ggml.token_type", &[3, 3, 3, 3, 3, 1, 1, 1])
+        .with_string_array("tokenizer.ggml.merges", &["a b"])
+        .with_u32("tokenizer.ggml.bos_token_id", 0)
+        .with_u32("tokenizer.ggml.eos_token_id", 0)
+        .with_u32("tokenizer.ggml.fim_pre_token_id", 1)
+        .with_u32("tokenizer.ggml.fim_suf_token_id", 3)
+        .with_u32("tokenizer.ggml.fim_mid_token_id", 2)
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+        .build()
+}
+
+#[test]
+fn psm_layout() {
+    let tok = Tokenizer::from_bytes(&starcoder_fim()).unwrap();
+    // <fim_prefix> ab <fim_suffix> b a <fim_middle>
+    assert_eq!(
+        tok.encode_infill("ab", "ba").unwrap(),
+        vec![1, 7, 3, 6, 5, 2]
+    );
+}
+
+#[test]
+fn spm_layout() {
+    let tok = Tokenizer::from_bytes(&starcoder_fim()).unwrap();
+    // <fim_suffix> b a <fim_prefix> ab <fim_middle>
+    assert_eq!(
+        tok.encode_infill_with_order("ab", "ba", InfillOrder::Spm)
+            .unwrap(),
+        vec![3, 6, 5, 1, 7, 2]
+    );
+}
+
+#[test]
+fn empty_segments_keep_markers() {
+    let tok = Tokenizer::from_bytes(&starcoder_fim()).unwrap();
+    assert_eq!(tok.encode_infill("", "").unwrap(), vec![1, 3, 2]);
+}
+
+#[test]
+fn bos_follows_the_model_flag() {
+    let tok = TokenizerBuilder::from_bytes(&starcoder_fim())
+        .unwrap()
+        .add_bos_token(true)
+        .build()
+        .unwrap();
+    assert_eq!(
+        tok.encode_infill("ab", "b").unwrap(),
+        vec![0, 1, 7, 3, 6, 2]
+    );
+}
+
+#[test]
+fn fim_markers_in_text_are_not_parsed() {
+    let tok = Tokenizer::from_bytes(&starcoder_fim()).unwrap();
+    let tokens = tok.encode_infill("<fim_middle>", "").unwrap();
+    assert_eq!(tokens.iter().filter(|&&t| t == 2).count(), 1);
+    assert_eq!(tokens.last(), Some(&2));
+}
+
+#[test]
+fn missing_fim_tokens_error() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert!(matches!(
+        tok.encode_infill("a", "b"),
+        Err(Error::VocabularyError(msg)) if msg.contains("FIM")
+    ));
+}
@@ -0,0 +1,49 @@
+//! Decoding sequences that contain out-of-vocabulary token IDs
+//! (`DecodeOptions::ignore_invalid_tokens`).
+
+mod common;
+
+use common::{bpe_gpt2_fixture, spm_llama_fixture};
+use shimmytok::{DecodeOptions, Error, Tokenizer};
+
+#[test]
+fn strict_decode_rejects_invalid_ids() {
+    for (bytes, ids) in [
+        (bpe_gpt2_fixture(), [7, u32::MAX, 6]),
+        (spm_llama_fixture(true), [263, u32::MAX, 262]),
+    ] {
+        let tok = Tokenizer::from_bytes(&bytes).unwrap();
+        // Previously `skip_special = true` panicked looking up the token type.
+        for skip_special in [false, true] {
+            assert!(matches!(
+                tok.decode(&ids, skip_special),
+                Err(Error::InvalidToken(msg)) if msg.contains(&u32::MAX.to_string())
+            ));
+        }
+    }
+}
+
+#[test]
+fn lossy_decode_skips_invalid_ids() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let ids = [7, u32::MAX, 6];
+    let opts = DecodeOptions::with_skip_special(false).with_ignore_invalid_tokens(false);
+    assert_eq!(tok.decode_with_options(&ids, &opts).unwrap(), "abcab");
+
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let ids = [263, u32::MAX, 262];
+    assert_eq!(tok.decode_with_options(&ids, &opts).unwrap(), " hihi");
+}
+
+#[test]
+fn lossy_decode_can_render_unk() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let ids = [263, u32::MAX, 262];
+
+    let opts = DecodeOptions::with_skip_special(false).with_ignore_invalid_tokens(true);
+    assert_eq!(tok.decode_with_options(&ids, &opts).unwrap(), " hi<unk>hi");
+
+    // The substituted UNK is special, so skip_special_tokens drops it.
+    let opts = DecodeOptions::with_skip_special(true).with_ignore_invalid_tokens(true);
+    assert_eq!(tok.decode_with_options(&ids, &opts).unwrap(), " hihi");
+}
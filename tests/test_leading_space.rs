@@ -0,0 +1,119 @@
+//! Tests for `EncodeOptions::add_leading_space`.
+
+mod common;
+
+use common::{spm_llama_fixture, GgufBuilder};
+use shimmytok::{EncodeOptions, Tokenizer};
+
+const BOS: u32 = 1;
+const EOS: u32 = 2;
+
+/// A GPT-2 vocabulary where `world` and `Ġworld` are single tokens.
+fn bpe_world_fixture() -> Tokenizer {
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "gpt-2")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &[
+                "<unk>", "<s>", "</s>", "w", "o", "r", "l", "d", "Ġ", "wo", "wor", "worl", "world",
+                "Ġworld",
+            ],
+        )
+        .with_string_array(
+            "tokenizer.ggml.merges",
+            &["w o", "wo r", "wor l", "worl d", "Ġ world"],
+        )
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .with_u32("tokenizer.ggml.bos_token_id", BOS)
+        .with_u32("tokenizer.ggml.eos_token_id", EOS)
+        .build();
+    Tokenizer::from_bytes(&bytes).unwrap()
+}
+
+fn plain() -> EncodeOptions {
+    EncodeOptions::with_special_tokens(false)
+}
+
+#[test]
+fn leading_space_changes_the_first_token() {
+    let tok = bpe_world_fixture();
+    let world = tok.get_token("world").unwrap();
+    let spaced = tok.get_token("Ġworld").unwrap();
+
+    assert_eq!(tok.encode("world", false).unwrap(), vec![world]);
+    assert_eq!(tok.encode(" world", false).unwrap(), vec![spaced]);
+
+    let options = plain().with_leading_space(true);
+    assert_eq!(
+        tok.encode_with_options("world", &options).unwrap(),
+        vec![spaced]
+    );
+    assert_eq!(
+        tok.decode(&tok.encode_with_options("world", &options).unwrap(), false)
+            .unwrap(),
+        " world"
+    );
+}
+
+#[test]
+fn existing_space_and_empty_text_are_untouched() {
+    let tok = bpe_world_fixture();
+    let options = plain().with_leading_space(true);
+    assert_eq!(
+        tok.encode_with_options(" world", &options).unwrap(),
+        tok.encode(" world", false).unwrap()
+    );
+    assert!(tok.encode_with_options("", &options).unwrap().is_empty());
+}
+
+#[test]
+fn other_backends_ignore_the_option() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let options = plain().with_leading_space(true);
+    assert_eq!(
+        tok.encode_with_options("hi", &options).unwrap(),
+        tok.encode("hi", false).unwrap()
+    );
+}
+
+#[test]
+fn offsets_exclude_the_added_space() {
+    let tok = bpe_world_fixture();
+    let spaced = tok.get_token("Ġworld").unwrap();
+    let options = plain().with_leading_space(true);
+
+    let (tokens, offsets) = tok
+        .encode_with_options_and_offsets("world world", &options)
+        .unwrap();
+    assert_eq!(tokens, vec![spaced, spaced]);
+    assert_eq!(offsets, vec![(0, 5), (5, 11)]);
+}
+
+#[test]
+fn offsets_give_specials_and_wrapping_tokens_empty_spans() {
+    let tok = bpe_world_fixture();
+    let options = plain()
+        .with_leading_space(true)
+        .with_added_bos(true)
+        .with_added_eos(true)
+        .with_prefix_tokens(vec![3]);
+
+    let (tokens, offsets) = tok
+        .encode_with_options_and_offsets("world", &options)
+        .unwrap();
+    assert_eq!(tokens, tok.encode_with_options("world", &options).unwrap());
+    assert_eq!(tokens, vec![BOS, 3, tok.get_token("Ġworld").unwrap(), EOS]);
+    assert_eq!(offsets, vec![(0, 0), (0, 0), (0, 5), (5, 5)]);
+}
+
+#[test]
+fn strict_parity_ignores_the_option() {
+    let mut tok = bpe_world_fixture();
+    tok.strict_llamacpp_parity(true);
+    let options = plain().with_leading_space(true);
+    assert_eq!(
+        tok.encode_with_options("world", &options).unwrap(),
+        vec![tok.get_token("world").unwrap()]
+    );
+}
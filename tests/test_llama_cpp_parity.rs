@@ -1,4 +1,5 @@
 // Tests for llama.cpp parity: special token IDs and cleanup flags
+use shimmytok::byte_encoder::parse_hex_byte_token as parse_byte_token;
 use shimmytok::Tokenizer;
 use std::path::Path;
 
@@ -125,7 +126,7 @@ fn test_token_to_piece() {
 
 #[test]
 fn test_byte_token_decode_unit() {
-    // Unit test for the byte token parsing logic
+    // Unit test for `byte_encoder::parse_hex_byte_token`, shared by every backend
     // This tests the pattern <0xXX> -> byte value
 
     // Valid byte tokens
@@ -139,16 +140,7 @@ fn test_byte_token_decode_unit() {
     assert_eq!(parse_byte_token("<0x0>"), None); // too short
     assert_eq!(parse_byte_token("<0x0AG>"), None); // invalid hex
     assert_eq!(parse_byte_token("0x0A"), None); // missing brackets
-}
-
-/// Helper to test byte token parsing (mirrors internal decode_byte_token)
-fn parse_byte_token(text: &str) -> Option<u8> {
-    if text.len() == 6 && text.starts_with("<0x") && text.ends_with('>') {
-        let hex = &text[3..5];
-        u8::from_str_radix(hex, 16).ok()
-    } else {
-        None
-    }
+    assert_eq!(parse_byte_token("<0x+A>"), None); // sign is not a hex digit
 }
 
 #[test]
@@ -0,0 +1,143 @@
+//! Tests for `Tokenizer::from_gguf_file_with_options` and `LoaderOptions` limits.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, GgufBuilder};
+use shimmytok::{Error, LoaderOptions, Tokenizer};
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+fn write_gguf(bytes: &[u8]) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(bytes).unwrap();
+    file
+}
+
+/// A GPT-2 vocabulary whose last piece is `len` bytes long.
+fn vocab_with_long_token(len: usize) -> Vec<u8> {
+    let long = "a".repeat(len);
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["<unk>", "<s>", "</s>", "a", &long],
+        )
+        .build()
+}
+
+#[test]
+fn long_token_needs_raised_limit() {
+    let file = write_gguf(&vocab_with_long_token(2000));
+
+    match Tokenizer::from_gguf_file(file.path()) {
+        Err(Error::VocabularyError(msg)) => {
+            assert!(
+                msg.contains("Token 4 too large: 2000 bytes (max: 1024)"),
+                "{msg}"
+            );
+        }
+        other => panic!("expected VocabularyError, got {:?}", other.err()),
+    }
+
+    let options = LoaderOptions {
+        max_token_length: 4096,
+        ..LoaderOptions::default()
+    };
+    let tok = Tokenizer::from_gguf_file_with_options(file.path(), &options).unwrap();
+    assert_eq!(tok.vocab_size(), 5);
+}
+
+#[test]
+fn long_metadata_string_needs_raised_limit() {
+    let name = "x".repeat(2 * 1024 * 1024);
+    let bytes = GgufBuilder::new()
+        .with_string("general.name", &name)
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string_array("tokenizer.ggml.tokens", &["<unk>", "<s>", "</s>"])
+        .build();
+    let file = write_gguf(&bytes);
+
+    assert!(matches!(
+        Tokenizer::from_gguf_file(file.path()),
+        Err(Error::InvalidMetadata(msg)) if msg.contains("String too large")
+    ));
+
+    let options = LoaderOptions {
+        max_string_size: 4 * 1024 * 1024,
+        ..LoaderOptions::default()
+    };
+    let tok = Tokenizer::from_gguf_file_with_options(file.path(), &options).unwrap();
+    assert_eq!(
+        tok.metadata()["general.name"].as_str().map(str::len),
+        Some(name.len())
+    );
+}
+
+#[test]
+fn lowered_limits_are_enforced() {
+    let file = write_gguf(&bpe_gpt2_fixture());
+
+    let options = LoaderOptions {
+        max_vocab_size: 5,
+        ..LoaderOptions::default()
+    };
+    assert!(matches!(
+        Tokenizer::from_gguf_file_with_options(file.path(), &options),
+        Err(Error::VocabularyError(msg)) if msg == "Vocabulary too large: 10 tokens (max: 5)"
+    ));
+
+    let options = LoaderOptions {
+        max_merge_count: 1,
+        ..LoaderOptions::default()
+    };
+    assert!(matches!(
+        Tokenizer::from_gguf_file_with_options(file.path(), &options),
+        Err(Error::VocabularyError(msg)) if msg == "Too many merge rules: 2 (max: 1)"
+    ));
+
+    let options = LoaderOptions {
+        max_array_len: 4,
+        ..LoaderOptions::default()
+    };
+    assert!(matches!(
+        Tokenizer::from_gguf_file_with_options(file.path(), &options),
+        Err(Error::InvalidMetadata(msg)) if msg.contains("Array too large: 10 elements")
+    ));
+}
+
+#[test]
+fn default_options_match_from_gguf_file() {
+    let file = write_gguf(&bpe_gpt2_fixture());
+    let a = Tokenizer::from_gguf_file(file.path()).unwrap();
+    let b = Tokenizer::from_gguf_file_with_options(file.path(), &LoaderOptions::default()).unwrap();
+    assert_eq!(
+        a.encode("abc ab", false).unwrap(),
+        b.encode("abc ab", false).unwrap()
+    );
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn mmap_loading_honors_limits() {
+    let file = write_gguf(&vocab_with_long_token(2000));
+    assert!(matches!(
+        Tokenizer::from_gguf_file_mmap(file.path()),
+        Err(Error::VocabularyError(_))
+    ));
+
+    let options = LoaderOptions {
+        max_token_length: 4096,
+        ..LoaderOptions::default()
+    };
+    let tok = Tokenizer::from_gguf_file_mmap_with_options(file.path(), &options).unwrap();
+    assert_eq!(tok.vocab_size(), 5);
+
+    let options = LoaderOptions {
+        max_vocab_size: 4,
+        ..options
+    };
+    assert!(matches!(
+        Tokenizer::from_gguf_file_mmap_with_options(file.path(), &options),
+        Err(Error::VocabularyError(msg)) if msg == "Vocabulary too large: 5 tokens (max: 4)"
+    ));
+}
@@ -0,0 +1,54 @@
+//! Load-time validation of BPE merge rules.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, spm_llama_fixture, GgufBuilder};
+use shimmytok::{Error, Tokenizer};
+
+fn gpt2_with_merges(merges: &[&str]) -> Vec<u8> {
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "gpt-2")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["<unk>", "<s>", "</s>", "a", "b", "c", "ab", "abc"],
+        )
+        .with_string_array("tokenizer.ggml.merges", merges)
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .build()
+}
+
+#[test]
+fn merge_count_reports_loaded_rules() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert_eq!(tok.merge_count(), 2);
+
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    assert_eq!(tok.merge_count(), 0);
+}
+
+#[test]
+fn duplicate_merge_is_rejected() {
+    let bytes = gpt2_with_merges(&["a b", "ab c", "a b"]);
+    let err = Tokenizer::from_bytes(&bytes).err().unwrap();
+    assert!(
+        matches!(&err, Error::VocabularyError(msg)
+            if msg.contains("Merge rule 2 duplicates rule 0") && msg.contains("'a b'")),
+        "unexpected error: {err}"
+    );
+}
+
+#[test]
+fn distinct_merges_sharing_a_token_load() {
+    let tok = Tokenizer::from_bytes(&gpt2_with_merges(&["a b", "ab c", "b c"])).unwrap();
+    assert_eq!(tok.merge_count(), 3);
+}
+
+#[test]
+fn unknown_merge_token_is_still_rejected() {
+    let bytes = gpt2_with_merges(&["a b", "ab d"]);
+    assert!(matches!(
+        Tokenizer::from_bytes(&bytes),
+        Err(Error::VocabularyError(msg)) if msg.contains("Merge rule 1 references unknown right token")
+    ));
+}
@@ -0,0 +1,58 @@
+//! Tests for `Tokenizer::merges` and `Tokenizer::export_merges`.
+
+mod common;
+
+use common::{spm_llama_fixture, GgufBuilder};
+use shimmytok::Tokenizer;
+
+/// GPT-2 style vocabulary; `Ġ` is the byte-level encoding of a space.
+fn bpe_with_merges(merges: &[&str]) -> Tokenizer {
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "gpt-2")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["<unk>", "t", "h", "e", "th", "the", "Ġ", "Ġthe"],
+        )
+        .with_string_array("tokenizer.ggml.merges", merges)
+        .build();
+    Tokenizer::from_bytes(&bytes).unwrap()
+}
+
+const MERGES: [&str; 3] = ["t h", "th e", "Ġ the"];
+
+#[test]
+fn merges_are_in_priority_order() {
+    let tok = bpe_with_merges(&MERGES);
+    assert_eq!(tok.merges(), tok.vocab().get_merges());
+    assert_eq!(
+        tok.merges(),
+        [
+            ("t".to_string(), "h".to_string()),
+            ("th".to_string(), "e".to_string()),
+            ("Ġ".to_string(), "the".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn export_round_trips_through_a_reload() {
+    let tok = bpe_with_merges(&MERGES);
+    let lines = tok.export_merges();
+    assert_eq!(lines, MERGES);
+
+    let line_refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let reloaded = bpe_with_merges(&line_refs);
+    assert_eq!(reloaded.merges(), tok.merges());
+    assert_eq!(
+        reloaded.encode("the the", false).unwrap(),
+        tok.encode("the the", false).unwrap()
+    );
+}
+
+#[test]
+fn non_bpe_models_have_no_merges() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    assert!(tok.merges().is_empty());
+    assert!(tok.export_merges().is_empty());
+}
@@ -0,0 +1,89 @@
+//! Tests for `ModelFamily` classification.
+
+mod common;
+
+use common::{
+    bpe_gpt2_fixture, bpe_starcoder_fixture, plamo2_fixture, rwkv_world_fixture, spm_llama_fixture,
+    ugm_t5_fixture, wpm_bert_fixture,
+};
+use shimmytok::{ModelFamily, Tokenizer};
+
+#[test]
+fn fixtures_report_their_family() {
+    let cases = [
+        (spm_llama_fixture(true), ModelFamily::Llama),
+        (bpe_gpt2_fixture(), ModelFamily::Gpt2),
+        (bpe_starcoder_fixture(), ModelFamily::StarCoder),
+        (wpm_bert_fixture(), ModelFamily::Bert),
+        (ugm_t5_fixture(), ModelFamily::T5),
+        (rwkv_world_fixture(), ModelFamily::Rwkv),
+        (plamo2_fixture(), ModelFamily::Plamo2),
+    ];
+    for (bytes, family) in cases {
+        let tok = Tokenizer::from_bytes(&bytes).unwrap();
+        assert_eq!(tok.model_family(), family, "model {}", tok.model_type());
+    }
+}
+
+#[test]
+fn set_pre_type_changes_the_family() {
+    let mut tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    tok.set_pre_type("llama3").unwrap();
+    assert_eq!(tok.model_family(), ModelFamily::Llama3);
+}
+
+#[test]
+fn detect_sentencepiece_models() {
+    assert_eq!(ModelFamily::detect("llama", None), ModelFamily::Llama);
+    assert_eq!(ModelFamily::detect("mistral", None), ModelFamily::Llama);
+    assert_eq!(ModelFamily::detect("gemma", None), ModelFamily::Gemma);
+    // `pre` is a BPE concept and does not affect SentencePiece models.
+    assert_eq!(
+        ModelFamily::detect("llama", Some("llama3")),
+        ModelFamily::Llama
+    );
+}
+
+#[test]
+fn detect_bpe_models_by_pre_type() {
+    let cases = [
+        ("gpt2", None, ModelFamily::Gpt2),
+        ("gpt2", Some("gpt-2"), ModelFamily::Gpt2),
+        ("gpt2", Some("llama3"), ModelFamily::Llama3),
+        ("gpt2", Some("llama-v3"), ModelFamily::Llama3),
+        ("gpt2", Some("llama-bpe"), ModelFamily::Llama3),
+        ("gpt2", Some("qwen2"), ModelFamily::Qwen2),
+        ("qwen2", None, ModelFamily::Qwen2),
+        ("qwen", None, ModelFamily::Qwen2),
+        ("gpt2", Some("deepseek-coder"), ModelFamily::DeepSeek),
+        ("gpt2", Some("deepseek-r1-qwen"), ModelFamily::DeepSeek),
+        ("gpt2", Some("starcoder"), ModelFamily::StarCoder),
+        (
+            "gpt2",
+            Some("tekken"),
+            ModelFamily::OtherBpe("tekken".to_string()),
+        ),
+    ];
+    for (model, pre, family) in cases {
+        assert_eq!(ModelFamily::detect(model, pre), family, "{model} / {pre:?}");
+    }
+}
+
+#[test]
+fn detect_other_backends() {
+    assert_eq!(ModelFamily::detect("bert", None), ModelFamily::Bert);
+    assert_eq!(ModelFamily::detect("wpm", None), ModelFamily::Bert);
+    assert_eq!(ModelFamily::detect("t5", None), ModelFamily::T5);
+    assert_eq!(ModelFamily::detect("ugm", None), ModelFamily::T5);
+    assert_eq!(ModelFamily::detect("unigram", None), ModelFamily::T5);
+    assert_eq!(ModelFamily::detect("rwkv", None), ModelFamily::Rwkv);
+    assert_eq!(ModelFamily::detect("plamo2", None), ModelFamily::Plamo2);
+}
+
+#[test]
+fn unknown_model_type_is_kept() {
+    assert_eq!(
+        ModelFamily::detect("mamba", Some("gpt-2")),
+        ModelFamily::Unknown("mamba".to_string())
+    );
+}
@@ -1,6 +1,8 @@
-//! Tests for the new `from_reader`, `from_bytes`, and `chat_template` APIs.
+//! Tests for the `from_reader`, `from_gguf_reader`, `from_bytes`, `from_gguf_bytes`, and `chat_template` APIs.
 
-use shimmytok::Tokenizer;
+mod common;
+
+use shimmytok::{Error, Tokenizer};
 use std::io::Write;
 use tempfile::NamedTempFile;
 
@@ -105,6 +107,69 @@ fn test_from_reader_file() {
     assert!(tokenizer.vocab_size() >= 3);
 }
 
+// ── from_gguf_reader ─────────────────────────────────────────────────────────
+
+#[test]
+fn test_from_gguf_reader_in_memory_cursor() {
+    use std::io::Cursor;
+    let bytes: Vec<u8> = common::bpe_gpt2_fixture();
+    let tokenizer =
+        Tokenizer::from_gguf_reader(Cursor::new(bytes)).expect("from_gguf_reader should succeed");
+    assert_eq!(tokenizer.model_type(), "gpt2");
+    let ids = tokenizer.encode("abc", false).unwrap();
+    assert_eq!(ids, vec![7]);
+    assert_eq!(tokenizer.decode(&ids, false).unwrap(), "abc");
+}
+
+#[test]
+fn test_from_gguf_reader_file() {
+    let bytes = minimal_gguf_bytes();
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(&bytes).unwrap();
+    tmp.flush().unwrap();
+
+    let file = std::fs::File::open(tmp.path()).unwrap();
+    let tokenizer = Tokenizer::from_gguf_reader(file).expect("from_gguf_reader(file)");
+    assert_eq!(tokenizer.vocab_size(), 3);
+}
+
+// ── from_gguf_bytes ──────────────────────────────────────────────────────────
+
+#[test]
+fn test_from_gguf_bytes_fixture() {
+    let bytes = common::bpe_gpt2_fixture();
+    let tokenizer = Tokenizer::from_gguf_bytes(&bytes).expect("from_gguf_bytes should succeed");
+    assert_eq!(tokenizer.encode("abc", false).unwrap(), vec![7]);
+    assert_eq!(
+        tokenizer.vocab_size(),
+        Tokenizer::from_bytes(&bytes).unwrap().vocab_size()
+    );
+}
+
+/// A blob declaring a multi-gigabyte token string must be rejected by the
+/// per-string limit before anything is allocated.
+#[test]
+fn test_from_gguf_bytes_rejects_oversized_string() {
+    let mut buf: Vec<u8> = Vec::new();
+    buf.extend_from_slice(b"GGUF");
+    buf.extend_from_slice(&3u32.to_le_bytes());
+    buf.extend_from_slice(&0u64.to_le_bytes());
+    buf.extend_from_slice(&1u64.to_le_bytes());
+    let key = "tokenizer.ggml.tokens";
+    buf.extend_from_slice(&(key.len() as u64).to_le_bytes());
+    buf.extend_from_slice(key.as_bytes());
+    buf.extend_from_slice(&9u32.to_le_bytes());
+    buf.extend_from_slice(&8u32.to_le_bytes());
+    buf.extend_from_slice(&1u64.to_le_bytes());
+    buf.extend_from_slice(&(4u64 << 30).to_le_bytes()); // 4 GiB string length
+
+    match Tokenizer::from_gguf_bytes(&buf) {
+        Err(Error::InvalidMetadata(msg)) => assert!(msg.contains("String too large"), "{msg}"),
+        Err(e) => panic!("wrong error: {e:?}"),
+        Ok(_) => panic!("oversized string must be rejected"),
+    }
+}
+
 // ── from_bytes vs from_gguf_file parity ──────────────────────────────────────
 
 #[test]
@@ -123,6 +188,34 @@ fn test_from_bytes_parity_with_from_gguf_file() {
     assert_eq!(from_file.eos_token(), from_bytes.eos_token());
 }
 
+// ── from_gguf_file_mmap ──────────────────────────────────────────────────────
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_from_gguf_file_mmap_matches_from_gguf_file() {
+    let bytes = common::bpe_gpt2_fixture();
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(&bytes).unwrap();
+    tmp.flush().unwrap();
+
+    let mapped = Tokenizer::from_gguf_file_mmap(tmp.path()).expect("from_gguf_file_mmap");
+    let buffered = Tokenizer::from_gguf_file(tmp.path()).expect("from_gguf_file");
+
+    assert_eq!(mapped.vocab_size(), buffered.vocab_size());
+    assert_eq!(mapped.model_type(), buffered.model_type());
+    assert_eq!(
+        mapped.encode("abcacb", false).unwrap(),
+        buffered.encode("abcacb", false).unwrap()
+    );
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_from_gguf_file_mmap_missing_file() {
+    let result = Tokenizer::from_gguf_file_mmap("/nonexistent/model.gguf");
+    assert!(matches!(result, Err(Error::Io(_))));
+}
+
 // ── chat_template ─────────────────────────────────────────────────────────────
 
 #[test]
@@ -181,3 +274,27 @@ fn test_chat_template_returns_str_not_owned() {
     let tmpl: Option<&str> = tokenizer.chat_template();
     assert!(tmpl.is_some());
 }
+
+#[test]
+fn test_named_chat_templates() {
+    let bytes = common::GgufBuilder::new()
+        .with_string_array("tokenizer.ggml.tokens", &["<unk>", "<s>", "</s>"])
+        .with_string("tokenizer.chat_template", "{{ messages }}")
+        .with_string("tokenizer.chat_template.tool_use", "{{ tools }}")
+        .with_string("tokenizer.chat_template.rag", "{{ documents }}")
+        .build();
+    let tokenizer = Tokenizer::from_bytes(&bytes).unwrap();
+    assert_eq!(tokenizer.chat_template(), Some("{{ messages }}"));
+
+    let named = tokenizer.chat_templates();
+    assert_eq!(named.len(), 2);
+    assert_eq!(named["tool_use"], "{{ tools }}");
+    assert_eq!(named["rag"], "{{ documents }}");
+}
+
+#[test]
+fn test_gpt2_has_no_chat_templates() {
+    let tokenizer = Tokenizer::from_bytes(&common::bpe_gpt2_fixture()).unwrap();
+    assert!(tokenizer.chat_template().is_none());
+    assert!(tokenizer.chat_templates().is_empty());
+}
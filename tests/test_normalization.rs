@@ -0,0 +1,177 @@
+//! Tests for the input-rewriting `EncodeOptions`: `normalization`, `strip_bom`,
+//! `strip_zero_width` and `normalize_newlines`.
+
+mod common;
+
+use common::{bpe_full_bytes_fixture, bpe_gpt2_fixture, spm_llama_fixture};
+use shimmytok::{EncodeOptions, NormalizationForm, Tokenizer};
+
+// ===== Unicode Normalization =====
+
+const COMPOSED: &str = "\u{e9}"; // é
+const DECOMPOSED: &str = "e\u{301}"; // e + combining acute
+
+fn tokenizer() -> Tokenizer {
+    Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap()
+}
+
+fn encode(tok: &Tokenizer, text: &str, form: Option<NormalizationForm>) -> Vec<u32> {
+    let mut opts = EncodeOptions::with_special_tokens(false);
+    if let Some(form) = form {
+        opts = opts.with_normalization(form);
+    }
+    tok.encode_with_options(text, &opts).unwrap()
+}
+
+#[test]
+fn off_by_default() {
+    let tok = tokenizer();
+    assert!(EncodeOptions::default().normalization.is_none());
+    assert_ne!(encode(&tok, COMPOSED, None), encode(&tok, DECOMPOSED, None));
+    assert_eq!(
+        encode(&tok, COMPOSED, None),
+        tok.encode(COMPOSED, false).unwrap()
+    );
+}
+
+#[test]
+fn nfc_unifies_composed_and_decomposed() {
+    let tok = tokenizer();
+    let nfc = Some(NormalizationForm::Nfc);
+    assert_eq!(encode(&tok, COMPOSED, nfc), encode(&tok, DECOMPOSED, nfc));
+    // `<0xC3><0xA9>` after the `▁` prefix.
+    assert_eq!(encode(&tok, DECOMPOSED, nfc), vec![259, 198, 172]);
+}
+
+#[test]
+fn nfd_unifies_composed_and_decomposed() {
+    let tok = tokenizer();
+    let nfd = Some(NormalizationForm::Nfd);
+    assert_eq!(encode(&tok, COMPOSED, nfd), encode(&tok, DECOMPOSED, nfd));
+    assert_eq!(encode(&tok, COMPOSED, nfd), encode(&tok, DECOMPOSED, None));
+}
+
+#[test]
+fn nfkc_folds_compatibility_characters() {
+    let tok = tokenizer();
+    // Full-width `ｈｉ` folds to `hi`, which is a single vocabulary piece.
+    assert_eq!(
+        encode(&tok, "\u{ff48}\u{ff49}", Some(NormalizationForm::Nfkc)),
+        vec![263]
+    );
+    assert_ne!(encode(&tok, "\u{ff48}\u{ff49}", None), vec![263]);
+    assert_eq!(
+        encode(&tok, "\u{ff48}\u{ff49}", Some(NormalizationForm::Nfkd)),
+        vec![263]
+    );
+}
+
+#[test]
+fn ignored_in_strict_llamacpp_parity() {
+    let mut tok = tokenizer();
+    tok.strict_llamacpp_parity(true);
+    let nfc = Some(NormalizationForm::Nfc);
+    assert_eq!(
+        encode(&tok, DECOMPOSED, nfc),
+        encode(&tok, DECOMPOSED, None)
+    );
+    assert_ne!(encode(&tok, DECOMPOSED, nfc), vec![259, 198, 172]);
+}
+
+// ===== BOM and Zero-Width Stripping =====
+
+#[test]
+fn leading_bom_is_encoded_unless_stripped() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(false)).unwrap();
+    let plain = tok.encode("hi", false).unwrap();
+
+    let with_bom = tok.encode("\u{FEFF}hi", false).unwrap();
+    assert_ne!(with_bom, plain);
+    assert!(with_bom.len() > plain.len(), "{with_bom:?} vs {plain:?}");
+
+    let options = EncodeOptions::with_special_tokens(false).with_strip_bom(true);
+    assert_eq!(
+        tok.encode_with_options("\u{FEFF}hi", &options).unwrap(),
+        plain
+    );
+    // Only a leading BOM is removed.
+    assert_eq!(
+        tok.encode_with_options("hi\u{FEFF}", &options).unwrap(),
+        tok.encode("hi\u{FEFF}", false).unwrap()
+    );
+}
+
+#[test]
+fn zero_width_characters_are_stripped_anywhere() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let text = "\u{FEFF}a\u{200B}b\u{200D}c\u{2060}";
+    assert_ne!(tok.encode(text, false).unwrap(), vec![7]);
+
+    let options = EncodeOptions::with_special_tokens(false).with_strip_zero_width(true);
+    assert_eq!(tok.encode_with_options(text, &options).unwrap(), vec![7]);
+}
+
+// ===== Newline Normalization =====
+
+/// Byte-level BPE with every single-byte piece, so `\r` and `\n` both
+/// have tokens of their own.
+fn byte_level_bpe() -> Tokenizer {
+    Tokenizer::from_bytes(&bpe_full_bytes_fixture(&[], &[])).unwrap()
+}
+
+fn encode_newlines(tok: &Tokenizer, text: &str, normalize: bool) -> Vec<u32> {
+    let options = EncodeOptions::with_special_tokens(false).with_normalize_newlines(normalize);
+    tok.encode_with_options(text, &options).unwrap()
+}
+
+#[test]
+fn crlf_and_cr_encode_like_lf() {
+    for tok in [
+        byte_level_bpe(),
+        Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap(),
+    ] {
+        let lf = encode_newlines(&tok, "a\nb", true);
+        assert_eq!(lf, tok.encode("a\nb", false).unwrap());
+        assert_eq!(
+            encode_newlines(&tok, "a\r\nb", true),
+            lf,
+            "{}",
+            tok.algorithm()
+        );
+        assert_eq!(
+            encode_newlines(&tok, "a\rb", true),
+            lf,
+            "{}",
+            tok.algorithm()
+        );
+        // Each line ending becomes exactly one `\n`.
+        assert_eq!(
+            encode_newlines(&tok, "a\r\n\r\rb\n", true),
+            tok.encode("a\n\n\nb\n", false).unwrap()
+        );
+    }
+}
+
+#[test]
+fn newline_normalization_off_by_default() {
+    let tok = byte_level_bpe();
+    assert!(!EncodeOptions::default().normalize_newlines);
+    assert_eq!(
+        encode_newlines(&tok, "a\r\nb", false),
+        tok.encode("a\r\nb", false).unwrap()
+    );
+    assert_ne!(
+        encode_newlines(&tok, "a\r\nb", false),
+        encode_newlines(&tok, "a\nb", false)
+    );
+}
+
+#[test]
+fn newline_normalization_ignored_in_strict_llamacpp_parity() {
+    let mut tok = byte_level_bpe();
+    tok.strict_llamacpp_parity(true);
+    assert_eq!(
+        encode_newlines(&tok, "a\r\nb", true),
+        tok.encode("a\r\nb", false).unwrap()
+    );
+}
@@ -0,0 +1,81 @@
+//! Tests for `Tokenizer::normalize`, the backend preprocessing step on its own.
+
+mod common;
+
+use common::{
+    bpe_gpt2_fixture, bpe_starcoder_fixture, plamo2_fixture, rwkv_world_fixture, spm_llama_fixture,
+    ugm_t5_fixture, wpm_bert_fixture,
+};
+use shimmytok::Tokenizer;
+
+fn normalize(fixture: &[u8], text: &str) -> String {
+    Tokenizer::from_bytes(fixture).unwrap().normalize(text)
+}
+
+#[test]
+fn bpe_shows_byte_encoded_fragments() {
+    assert_eq!(
+        normalize(&bpe_gpt2_fixture(), "Hello, world"),
+        "Hello , Ġworld"
+    );
+    assert_eq!(normalize(&bpe_gpt2_fixture(), "12ab 你好"), "12 ab Ġä½łå¥½");
+    // StarCoder splits digits individually first.
+    assert_eq!(normalize(&bpe_starcoder_fixture(), "12ab"), "1 2 ab");
+}
+
+#[test]
+fn spm_replaces_spaces_and_adds_prefix() {
+    assert_eq!(
+        normalize(&spm_llama_fixture(true), "Hello, world"),
+        "▁Hello,▁world"
+    );
+    assert_eq!(
+        normalize(&spm_llama_fixture(false), "Hello, world"),
+        "Hello,▁world"
+    );
+    // A leading space already provides the prefix.
+    assert_eq!(normalize(&spm_llama_fixture(true), " hi"), "▁hi");
+}
+
+#[test]
+fn ugm_folds_whitespace() {
+    assert_eq!(
+        normalize(&ugm_t5_fixture(), "Hello, world"),
+        "▁Hello,▁world"
+    );
+    assert_eq!(normalize(&ugm_t5_fixture(), "Hello\tworld"), "▁Hello▁world");
+}
+
+#[test]
+fn wpm_shows_preprocessed_words() {
+    assert_eq!(
+        normalize(&wpm_bert_fixture(), "Hello, world"),
+        "▁hello▁,▁world"
+    );
+    assert_eq!(
+        normalize(&wpm_bert_fixture(), "  Héllo\tWORLD!  "),
+        "▁hello▁world▁!"
+    );
+    assert_eq!(normalize(&wpm_bert_fixture(), "你好"), "▁你▁好");
+}
+
+#[test]
+fn raw_backends_leave_text_unchanged() {
+    for fixture in [rwkv_world_fixture(), plamo2_fixture()] {
+        assert_eq!(
+            normalize(&fixture, "  Héllo\tWORLD!  "),
+            "  Héllo\tWORLD!  "
+        );
+    }
+}
+
+#[test]
+fn empty_input_stays_empty() {
+    for fixture in [
+        spm_llama_fixture(true),
+        ugm_t5_fixture(),
+        wpm_bert_fixture(),
+    ] {
+        assert_eq!(normalize(&fixture, ""), "");
+    }
+}
@@ -0,0 +1,164 @@
+//! Tests for `Tokenizer::encode_with_offsets`, built on the model-free
+//! fixtures in [`common`].
+
+mod common;
+
+use common::{
+    bpe_gpt2_fixture, precompiled_charsmap, spm_llama_fixture, ty, ugm_t5_fixture,
+    wpm_bert_fixture, GgufBuilder,
+};
+use shimmytok::Tokenizer;
+
+fn slices<'a>(text: &'a str, offsets: &[(usize, usize)]) -> Vec<&'a str> {
+    offsets.iter().map(|&(s, e)| &text[s..e]).collect()
+}
+
+#[test]
+fn bpe_offsets_reconstruct_original_text() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let text = "abc acb ab 12";
+    let (ids, offsets) = tok.encode_with_offsets(text, false).unwrap();
+
+    assert_eq!(ids, tok.encode(text, false).unwrap());
+    assert_eq!(ids.len(), offsets.len());
+    for (&id, &(s, e)) in ids.iter().zip(&offsets) {
+        if id == 0 {
+            // The fixture has no `Ġ`, so each space is `<unk>` and spans it.
+            assert_eq!(&text[s..e], " ");
+        } else {
+            assert_eq!(&text[s..e], tok.decode_single(id, false).unwrap());
+        }
+    }
+    assert_eq!(slices(text, &offsets).concat(), text);
+}
+
+#[test]
+fn spm_prefix_space_is_excluded_from_first_span() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let text = "hi hi";
+    let (ids, offsets) = tok.encode_with_offsets(text, false).unwrap();
+    assert_eq!(ids, vec![263, 263]);
+    assert_eq!(offsets, vec![(0, 2), (2, 5)]);
+    assert_eq!(slices(text, &offsets), vec!["hi", " hi"]);
+}
+
+#[test]
+fn spm_byte_fallback_tokens_span_whole_character() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let text = "é";
+    let (ids, offsets) = tok.encode_with_offsets(text, false).unwrap();
+    assert_eq!(ids, vec![259, 3 + 0xC3, 3 + 0xA9]);
+    // The synthetic ▁ is zero-width; both byte tokens cover "é".
+    assert_eq!(offsets, vec![(0, 0), (0, 2), (0, 2)]);
+
+    let text = "hi\nhi";
+    let (_, offsets) = tok.encode_with_offsets(text, false).unwrap();
+    assert_eq!(slices(text, &offsets), vec!["hi", "\n", "hi"]);
+}
+
+#[test]
+fn special_tokens_get_zero_width_spans() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    let text = "Hello, world!";
+    let (ids, offsets) = tok.encode_with_offsets(text, true).unwrap();
+
+    assert_eq!(ids, tok.encode(text, true).unwrap());
+    assert_eq!(ids.first(), Some(&2));
+    assert_eq!(ids.last(), Some(&3));
+    assert_eq!(offsets.first(), Some(&(0, 0)));
+    assert_eq!(offsets.last(), Some(&(text.len(), text.len())));
+    assert_eq!(
+        slices(text, &offsets[1..offsets.len() - 1]),
+        vec!["Hello", ",", " world", "!"]
+    );
+}
+
+#[test]
+fn ugm_unknown_token_covers_unmatched_input() {
+    let tok = Tokenizer::from_bytes(&ugm_t5_fixture()).unwrap();
+    let text = "Hello xyz world";
+    let (ids, offsets) = tok.encode_with_offsets(text, false).unwrap();
+    assert_eq!(ids, vec![4, 3, 2, 5]);
+    assert_eq!(slices(text, &offsets), vec!["Hello", " ", "xyz", " world"]);
+}
+
+/// WordPiece vocabulary whose only word is `cafe`; WPM strips accents, so
+/// `café` encodes to it too.
+fn wpm_cafe_fixture() -> Vec<u8> {
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "bert")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["[PAD]", "[UNK]", "[CLS]", "[SEP]", "\u{2581}cafe"],
+        )
+        .with_i32_array("tokenizer.ggml.token_type", &[3, 2, 3, 3, 1])
+        .with_u32("tokenizer.ggml.unknown_token_id", 1)
+        .with_u32("tokenizer.ggml.bos_token_id", 2)
+        .with_u32("tokenizer.ggml.eos_token_id", 3)
+        .build()
+}
+
+#[test]
+fn wpm_accented_words_align_to_their_own_span() {
+    let tok = Tokenizer::from_bytes(&wpm_cafe_fixture()).unwrap();
+
+    let text = "café cafe";
+    let (ids, offsets) = tok.encode_with_offsets(text, false).unwrap();
+    assert_eq!(ids, vec![4, 4]);
+    assert_eq!(slices(text, &offsets), vec!["café", " cafe"]);
+
+    // Decomposed input: the combining acute belongs to the first word.
+    let text = "Cafe\u{301} CAFÉ";
+    let (_, offsets) = tok.encode_with_offsets(text, false).unwrap();
+    assert_eq!(slices(text, &offsets), vec!["Cafe\u{301}", " CAFÉ"]);
+}
+
+#[test]
+fn wpm_long_accented_input_aligns_every_token() {
+    let tok = Tokenizer::from_bytes(&wpm_cafe_fixture()).unwrap();
+    let text = "café ".repeat(20_000);
+    let (ids, offsets) = tok.encode_with_offsets(&text, false).unwrap();
+    assert_eq!(ids.len(), 20_000);
+    assert_eq!(offsets[0], (0, 5));
+    assert_eq!(offsets[19_999], (text.len() - 7, text.len() - 1));
+}
+
+#[test]
+fn ugm_charsmap_pieces_align_to_full_width_input() {
+    let charsmap = precompiled_charsmap(&[("Ａ", "A"), ("Ｂ", "B")]);
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "t5")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["<pad>", "</s>", "<unk>", "\u{2581}", "\u{2581}AB", "A", "B"],
+        )
+        .with_f32_array(
+            "tokenizer.ggml.scores",
+            &[0.0, 0.0, 0.0, -5.0, -1.0, -6.0, -6.0],
+        )
+        .with_i32_array("tokenizer.ggml.token_type", &[3, 3, 2, 1, 1, 1, 1])
+        .with_u32("tokenizer.ggml.unknown_token_id", 2)
+        .with_u32("tokenizer.ggml.eos_token_id", 1)
+        .with_bool("tokenizer.ggml.add_eos_token", false)
+        .with_raw_array(
+            "tokenizer.ggml.precompiled_charsmap",
+            ty::U8,
+            charsmap.len() as u64,
+            &charsmap,
+        )
+        .build();
+    let tok = Tokenizer::from_bytes(&bytes).unwrap();
+
+    let text = "ＡＢ ＡＢ AB";
+    let (ids, offsets) = tok.encode_with_offsets(text, false).unwrap();
+    assert_eq!(ids, vec![4, 4, 4]);
+    assert_eq!(slices(text, &offsets), vec!["ＡＢ", " ＡＢ", " AB"]);
+}
+
+#[test]
+fn empty_text_has_no_offsets() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let (ids, offsets) = tok.encode_with_offsets("", false).unwrap();
+    assert!(ids.is_empty());
+    assert!(offsets.is_empty());
+}
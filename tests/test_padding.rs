@@ -0,0 +1,151 @@
+//! Tests for batch encoding: `Tokenizer::encode_batch_padded`, the padding-token
+//! accessors, and `Tokenizer::token_histogram` / `top_k`.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, wpm_bert_fixture};
+use shimmytok::{top_k, Error, PadStrategy, Tokenizer, Vocabulary, MAX_OUTPUT_TOKENS};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+// ===== Batch Padding =====
+
+// wpm_bert_fixture: [PAD]=0, BOS=[CLS]=2, EOS=[SEP]=3.
+// "hello" -> [5], "Hello, world!" -> [5, 7, 6, 8]
+
+#[test]
+fn ragged_batch_padded_to_longest() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    let (rows, mask) = tok
+        .encode_batch_padded(&["hello", "Hello, world!"], true, PadStrategy::Longest)
+        .unwrap();
+
+    assert_eq!(rows, vec![vec![2, 5, 3, 0, 0, 0], vec![2, 5, 7, 6, 8, 3]]);
+    assert_eq!(mask, vec![vec![1, 1, 1, 0, 0, 0], vec![1; 6]]);
+}
+
+#[test]
+fn fixed_width_pads_short_rows_and_truncates_long_rows() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    let (rows, mask) = tok
+        .encode_batch_padded(&["hello", "Hello, world!"], true, PadStrategy::Fixed(4))
+        .unwrap();
+
+    // The long row is right-truncated but keeps its EOS.
+    assert_eq!(rows, vec![vec![2, 5, 3, 0], vec![2, 5, 7, 3]]);
+    assert_eq!(mask, vec![vec![1, 1, 1, 0], vec![1, 1, 1, 1]]);
+}
+
+#[test]
+fn padded_rows_match_encode_batch_where_unpadded() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    let texts = ["hello world", "", "world!"];
+    let plain = tok.encode_batch(&texts, true).unwrap();
+    let (rows, mask) = tok
+        .encode_batch_padded(&texts, true, PadStrategy::Longest)
+        .unwrap();
+
+    for ((row, mask), plain) in rows.iter().zip(&mask).zip(&plain) {
+        let real = mask.iter().filter(|&&m| m == 1).count();
+        assert_eq!(&row[..real], plain.as_slice());
+        assert!(row[real..].iter().all(|&t| t == 0));
+    }
+}
+
+#[test]
+fn empty_batch_is_empty() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    let (rows, mask) = tok
+        .encode_batch_padded(&[], true, PadStrategy::Longest)
+        .unwrap();
+    assert!(rows.is_empty());
+    assert!(mask.is_empty());
+}
+
+#[test]
+fn padding_without_pad_token_errors() {
+    // The GPT-2 fixture defines no padding token.
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let result = tok.encode_batch_padded(&["a", "abc"], false, PadStrategy::Longest);
+    assert!(matches!(result, Err(Error::VocabularyError(_))));
+}
+
+#[test]
+fn fixed_width_above_output_limit_errors() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    let result =
+        tok.encode_batch_padded(&["hello"], true, PadStrategy::Fixed(MAX_OUTPUT_TOKENS + 1));
+    assert!(matches!(result, Err(Error::TokenizationFailed(_))));
+}
+
+#[test]
+fn pad_token_reported_when_defined() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    assert_eq!(tok.pad_token(), Some(0));
+    assert!(tok.has_pad_token());
+
+    let vocab = Vocabulary::from_reader(Cursor::new(wpm_bert_fixture())).unwrap();
+    assert_eq!(vocab.pad_token_id(), Some(0));
+}
+
+#[test]
+fn pad_token_absent_when_not_defined() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert_eq!(tok.pad_token(), None);
+    assert!(!tok.has_pad_token());
+
+    let vocab = Vocabulary::from_reader(Cursor::new(bpe_gpt2_fixture())).unwrap();
+    assert_eq!(vocab.pad_token_id(), None);
+}
+
+// ===== Token Histogram =====
+
+fn tokenizer() -> Tokenizer {
+    Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap()
+}
+
+#[test]
+fn counts_match_hand_computed_frequencies() {
+    let tok = tokenizer();
+    // ab -> [ab], abc -> [abc], c -> [c], ca -> [c, a], 12 -> [1, 2]
+    let texts = ["ab", "abc", "ab", "c", "ca", "12"];
+    let histogram = tok.token_histogram(&texts).unwrap();
+
+    let expected = HashMap::from([(6, 2), (7, 1), (5, 2), (3, 1), (8, 1), (9, 1)]);
+    assert_eq!(histogram, expected);
+    assert_eq!(histogram.values().sum::<u64>(), 8);
+
+    assert_eq!(top_k(&histogram, 3), vec![(5, 2), (6, 2), (3, 1)]);
+    assert_eq!(top_k(&histogram, 100).len(), 6);
+    assert!(top_k(&histogram, 0).is_empty());
+}
+
+#[test]
+fn special_tokens_are_not_counted() {
+    let tok = tokenizer();
+    let histogram = tok.token_histogram(&["ab"]).unwrap();
+    assert!(!histogram.contains_key(&tok.bos_token()));
+    assert!(!histogram.contains_key(&tok.eos_token()));
+}
+
+#[test]
+fn empty_input_gives_empty_histogram() {
+    let tok = tokenizer();
+    assert!(tok.token_histogram(&[]).unwrap().is_empty());
+    assert!(tok.token_histogram(&["", ""]).unwrap().is_empty());
+}
+
+#[test]
+fn counts_accumulate_across_chunks() {
+    let tok = tokenizer();
+    let texts = vec!["abcab"; 2500];
+    let histogram = tok.token_histogram(&texts).unwrap();
+    assert_eq!(histogram, HashMap::from([(7, 2500), (6, 2500)]));
+}
+
+#[test]
+fn encode_errors_are_returned() {
+    let tok = tokenizer();
+    let huge = "a".repeat(shimmytok::MAX_INPUT_SIZE + 1);
+    assert!(tok.token_histogram(&["ab", &huge]).is_err());
+}
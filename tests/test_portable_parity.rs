@@ -16,7 +16,7 @@
 mod common;
 
 use common::{bpe_gpt2_fixture, bpe_starcoder_fixture};
-use shimmytok::Tokenizer;
+use shimmytok::{Tokenizer, TokenizerBuilder};
 
 // ── Send + Sync ─────────────────────────────────────────────────────────────
 
@@ -55,6 +55,66 @@ fn tokenizer_shared_across_threads() {
     }
 }
 
+/// A clone encodes and decodes identically to the original for every backend,
+/// including after the original is dropped.
+#[test]
+fn cloned_tokenizer_behaves_identically() {
+    let cases: [(Vec<u8>, &str); 6] = [
+        (bpe_gpt2_fixture(), "abc acb ab 12"),
+        (bpe_starcoder_fixture(), "12ab 1a"),
+        (common::spm_llama_fixture(true), "hi hi\né"),
+        (common::wpm_bert_fixture(), "Hello, world!"),
+        (common::ugm_t5_fixture(), "Hello world"),
+        (common::rwkv_world_fixture(), "ab\n\tab\n\n"),
+    ];
+
+    for (bytes, text) in cases {
+        let original = Tokenizer::from_bytes(&bytes).unwrap();
+        let expected = original.encode(text, true).unwrap();
+        let decoded = original.decode(&expected, false).unwrap();
+        let clone = original.clone();
+        drop(original);
+
+        assert_eq!(clone.encode(text, true).unwrap(), expected);
+        assert_eq!(clone.decode(&expected, false).unwrap(), decoded);
+    }
+}
+
+/// Each thread owns its own clone (no outer `Arc`); all clones share the same
+/// immutable state and must produce identical output concurrently.
+#[test]
+fn cloned_handles_encode_concurrently() {
+    use std::thread;
+
+    let tok = Tokenizer::from_bytes(&bpe_starcoder_fixture()).unwrap();
+    let texts = ["12ab", "ab 1a", "a1b2", ""];
+    let expected: Vec<_> = texts
+        .iter()
+        .map(|t| tok.encode(t, false).unwrap())
+        .collect();
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let tok = tok.clone();
+            let expected = expected.clone();
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    for (text, want) in texts.iter().zip(&expected) {
+                        assert_eq!(&tok.encode(text, false).unwrap(), want);
+                    }
+                }
+                tok
+            })
+        })
+        .collect();
+
+    drop(tok);
+    for h in handles {
+        let tok = h.join().unwrap();
+        assert_eq!(tok.encode("12ab", false).unwrap(), expected[0]);
+    }
+}
+
 // ── Single-pattern BPE (GPT-2 style) ────────────────────────────────────────
 
 #[test]
@@ -83,6 +143,31 @@ fn gpt2_round_trip() {
     }
 }
 
+/// A long document (~100 KB, thousands of pre-tokenized fragments) must encode
+/// exactly like its repeated unit. The merge-rank map is built once at load
+/// time and shared by every fragment, so per-fragment work never changes the
+/// output.
+#[test]
+fn gpt2_long_document_matches_repeated_unit() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let unit = " abc acb ab 12";
+    let unit_ids = tok.encode(unit, false).unwrap();
+    let reps = 100 * 1024 / unit.len();
+
+    let ids = tok.encode(&unit.repeat(reps), false).unwrap();
+    assert_eq!(ids.len(), unit_ids.len() * reps);
+    assert!(ids.chunks(unit_ids.len()).all(|chunk| chunk == unit_ids));
+}
+
+/// A single long word makes every adjacent pair a merge candidate; the
+/// ID-keyed bigram lookup must still merge it exactly like the short case.
+#[test]
+fn gpt2_merge_heavy_word() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let ids = tok.encode(&"abc".repeat(4096), false).unwrap();
+    assert_eq!(ids, vec![7; 4096]);
+}
+
 // ── Multi-pattern BPE (StarCoder style) ─────────────────────────────────────
 
 #[test]
@@ -97,6 +182,26 @@ fn starcoder_multipattern_vectors() {
     assert_eq!(tok.encode("1a", false).unwrap(), vec![3, 5]);
 }
 
+#[test]
+fn unmatched_gaps_are_kept_as_fragments() {
+    let with_pre = |pre: &str| {
+        TokenizerBuilder::from_bytes(&bpe_gpt2_fixture())
+            .unwrap()
+            .pre_type(pre)
+            .build()
+            .unwrap()
+    };
+
+    // deepseek-coder's five patterns match letters, punctuation, CJK, digits
+    // and newlines — `+` is a symbol, so none of them matches it.
+    let tok = with_pre("deepseek-coder");
+    assert_eq!(tok.encode("ab+ab", false).unwrap(), vec![6, 0, 6]);
+
+    // viking's single pattern never matches `.`; it is kept as a gap.
+    let tok = with_pre("viking");
+    assert_eq!(tok.encode("ab.ab", false).unwrap(), vec![6, 0, 6]);
+}
+
 // ── encode_batch order + equivalence ────────────────────────────────────────
 
 #[test]
@@ -146,6 +251,46 @@ fn large_batch_matches_individual() {
     }
 }
 
+/// Several threads calling `encode_batch` on one shared tokenizer at the same
+/// time must each get exactly what sequential `encode` produces. Pre-tokenizer
+/// regexes are compiled at construction and shared lock-free, so concurrent
+/// batches neither serialize nor interfere.
+#[test]
+fn concurrent_batches_match_sequential_encode() {
+    use std::sync::Arc;
+    use std::thread;
+
+    for fixture in [bpe_gpt2_fixture(), bpe_starcoder_fixture()] {
+        let tok = Arc::new(Tokenizer::from_bytes(&fixture).unwrap());
+        let unit = "abcacbab 12 ".repeat(8);
+        let texts: Vec<String> = (0..64).map(|i| format!("{unit}{}", i % 7)).collect();
+        let expected: Vec<Vec<u32>> = texts
+            .iter()
+            .map(|t| tok.encode(t, false).unwrap())
+            .collect();
+        let texts = Arc::new(texts);
+        let expected = Arc::new(expected);
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let tok = Arc::clone(&tok);
+                let texts = Arc::clone(&texts);
+                let expected = Arc::clone(&expected);
+                thread::spawn(move || {
+                    let refs: Vec<&str> = texts.iter().map(String::as_str).collect();
+                    for _ in 0..10 {
+                        assert_eq!(tok.encode_batch(&refs, false).unwrap(), *expected);
+                    }
+                })
+            })
+            .collect();
+
+        for h in handles {
+            h.join().unwrap();
+        }
+    }
+}
+
 /// When several inputs fail, `encode_batch` must return the error from the
 /// LOWEST failing index — deterministically, regardless of backend. We exploit
 /// the fact that oversized-input errors embed the offending byte length, so we
@@ -208,6 +353,47 @@ fn small_batch_returns_lowest_index_error() {
     assert!(!msg.contains(&second_bad_len.to_string()), "got: {msg}");
 }
 
+// ── decode_batch ────────────────────────────────────────────────────────────
+
+#[test]
+fn decode_batch_matches_individual_decode() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+
+    // Small (sequential) and large (parallel when enabled) batches.
+    for count in [3usize, 1000] {
+        let encoded: Vec<Vec<u32>> = (0..count)
+            .map(|i| tok.encode(&"abc acb ab 12"[..(i % 13) + 1], false).unwrap())
+            .collect();
+        let sequences: Vec<&[u32]> = encoded.iter().map(Vec::as_slice).collect();
+
+        let batch = tok.decode_batch(&sequences, false).unwrap();
+        assert_eq!(batch.len(), count);
+        for (tokens, text) in sequences.iter().zip(&batch) {
+            assert_eq!(text, &tok.decode(tokens, false).unwrap());
+        }
+    }
+    assert!(tok.decode_batch(&[], false).unwrap().is_empty());
+}
+
+#[test]
+fn decode_batch_returns_lowest_index_error() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let good: Vec<u32> = vec![7, 3, 5, 4, 6];
+    let first_bad: Vec<u32> = vec![7, 1111];
+    let second_bad: Vec<u32> = vec![2222];
+
+    for padding in [0usize, 1000] {
+        let mut sequences: Vec<&[u32]> = vec![&good; padding];
+        sequences.extend([first_bad.as_slice(), &good, &second_bad]);
+
+        let msg = tok.decode_batch(&sequences, false).unwrap_err().to_string();
+        assert!(
+            msg.contains("1111"),
+            "expected lowest-index error, got: {msg}"
+        );
+    }
+}
+
 // ── get_token exact lookup ──────────────────────────────────────────────────
 
 #[test]
@@ -247,3 +433,43 @@ fn get_token_round_trips_with_token_to_piece() {
         );
     }
 }
+
+// ── token_to_id / id_to_token ───────────────────────────────────────────────
+
+#[test]
+fn token_to_id_and_id_to_token_lookups() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+
+    assert_eq!(tok.token_to_id("abc"), Some(7));
+    assert_eq!(tok.id_to_token(7), Some("abc"));
+    assert_eq!(tok.id_to_token(0), Some("<unk>"));
+
+    // Unknown strings and out-of-range IDs.
+    assert_eq!(tok.token_to_id("zzz"), None);
+    assert_eq!(tok.token_to_id(""), None);
+    assert_eq!(tok.id_to_token(10), None);
+    assert_eq!(tok.id_to_token(u32::MAX), None);
+
+    for id in 0..tok.vocab_size() as u32 {
+        let piece = tok.id_to_token(id).unwrap();
+        assert_eq!(tok.token_to_id(piece), Some(id));
+    }
+}
+
+// ── get_vocab / vocab_iter ──────────────────────────────────────────────────
+
+#[test]
+fn get_vocab_covers_every_token() {
+    let tok = Tokenizer::from_bytes(&bpe_starcoder_fixture()).unwrap();
+    let vocab = tok.get_vocab();
+    assert_eq!(vocab.len(), tok.vocab_size());
+    assert_eq!(vocab.get("ab"), Some(&7));
+    assert_eq!(tok.id_to_token(vocab["ab"]), Some("ab"));
+
+    let pairs: Vec<(&str, u32)> = tok.vocab_iter().collect();
+    assert_eq!(pairs.len(), tok.vocab_size());
+    for (i, &(piece, id)) in pairs.iter().enumerate() {
+        assert_eq!(id, i as u32, "vocab_iter must yield IDs in order");
+        assert_eq!(vocab.get(piece), Some(&id));
+    }
+}
@@ -0,0 +1,47 @@
+//! Tests for `Tokenizer::pre_tokenize_debug`.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, bpe_starcoder_fixture, spm_llama_fixture, wpm_bert_fixture};
+use shimmytok::{Error, Tokenizer};
+
+#[test]
+fn gpt2_splits_lines_into_words_and_newlines() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let fragments = tok.pre_tokenize_debug("Multiple\nlines\nof\ntext").unwrap();
+    assert_eq!(
+        fragments,
+        ["Multiple", "\n", "lines", "\n", "of", "\n", "text"]
+    );
+    assert_eq!(
+        tok.pre_tokenize_debug("Multiple\nlines").unwrap(),
+        ["Multiple", "\n", "lines"]
+    );
+}
+
+#[test]
+fn fragments_are_raw_text_and_cover_the_input() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let text = "Hello, world 你好  12";
+    let fragments = tok.pre_tokenize_debug(text).unwrap();
+    assert_eq!(fragments, ["Hello", ",", " world", " 你好", " ", " 12"]);
+    assert_eq!(fragments.concat(), text);
+    assert!(tok.pre_tokenize_debug("").unwrap().is_empty());
+}
+
+#[test]
+fn multi_pattern_models_apply_every_pattern() {
+    // StarCoder splits digits individually before the GPT-2 pattern runs.
+    let tok = Tokenizer::from_bytes(&bpe_starcoder_fixture()).unwrap();
+    assert_eq!(tok.pre_tokenize_debug("12ab").unwrap(), ["1", "2", "ab"]);
+}
+
+#[test]
+fn non_bpe_models_are_rejected() {
+    for fixture in [spm_llama_fixture(true), wpm_bert_fixture()] {
+        let tok = Tokenizer::from_bytes(&fixture).unwrap();
+        let err = tok.pre_tokenize_debug("hello").unwrap_err();
+        assert!(matches!(err, Error::UnsupportedModel(_)), "{err}");
+        assert!(err.to_string().contains("BPE"), "{err}");
+    }
+}
@@ -0,0 +1,104 @@
+//! Tests for `EncodeOptions::prefix_tokens` / `suffix_tokens`.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, GgufBuilder};
+use shimmytok::{EncodeOptions, Error, Tokenizer, TruncationSide};
+
+const BOS: u32 = 1;
+const EOS: u32 = 2;
+
+fn tokenizer() -> Tokenizer {
+    Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap()
+}
+
+#[test]
+fn body_tokens_are_unchanged_and_wrapping_is_verbatim() {
+    let tok = tokenizer();
+    let prefix = tok.encode("a", false).unwrap();
+    let suffix = tok.encode("c", false).unwrap();
+    let body = tok.encode("bc", false).unwrap();
+
+    let options = EncodeOptions::with_special_tokens(false)
+        .with_prefix_tokens(prefix.clone())
+        .with_suffix_tokens(suffix.clone());
+    let wrapped = tok.encode_with_options("bc", &options).unwrap();
+    assert_eq!(wrapped, [prefix, body, suffix].concat());
+
+    // Concatenating the strings instead merges across the boundary.
+    assert_eq!(tok.encode("abcc", false).unwrap(), vec![7, 5]);
+    assert_ne!(wrapped, vec![7, 5]);
+}
+
+#[test]
+fn wrapping_sits_inside_bos_and_eos() {
+    let tok = tokenizer();
+    let options = EncodeOptions::with_special_tokens(false)
+        .with_added_bos(true)
+        .with_added_eos(true)
+        .with_prefix_tokens(vec![8])
+        .with_suffix_tokens(vec![9]);
+    assert_eq!(
+        tok.encode_with_options("ab", &options).unwrap(),
+        vec![BOS, 8, 6, 9, EOS]
+    );
+}
+
+#[test]
+fn truncation_keeps_wrapping_tokens() {
+    let tok = tokenizer();
+    let options = EncodeOptions::with_special_tokens(false)
+        .with_prefix_tokens(vec![8])
+        .with_suffix_tokens(vec![9]);
+    let full = tok.encode_with_options("ab ab ab", &options).unwrap();
+    assert!(full.len() > 3);
+
+    let right = options.clone().with_truncation(3, TruncationSide::Right);
+    let out = tok.encode_with_options("ab ab ab", &right).unwrap();
+    assert_eq!(out, vec![8, full[1], 9]);
+
+    let left = options.with_truncation(3, TruncationSide::Left);
+    let out = tok.encode_with_options("ab ab ab", &left).unwrap();
+    assert_eq!(out, vec![8, full[full.len() - 2], 9]);
+}
+
+#[test]
+fn empty_body_yields_only_wrapping() {
+    let tok = tokenizer();
+    let options = EncodeOptions::with_special_tokens(false)
+        .with_prefix_tokens(vec![3, 4])
+        .with_suffix_tokens(vec![5]);
+    assert_eq!(
+        tok.encode_with_options("", &options).unwrap(),
+        vec![3, 4, 5]
+    );
+}
+
+#[test]
+fn out_of_range_wrapping_token_errors() {
+    let tok = tokenizer();
+    let options = EncodeOptions::with_special_tokens(false).with_suffix_tokens(vec![10]);
+    assert!(matches!(
+        tok.encode_with_options("ab", &options),
+        Err(Error::InvalidToken(msg))
+            if msg == "Token ID 10 not found in vocabulary of 10 tokens"
+    ));
+}
+
+#[test]
+fn wrapping_tokens_are_kept_under_strict_parity() {
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "gpt-2")
+        .with_string_array("tokenizer.ggml.tokens", &["<unk>", "a", "b"])
+        .build();
+    let mut tok = Tokenizer::from_bytes(&bytes).unwrap();
+    tok.strict_llamacpp_parity(true);
+    let options = EncodeOptions::with_special_tokens(false)
+        .with_prefix_tokens(vec![2])
+        .with_suffix_tokens(vec![2]);
+    assert_eq!(
+        tok.encode_with_options("a", &options).unwrap(),
+        vec![2, 1, 2]
+    );
+}
@@ -0,0 +1,50 @@
+//! Fragment boundaries for the `superbpe` and `kimi-k2` pre-tokenizers.
+//!
+//! `Tokenizer::normalize` shows BPE fragments byte-encoded and separated by
+//! plain spaces, so `Ġ` inside a fragment is a space of the input.
+
+mod common;
+
+use common::GgufBuilder;
+use shimmytok::Tokenizer;
+
+fn fragments(pre: &str, text: &str) -> String {
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", pre)
+        .with_string_array("tokenizer.ggml.tokens", &["<unk>", "<s>", "</s>", "a"])
+        .with_string_array("tokenizer.ggml.merges", &[])
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .build();
+    Tokenizer::from_bytes(&bytes).unwrap().normalize(text)
+}
+
+#[test]
+fn superbpe_groups_digits_in_threes_from_the_right() {
+    assert_eq!(fragments("superbpe", "1000000"), "1 000 000");
+    assert_eq!(fragments("superbpe", "123456"), "123 456");
+    assert_eq!(fragments("superbpe", "12"), "12");
+    assert_eq!(fragments("superbpe", "1234"), "1 234");
+}
+
+#[test]
+fn superbpe_keeps_non_digit_text_whole() {
+    assert_eq!(fragments("superbpe", "ab 1234 5"), "abĠ 1 234 Ġ 5");
+    assert_eq!(fragments("superbpe", "x10000y"), "x 10 000 y");
+}
+
+#[test]
+fn kimi_k2_splits_han_runs_from_other_scripts() {
+    assert_eq!(fragments("kimi-k2", "你好世界"), "ä½łå¥½ä¸ĸçķĮ");
+    assert_eq!(fragments("kimi-k2", "Hello你好World"), "Hello ä½łå¥½ World");
+    // A space before a Han run is not folded into it.
+    assert_eq!(fragments("kimi-k2", "hi 你好"), "hi Ġ ä½łå¥½");
+}
+
+#[test]
+fn kimi_k2_splits_words_digits_and_punctuation() {
+    assert_eq!(
+        fragments("kimi-k2", "Hello world, it's 1234567!"),
+        "Hello Ġworld , Ġit's Ġ 123 456 7 !"
+    );
+}
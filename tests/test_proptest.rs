@@ -2,6 +2,8 @@
 //!
 //! Uses proptest to verify that tokenizer properties hold across a wide range of inputs.
 
+mod common;
+
 use proptest::prelude::*;
 use shimmytok::Tokenizer;
 use std::path::Path;
@@ -211,3 +213,174 @@ mod unit_tests {
         let _ = get_model_path();
     }
 }
+
+// ── Model-free properties (in-memory fixtures) ─────────────────────────────
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(200))]
+
+    /// Property: `count_tokens` always equals `encode(..).len()`.
+    #[test]
+    fn prop_count_tokens_matches_encode_len(
+        text in "[abc12 \\n]{0,64}|\\PC{0,32}",
+        add_special in any::<bool>(),
+    ) {
+        for fixture in [
+            common::bpe_gpt2_fixture(),
+            common::spm_llama_fixture(true),
+            common::ugm_t5_fixture(),
+        ] {
+            let tokenizer = Tokenizer::from_bytes(&fixture).unwrap();
+            let encoded = tokenizer.encode(&text, add_special).map(|t| t.len());
+            let counted = tokenizer.count_tokens(&text, add_special);
+            match (encoded, counted) {
+                (Ok(e), Ok(c)) => prop_assert_eq!(e, c),
+                (Err(_), Err(_)) => {}
+                (e, c) => prop_assert!(false, "encode {:?} vs count {:?}", e, c),
+            }
+        }
+    }
+}
+
+/// Byte-level BPE vocabulary with `Ġ` pieces, so spaces survive encoding and
+/// `encode_incremental` can reuse the tokens before a split point. `aĠ` and
+/// `aĠb` merge across a space, which only pre-tokenizers that keep a space
+/// inside a fragment (e.g. `superbpe`) can produce.
+fn bpe_with_spaces(pre: &str) -> Vec<u8> {
+    common::GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", pre)
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &[
+                "<unk>", "<s>", "</s>", "a", "b", "c", "1", "2", "Ġ", "Ċ", "ab", "abc", "Ġa",
+                "Ġab", "ĠĠ", "12", "aĠ", "aĠb",
+            ],
+        )
+        .with_string_array(
+            "tokenizer.ggml.merges",
+            &["a b", "ab c", "Ġ a", "Ġa b", "Ġ Ġ", "1 2", "a Ġ", "aĠ b"],
+        )
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .with_u32("tokenizer.ggml.bos_token_id", 1)
+        .with_u32("tokenizer.ggml.eos_token_id", 2)
+        .build()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(200))]
+
+    /// Property: `encode_incremental` equals a full `encode` after any edit.
+    #[test]
+    fn prop_encode_incremental_matches_encode(
+        prev in "[abc12 \\n]{0,48}",
+        insert in "[abc12 \\n]{0,6}",
+        pos in any::<prop::sample::Index>(),
+        delete in 0usize..6,
+    ) {
+        let start = pos.index(prev.len() + 1);
+        let end = (start + delete).min(prev.len());
+        let new = format!("{}{insert}{}", &prev[..start], &prev[end..]);
+
+        for fixture in [
+            bpe_with_spaces("gpt-2"),
+            bpe_with_spaces("llama-bpe"),
+            bpe_with_spaces("superbpe"),
+            common::bpe_starcoder_fixture(),
+            common::spm_llama_fixture(true),
+        ] {
+            let tokenizer = Tokenizer::from_bytes(&fixture).unwrap();
+            let prev_tokens = tokenizer.encode(&prev, false).unwrap();
+            let full = tokenizer.encode(&new, false).unwrap();
+            prop_assert_eq!(
+                tokenizer.encode_incremental(&prev, &prev_tokens, &new).unwrap(),
+                full.clone()
+            );
+            // Stale tokens are detected and trigger a full encode.
+            prop_assert_eq!(
+                tokenizer.encode_incremental(&prev, &[3, 3, 3], &new).unwrap(),
+                full
+            );
+        }
+    }
+}
+
+/// `superbpe` only splits digits, so `a b` is one fragment and `aĠ` merges
+/// across the space: the tokens before the space cannot be reused.
+#[test]
+fn encode_incremental_does_not_split_superbpe_at_spaces() {
+    let bytes = common::GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "superbpe")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["<unk>", "a", "b", "c", "Ġ", "Ġc", "aĠ", "aĠb"],
+        )
+        .with_string_array("tokenizer.ggml.merges", &["Ġ c", "a Ġ", "aĠ b"])
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .build();
+    let tokenizer = Tokenizer::from_bytes(&bytes).unwrap();
+
+    let prev_tokens = tokenizer.encode("a c", false).unwrap();
+    assert_eq!(prev_tokens, vec![1, 5]);
+    assert_eq!(tokenizer.encode("a b", false).unwrap(), vec![7]);
+    assert_eq!(
+        tokenizer
+            .encode_incremental("a c", &prev_tokens, "a b")
+            .unwrap(),
+        vec![7]
+    );
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(8))]
+
+    /// Property: `encode_large` equals a serial `encode` on inputs large
+    /// enough to be split into parallel chunks.
+    #[test]
+    fn prop_encode_large_matches_encode(
+        pieces in prop::collection::vec("[abc12 \\n]{1,64}", 1..16),
+        add_special in any::<bool>(),
+    ) {
+        let mut text = String::new();
+        while text.len() < 40 * 1024 {
+            for piece in &pieces {
+                text.push_str(piece);
+            }
+        }
+
+        for fixture in [
+            bpe_with_spaces("gpt-2"),
+            bpe_with_spaces("llama-bpe"),
+            bpe_with_spaces("deepseek-llm"),
+            common::bpe_starcoder_fixture(),
+        ] {
+            let tokenizer = Tokenizer::from_bytes(&fixture).unwrap();
+            prop_assert_eq!(
+                tokenizer.encode_large(&text, add_special).unwrap(),
+                tokenizer.encode(&text, add_special).unwrap()
+            );
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(200))]
+
+    /// Property: SentencePiece encoding of arbitrary Unicode never fails and
+    /// round-trips, so merged symbol ranges stay on UTF-8 boundaries.
+    ///
+    /// `Ġ` is excluded: SPM decode also renders it as a space, which
+    /// `test_spm_utf8_boundaries` pins down separately.
+    #[test]
+    fn prop_spm_multibyte_round_trips(text in "[\\PC&&[^Ġ]]{0,64}") {
+        let tokenizer = Tokenizer::from_bytes(&common::spm_llama_fixture(true)).unwrap();
+        let tokens = tokenizer.encode(&text, false).unwrap();
+        let decoded = tokenizer.decode(&tokens, false).unwrap();
+        // The space prefix is only added when the text does not already
+        // start with a space.
+        let prefix = if text.is_empty() || text.starts_with(' ') { "" } else { " " };
+        let expected = format!("{prefix}{}", text.replace('▁', " "));
+        prop_assert_eq!(decoded, expected);
+    }
+}
@@ -10,7 +10,7 @@
 //! All tests are `#[ignore]` so they never block `cargo test` in CI where
 //! model files and llama-tokenize are not available.
 
-use shimmytok::Tokenizer;
+use shimmytok::{EncodeOptions, Tokenizer};
 use std::process::Command;
 
 // ── constants ─────────────────────────────────────────────────────────────────
@@ -162,6 +162,64 @@ fn validate_t5() {
     validate_model("T5-v1_1-xxl-encoder (UGM/T5)", MODEL_T5);
 }
 
+// ── strict parity mode ────────────────────────────────────────────────────────
+
+/// Inputs that the shimmytok-only `EncodeOptions` would otherwise rewrite.
+const INVISIBLE_CORPUS: &[&str] = &[
+    "\u{FEFF}Hello world",
+    "zero\u{200B}width\u{200D}joiners\u{2060}",
+    "Cafe\u{301} vs café",
+    "Windows\r\nline endings\r",
+];
+
+/// With `strict_llamacpp_parity`, every convenience is requested and must
+/// still be ignored, matching plain `llama-tokenize` output.
+fn validate_strict_parity(label: &str, model_path: &str) {
+    let mut tokenizer =
+        Tokenizer::from_gguf_file(model_path).expect("shimmytok failed to load model");
+    tokenizer.strict_llamacpp_parity(true);
+
+    let options = EncodeOptions::with_special_tokens(false)
+        .with_strip_bom(true)
+        .with_strip_zero_width(true)
+        .with_normalize_newlines(true)
+        .with_normalization(shimmytok::NormalizationForm::Nfc);
+
+    let mut fail = 0usize;
+    for text in CORPUS.iter().chain(INVISIBLE_CORPUS) {
+        let shimmy = tokenizer
+            .encode_with_options(text, &options)
+            .expect("shimmytok encode failed");
+        let Some(llama) = llama_tokens(model_path, text) else {
+            println!("  SKIP (llama-tokenize unavailable): {text:?}");
+            continue;
+        };
+        if shimmy == llama {
+            println!("  ✅  {text:?}  →  {shimmy:?}");
+        } else {
+            println!("  ❌  {text:?}");
+            println!("      shimmytok : {shimmy:?}");
+            println!("      llama.cpp : {llama:?}");
+            fail += 1;
+        }
+    }
+
+    println!("\n{label} (strict parity): {fail} failed");
+    assert_eq!(fail, 0, "{fail} token mismatches against llama-tokenize");
+}
+
+#[test]
+#[ignore = "requires local model files — run: cargo test --test test_real_models -- --ignored --nocapture"]
+fn strict_parity_tinyllama_spm() {
+    validate_strict_parity("TinyLlama-1.1B (SPM/llama)", MODEL_TINYLLAMA);
+}
+
+#[test]
+#[ignore = "requires local model files — run: cargo test --test test_real_models -- --ignored --nocapture"]
+fn strict_parity_qwen2_bpe() {
+    validate_strict_parity("Qwen2-0.5B-Instruct (BPE/qwen2)", MODEL_QWEN2_05B);
+}
+
 // ── smoke tests (no llama-tokenize required) ──────────────────────────────────
 //
 // These also run with --ignored because they load large files from D:,
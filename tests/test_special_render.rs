@@ -0,0 +1,79 @@
+//! Tests for `DecodeOptions::special_token_render`.
+
+mod common;
+
+use common::bpe_gpt2_fixture;
+use shimmytok::{DecodeOptions, SpecialRender, Tokenizer};
+
+/// `<s> ab </s>` in the GPT-2 fixture.
+const TOKENS: [u32; 3] = [1, 6, 2];
+
+fn decode(tok: &Tokenizer, options: &DecodeOptions) -> String {
+    tok.decode_with_options(&TOKENS, options).unwrap()
+}
+
+#[test]
+fn renders_bos_and_eos_in_each_mode() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let base = DecodeOptions::with_skip_special(false);
+
+    assert_eq!(decode(&tok, &base), "<s>ab</s>");
+    assert_eq!(
+        decode(&tok, &base.clone().with_special_render(SpecialRender::Raw)),
+        "<s>ab</s>"
+    );
+    assert_eq!(
+        decode(
+            &tok,
+            &base.clone().with_special_render(SpecialRender::Hidden)
+        ),
+        "ab"
+    );
+    assert_eq!(
+        decode(
+            &tok,
+            &base.clone().with_special_render(SpecialRender::Named)
+        ),
+        "<bos>ab<eos>"
+    );
+}
+
+#[test]
+fn named_applies_to_strict_utf8_and_lstrip_paths() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+
+    let mut strict =
+        DecodeOptions::with_skip_special(false).with_special_render(SpecialRender::Named);
+    strict.strict_utf8 = true;
+    assert_eq!(decode(&tok, &strict), "<bos>ab<eos>");
+
+    let lstrip = DecodeOptions::new(false, true, true).with_special_render(SpecialRender::Named);
+    assert_eq!(decode(&tok, &lstrip), "<bos>ab<eos>");
+}
+
+#[test]
+fn include_special_text_and_skip_special_still_apply() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+
+    // `Raw` defers to `include_special_text`; `Named` overrides it.
+    let hidden = DecodeOptions::new(false, false, false);
+    assert_eq!(decode(&tok, &hidden), "ab");
+    assert_eq!(
+        decode(&tok, &hidden.with_special_render(SpecialRender::Named)),
+        "<bos>ab<eos>"
+    );
+
+    // Skipped specials never reach the renderer.
+    let skipped = DecodeOptions::with_skip_special(true).with_special_render(SpecialRender::Named);
+    assert_eq!(decode(&tok, &skipped), "ab");
+}
+
+#[test]
+fn special_token_name_covers_known_roles() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let vocab = tok.vocab();
+    assert_eq!(vocab.special_token_name(0), Some("<unk>"));
+    assert_eq!(vocab.special_token_name(1), Some("<bos>"));
+    assert_eq!(vocab.special_token_name(2), Some("<eos>"));
+    assert_eq!(vocab.special_token_name(6), None);
+}
@@ -0,0 +1,364 @@
+//! Tests for special-token accessors and handling.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, spm_llama_fixture, wpm_bert_fixture, GgufBuilder};
+use shimmytok::{EncodeOptions, SpecialTokensMap, TokenType, Tokenizer, TokenizerBuilder};
+
+// ===== UNK Token =====
+
+/// SentencePiece vocabulary with no `<0xNN>` byte tokens, so any character
+/// outside `▁ h i` has no fallback and encodes as `<unk>` (id 0).
+fn spm_without_byte_fallback() -> Vec<u8> {
+    let tokens = ["<unk>", "<s>", "</s>", "▁", "h", "i", "hi", "▁hi"];
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "llama")
+        .with_string_array("tokenizer.ggml.tokens", &tokens)
+        .with_f32_array(
+            "tokenizer.ggml.scores",
+            &[0.0, 0.0, 0.0, -5.0, -5.0, -5.0, -1.0, -0.5],
+        )
+        .with_i32_array("tokenizer.ggml.token_type", &[2, 3, 3, 1, 1, 1, 1, 1])
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .with_u32("tokenizer.ggml.bos_token_id", 1)
+        .with_u32("tokenizer.ggml.eos_token_id", 2)
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+        .with_bool("tokenizer.ggml.add_eos_token", false)
+        .build()
+}
+
+#[test]
+fn out_of_vocabulary_character_encodes_as_unk() {
+    let tok = Tokenizer::from_bytes(&spm_without_byte_fallback()).unwrap();
+    assert_eq!(tok.unk_token(), 0);
+    assert!(tok.has_unk());
+    assert_eq!(tok.token_type(tok.unk_token()), TokenType::Unknown);
+
+    let clean = tok.encode("hi hi", false).unwrap();
+    assert_eq!(tok.unk_count(&clean), 0);
+
+    let tokens = tok.encode("hi x hi", false).unwrap();
+    assert!(tokens.contains(&0), "expected <unk> in {tokens:?}");
+    assert_eq!(tok.unk_count(&tokens), 1);
+    assert_eq!(tok.unk_count(&tok.encode("xyhi", false).unwrap()), 2);
+}
+
+#[test]
+fn byte_fallback_avoids_unk() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    assert!(tok.has_unk());
+    let tokens = tok.encode("hi x é", false).unwrap();
+    assert_eq!(tok.unk_count(&tokens), 0);
+}
+
+#[test]
+fn default_unk_id_is_not_a_real_unk() {
+    // The GPT-2 fixture has no token types, so `<unk>` (id 0) is an ordinary
+    // token and must not be counted.
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert_eq!(tok.unk_token(), 0);
+    assert!(!tok.has_unk());
+    assert_eq!(tok.unk_count(&[0, 0, 7]), 0);
+}
+
+// ===== Special Flags =====
+
+#[test]
+fn flags_only_the_parsed_special_token() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let opts = EncodeOptions::with_parse_special(false, true);
+
+    let (tokens, special) = tok.encode_with_special_flags("ab</s>abc", &opts).unwrap();
+    assert_eq!(tokens, vec![6, 2, 7]);
+    assert_eq!(special, vec![false, true, false]);
+    assert_eq!(tokens, tok.encode_with_options("ab</s>abc", &opts).unwrap());
+
+    let (tokens, special) = tok.encode_with_special_flags("abcab", &opts).unwrap();
+    assert_eq!(tokens.len(), special.len());
+    assert!(special.iter().all(|&s| !s));
+}
+
+#[test]
+fn flags_added_bos_and_eos() {
+    let tok = TokenizerBuilder::from_bytes(&bpe_gpt2_fixture())
+        .unwrap()
+        .add_bos_token(true)
+        .add_eos_token(true)
+        .build()
+        .unwrap();
+    let (tokens, special) = tok
+        .encode_with_special_flags("abc", &EncodeOptions::with_special_tokens(true))
+        .unwrap();
+    assert_eq!(tokens, vec![1, 7, 2]);
+    assert_eq!(special, vec![true, false, true]);
+}
+
+// ===== Special Token List =====
+
+#[test]
+fn llama_lists_bos_eos_and_unk() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    assert_eq!(tok.special_token_ids(), vec![0, 1, 2]);
+    assert_eq!(
+        tok.special_tokens(),
+        vec![(0, "<unk>"), (1, "<s>"), (2, "</s>")]
+    );
+}
+
+#[test]
+fn list_matches_is_special_token() {
+    for bytes in [bpe_gpt2_fixture(), wpm_bert_fixture()] {
+        let tok = Tokenizer::from_bytes(&bytes).unwrap();
+        let expected: Vec<u32> = (0..tok.vocab_size() as u32)
+            .filter(|&id| tok.is_special_token(id))
+            .collect();
+        assert_eq!(tok.special_token_ids(), expected);
+    }
+}
+
+#[test]
+fn runtime_registered_tokens_are_listed() {
+    let mut tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert!(!tok.special_token_ids().contains(&7));
+    tok.add_special_tokens(&[("<tool>".to_string(), 7)])
+        .unwrap();
+    assert!(tok.special_token_ids().contains(&7));
+    // The listed text is the vocabulary piece, not the registered string.
+    assert!(tok.special_tokens().contains(&(7, "abc")));
+}
+
+// ===== End of Generation =====
+
+/// A Llama-3 style vocabulary: EOS is `<|end_of_text|>`, but chat turns end
+/// with `<|eot_id|>`, which only some converters record as `eot_token_id`.
+fn llama3_chat(eot_key: bool) -> Vec<u8> {
+    let builder = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "llama-bpe")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &[
+                "a",
+                "b",
+                "ab",
+                "<|begin_of_text|>",
+                "<|end_of_text|>",
+                "<|start_header_id|>",
+                "<|eot_id|>",
+                "<|eom_id|>",
+            ],
+        )
+        .with_i32_array("tokenizer.ggml.token_type", &[1, 1, 1, 3, 3, 3, 3, 3])
+        .with_string_array("tokenizer.ggml.merges", &["a b"])
+        .with_u32("tokenizer.ggml.bos_token_id", 3)
+        .with_u32("tokenizer.ggml.eos_token_id", 4);
+    if eot_key {
+        builder.with_u32("tokenizer.ggml.eot_token_id", 6).build()
+    } else {
+        builder.build()
+    }
+}
+
+#[test]
+fn llama3_eot_id_ends_generation() {
+    for eot_key in [false, true] {
+        let tok = Tokenizer::from_bytes(&llama3_chat(eot_key)).unwrap();
+        assert!(tok.is_eog_token(6), "<|eot_id|> (eot key: {eot_key})");
+        assert!(tok.is_eog_token(7), "<|eom_id|>");
+        assert!(tok.is_eog_token(tok.eos_token()));
+        assert!(!tok.is_eog_token(tok.bos_token()));
+        assert!(!tok.is_eog_token(5));
+        assert!(!tok.is_eog_token(2));
+        assert_eq!(tok.eog_token_ids(), &[4, 6, 7]);
+    }
+}
+
+#[test]
+fn eom_key_is_read() {
+    // A nonstandard piece that is only recognizable through the key.
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string_array("tokenizer.ggml.tokens", &["a", "<eos>", "<stop>", "<halt>"])
+        .with_u32("tokenizer.ggml.eos_token_id", 1)
+        .with_u32("tokenizer.ggml.eom_token_id", 3)
+        .build();
+    let tok = Tokenizer::from_bytes(&bytes).unwrap();
+    assert_eq!(tok.vocab().eom_token_id(), Some(3));
+    assert_eq!(tok.eog_token_ids(), &[1, 3]);
+    assert!(!tok.is_eog_token(2));
+}
+
+#[test]
+fn plain_models_only_stop_on_eos() {
+    for bytes in [bpe_gpt2_fixture(), spm_llama_fixture(true)] {
+        let tok = Tokenizer::from_bytes(&bytes).unwrap();
+        assert_eq!(tok.eog_token_ids(), &[tok.eos_token()]);
+        assert!(!tok.is_eog_token(u32::MAX));
+    }
+}
+
+// ===== Optional Special IDs =====
+
+const CODE_TOKENS: &[&str] = &[
+    "<|endoftext|>",
+    "<fim_prefix>",
+    "<fim_middle>",
+    "<fim_suffix>",
+    "<fim_pad>",
+    "a",
+    "b",
+    "ab",
+];
+
+fn code_model(keys: &[(&str, u32)]) -> Vec<u8> {
+    let mut builder = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "starcoder")
+        .with_string_array("tokenizer.ggml.tokens", CODE_TOKENS)
+        .with_i32_array("tokenizer.ggml.token_type", &[3, 3, 3, 3, 3, 1, 1, 1])
+        .with_string_array("tokenizer.ggml.merges", &["a b"])
+        .with_u32("tokenizer.ggml.bos_token_id", 0)
+        .with_u32("tokenizer.ggml.eos_token_id", 0);
+    for &(key, id) in keys {
+        builder = builder.with_u32(key, id);
+    }
+    builder.build()
+}
+
+#[test]
+fn fim_ids_are_read() {
+    let tok = Tokenizer::from_bytes(&code_model(&[
+        ("tokenizer.ggml.fim_pre_token_id", 1),
+        ("tokenizer.ggml.fim_suf_token_id", 3),
+        ("tokenizer.ggml.fim_mid_token_id", 2),
+    ]))
+    .unwrap();
+    assert_eq!(tok.fim_pre_token(), Some(1));
+    assert_eq!(tok.fim_suf_token(), Some(3));
+    assert_eq!(tok.fim_mid_token(), Some(2));
+    assert!(tok.is_special_token(1));
+}
+
+#[test]
+fn legacy_fim_keys_are_read() {
+    let tok = Tokenizer::from_bytes(&code_model(&[
+        ("tokenizer.ggml.prefix_token_id", 1),
+        ("tokenizer.ggml.suffix_token_id", 3),
+        ("tokenizer.ggml.middle_token_id", 2),
+    ]))
+    .unwrap();
+    assert_eq!(tok.fim_pre_token(), Some(1));
+    assert_eq!(tok.fim_suf_token(), Some(3));
+    assert_eq!(tok.fim_mid_token(), Some(2));
+}
+
+#[test]
+fn eot_eom_mask_and_sep_are_read() {
+    let tok = Tokenizer::from_bytes(&code_model(&[
+        ("tokenizer.ggml.eot_token_id", 0),
+        ("tokenizer.ggml.eom_token_id", 4),
+        ("tokenizer.ggml.mask_token_id", 4),
+        ("tokenizer.ggml.separator_token_id", 0),
+    ]))
+    .unwrap();
+    assert_eq!(tok.eot_token(), Some(0));
+    assert_eq!(tok.eom_token(), Some(4));
+    assert_eq!(tok.mask_token(), Some(4));
+    assert_eq!(tok.sep_token(), Some(0));
+}
+
+#[test]
+fn absent_ids_are_none() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert_eq!(tok.eot_token(), None);
+    assert_eq!(tok.eom_token(), None);
+    assert_eq!(tok.mask_token(), None);
+    assert_eq!(tok.fim_pre_token(), None);
+    assert_eq!(tok.fim_suf_token(), None);
+    assert_eq!(tok.fim_mid_token(), None);
+}
+
+// ===== Special Tokens Map =====
+
+fn llama() -> Tokenizer {
+    Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap()
+}
+
+/// A BPE vocabulary with a padding token and Llama-3 chat sentinels.
+fn chat_bpe() -> Tokenizer {
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "llama-bpe")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &[
+                "<unk>",
+                "<|begin_of_text|>",
+                "<|end_of_text|>",
+                "<|start_header_id|>",
+                "<|end_header_id|>",
+                "<|finetune_right_pad_id|>",
+            ],
+        )
+        .with_string_array("tokenizer.ggml.merges", &[])
+        .with_i32_array("tokenizer.ggml.token_type", &[2, 3, 3, 3, 3, 3])
+        .with_u32("tokenizer.ggml.bos_token_id", 1)
+        .with_u32("tokenizer.ggml.eos_token_id", 2)
+        .with_u32("tokenizer.ggml.padding_token_id", 5)
+        .build();
+    Tokenizer::from_bytes(&bytes).unwrap()
+}
+
+#[test]
+fn llama_map_has_bos_eos_unk() {
+    assert_eq!(
+        llama().special_tokens_map(),
+        SpecialTokensMap {
+            bos_token: Some((1, "<s>".to_string())),
+            eos_token: Some((2, "</s>".to_string())),
+            unk_token: Some((0, "<unk>".to_string())),
+            ..SpecialTokensMap::default()
+        }
+    );
+}
+
+#[test]
+fn other_special_tokens_are_additional() {
+    let map = chat_bpe().special_tokens_map();
+    assert_eq!(map.bos_token, Some((1, "<|begin_of_text|>".to_string())));
+    assert_eq!(map.eos_token, Some((2, "<|end_of_text|>".to_string())));
+    assert_eq!(
+        map.pad_token,
+        Some((5, "<|finetune_right_pad_id|>".to_string()))
+    );
+    assert_eq!(
+        map.additional_special_tokens,
+        vec![
+            (3, "<|start_header_id|>".to_string()),
+            (4, "<|end_header_id|>".to_string()),
+        ]
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serializes_to_huggingface_json_shape() {
+    assert_eq!(
+        serde_json::to_value(llama().special_tokens_map()).unwrap(),
+        serde_json::json!({
+            "bos_token": "<s>",
+            "eos_token": "</s>",
+            "unk_token": "<unk>",
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(chat_bpe().special_tokens_map()).unwrap(),
+        serde_json::json!({
+            "bos_token": "<|begin_of_text|>",
+            "eos_token": "<|end_of_text|>",
+            "unk_token": "<unk>",
+            "pad_token": "<|finetune_right_pad_id|>",
+            "additional_special_tokens": ["<|start_header_id|>", "<|end_header_id|>"],
+        })
+    );
+}
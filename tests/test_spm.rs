@@ -0,0 +1,105 @@
+//! Model-free SentencePiece (SPM) behaviour tests, built on
+//! [`common::spm_llama_fixture`].
+
+mod common;
+
+use common::{spm_llama_fixture, ugm_t5_fixture};
+use shimmytok::{DecodeOptions, Tokenizer};
+
+#[test]
+fn spm_newline_round_trips_through_byte_token() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let ids = tok.encode("hi\nhi", false).unwrap();
+    assert_eq!(
+        ids,
+        vec![263, 13, 262],
+        "newline should fall back to <0x0A>"
+    );
+
+    // llama.cpp decodes the <0x0A> piece to a real newline, not the literal text.
+    let text = tok.decode(&ids, false).unwrap();
+    assert_eq!(text, " hi\nhi");
+    assert!(!text.contains("<0x0A>"));
+}
+
+#[test]
+fn spm_adjacent_byte_tokens_decode_as_one_utf8_sequence() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let ids = tok.encode("é", false).unwrap();
+    assert_eq!(ids, vec![259, 3 + 0xC3, 3 + 0xA9]);
+    assert_eq!(tok.decode(&ids, false).unwrap(), " é");
+}
+
+#[test]
+fn spm_add_space_prefix_false_skips_leading_marker() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(false)).unwrap();
+    assert!(!tok.add_space_prefix());
+    // llama.cpp: no leading ▁, but the interior space still becomes ▁.
+    assert_eq!(tok.encode("hi hi", false).unwrap(), vec![262, 263]);
+}
+
+#[test]
+fn spm_add_space_prefix_true_prepends_marker() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    assert!(tok.add_space_prefix());
+    assert_eq!(tok.encode("hi hi", false).unwrap(), vec![263, 263]);
+}
+
+#[test]
+fn spm_long_run_of_equal_score_merges_terminates() {
+    // Termination smoke test only. Every piece has the same score, so the
+    // merge queue is full of ties and stale bigrams. The loop pops at most
+    // `3 * (n - 1)` entries, so the old `10 * n` iteration cap could never
+    // fire and no input reproduces it; this just checks a long tied run
+    // finishes with the expected tokens.
+    let tokens = ["<unk>", "<s>", "</s>", "▁", "a", "aa", "aaaa"];
+    let bytes = common::GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "llama")
+        .with_string_array("tokenizer.ggml.tokens", &tokens)
+        .with_f32_array(
+            "tokenizer.ggml.scores",
+            &[0.0, 0.0, 0.0, -1.0, -1.0, -1.0, -1.0],
+        )
+        .with_i32_array("tokenizer.ggml.token_type", &[2, 3, 3, 1, 1, 1, 1])
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .with_u32("tokenizer.ggml.bos_token_id", 1)
+        .with_u32("tokenizer.ggml.eos_token_id", 2)
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+        .build();
+    let tok = Tokenizer::from_bytes(&bytes).unwrap();
+
+    let text = "a".repeat(5000);
+    let ids = tok.encode(&text, false).unwrap();
+    // Ties merge left to right: aa pairs first, then aaaa.
+    assert_eq!(ids.len(), 1 + 1250);
+    assert_eq!(ids[0], 3);
+    assert!(ids[1..].iter().all(|&id| id == 6));
+    assert_eq!(tok.decode(&ids, false).unwrap(), format!(" {text}"));
+}
+
+#[test]
+fn strip_prefix_space_drops_only_the_synthetic_space() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let ids = tok.encode("hi", false).unwrap();
+    assert_eq!(tok.decode(&ids, false).unwrap(), " hi");
+
+    let opts = DecodeOptions::with_skip_special(false).with_strip_prefix_space(true);
+    assert_eq!(tok.decode_with_options(&ids, &opts).unwrap(), "hi");
+
+    // Interior spaces and newlines are untouched, unlike `lstrip`.
+    let ids = tok.encode("hi hi\nhi", false).unwrap();
+    assert_eq!(tok.decode_with_options(&ids, &opts).unwrap(), "hi hi\nhi");
+
+    let tok = Tokenizer::from_bytes(&ugm_t5_fixture()).unwrap();
+    let ids = tok.encode("Hello world", false).unwrap();
+    assert_eq!(tok.decode_with_options(&ids, &opts).unwrap(), "Hello world");
+}
+
+#[test]
+fn strip_prefix_space_is_a_no_op_without_add_space_prefix() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(false)).unwrap();
+    // The leading space here was typed, not added, so it is kept.
+    let ids = tok.encode(" hi", false).unwrap();
+    let opts = DecodeOptions::with_skip_special(false).with_strip_prefix_space(true);
+    assert_eq!(tok.decode_with_options(&ids, &opts).unwrap(), " hi");
+}
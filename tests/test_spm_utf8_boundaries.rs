@@ -0,0 +1,99 @@
+//! SentencePiece merging over multi-byte characters: symbol ranges must stay
+//! on UTF-8 boundaries however pieces of different widths combine.
+
+mod common;
+
+use common::GgufBuilder;
+use shimmytok::Tokenizer;
+
+/// Byte tokens plus merges mixing 1-, 2-, 3- and 4-byte characters:
+/// `é` (2), `▁`/`日`/`本` (3), `😀` (4) and combining U+0301 (2).
+fn multibyte_spm() -> Tokenizer {
+    let mut tokens: Vec<String> = vec!["<unk>".into(), "<s>".into(), "</s>".into()];
+    let mut types = vec![2i32, 3, 3];
+    let mut scores = vec![0.0f32; 3];
+    for b in 0..=255u8 {
+        tokens.push(format!("<0x{b:02X}>"));
+        types.push(6);
+        scores.push(0.0);
+    }
+    for (piece, score) in [
+        ("▁", -5.0f32),
+        ("日", -5.0),
+        ("本", -5.0),
+        ("é", -5.0),
+        ("😀", -5.0),
+        ("a", -5.0),
+        ("\u{301}", -5.0),
+        ("日本", -2.0),
+        ("▁日本", -1.0),
+        ("▁é", -2.0),
+        ("é😀", -1.5),
+        ("😀a", -3.0),
+        ("a\u{301}", -2.5),
+        ("😀😀", -1.0),
+    ] {
+        tokens.push(piece.to_string());
+        types.push(1);
+        scores.push(score);
+    }
+    let token_refs: Vec<&str> = tokens.iter().map(String::as_str).collect();
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "llama")
+        .with_string_array("tokenizer.ggml.tokens", &token_refs)
+        .with_f32_array("tokenizer.ggml.scores", &scores)
+        .with_i32_array("tokenizer.ggml.token_type", &types)
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .with_u32("tokenizer.ggml.bos_token_id", 1)
+        .with_u32("tokenizer.ggml.eos_token_id", 2)
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+        .with_bool("tokenizer.ggml.add_space_prefix", true)
+        .build();
+    Tokenizer::from_bytes(&bytes).unwrap()
+}
+
+fn pieces(tok: &Tokenizer, text: &str) -> Vec<String> {
+    tok.encode(text, false)
+        .unwrap()
+        .into_iter()
+        .map(|id| tok.token_to_piece(id).unwrap())
+        .collect()
+}
+
+#[test]
+fn merges_across_mixed_width_characters() {
+    let tok = multibyte_spm();
+    assert_eq!(pieces(&tok, "日本"), ["▁日本"]);
+    assert_eq!(pieces(&tok, "é😀😀a"), ["▁é", "😀😀", "a"]);
+    assert_eq!(pieces(&tok, "a\u{301}😀"), ["▁", "a\u{301}", "😀"]);
+}
+
+#[test]
+fn adversarial_multibyte_input_round_trips() {
+    let tok = multibyte_spm();
+    let inputs = [
+        "😀😀😀😀😀😀😀",
+        "é😀é😀é😀é",
+        "日本日本本日😀a\u{301}\u{301}",
+        "▁▁▁日▁本▁",
+        "\u{301}\u{301}a\u{301}😀a",
+        "𝔘𝔫𝔦𝔠𝔬𝔡𝔢 ﷽ 👩‍👩‍👧‍👦 e\u{301}\u{302}\u{303}",
+        "\u{FEFF}\u{10FFFF}\u{7FF}\u{800}\u{FFFF}\u{10000}",
+    ];
+    for text in inputs {
+        let ids = tok.encode(text, false).unwrap();
+        let decoded = tok.decode(&ids, false).unwrap();
+        // A literal `▁` in the input decodes as a space, like any SPM piece.
+        assert_eq!(decoded, format!(" {}", text.replace('▁', " ")), "{text:?}");
+    }
+}
+
+/// SPM decode treats `Ġ` (U+0120) as an alternate space marker, so a literal
+/// `Ġ` in the input does not survive a round-trip. The property tests exclude
+/// it; this pins the lossy mapping down so a change to it is deliberate.
+#[test]
+fn literal_g_with_dot_decodes_as_space() {
+    let tok = multibyte_spm();
+    let ids = tok.encode("aĠa", false).unwrap();
+    assert_eq!(tok.decode(&ids, false).unwrap(), " a a");
+}
@@ -1,3 +1,5 @@
+mod common;
+
 use shimmytok::Tokenizer;
 use std::path::Path;
 
@@ -191,3 +193,104 @@ fn test_streaming_simulation() {
 
     assert_eq!(streamed, full, "Streamed decode should match full decode");
 }
+
+// ── StreamDecoder (model-free) ──────────────────────────────────────────────
+
+#[test]
+fn stream_decoder_holds_back_split_emoji() {
+    // 🦀 is not in the SPM fixture, so it encodes as ▁ + four <0xNN> tokens.
+    let tokenizer = Tokenizer::from_bytes(&common::spm_llama_fixture(true)).unwrap();
+    let tokens = tokenizer.encode("🦀", false).unwrap();
+    assert_eq!(tokens.len(), 5);
+
+    // Per-token decoding splits the character and emits replacement chars.
+    let naive: String = tokens
+        .iter()
+        .map(|&t| tokenizer.decode_single(t, false).unwrap())
+        .collect();
+    assert!(naive.contains('\u{FFFD}'));
+
+    let mut stream = tokenizer.stream_decoder();
+    let mut pieces = Vec::new();
+    for &t in &tokens {
+        pieces.push(stream.push(t).unwrap());
+    }
+    pieces.push(stream.finish());
+
+    let streamed: String = pieces.concat();
+    assert!(!streamed.contains('\u{FFFD}'), "got {streamed:?}");
+    assert_eq!(streamed, " 🦀");
+    // Nothing is emitted until the fourth byte arrives.
+    assert_eq!(pieces[1..4].concat(), "");
+    assert_eq!(pieces[4], "🦀");
+}
+
+#[test]
+fn stream_decoder_matches_full_decode() {
+    let tokenizer = Tokenizer::from_bytes(&common::plamo2_fixture()).unwrap();
+    let text = "Hello こんにちは🦀世界!";
+    let tokens = tokenizer.encode(text, false).unwrap();
+
+    let mut stream = tokenizer.stream_decoder();
+    let mut streamed = String::new();
+    for &t in &tokens {
+        streamed.push_str(&stream.push(t).unwrap());
+    }
+    streamed.push_str(&stream.finish());
+    assert_eq!(streamed, tokenizer.decode(&tokens, false).unwrap());
+    assert_eq!(streamed, text);
+}
+
+#[test]
+fn stream_decoder_finish_flushes_incomplete_bytes() {
+    let tokenizer = Tokenizer::from_bytes(&common::plamo2_fixture()).unwrap();
+    let mut stream = tokenizer.stream_decoder();
+    assert_eq!(stream.push(0xF0).unwrap(), "");
+    assert_eq!(stream.pending_len(), 1);
+    assert_eq!(stream.finish(), "\u{FFFD}");
+    assert_eq!(stream.pending_len(), 0);
+}
+
+#[test]
+fn stream_decoder_skips_special_and_rejects_invalid_ids() {
+    let tokenizer = Tokenizer::from_bytes(&common::spm_llama_fixture(true)).unwrap();
+    let mut stream = tokenizer.stream_decoder().skip_special_tokens(true);
+    assert_eq!(stream.push(tokenizer.bos_token()).unwrap(), "");
+    assert!(stream.push(u32::MAX).is_err());
+}
+
+#[test]
+fn try_decode_partial_reports_pending_emoji_bytes() {
+    // `▁` + the four <0xNN> byte tokens of 🦀 in the SPM fixture.
+    let tokenizer = Tokenizer::from_bytes(&common::spm_llama_fixture(true)).unwrap();
+    let tokens = tokenizer.encode("ab🦀", false).unwrap();
+    let (emoji_start, n) = (tokens.len() - 4, tokens.len());
+
+    for cut in emoji_start..n {
+        let (text, pending) = tokenizer.try_decode_partial(&tokens[..cut]).unwrap();
+        assert_eq!(pending, cut - emoji_start, "cut at {cut}");
+        assert!(!text.contains('\u{FFFD}'), "got {text:?}");
+        assert!(text.ends_with("ab"), "got {text:?}");
+    }
+
+    let (text, pending) = tokenizer.try_decode_partial(&tokens).unwrap();
+    assert_eq!(pending, 0);
+    assert!(text.ends_with("ab🦀"), "got {text:?}");
+}
+
+#[test]
+fn try_decode_partial_matches_decode_when_complete() {
+    let tokenizer = Tokenizer::from_bytes(&common::plamo2_fixture()).unwrap();
+    let text = "Hello こんにちは🦀世界!";
+    let tokens = tokenizer.encode(text, false).unwrap();
+    assert_eq!(
+        tokenizer.try_decode_partial(&tokens).unwrap(),
+        (text.to_string(), 0)
+    );
+    // 0xF0 opens a four-byte sequence: held back, not replaced.
+    assert_eq!(
+        tokenizer.try_decode_partial(&[0xF0]).unwrap(),
+        (String::new(), 1)
+    );
+    assert!(tokenizer.try_decode_partial(&[u32::MAX]).is_err());
+}
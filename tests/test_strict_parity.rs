@@ -0,0 +1,78 @@
+//! Tests for `Tokenizer::strict_llamacpp_parity`.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, spm_llama_fixture, GgufBuilder};
+use shimmytok::{DecodeOptions, EncodeOptions, SpecialRender, Tokenizer};
+
+#[test]
+fn encode_conveniences_are_ignored() {
+    let mut tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let text = "\u{FEFF}a\u{200B}b\u{200D}c\u{2060}";
+    let options = EncodeOptions::with_special_tokens(false)
+        .with_strip_bom(true)
+        .with_strip_zero_width(true);
+    assert_eq!(tok.encode_with_options(text, &options).unwrap(), vec![7]);
+
+    assert!(!tok.is_strict_llamacpp_parity());
+    tok.strict_llamacpp_parity(true);
+    assert!(tok.is_strict_llamacpp_parity());
+    assert_eq!(
+        tok.encode_with_options(text, &options).unwrap(),
+        tok.encode(text, false).unwrap()
+    );
+
+    tok.strict_llamacpp_parity(false);
+    assert_eq!(tok.encode_with_options(text, &options).unwrap(), vec![7]);
+}
+
+#[test]
+fn decode_conveniences_are_ignored() {
+    let mut tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    tok.strict_llamacpp_parity(true);
+    let ids = tok.encode("hi", false).unwrap();
+    let opts = DecodeOptions::with_skip_special(false).with_strip_prefix_space(true);
+    assert_eq!(tok.decode_with_options(&ids, &opts).unwrap(), " hi");
+
+    let mut tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    tok.strict_llamacpp_parity(true);
+    // `<s> ab </s>`; `Named` falls back to the vocabulary text.
+    let ids = [1, 6, 2];
+    let named = DecodeOptions::with_skip_special(false).with_special_render(SpecialRender::Named);
+    assert_eq!(tok.decode_with_options(&ids, &named).unwrap(), "<s>ab</s>");
+    let hidden = DecodeOptions::new(false, false, false).with_special_render(SpecialRender::Named);
+    assert_eq!(tok.decode_with_options(&ids, &hidden).unwrap(), "ab");
+    // Options llama.cpp has itself still apply.
+    let skipped = DecodeOptions::with_skip_special(true);
+    assert_eq!(tok.decode_with_options(&ids, &skipped).unwrap(), "ab");
+}
+
+#[test]
+fn clean_spaces_follows_only_the_model_flag() {
+    // Byte-level BPE without `tokenizer.ggml.clean_spaces`.
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "gpt-2")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["<unk>", "<s>", "</s>", "a", "\u{120}", ",", "."],
+        )
+        .with_string_array("tokenizer.ggml.merges", &[])
+        .build();
+    let mut tok = Tokenizer::from_bytes(&bytes).unwrap();
+    let ids = tok.encode("a , a .", false).unwrap();
+    let opts = DecodeOptions::with_skip_special(false).with_clean_spaces(true);
+    assert_eq!(tok.decode_with_options(&ids, &opts).unwrap(), "a, a.");
+
+    tok.strict_llamacpp_parity(true);
+    assert_eq!(tok.decode_with_options(&ids, &opts).unwrap(), "a , a .");
+}
+
+#[test]
+fn setting_is_per_handle() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let mut strict = tok.clone();
+    strict.strict_llamacpp_parity(true);
+    assert!(!tok.is_strict_llamacpp_parity());
+    assert!(strict.clone().is_strict_llamacpp_parity());
+}
@@ -0,0 +1,58 @@
+//! Opt-in strict UTF-8 decoding (`DecodeOptions::strict_utf8`).
+//!
+//! By default, byte-level backends replace invalid UTF-8 with U+FFFD; in
+//! strict mode they surface `Error::InvalidUtf8` instead.
+
+mod common;
+
+use common::{plamo2_fixture, rwkv_world_fixture, ugm_t5_fixture};
+use shimmytok::{DecodeOptions, Error, Tokenizer};
+
+fn strict() -> DecodeOptions {
+    DecodeOptions::with_skip_special(false).with_strict_utf8(true)
+}
+
+#[test]
+fn lossy_decode_replaces_invalid_bytes_by_default() {
+    let tok = Tokenizer::from_bytes(&rwkv_world_fixture()).unwrap();
+    assert_eq!(tok.decode(&[1, 8], false).unwrap(), "a\u{FFFD}");
+}
+
+#[test]
+fn strict_decode_rejects_invalid_bytes() {
+    let tok = Tokenizer::from_bytes(&rwkv_world_fixture()).unwrap();
+    let result = tok.decode_with_options(&[1, 8], &strict());
+    assert!(
+        matches!(result, Err(Error::InvalidUtf8(_))),
+        "expected InvalidUtf8, got {result:?}"
+    );
+}
+
+#[test]
+fn strict_decode_accepts_valid_text() {
+    let tok = Tokenizer::from_bytes(&rwkv_world_fixture()).unwrap();
+    assert_eq!(
+        tok.decode_with_options(&[3, 4, 7], &strict()).unwrap(),
+        "ab\nA"
+    );
+
+    let ugm = Tokenizer::from_bytes(&ugm_t5_fixture()).unwrap();
+    assert_eq!(
+        ugm.decode_with_options(&[4, 5], &strict()).unwrap(),
+        " Hello world"
+    );
+}
+
+#[test]
+fn strict_decode_validates_sequences_split_across_tokens() {
+    // "世" = E4 B8 96, spread over three BYTE tokens.
+    let tok = Tokenizer::from_bytes(&plamo2_fixture()).unwrap();
+    let ids = [0xE4, 0xB8, 0x96];
+    assert_eq!(tok.decode_with_options(&ids, &strict()).unwrap(), "世");
+
+    let lstrip = DecodeOptions::new(false, true, true).with_strict_utf8(true);
+    assert_eq!(tok.decode_with_options(&ids, &lstrip).unwrap(), "世");
+
+    let truncated = tok.decode_with_options(&ids[..2], &strict());
+    assert!(matches!(truncated, Err(Error::InvalidUtf8(_))));
+}
@@ -0,0 +1,135 @@
+//! Tests for `.tiktoken` vocabulary import.
+
+use shimmytok::{Error, Tokenizer, TokenizerBuilder};
+use std::io::Write;
+
+/// Standard base64, as used by `.tiktoken` files.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let mut buf = [0u8; 3];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let n = u32::from_be_bytes([0, buf[0], buf[1], buf[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// All 256 single bytes at ranks 0..=255, then `bc` (256), `ab` (257) and
+/// `abc` (258). `bc` outranks `ab`, so `abc` is built as `a` + `bc`.
+fn tiny_tiktoken() -> tempfile::NamedTempFile {
+    let mut lines: Vec<String> = (0..=255u8)
+        .map(|b| format!("{} {b}", base64(&[b])))
+        .collect();
+    for (rank, token) in [(256, "bc"), (257, "ab"), (258, "abc")] {
+        lines.push(format!("{} {rank}", base64(token.as_bytes())));
+    }
+    write_file(&lines.join("\n"))
+}
+
+fn write_file(contents: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(contents.as_bytes()).unwrap();
+    file
+}
+
+fn specials() -> Vec<(String, u32)> {
+    vec![("<|endoftext|>".to_string(), 260)]
+}
+
+#[test]
+fn merges_follow_rank_order() {
+    let file = tiny_tiktoken();
+    let tok = Tokenizer::from_tiktoken(file.path(), &specials()).unwrap();
+
+    assert_eq!(tok.model_type(), "gpt2");
+    assert_eq!(tok.pre_type(), Some("gpt-2"));
+    assert_eq!(tok.encode("abc", false).unwrap(), vec![258]);
+    assert_eq!(tok.encode("ab", false).unwrap(), vec![257]);
+    // `bc` (256) merges before `ab` (257): a|b|c|a|b -> a|bc|a|b -> a|bc|ab -> abc|ab.
+    // Merging by file order instead would give ab|c|ab.
+    assert_eq!(tok.encode("abcab", false).unwrap(), vec![258, 257]);
+    assert_eq!(
+        tok.encode("abd", false).unwrap(),
+        vec![257, u32::from(b'd')]
+    );
+}
+
+#[test]
+fn round_trips_arbitrary_text() {
+    let file = tiny_tiktoken();
+    let tok = Tokenizer::from_tiktoken(file.path(), &specials()).unwrap();
+    for text in ["abc abcab cab", "Hello, world!\n", "héllo 你好 🎉", ""] {
+        let ids = tok.encode(text, false).unwrap();
+        assert_eq!(
+            tok.decode(&ids, false).unwrap(),
+            text,
+            "round trip of {text:?}"
+        );
+    }
+}
+
+#[test]
+fn special_tokens_are_registered() {
+    let file = tiny_tiktoken();
+    let tok = Tokenizer::from_tiktoken(file.path(), &specials()).unwrap();
+
+    // ID 259 is a gap between the ranks and the special token.
+    assert_eq!(tok.vocab_size(), 261);
+    assert_eq!(tok.eos_token(), 260);
+    assert!(tok.is_special_token(260));
+    assert!(!tok.is_special_token(0));
+    assert_eq!(tok.token_to_id("<|endoftext|>"), Some(260));
+
+    let opts = shimmytok::EncodeOptions::with_parse_special(false, true);
+    assert_eq!(
+        tok.encode_with_options("ab<|endoftext|>", &opts).unwrap(),
+        vec![257, 260]
+    );
+    assert_eq!(tok.decode(&[257, 260], true).unwrap(), "ab");
+}
+
+#[test]
+fn builder_can_override_pre_type() {
+    let file = tiny_tiktoken();
+    let tok = TokenizerBuilder::from_tiktoken(file.path(), &specials())
+        .unwrap()
+        .pre_type("gpt-4o")
+        .build()
+        .unwrap();
+    assert_eq!(tok.pre_type(), Some("gpt-4o"));
+    assert_eq!(tok.encode("abcab", false).unwrap(), vec![258, 257]);
+}
+
+#[test]
+fn invalid_files_are_rejected() {
+    let file = write_file("not-base64! 0\n");
+    assert!(matches!(
+        Tokenizer::from_tiktoken(file.path(), &specials()),
+        Err(Error::InvalidMetadata(_))
+    ));
+
+    let file = write_file("YQ== 0\nYg== zero\n");
+    assert!(matches!(
+        Tokenizer::from_tiktoken(file.path(), &specials()),
+        Err(Error::InvalidMetadata(_))
+    ));
+
+    let file = tiny_tiktoken();
+    let colliding = vec![("<|endoftext|>".to_string(), 258)];
+    assert!(matches!(
+        Tokenizer::from_tiktoken(file.path(), &colliding),
+        Err(Error::VocabularyError(_))
+    ));
+    assert!(matches!(
+        Tokenizer::from_tiktoken(file.path(), &[]),
+        Err(Error::VocabularyError(_))
+    ));
+}
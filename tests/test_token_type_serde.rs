@@ -0,0 +1,52 @@
+//! Tests for `TokenType` integer codes and its `serde` support.
+
+use shimmytok::TokenType;
+
+const ALL: [(TokenType, i32); 7] = [
+    (TokenType::Undefined, 0),
+    (TokenType::Normal, 1),
+    (TokenType::Unknown, 2),
+    (TokenType::Control, 3),
+    (TokenType::UserDefined, 4),
+    (TokenType::Unused, 5),
+    (TokenType::Byte, 6),
+];
+
+#[test]
+fn as_i32_inverts_from_i32() {
+    for (ttype, code) in ALL {
+        assert_eq!(ttype.as_i32(), code);
+        assert_eq!(TokenType::from(code), ttype);
+        assert_eq!(i32::from(ttype), code);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serializes_as_gguf_integer_code() {
+    for (ttype, code) in ALL {
+        let json = serde_json::to_string(&ttype).unwrap();
+        assert_eq!(json, code.to_string());
+        let back: TokenType = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, ttype);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn out_of_range_codes_deserialize_as_undefined() {
+    for json in ["-1", "7", "1000"] {
+        let ttype: TokenType = serde_json::from_str(json).unwrap();
+        assert_eq!(ttype, TokenType::Undefined, "code {json}");
+    }
+    let types: Vec<TokenType> = serde_json::from_str("[1, 3, 6, 42]").unwrap();
+    assert_eq!(
+        types,
+        vec![
+            TokenType::Normal,
+            TokenType::Control,
+            TokenType::Byte,
+            TokenType::Undefined
+        ]
+    );
+}
@@ -0,0 +1,151 @@
+//! Tests for `Tokenizer::equivalent` and `Tokenizer::diff`.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, spm_llama_fixture, GgufBuilder};
+use shimmytok::{TokenType, Tokenizer, TokenizerDiff};
+
+const TOKENS: [&str; 10] = ["<unk>", "<s>", "</s>", "a", "b", "c", "ab", "abc", "1", "2"];
+
+/// `bpe_gpt2_fixture` with the tokens, merges and EOS ID overridable.
+fn bpe(tokens: &[&str], merges: &[&str], eos: u32) -> Tokenizer {
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "gpt-2")
+        .with_string_array("tokenizer.ggml.tokens", tokens)
+        .with_string_array("tokenizer.ggml.merges", merges)
+        .with_u32("tokenizer.ggml.unknown_token_id", 0)
+        .with_u32("tokenizer.ggml.bos_token_id", 1)
+        .with_u32("tokenizer.ggml.eos_token_id", eos)
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+        .with_bool("tokenizer.ggml.add_eos_token", false)
+        .build();
+    Tokenizer::from_bytes(&bytes).unwrap()
+}
+
+#[test]
+fn tokenizer_is_equivalent_to_itself_and_reloads() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert!(tok.equivalent(&tok));
+    assert!(tok.equivalent(&tok.clone()));
+
+    let reloaded = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert_eq!(tok.diff(&reloaded), None);
+    assert!(tok.equivalent(&bpe(&TOKENS, &["a b", "ab c"], 2)));
+}
+
+#[test]
+fn tweaked_special_token_id_is_named() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let tweaked = bpe(&TOKENS, &["a b", "ab c"], 7);
+
+    assert!(!tok.equivalent(&tweaked));
+    let diff = tok.diff(&tweaked).unwrap();
+    assert_eq!(
+        diff,
+        TokenizerDiff::SpecialToken {
+            role: "eos",
+            left: Some(2),
+            right: Some(7),
+        }
+    );
+    assert_eq!(diff.to_string(), "eos token ID differs: Some(2) vs Some(7)");
+}
+
+#[test]
+fn extra_and_changed_merges_are_reported() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+
+    let extra = bpe(&TOKENS, &["a b", "ab c", "1 2"], 2);
+    assert_eq!(
+        tok.diff(&extra),
+        Some(TokenizerDiff::Merge {
+            index: 2,
+            left: None,
+            right: Some(("1".to_string(), "2".to_string())),
+        })
+    );
+
+    let reordered = bpe(&TOKENS, &["ab c", "a b"], 2);
+    assert!(matches!(
+        tok.diff(&reordered),
+        Some(TokenizerDiff::Merge { index: 0, .. })
+    ));
+}
+
+#[test]
+fn first_divergence_wins() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+
+    // Model type is checked before everything else.
+    let spm = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    assert!(matches!(
+        tok.diff(&spm),
+        Some(TokenizerDiff::ModelType { left, right }) if left == "gpt2" && right == "llama"
+    ));
+
+    let shorter = bpe(&TOKENS[..9], &["a b", "ab c"], 2);
+    assert_eq!(
+        tok.diff(&shorter),
+        Some(TokenizerDiff::VocabSize { left: 10, right: 9 })
+    );
+
+    // A different piece is reported before the differing EOS.
+    let mut renamed = TOKENS;
+    renamed[9] = "3";
+    let diff = tok.diff(&bpe(&renamed, &["a b", "ab c"], 5)).unwrap();
+    assert_eq!(
+        diff,
+        TokenizerDiff::Token {
+            id: 9,
+            left: "2".to_string(),
+            right: "3".to_string(),
+        }
+    );
+}
+
+#[test]
+fn flags_types_and_registered_specials_are_compared() {
+    let base = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let no_prefix = Tokenizer::from_bytes(&spm_llama_fixture(false)).unwrap();
+    assert_eq!(
+        base.diff(&no_prefix),
+        Some(TokenizerDiff::Flag {
+            name: "add_space_prefix",
+            left: true,
+            right: false,
+        })
+    );
+
+    let hi = base.get_token("hi").unwrap();
+    let mut registered = base.clone();
+    registered
+        .add_special_tokens(&[("<hi>".to_string(), hi)])
+        .unwrap();
+    assert_eq!(
+        base.diff(&registered),
+        Some(TokenizerDiff::SpecialTokenText {
+            text: "<hi>".to_string(),
+            left: None,
+            right: Some(hi),
+        })
+    );
+
+    let typed = |types: &[i32]| {
+        let bytes = GgufBuilder::new()
+            .with_string("tokenizer.ggml.model", "gpt2")
+            .with_string("tokenizer.ggml.pre", "gpt-2")
+            .with_string_array("tokenizer.ggml.tokens", &["<unk>", "a", "b"])
+            .with_i32_array("tokenizer.ggml.token_type", types)
+            .build();
+        Tokenizer::from_bytes(&bytes).unwrap()
+    };
+    assert_eq!(
+        typed(&[2, 1, 1]).diff(&typed(&[2, 1, 4])),
+        Some(TokenizerDiff::TokenType {
+            id: 2,
+            left: TokenType::Normal,
+            right: TokenType::UserDefined,
+        })
+    );
+}
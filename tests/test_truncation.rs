@@ -0,0 +1,65 @@
+//! Tests for `EncodeOptions::max_length` truncation.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, wpm_bert_fixture};
+use shimmytok::{EncodeOptions, Tokenizer, TruncationSide};
+
+// wpm_bert_fixture adds BOS (2) and EOS (3):
+// "Hello, world!" -> [2, 5, 7, 6, 8, 3]
+const TEXT: &str = "Hello, world!";
+
+fn encode(tok: &Tokenizer, add_special: bool, max: usize, side: TruncationSide) -> Vec<u32> {
+    let opts = EncodeOptions::with_special_tokens(add_special).with_truncation(max, side);
+    tok.encode_with_options(TEXT, &opts).unwrap()
+}
+
+#[test]
+fn right_truncation_keeps_prefix_and_eos() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    assert_eq!(tok.encode(TEXT, true).unwrap(), vec![2, 5, 7, 6, 8, 3]);
+    assert_eq!(
+        encode(&tok, true, 4, TruncationSide::Right),
+        vec![2, 5, 7, 3]
+    );
+}
+
+#[test]
+fn left_truncation_keeps_suffix_and_bos() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    assert_eq!(
+        encode(&tok, true, 4, TruncationSide::Left),
+        vec![2, 6, 8, 3]
+    );
+}
+
+#[test]
+fn truncation_without_special_tokens() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    assert_eq!(encode(&tok, false, 2, TruncationSide::Right), vec![5, 7]);
+    assert_eq!(encode(&tok, false, 2, TruncationSide::Left), vec![6, 8]);
+}
+
+#[test]
+fn max_length_at_or_above_length_is_a_no_op() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    let full = tok.encode(TEXT, true).unwrap();
+    assert_eq!(encode(&tok, true, full.len(), TruncationSide::Right), full);
+    assert_eq!(encode(&tok, true, 100, TruncationSide::Left), full);
+
+    let bpe = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let opts = EncodeOptions::with_special_tokens(false).with_truncation(0, TruncationSide::Right);
+    assert!(bpe.encode_with_options("", &opts).unwrap().is_empty());
+}
+
+#[test]
+fn max_length_smaller_than_special_tokens() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    // Exactly the specials: every text token is dropped.
+    assert_eq!(encode(&tok, true, 2, TruncationSide::Right), vec![2, 3]);
+    assert_eq!(encode(&tok, true, 2, TruncationSide::Left), vec![2, 3]);
+    // Fewer than the specials: they are cut from the truncation side.
+    assert_eq!(encode(&tok, true, 1, TruncationSide::Right), vec![2]);
+    assert_eq!(encode(&tok, true, 1, TruncationSide::Left), vec![3]);
+    assert!(encode(&tok, true, 0, TruncationSide::Right).is_empty());
+}
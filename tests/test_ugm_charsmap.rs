@@ -0,0 +1,101 @@
+//! Tests for UGM normalization from `tokenizer.ggml.precompiled_charsmap`.
+//!
+//! Expected strings follow llama.cpp's `llm_tokenizer_ugm_session::normalize`:
+//! charsmap replacement per longest prefix, then `▁` for spaces with optional
+//! prefix and whitespace collapsing.
+
+mod common;
+
+use common::{precompiled_charsmap, ty, GgufBuilder};
+use shimmytok::{Error, Tokenizer, UgmTokenizer, Vocabulary};
+use std::io::Cursor;
+
+/// A few NFKC-style rules: full-width Latin, the ideographic space, and a
+/// half-width katakana pair that composes (longest match wins over `ｶ`).
+const RULES: &[(&str, &str)] = &[
+    ("Ａ", "A"),
+    ("Ｂ", "B"),
+    ("Ｃ", "C"),
+    ("\u{3000}", " "),
+    ("ｶ", "カ"),
+    ("ｶﾞ", "ガ"),
+];
+
+const TOKENS: &[&str] = &[
+    "<pad>", "</s>", "<unk>", "▁", "▁AB", "▁C", "A", "B", "C", "▁ガ", "カ",
+];
+
+fn t5_with_charsmap(charsmap: &[u8], remove_extra_whitespaces: bool) -> Vec<u8> {
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "t5")
+        .with_string_array("tokenizer.ggml.tokens", TOKENS)
+        .with_f32_array(
+            "tokenizer.ggml.scores",
+            &[
+                0.0, 0.0, 0.0, -5.0, -1.0, -1.0, -6.0, -6.0, -6.0, -1.0, -6.0,
+            ],
+        )
+        .with_i32_array(
+            "tokenizer.ggml.token_type",
+            &[3, 3, 2, 1, 1, 1, 1, 1, 1, 1, 1],
+        )
+        .with_u32("tokenizer.ggml.unknown_token_id", 2)
+        .with_u32("tokenizer.ggml.eos_token_id", 1)
+        .with_bool("tokenizer.ggml.add_bos_token", false)
+        .with_bool("tokenizer.ggml.add_eos_token", false)
+        .with_bool(
+            "tokenizer.ggml.remove_extra_whitespaces",
+            remove_extra_whitespaces,
+        )
+        .with_raw_array(
+            "tokenizer.ggml.precompiled_charsmap",
+            ty::U8,
+            charsmap.len() as u64,
+            charsmap,
+        )
+        .build()
+}
+
+fn normalize(fixture: &[u8], text: &str) -> String {
+    let vocab = Vocabulary::from_reader(Cursor::new(fixture)).unwrap();
+    UgmTokenizer::new(&vocab).normalize(text, &vocab)
+}
+
+#[test]
+fn full_width_and_space_runs_collapse() {
+    let fixture = t5_with_charsmap(&precompiled_charsmap(RULES), true);
+    assert_eq!(normalize(&fixture, "ＡＢ   Ｃ"), "▁AB▁C");
+    assert_eq!(normalize(&fixture, "  ＡＢ\u{3000}\u{3000}Ｃ  "), "▁AB▁C");
+    assert_eq!(normalize(&fixture, "ｶﾞｶ"), "▁ガカ");
+    assert_eq!(normalize(&fixture, "   "), "");
+
+    let tok = Tokenizer::from_bytes(&fixture).unwrap();
+    assert_eq!(tok.encode("ＡＢ   Ｃ", false).unwrap(), vec![4, 5]);
+    assert_eq!(tok.encode("ｶﾞ", false).unwrap(), vec![9]);
+}
+
+#[test]
+fn spaces_are_kept_without_remove_extra_whitespaces() {
+    let fixture = t5_with_charsmap(&precompiled_charsmap(RULES), false);
+    assert_eq!(normalize(&fixture, "ＡＢ  Ｃ"), "▁AB▁▁C");
+    assert_eq!(normalize(&fixture, " ＡＢ\u{3000}"), "▁▁AB▁");
+}
+
+#[test]
+fn unmapped_text_passes_through() {
+    let fixture = t5_with_charsmap(&precompiled_charsmap(RULES), true);
+    assert_eq!(normalize(&fixture, "AB C"), "▁AB▁C");
+    assert_eq!(normalize(&fixture, "日本"), "▁日本");
+}
+
+#[test]
+fn malformed_charsmap_is_rejected() {
+    // Header claims more XCDA bytes than the blob holds.
+    let mut blob = precompiled_charsmap(RULES);
+    blob[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    let result = Tokenizer::from_bytes(&t5_with_charsmap(&blob, true));
+    assert!(matches!(result, Err(Error::VocabularyError(_))));
+
+    let result = Tokenizer::from_bytes(&t5_with_charsmap(&[1, 0], true));
+    assert!(matches!(result, Err(Error::VocabularyError(_))));
+}
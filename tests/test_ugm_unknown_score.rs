@@ -0,0 +1,61 @@
+//! Tests for the UGM unknown-token score (`tokenizer.ggml.unknown_token_score`).
+
+mod common;
+
+use common::{ty, GgufBuilder};
+use shimmytok::Tokenizer;
+
+/// A UGM vocabulary where `xy` is either the single piece `▁xy` or
+/// `▁` + `x` + an unknown `y` (there is no single-character `y` piece), so
+/// the Viterbi winner depends on the unknown-token score.
+fn ugm_fixture(unknown_token_score: Option<(u32, &[u8])>) -> Vec<u8> {
+    let builder = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "t5")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["<pad>", "</s>", "<unk>", "\u{2581}", "x", "\u{2581}xy"],
+        )
+        .with_f32_array("tokenizer.ggml.scores", &[0.0, 0.0, 0.0, -1.0, -2.0, -30.0])
+        .with_i32_array("tokenizer.ggml.token_type", &[3, 3, 2, 1, 1, 1])
+        .with_u32("tokenizer.ggml.unknown_token_id", 2)
+        .with_u32("tokenizer.ggml.eos_token_id", 1)
+        .with_u32("tokenizer.ggml.padding_token_id", 0)
+        .with_bool("tokenizer.ggml.add_eos_token", false);
+    match unknown_token_score {
+        Some((type_id, payload)) => builder
+            .with_raw("tokenizer.ggml.unknown_token_score", type_id, payload)
+            .build(),
+        None => builder.build(),
+    }
+}
+
+#[test]
+fn default_penalty_prefers_known_pieces() {
+    // Without the key the unknown score is llama.cpp's min_score - 10 = -40,
+    // so `▁` + `x` + <unk> (-43) loses to `▁xy` (-30).
+    let tok = Tokenizer::from_bytes(&ugm_fixture(None)).unwrap();
+    assert_eq!(tok.vocab().unknown_token_score(), None);
+    assert_eq!(tok.encode("xy", false).unwrap(), vec![5]);
+}
+
+#[test]
+fn declared_score_changes_segmentation() {
+    // A declared score of -1 makes `▁` + `x` + <unk> (-4) beat `▁xy` (-30).
+    let tok =
+        Tokenizer::from_bytes(&ugm_fixture(Some((ty::F32, &(-1.0f32).to_le_bytes())))).unwrap();
+    assert_eq!(tok.vocab().unknown_token_score(), Some(-1.0));
+    assert_eq!(tok.encode("xy", false).unwrap(), vec![3, 4, 2]);
+
+    // F64 is accepted too; a score below the default keeps `▁xy`.
+    let tok =
+        Tokenizer::from_bytes(&ugm_fixture(Some((ty::F64, &(-100.0f64).to_le_bytes())))).unwrap();
+    assert_eq!(tok.vocab().unknown_token_score(), Some(-100.0));
+    assert_eq!(tok.encode("xy", false).unwrap(), vec![5]);
+}
+
+#[test]
+fn non_float_score_is_ignored() {
+    let tok = Tokenizer::from_bytes(&ugm_fixture(Some((ty::U32, &1u32.to_le_bytes())))).unwrap();
+    assert_eq!(tok.vocab().unknown_token_score(), None);
+    assert_eq!(tok.encode("xy", false).unwrap(), vec![5]);
+}
@@ -0,0 +1,159 @@
+//! Reading vocabulary data through an already-loaded `Tokenizer`.
+
+mod common;
+
+use common::{bpe_gpt2_fixture, spm_llama_fixture, wpm_bert_fixture, GgufBuilder};
+use shimmytok::{Error, TokenType, Tokenizer};
+
+#[test]
+fn vocab_exposes_text_and_score_without_reloading() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let vocab = tok.vocab();
+
+    assert_eq!(vocab.n_tokens(), tok.vocab_size());
+    assert_eq!(vocab.get_token_text(263), Some("▁hi"));
+    assert_eq!(vocab.get_token_score(263), -0.5);
+    assert_eq!(vocab.get_token_type(3), TokenType::Byte);
+    assert_eq!(vocab.get_token_id("hi"), Some(262));
+}
+
+#[test]
+fn vocab_exposes_merges() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let merges = tok.vocab().get_merges();
+    assert_eq!(merges.len(), tok.merge_count());
+    assert_eq!(merges[1], ("ab".to_string(), "c".to_string()));
+}
+
+#[test]
+fn longest_token_len_is_the_true_maximum() {
+    for bytes in [
+        bpe_gpt2_fixture(),
+        spm_llama_fixture(true),
+        wpm_bert_fixture(),
+    ] {
+        let tok = Tokenizer::from_bytes(&bytes).unwrap();
+        let expected = tok.vocab_iter().map(|(text, _)| text.len()).max().unwrap();
+        assert_eq!(tok.longest_token_len(), expected);
+        assert_eq!(tok.vocab().longest_token_byte_len(), expected);
+    }
+
+    // `<unk>`, `<s>`, `</s>`, `a`, `b`, `c`, `ab`, `abc`, `1`, `2`
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert_eq!(tok.longest_token_len(), 5);
+}
+
+/// A SentencePiece vocabulary with two reserved `Unused` slots at the end.
+fn vocab_with_reserved_slots(token_count: Option<u32>) -> Vec<u8> {
+    let mut builder = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "llama")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["<unk>", "<s>", "</s>", "▁a", "▁b", "a", "[PAD6]", "[PAD7]"],
+        )
+        .with_i32_array("tokenizer.ggml.token_type", &[2, 3, 3, 1, 1, 1, 5, 5]);
+    if let Some(count) = token_count {
+        builder = builder.with_u32("tokenizer.ggml.token_count", count);
+    }
+    builder.build()
+}
+
+#[test]
+fn padded_and_usable_vocab_sizes() {
+    let tok = Tokenizer::from_bytes(&vocab_with_reserved_slots(None)).unwrap();
+    assert_eq!(tok.vocab_size(), 8);
+    assert_eq!(tok.usable_vocab_size(), 6);
+    assert_eq!(tok.padded_vocab_size(), 8);
+
+    let tok = Tokenizer::from_bytes(&vocab_with_reserved_slots(Some(16))).unwrap();
+    assert_eq!(tok.vocab_size(), 8);
+    assert_eq!(tok.usable_vocab_size(), 6);
+    assert_eq!(tok.padded_vocab_size(), 16);
+
+    // A declared count smaller than the token list is ignored.
+    let tok = Tokenizer::from_bytes(&vocab_with_reserved_slots(Some(4))).unwrap();
+    assert_eq!(tok.padded_vocab_size(), 8);
+
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    assert_eq!(tok.usable_vocab_size(), tok.vocab_size());
+    assert_eq!(tok.padded_vocab_size(), tok.vocab_size());
+}
+
+#[test]
+fn max_token_id_is_the_last_slot() {
+    let tok = Tokenizer::from_bytes(&vocab_with_reserved_slots(Some(16))).unwrap();
+    assert_eq!(tok.max_token_id(), 7);
+    assert!(tok.id_to_token(tok.max_token_id()).is_some());
+    assert!(tok.id_to_token(tok.max_token_id() + 1).is_none());
+}
+
+#[test]
+fn logits_len_must_match_vocab_or_padded_size() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let n = tok.vocab_size();
+    assert!(tok.validate_logits_len(n).is_ok());
+    for len in [0, n - 1, n + 1] {
+        let err = tok.validate_logits_len(len).unwrap_err();
+        assert!(matches!(err, Error::VocabularyError(_)), "{err}");
+        assert!(err.to_string().contains(&format!("{len} does not match")));
+    }
+
+    let tok = Tokenizer::from_bytes(&vocab_with_reserved_slots(Some(16))).unwrap();
+    assert!(tok.validate_logits_len(8).is_ok());
+    assert!(tok.validate_logits_len(16).is_ok());
+    let err = tok.validate_logits_len(12).unwrap_err();
+    assert!(err.to_string().contains("8 or 16 (padded)"), "{err}");
+}
+
+#[test]
+fn vocab_is_shared_by_clones() {
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let clone = tok.clone();
+    assert!(std::ptr::eq(tok.vocab(), clone.vocab()));
+}
+
+#[test]
+fn token_score_is_checked() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    assert_eq!(tok.token_score(263), Some(-0.5));
+    assert_eq!(tok.token_score(262), Some(-1.0));
+    assert_eq!(tok.token_score(0), Some(0.0));
+
+    let n = tok.vocab_size() as u32;
+    assert_eq!(tok.token_score(n), None);
+    assert_eq!(tok.token_score(u32::MAX), None);
+}
+
+#[test]
+fn vocab_getters_do_not_panic_out_of_range() {
+    for bytes in [spm_llama_fixture(true), bpe_gpt2_fixture()] {
+        let tok = Tokenizer::from_bytes(&bytes).unwrap();
+        let vocab = tok.vocab();
+        let n = vocab.n_tokens() as u32;
+        for id in [n, u32::MAX] {
+            assert_eq!(vocab.get_token_score(id), 0.0);
+            assert_eq!(vocab.get_token_type(id), TokenType::Undefined);
+            assert_eq!(vocab.get_token_text(id), None);
+            assert!(!vocab.is_special_token(id));
+            assert_eq!(tok.token_type(id), TokenType::Undefined);
+        }
+    }
+}
+
+#[test]
+fn byte_token_coverage_reports_missing_bytes() {
+    // SentencePiece `<0x00>`..`<0xFF>` pieces.
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    let (count, covered) = tok.vocab().byte_token_coverage();
+    assert_eq!(count, 256);
+    assert!(covered.iter().all(|&c| c));
+    assert!(tok.has_full_byte_fallback());
+
+    // GPT-2 pieces `a`, `b`, `c`, `1`, `2` only.
+    let tok = Tokenizer::from_bytes(&bpe_gpt2_fixture()).unwrap();
+    let (count, covered) = tok.vocab().byte_token_coverage();
+    assert_eq!(count, 5);
+    assert!(covered[usize::from(b'a')] && covered[usize::from(b'2')]);
+    assert!(!covered[usize::from(b'd')] && !covered[0x20]);
+    assert!(!tok.has_full_byte_fallback());
+}
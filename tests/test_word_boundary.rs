@@ -0,0 +1,52 @@
+//! Tests for `Tokenizer::token_starts_word`.
+
+mod common;
+
+use common::{spm_llama_fixture, wpm_bert_fixture, GgufBuilder};
+use shimmytok::Tokenizer;
+
+/// Byte-level BPE with `Ġ` (encoded space) pieces.
+fn gpt2_with_spaces() -> Vec<u8> {
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "gpt2")
+        .with_string("tokenizer.ggml.pre", "gpt-2")
+        .with_string_array(
+            "tokenizer.ggml.tokens",
+            &["<|endoftext|>", "a", "b", "ab", "Ġ", "Ġa", "Ġab", "Ċ"],
+        )
+        .with_string_array("tokenizer.ggml.merges", &["a b", "Ġ a", "Ġ ab"])
+        .with_u32("tokenizer.ggml.bos_token_id", 0)
+        .with_u32("tokenizer.ggml.eos_token_id", 0)
+        .build()
+}
+
+#[test]
+fn gpt2_words_start_with_encoded_space() {
+    let tok = Tokenizer::from_bytes(&gpt2_with_spaces()).unwrap();
+    let tokens = tok.encode("ab ab\nab", false).unwrap();
+    assert_eq!(tokens, vec![3, 6, 7, 3]);
+    let starts: Vec<bool> = tokens.iter().map(|&id| tok.token_starts_word(id)).collect();
+    assert_eq!(starts, vec![false, true, false, false]);
+
+    assert!(tok.token_starts_word(4));
+    assert!(!tok.token_starts_word(0));
+}
+
+#[test]
+fn spm_and_wpm_words_start_with_sentencepiece_marker() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    assert_eq!(tok.token_to_piece(263).unwrap(), "▁hi");
+    assert!(tok.token_starts_word(263));
+    assert!(!tok.token_starts_word(262));
+    assert!(!tok.token_starts_word(tok.bos_token()));
+
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    assert!(tok.token_starts_word(9)); // ▁wor
+    assert!(!tok.token_starts_word(10)); // ld
+}
+
+#[test]
+fn out_of_range_id_is_not_a_word_start() {
+    let tok = Tokenizer::from_bytes(&spm_llama_fixture(true)).unwrap();
+    assert!(!tok.token_starts_word(u32::MAX));
+}
@@ -0,0 +1,90 @@
+//! Tests for BERT-style `[CLS]` / `[SEP]` wrapping and pair encoding on the
+//! WPM backend.
+
+mod common;
+
+use common::{wpm_bert_fixture, GgufBuilder};
+use shimmytok::{EncodeOptions, Tokenizer, TruncationSide};
+
+const BERT_TOKENS: &[&str] = &[
+    "[PAD]",
+    "[UNK]",
+    "[CLS]",
+    "[SEP]",
+    "[MASK]",
+    "\u{2581}hello",
+    "\u{2581}world",
+    "\u{2581},",
+    "\u{2581}!",
+];
+
+/// The BERT vocabulary with no BOS/EOS/CLS/SEP keys and no add-token flags,
+/// as some converters produce.
+fn bert_without_special_keys() -> Vec<u8> {
+    GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "bert")
+        .with_string_array("tokenizer.ggml.tokens", BERT_TOKENS)
+        .with_i32_array("tokenizer.ggml.token_type", &[3, 2, 3, 3, 3, 1, 1, 1, 1])
+        .with_u32("tokenizer.ggml.unknown_token_id", 1)
+        .build()
+}
+
+#[test]
+fn cls_and_sep_found_by_name() {
+    let tok = Tokenizer::from_bytes(&bert_without_special_keys()).unwrap();
+    assert_eq!(tok.cls_token(), Some(2));
+    assert_eq!(tok.sep_token(), Some(3));
+
+    // Wrapped even though the GGUF has no add_eos_token flag.
+    assert_eq!(tok.encode("hello world", true).unwrap(), vec![2, 5, 6, 3]);
+    assert_eq!(tok.encode("hello world", false).unwrap(), vec![5, 6]);
+    assert!(tok.is_special_token(2));
+    assert!(tok.is_special_token(3));
+}
+
+#[test]
+fn cls_and_sep_read_from_llama_cpp_keys() {
+    // Swap the roles to prove the keys win over the piece names.
+    let bytes = GgufBuilder::new()
+        .with_string("tokenizer.ggml.model", "bert")
+        .with_string_array("tokenizer.ggml.tokens", BERT_TOKENS)
+        .with_u32("tokenizer.ggml.unknown_token_id", 1)
+        .with_u32("tokenizer.ggml.cls_token_id", 3)
+        .with_u32("tokenizer.ggml.seperator_token_id", 2)
+        .build();
+    let tok = Tokenizer::from_bytes(&bytes).unwrap();
+    assert_eq!(tok.cls_token(), Some(3));
+    assert_eq!(tok.sep_token(), Some(2));
+    assert_eq!(tok.encode("hello", true).unwrap(), vec![3, 5, 2]);
+}
+
+#[test]
+fn special_pieces_are_not_looked_up_for_other_models() {
+    let tok = Tokenizer::from_bytes(&common::bpe_gpt2_fixture()).unwrap();
+    assert_eq!(tok.cls_token(), None);
+    assert_eq!(tok.sep_token(), None);
+}
+
+#[test]
+fn truncation_keeps_cls_and_sep() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    let opts = EncodeOptions::with_special_tokens(true).with_truncation(3, TruncationSide::Right);
+    assert_eq!(
+        tok.encode_with_options("hello world !", &opts).unwrap(),
+        vec![2, 5, 3]
+    );
+}
+
+#[test]
+fn encode_pair_layout_and_segments() {
+    let tok = Tokenizer::from_bytes(&wpm_bert_fixture()).unwrap();
+    let (tokens, segments) = tok.encode_pair("Hello, world", "world!", true).unwrap();
+
+    // [CLS] hello , world [SEP] world ! [SEP]
+    assert_eq!(tokens, vec![2, 5, 7, 6, 3, 6, 8, 3]);
+    assert_eq!(segments, vec![0, 0, 0, 0, 0, 1, 1, 1]);
+
+    let (tokens, segments) = tok.encode_pair("hello", "world", false).unwrap();
+    assert_eq!(tokens, vec![5, 6]);
+    assert_eq!(segments, vec![0, 1]);
+}